@@ -21,9 +21,8 @@ fn main() {
     let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
 
     //Operation fails if output type does not match the input type
-    match graph.add_input(&add_handle, &mul_handle) {
-        Err(msg) => eprintln!("{:?}", msg),
-        _ => {}
+    if let Err(msg) = graph.add_input(&add_handle, &mul_handle) {
+        eprintln!("{:?}", msg)
     };
 
     //Lets setup the rest of the nodes and ignore errors..
@@ -32,10 +31,10 @@ fn main() {
 
     //By default the graph sends the input to any input that has no other inputs
     //If you want a node to have the input together with other nodes you have to manually assign it
-    graph.connect_to_input(&mul_handle);
+    graph.connect_to_input(&mul_handle).unwrap();
 
     //We can specify an output node:
-    graph.set_output_node(&add_handle);
+    graph.set_output_node(&add_handle).unwrap();
     //We must build a ComputeGraph before we can compute anything
     //Graph fails if input type does not match output type, or there are cycles in the graph.
     let compute_graph = graph.build::<f64, f64>().unwrap();
@@ -0,0 +1,62 @@
+//! Applies a random sequence of edits to a graph and checks its invariants after each.
+//! Run with `cargo fuzz run graph_mutations`.
+#![no_main]
+
+use compute_graph::prelude::*;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut graph = Graph::new();
+    let mut handles: Vec<NodeHandle> = Vec::new();
+    for op in data.chunks_exact(3) {
+        let pick = |byte: u8| (!handles.is_empty()).then(|| handles[byte as usize % handles.len()]);
+        let (a, b) = (pick(op[1]), pick(op[2]));
+        match op[0] % 12 {
+            0 => handles.push(*graph.insert_node("constant", Constant(op[1] as f64))),
+            1 => handles.push(*graph.insert_node("add", AddInputs::<f64>::new())),
+            2 => handles.push(*graph.insert_node("mul", MulInputs::<f64>::new())),
+            3 => handles.push(*graph.insert_node("flag", Constant(op[1] > 127))),
+            4 => {
+                if let Some(a) = a {
+                    let _ = graph.remove_node(&a);
+                }
+            }
+            5 => {
+                if let (Some(a), Some(b)) = (a, b) {
+                    let _ = graph.add_input(&a, &b);
+                }
+            }
+            6 => {
+                if let (Some(a), Some(b)) = (a, b) {
+                    let _ = graph.remove_input(&a, &b);
+                }
+            }
+            7 => {
+                if let Some(a) = a {
+                    let _ = graph.set_output_node(&a);
+                }
+            }
+            8 => {
+                if let Some(a) = a {
+                    let _ = graph.connect_to_input(&a);
+                }
+            }
+            9 => {
+                if let Some(a) = a {
+                    let _ = graph.replace_node(&a, Constant(op[2] as f64));
+                }
+            }
+            10 => {
+                graph.dedup_constants();
+            }
+            _ => {
+                if let Ok(compute_graph) = graph.build::<f64, f64>() {
+                    let _ = compute_graph.try_compute(&1.0);
+                }
+            }
+        }
+        // Removing or merging nodes invalidates their handles
+        handles.retain(|handle| graph.get_name(handle).is_ok());
+        graph.check_invariants().unwrap();
+    }
+});
@@ -0,0 +1,519 @@
+use crate::com_graph::ComputeGraph;
+use crate::compute::Value;
+use crate::graph::ComputeGraphErrors;
+use std::thread;
+
+/// Distribution of one graph input, sampled through its quantile function.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Distribution {
+    Fixed(f64),
+    Uniform { min: f64, max: f64 },
+    Normal { mean: f64, std_dev: f64 },
+    LogNormal { mu: f64, sigma: f64 },
+    Triangular { min: f64, mode: f64, max: f64 },
+}
+
+impl Distribution {
+    /// Maps `u` in `[0, 1)` to the value below which that fraction of samples fall.
+    pub fn quantile(&self, u: f64) -> f64 {
+        match *self {
+            Distribution::Fixed(value) => value,
+            Distribution::Uniform { min, max } => min + u * (max - min),
+            Distribution::Normal { mean, std_dev } => mean + std_dev * standard_normal_quantile(u),
+            Distribution::LogNormal { mu, sigma } => {
+                (mu + sigma * standard_normal_quantile(u)).exp()
+            }
+            Distribution::Triangular { min, mode, max } => {
+                let split = (mode - min) / (max - min);
+                if u < split {
+                    min + (u * (max - min) * (mode - min)).sqrt()
+                } else {
+                    max - ((1.0 - u) * (max - min) * (max - mode)).sqrt()
+                }
+            }
+        }
+    }
+}
+
+/// Acklam's rational approximation, accurate to about 1e-9.
+fn standard_normal_quantile(u: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    let u = u.clamp(f64::MIN_POSITIVE, 1.0 - f64::EPSILON);
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if u < 0.02425 {
+        tail((-2.0 * u.ln()).sqrt())
+    } else if u > 1.0 - 0.02425 {
+        -tail((-2.0 * (1.0 - u).ln()).sqrt())
+    } else {
+        let q = u - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// SplitMix64, small and good enough for sampling inputs.
+#[derive(Clone, Debug)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bin {
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+}
+
+/// Summary of sampled graph outputs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputStats {
+    /// All outputs, sorted ascending.
+    pub samples: Vec<f64>,
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+impl OutputStats {
+    pub fn new(mut samples: Vec<f64>) -> Self {
+        samples.sort_by(f64::total_cmp);
+        let count = samples.len().max(1) as f64;
+        let mean = samples.iter().sum::<f64>() / count;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / count;
+        Self {
+            samples,
+            mean,
+            std_dev: variance.sqrt(),
+        }
+    }
+
+    pub fn min(&self) -> f64 {
+        self.samples.first().copied().unwrap_or(f64::NAN)
+    }
+
+    pub fn max(&self) -> f64 {
+        self.samples.last().copied().unwrap_or(f64::NAN)
+    }
+
+    /// Value below which a `p` fraction of the outputs fall, e.g. `0.95` for the 95th percentile.
+    pub fn percentile(&self, p: f64) -> f64 {
+        if self.samples.is_empty() {
+            return f64::NAN;
+        }
+        let index = (p.clamp(0.0, 1.0) * (self.samples.len() - 1) as f64).round() as usize;
+        self.samples[index]
+    }
+
+    /// Counts outputs in `bins` equal-width bins spanning `min()..=max()`.
+    pub fn histogram(&self, bins: usize) -> Vec<Bin> {
+        let (min, max) = (self.min(), self.max());
+        let width = (max - min) / bins as f64;
+        let mut histogram = (0..bins)
+            .map(|i| Bin {
+                start: min + i as f64 * width,
+                end: min + (i + 1) as f64 * width,
+                count: 0,
+            })
+            .collect::<Vec<_>>();
+        for sample in self.samples.iter() {
+            let index = if width > 0.0 {
+                (((sample - min) / width) as usize).min(bins - 1)
+            } else {
+                0
+            };
+            if let Some(bin) = histogram.get_mut(index) {
+                bin.count += 1;
+            }
+        }
+        histogram
+    }
+}
+
+/// Evaluates `graph` for every input, split across one clone of the graph per available core.
+/// Outputs keep the order of `inputs`.
+pub fn evaluate_parallel<In, Out>(
+    graph: &ComputeGraph<In, Out>,
+    inputs: &[In],
+) -> Result<Vec<Out>, ComputeGraphErrors>
+where
+    In: Value,
+    Out: Value,
+{
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = inputs.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let workers = inputs
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let graph = graph.clone();
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|input| graph.try_compute(input))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut outputs = Vec::with_capacity(inputs.len());
+        for worker in workers {
+            outputs.extend(worker.join().expect("evaluation thread panicked")?);
+        }
+        Ok(outputs)
+    })
+}
+
+/// How `sweep` spreads points over the unit hypercube before mapping them through the input
+/// distributions. Latin hypercube and Sobol points cover the space far more evenly than
+/// random or grid sampling for the same number of evaluations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Sampler {
+    #[default]
+    Random,
+    /// Each dimension is split into `n` strata holding one point each.
+    LatinHypercube,
+    /// Sobol sequence with a seeded digital shift, for up to `MAX_SOBOL_DIMENSIONS` inputs.
+    /// Balanced for powers of two.
+    Sobol,
+}
+
+pub const MAX_SOBOL_DIMENSIONS: usize = 16;
+
+/// Joe & Kuo direction numbers `(degree, coefficients, initial m)` for dimensions 2 and up.
+const SOBOL_PARAMETERS: [(usize, u32, &[u32]); MAX_SOBOL_DIMENSIONS - 1] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+];
+
+fn sobol_directions(dimension: usize) -> [u32; 32] {
+    let mut v = [0u32; 32];
+    if dimension == 0 {
+        for (k, v) in v.iter_mut().enumerate() {
+            *v = 1 << (31 - k);
+        }
+        return v;
+    }
+    let (s, a, m) = SOBOL_PARAMETERS[dimension - 1];
+    for k in 0..32 {
+        v[k] = if k < s {
+            m[k] << (31 - k)
+        } else {
+            let mut value = v[k - s] ^ (v[k - s] >> s);
+            for l in 1..s {
+                if (a >> (s - 1 - l)) & 1 == 1 {
+                    value ^= v[k - l];
+                }
+            }
+            value
+        };
+    }
+    v
+}
+
+impl Sampler {
+    /// `n` points in `[0, 1)^N`. The same `seed` always gives the same points.
+    pub fn unit_points<const N: usize>(
+        &self,
+        n: usize,
+        seed: u64,
+    ) -> Result<Vec<[f64; N]>, String> {
+        let mut rng = Rng::new(seed);
+        match self {
+            Sampler::Random => Ok((0..n).map(|_| [(); N].map(|_| rng.next_f64())).collect()),
+            Sampler::LatinHypercube => {
+                let mut points = vec![[0.0; N]; n];
+                for dimension in 0..N {
+                    let mut strata = (0..n).collect::<Vec<_>>();
+                    for i in (1..n).rev() {
+                        strata.swap(i, rng.next_u64() as usize % (i + 1));
+                    }
+                    for (point, stratum) in points.iter_mut().zip(strata) {
+                        point[dimension] = (stratum as f64 + rng.next_f64()) / n as f64;
+                    }
+                }
+                Ok(points)
+            }
+            Sampler::Sobol => {
+                if N > MAX_SOBOL_DIMENSIONS {
+                    return Err(format!(
+                        "Sobol sampling supports at most {} dimensions, got {}",
+                        MAX_SOBOL_DIMENSIONS, N
+                    ));
+                }
+                let directions = (0..N).map(sobol_directions).collect::<Vec<_>>();
+                let mut x = [0u32; N].map(|_| (rng.next_u64() >> 32) as u32);
+                let mut points = Vec::with_capacity(n);
+                for i in 0..n {
+                    if i > 0 {
+                        let bit = (i - 1).trailing_ones() as usize;
+                        for (x, v) in x.iter_mut().zip(directions.iter()) {
+                            *x ^= v[bit];
+                        }
+                    }
+                    points.push(x.map(|x| x as f64 / (1u64 << 32) as f64));
+                }
+                Ok(points)
+            }
+        }
+    }
+}
+
+/// Evaluates `graph` at `n` inputs spread by `sampler` and mapped through the per-element
+/// distributions, returning each input with its output.
+pub fn sweep<const N: usize, Out>(
+    graph: &ComputeGraph<[f64; N], Out>,
+    input_distributions: &[Distribution; N],
+    n: usize,
+    sampler: Sampler,
+    seed: u64,
+) -> Result<Vec<([f64; N], Out)>, ComputeGraphErrors>
+where
+    [f64; N]: Value,
+    Out: Value,
+{
+    let inputs = sampler
+        .unit_points::<N>(n, seed)
+        .map_err(|err| ComputeGraphErrors::InvalidParameter(err.into()))?
+        .into_iter()
+        .map(|point| {
+            let mut input = [0.0; N];
+            for ((value, u), distribution) in input.iter_mut().zip(point).zip(input_distributions) {
+                *value = distribution.quantile(u);
+            }
+            input
+        })
+        .collect::<Vec<_>>();
+    let outputs = evaluate_parallel(graph, &inputs)?;
+    Ok(inputs.into_iter().zip(outputs).collect())
+}
+
+/// Whether `a` is at least as good as `b` in every objective and better in one,
+/// with every objective minimized.
+pub fn dominates<const M: usize>(a: &[f64; M], b: &[f64; M]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a <= b) && a.iter().zip(b).any(|(a, b)| a < b)
+}
+
+/// Evaluates every candidate and keeps the non-dominated ones, minimizing each of the `M`
+/// outputs. Negate an output in the graph to maximize it instead. Candidates producing a
+/// NaN objective are dropped. The front keeps the order of `candidates`.
+pub fn pareto_front<In, const M: usize>(
+    graph: &ComputeGraph<In, [f64; M]>,
+    candidates: &[In],
+) -> Result<Vec<(In, [f64; M])>, ComputeGraphErrors>
+where
+    In: Value,
+    [f64; M]: Value,
+{
+    let outputs = evaluate_parallel(graph, candidates)?;
+    let evaluated = candidates
+        .iter()
+        .cloned()
+        .zip(outputs)
+        .filter(|(_, objectives)| objectives.iter().all(|v| !v.is_nan()))
+        .collect::<Vec<_>>();
+    Ok(evaluated
+        .iter()
+        .filter(|(_, objectives)| {
+            !evaluated
+                .iter()
+                .any(|(_, other)| dominates(other, objectives))
+        })
+        .cloned()
+        .collect())
+}
+
+/// Samples `n` inputs from independent per-element distributions, evaluates them in parallel
+/// and summarizes the outputs. The same `seed` always gives the same samples.
+pub fn monte_carlo<const N: usize>(
+    graph: &ComputeGraph<[f64; N], f64>,
+    input_distributions: &[Distribution; N],
+    n: usize,
+    seed: u64,
+) -> Result<OutputStats, ComputeGraphErrors>
+where
+    [f64; N]: Value,
+{
+    let outputs = sweep(graph, input_distributions, n, Sampler::Random, seed)?;
+    Ok(OutputStats::new(
+        outputs.into_iter().map(|(_, output)| output).collect(),
+    ))
+}
+
+#[cfg(test)]
+mod analysis_tests {
+    use crate::analysis::*;
+    use crate::prelude::*;
+
+    fn sum_graph() -> Result<ComputeGraph<[f64; 2], f64>, ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let a_handle = graph.insert_node("a", Element::<f64, 2>::new(0));
+        let b_handle = graph.insert_node("b", Element::<f64, 2>::new(1));
+        let sum_handle = graph.insert_node("sum", AddInputs::<f64>::new());
+        graph.add_input(&sum_handle, &a_handle)?;
+        graph.add_input(&sum_handle, &b_handle)?;
+        graph.set_output_node(&sum_handle)?;
+        graph.build()
+    }
+
+    #[test]
+    fn test_monte_carlo() -> Result<(), ComputeGraphErrors> {
+        let compute_graph = sum_graph()?;
+        let inputs = [
+            Distribution::Uniform { min: 0.0, max: 1.0 },
+            Distribution::Normal {
+                mean: 5.0,
+                std_dev: 2.0,
+            },
+        ];
+        let stats = monte_carlo(&compute_graph, &inputs, 20_000, 7)?;
+        assert_eq!(stats.samples.len(), 20_000);
+        assert!((stats.mean - 5.5).abs() < 0.05);
+        // Var(U(0,1)) + Var(N(5,2)) = 1/12 + 4
+        assert!((stats.std_dev - (1.0f64 / 12.0 + 4.0).sqrt()).abs() < 0.05);
+        assert!((stats.percentile(0.5) - 5.5).abs() < 0.1);
+        assert!(stats.min() <= stats.percentile(0.05) && stats.percentile(0.95) <= stats.max());
+
+        let histogram = stats.histogram(10);
+        assert_eq!(histogram.iter().map(|bin| bin.count).sum::<usize>(), 20_000);
+        assert_eq!(histogram[9].end, stats.max());
+
+        let first = monte_carlo(&compute_graph, &inputs, 100, 7)?;
+        let second = monte_carlo(&compute_graph, &inputs, 100, 7)?;
+        assert_eq!(first, second);
+        Ok(())
+    }
+
+    #[test]
+    fn test_samplers_stratify() -> Result<(), String> {
+        for sampler in [Sampler::LatinHypercube, Sampler::Sobol] {
+            let points = sampler.unit_points::<3>(16, 3)?;
+            for dimension in 0..3 {
+                let mut strata = points
+                    .iter()
+                    .map(|point| (point[dimension] * 16.0) as usize)
+                    .collect::<Vec<_>>();
+                strata.sort();
+                assert_eq!(strata, (0..16).collect::<Vec<_>>(), "{:?}", sampler);
+            }
+        }
+        assert!(Sampler::Sobol.unit_points::<17>(4, 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sobol_sweep() -> Result<(), ComputeGraphErrors> {
+        let compute_graph = sum_graph()?;
+        let inputs = [Distribution::Uniform { min: 0.0, max: 1.0 }; 2];
+        let results = sweep(&compute_graph, &inputs, 256, Sampler::Sobol, 11)?;
+        assert_eq!(results.len(), 256);
+        assert!(results
+            .iter()
+            .all(|(input, output)| input[0] + input[1] == *output));
+        let mean = results.iter().map(|(_, output)| output).sum::<f64>() / 256.0;
+        assert!((mean - 1.0).abs() < 0.01);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pareto_front() -> Result<(), ComputeGraphErrors> {
+        //  Cost and weight of a beam of a given thickness, both minimized:
+        //  thicker is heavier but cheaper to reinforce.
+        let mut graph = Graph::new();
+        let weight_handle = graph.insert_node("weight", MulInputs::<f64>::new());
+        let two_handle = graph.insert_node("two", Constant(2.0));
+        let cost_handle = graph.insert_node("cost", Select::new(10.0, 1.0));
+        let reinforce_handle = graph.insert_node("thin", Compare::new(CompareOp::Lt, 3.0));
+        let objectives_handle = graph.insert_node("objectives", Collect::<f64, 2>::new());
+        graph.add_input(&weight_handle, &two_handle)?;
+        graph.connect_to_input(&weight_handle)?;
+        graph.add_input(&cost_handle, &reinforce_handle)?;
+        graph.add_input(&objectives_handle, &weight_handle)?;
+        graph.add_input(&objectives_handle, &cost_handle)?;
+        graph.set_output_node(&objectives_handle)?;
+        let compute_graph = graph.build::<f64, [f64; 2]>()?;
+
+        let front = pareto_front(&compute_graph, &[1.0, 2.0, 3.0, 4.0, f64::NAN])?;
+        assert_eq!(front, vec![(1.0, [2.0, 10.0]), (3.0, [6.0, 1.0])]);
+        assert!(dominates(&[1.0, 2.0], &[1.0, 3.0]));
+        assert!(!dominates(&[1.0, 2.0], &[1.0, 2.0]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_quantiles() {
+        let normal = Distribution::Normal {
+            mean: 0.0,
+            std_dev: 1.0,
+        };
+        assert!(normal.quantile(0.5).abs() < 1e-9);
+        assert!((normal.quantile(0.975) - 1.959964).abs() < 1e-5);
+        assert!((normal.quantile(0.01) + 2.326348).abs() < 1e-5);
+        let triangular = Distribution::Triangular {
+            min: 0.0,
+            mode: 1.0,
+            max: 2.0,
+        };
+        assert_eq!(triangular.quantile(0.5), 1.0);
+        assert_eq!(Distribution::Fixed(3.0).quantile(0.2), 3.0);
+    }
+}
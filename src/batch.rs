@@ -0,0 +1,112 @@
+use crate::com_graph::ComputeGraph;
+use crate::compute::Value;
+use crate::graph::ComputeGraphErrors;
+use std::thread;
+
+fn check_lengths(inputs: usize, outputs: usize) -> Result<(), ComputeGraphErrors> {
+    if inputs != outputs {
+        return Err(ComputeGraphErrors::InvalidParameter(
+            format!(
+                "batch has {} inputs but room for {} outputs",
+                inputs, outputs
+            )
+            .into(),
+        ));
+    }
+    Ok(())
+}
+
+impl<In, Out> ComputeGraph<In, Out>
+where
+    In: Value,
+    Out: Value,
+{
+    /// Evaluates every input, reusing one context for the whole batch.
+    pub fn compute_batch(&self, inputs: &[In]) -> Result<Vec<Out>, ComputeGraphErrors> {
+        let mut outputs = vec![Out::default(); inputs.len()];
+        self.compute_batch_into(inputs, &mut outputs)?;
+        Ok(outputs)
+    }
+
+    /// Like `compute_batch`, split over `threads` threads, see `compute_batch_into_parallel`.
+    pub fn compute_batch_parallel(
+        &self,
+        inputs: &[In],
+        threads: usize,
+    ) -> Result<Vec<Out>, ComputeGraphErrors> {
+        let mut outputs = vec![Out::default(); inputs.len()];
+        self.compute_batch_into_parallel(inputs, &mut outputs, threads)?;
+        Ok(outputs)
+    }
+
+    /// Evaluates every input into the matching slot of `outputs`. Apart from what the nodes
+    /// themselves allocate, nothing is allocated for nodes with up to eight inputs.
+    pub fn compute_batch_into(
+        &self,
+        inputs: &[In],
+        outputs: &mut [Out],
+    ) -> Result<(), ComputeGraphErrors> {
+        check_lengths(inputs.len(), outputs.len())?;
+        self.with_context(|context| {
+            for (input, output) in inputs.iter().zip(outputs.iter_mut()) {
+                *output = self.try_compute_with(input, context)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Like `compute_batch_into`, with the batch split into contiguous chunks evaluated on
+    /// `threads` threads sharing the graph.
+    pub fn compute_batch_into_parallel(
+        &self,
+        inputs: &[In],
+        outputs: &mut [Out],
+        threads: usize,
+    ) -> Result<(), ComputeGraphErrors> {
+        check_lengths(inputs.len(), outputs.len())?;
+        let chunk_size = inputs.len().div_ceil(threads.max(1)).max(1);
+        thread::scope(|scope| {
+            let workers = inputs
+                .chunks(chunk_size)
+                .zip(outputs.chunks_mut(chunk_size))
+                .map(|(inputs, outputs)| scope.spawn(|| self.compute_batch_into(inputs, outputs)))
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .try_for_each(|worker| worker.join().expect("evaluation thread panicked"))
+        })
+    }
+}
+
+#[cfg(test)]
+mod batch_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_batch_into() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let gain_handle = graph.insert_node("gain", Constant(3.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &gain_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.set_output_node(&mul_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+        let inputs = (0..1000).map(|x| x as f64).collect::<Vec<_>>();
+        let expected = inputs.iter().map(|x| x * 3.0).collect::<Vec<_>>();
+
+        // That this doesn't allocate is tested in `tests/tracking_allocator.rs`
+        let mut outputs = vec![0.0; inputs.len()];
+        compute_graph.compute_batch_into(&inputs, &mut outputs)?;
+        assert_eq!(outputs, expected);
+
+        let mut parallel = vec![0.0; inputs.len()];
+        compute_graph.compute_batch_into_parallel(&inputs, &mut parallel, 4)?;
+        assert_eq!(parallel, expected);
+        assert_eq!(compute_graph.compute_batch(&inputs)?, expected);
+        assert_eq!(compute_graph.compute_batch_parallel(&inputs, 3)?, expected);
+        assert!(compute_graph
+            .compute_batch_into(&inputs, &mut outputs[..10])
+            .is_err());
+        Ok(())
+    }
+}
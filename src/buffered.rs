@@ -0,0 +1,204 @@
+use crate::com_graph::ComputeGraph;
+use crate::compute::Value;
+use crate::graph::{ComputeGraphErrors, NodeHandle};
+use arc_swap::ArcSwap;
+use std::any::Any;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+
+/// Every node output of one completed evaluation of a `DoubleBuffered` graph.
+pub struct Frame<Out> {
+    generation: u64,
+    handles: Arc<Vec<NodeHandle>>,
+    outputs: Vec<Box<dyn Any + Send + Sync>>,
+    _output: PhantomData<fn() -> Out>,
+}
+
+impl<Out: Any> Frame<Out> {
+    /// Number of evaluations that completed before this frame, 0 before the first one.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn output(&self) -> &Out {
+        self.outputs.last().unwrap().downcast_ref().unwrap()
+    }
+
+    /// Output of `node_handle`, if it is part of the graph and has type `T`.
+    pub fn get<T: Any>(&self, node_handle: &NodeHandle) -> Option<&T> {
+        let index = self
+            .handles
+            .iter()
+            .position(|handle| handle == node_handle)?;
+        self.outputs[index].downcast_ref()
+    }
+}
+
+/// Reads the latest frame of a `DoubleBuffered` graph without locking. Cheap to clone.
+pub struct FrameReader<Out> {
+    front: Arc<ArcSwap<Frame<Out>>>,
+}
+
+impl<Out> Clone for FrameReader<Out> {
+    fn clone(&self) -> Self {
+        Self {
+            front: self.front.clone(),
+        }
+    }
+}
+
+impl<Out> FrameReader<Out> {
+    /// The last completed frame. Holding it doesn't block evaluations, but keeps the
+    /// next one from reusing its buffer.
+    pub fn latest(&self) -> Arc<Frame<Out>> {
+        self.front.load_full()
+    }
+}
+
+struct Writer<In, Out> {
+    graph: ComputeGraph<In, Out>,
+    back: Arc<Frame<Out>>,
+}
+
+/// A stateful graph whose outputs are published in two alternating buffers, so readers on
+/// other threads see the last completed evaluation while the next one is in flight.
+pub struct DoubleBuffered<In, Out> {
+    writer: Mutex<Writer<In, Out>>,
+    reader: FrameReader<Out>,
+}
+
+impl<In, Out> DoubleBuffered<In, Out>
+where
+    In: Value,
+    Out: Value,
+{
+    pub fn new(graph: ComputeGraph<In, Out>) -> Self {
+        let handles = Arc::new(graph.nodes.iter().map(|node| node.handle).collect());
+        let frame = |handles| Frame {
+            generation: 0,
+            handles,
+            outputs: graph.new_context().outputs,
+            _output: PhantomData,
+        };
+        let front = frame(Arc::clone(&handles));
+        let back = frame(handles);
+        Self {
+            writer: Mutex::new(Writer {
+                graph,
+                back: Arc::new(back),
+            }),
+            reader: FrameReader {
+                front: Arc::new(ArcSwap::from_pointee(front)),
+            },
+        }
+    }
+
+    pub fn reader(&self) -> FrameReader<Out> {
+        self.reader.clone()
+    }
+
+    pub fn latest(&self) -> Arc<Frame<Out>> {
+        self.reader.latest()
+    }
+
+    /// Evaluates the graph through `ComputeGraph::try_compute_mut` and publishes the
+    /// outputs as the next frame, returning its generation. Evaluations are serialized;
+    /// a failed one publishes nothing.
+    pub fn try_compute(&self, input: &In) -> Result<u64, ComputeGraphErrors> {
+        let mut writer = self.writer.lock().unwrap();
+        let Writer { graph, back } = &mut *writer;
+        graph.try_compute_mut(input)?;
+
+        // Readers still holding the back buffer get to keep it
+        if Arc::get_mut(back).is_none() {
+            *back = Arc::new(Frame {
+                generation: 0,
+                handles: back.handles.clone(),
+                outputs: graph.new_context().outputs,
+                _output: PhantomData,
+            });
+        }
+        let frame = Arc::get_mut(back).unwrap();
+        let generation = self.reader.front.load().generation + 1;
+        frame.generation = generation;
+        graph.with_context(|context| {
+            for ((node, from), to) in graph
+                .nodes
+                .iter()
+                .zip(context.outputs.iter())
+                .zip(frame.outputs.iter_mut())
+            {
+                node.func.copy_output(from.as_ref(), to.as_mut());
+            }
+        });
+        let published = back.clone();
+        *back = self.reader.front.swap(published);
+        Ok(generation)
+    }
+}
+
+#[cfg(test)]
+mod buffered_tests {
+    use crate::prelude::*;
+    use std::sync::mpsc::{channel, Receiver};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    /// Running sum of its inputs, blocking on every call until it is released.
+    #[derive(Clone)]
+    struct Gated {
+        total: f64,
+        gate: Arc<Mutex<Receiver<()>>>,
+    }
+
+    impl Compute for Gated {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            self.total + inputs.iter().copied().sum::<f64>()
+        }
+        fn try_compute_mut(&mut self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+            self.gate
+                .lock()
+                .unwrap()
+                .recv()
+                .map_err(|err| err.to_string())?;
+            self.total = self.compute(inputs);
+            Ok(self.total)
+        }
+    }
+
+    #[test]
+    fn test_read_during_compute() -> Result<(), ComputeGraphErrors> {
+        let (release, gate) = channel();
+        let mut graph = Graph::new();
+        let sum_handle = graph.insert_node(
+            "sum",
+            Gated {
+                total: 0.0,
+                gate: Arc::new(Mutex::new(gate)),
+            },
+        );
+        graph.set_output_node(&sum_handle)?;
+        let buffered = Arc::new(DoubleBuffered::new(graph.build::<f64, f64>()?));
+        let reader = buffered.reader();
+        assert_eq!(reader.latest().generation(), 0);
+
+        release.send(()).unwrap();
+        assert_eq!(buffered.try_compute(&2.0)?, 1);
+        let first = reader.latest();
+
+        // The second evaluation blocks in `sum` while the first frame is still readable
+        let writer = buffered.clone();
+        let computing = thread::spawn(move || writer.try_compute(&3.0));
+        assert_eq!(*reader.latest().output(), 2.0);
+        release.send(()).unwrap();
+        assert_eq!(computing.join().unwrap()?, 2);
+
+        let second = reader.latest();
+        assert_eq!(second.get::<f64>(&sum_handle), Some(&5.0));
+        // Still held above, so the frame wasn't reused for the second evaluation
+        assert_eq!((first.generation(), *first.output()), (1, 2.0));
+        Ok(())
+    }
+}
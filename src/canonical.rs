@@ -0,0 +1,199 @@
+use crate::checkpoint::hash_operation;
+use crate::graph::{Graph, GraphKey};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// FNV-1a over little-endian integers, so stable ids don't depend on the platform or on
+/// the Rust release like `DefaultHasher` does.
+struct StableHasher(u64);
+
+impl StableHasher {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+}
+
+impl Graph {
+    /// Node keys in canonical order: a topological order where ties are broken by each
+    /// node's stable id, a hash of its name, operation, parameters and the stable ids of its
+    /// inputs. The order only depends on the insertion order for nodes that can't be told
+    /// apart. Nodes on a cycle come last.
+    pub(crate) fn canonical_order(&self) -> Vec<GraphKey> {
+        let positions = self
+            .nodes
+            .keys()
+            .enumerate()
+            .map(|(position, key)| (key, position))
+            .collect::<HashMap<_, _>>();
+        let mut consumers = HashMap::<GraphKey, Vec<GraphKey>>::new();
+        let mut pending = HashMap::new();
+        for (key, node) in self.nodes.iter() {
+            for input in node.inputs.iter() {
+                consumers.entry(*input).or_default().push(key);
+            }
+            pending.insert(key, node.inputs.len());
+        }
+
+        let mut stable_ids = HashMap::new();
+        let stable_id = |key: GraphKey, stable_ids: &HashMap<GraphKey, u64>| {
+            let node = &self.nodes[key];
+            let mut hasher = StableHasher::new();
+            node.name.hash(&mut hasher);
+            node.connected_to_input.hash(&mut hasher);
+            hash_operation(node.inner.as_ref(), &mut hasher);
+            for input in node.inputs.iter() {
+                stable_ids
+                    .get(input)
+                    .copied()
+                    .unwrap_or(0)
+                    .hash(&mut hasher);
+            }
+            (hasher.finish(), positions[&key], key)
+        };
+        let mut ready = BinaryHeap::new();
+        for (key, _) in pending.iter().filter(|(_, count)| **count == 0) {
+            ready.push(Reverse(stable_id(*key, &stable_ids)));
+        }
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(Reverse((id, _, key))) = ready.pop() {
+            stable_ids.insert(key, id);
+            order.push(key);
+            for consumer in consumers.get(&key).into_iter().flatten() {
+                let count = pending.get_mut(consumer).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(Reverse(stable_id(*consumer, &stable_ids)));
+                }
+            }
+        }
+
+        let mut cyclic = self
+            .nodes
+            .keys()
+            .filter(|key| !stable_ids.contains_key(key))
+            .map(|key| stable_id(key, &stable_ids))
+            .collect::<Vec<_>>();
+        cyclic.sort();
+        order.extend(cyclic.into_iter().map(|(_, _, key)| key));
+        order
+    }
+
+    /// Copy of the graph with its nodes stored in canonical order and their tags sorted, so
+    /// graphs built in a different order serialize, fingerprint and sign the same.
+    /// Handles of this graph don't resolve in the copy.
+    pub fn canonicalize(&self) -> Graph {
+        let mut graph = Graph::new();
+        graph.type_names = self.type_names.clone();
+        graph.limits = self.limits.clone();
+        graph.provenance = self.provenance.clone();
+        let mut keys = HashMap::new();
+        for key in self.canonical_order() {
+            let mut node = self.nodes[key].as_ref().clone();
+            node.tags.sort();
+            keys.insert(key, graph.nodes.insert(Arc::new(node)));
+        }
+        for node in graph.nodes.values_mut() {
+            let node = Arc::make_mut(node);
+            for input in node.inputs.iter_mut().chain(node.after.iter_mut()) {
+                *input = keys[input];
+            }
+        }
+        graph.output_node = self.output_node.map(|key| keys[&key]);
+        graph
+    }
+}
+
+#[cfg(test)]
+mod canonical_tests {
+    use crate::prelude::*;
+
+    fn build(reversed: bool) -> Graph {
+        let mut graph = Graph::new();
+        let mut insert = |name: &str, value: f64| graph.insert_node(name, Constant(value));
+        let (a_handle, b_handle) = if reversed {
+            let b_handle = insert("b", 2.0);
+            (insert("a", 1.0), b_handle)
+        } else {
+            (insert("a", 1.0), insert("b", 2.0))
+        };
+        let sub_handle = graph.insert_node("sub", SubInputs::<f64>::new());
+        graph.add_input(&sub_handle, &b_handle).unwrap();
+        graph.add_input(&sub_handle, &a_handle).unwrap();
+        graph
+            .add_tag(&sub_handle, if reversed { "y" } else { "x" })
+            .unwrap();
+        graph
+            .add_tag(&sub_handle, if reversed { "x" } else { "y" })
+            .unwrap();
+        graph.set_output_node(&sub_handle).unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_canonical_order() -> Result<(), ComputeGraphErrors> {
+        let (first, second) = (build(false), build(true));
+        assert_eq!(first.fingerprint(), second.fingerprint());
+        let names = |graph: &Graph| {
+            graph
+                .get_all_node_metas()
+                .into_iter()
+                .map(|meta| (meta.name, meta.tags))
+                .collect::<Vec<_>>()
+        };
+        let canonical = first.canonicalize();
+        assert_eq!(names(&canonical), names(&second.canonicalize()));
+        assert_eq!(names(&canonical).last().unwrap().1, ["x", "y"]);
+        // Input order is part of what a node computes and is kept
+        assert_eq!(
+            canonical.clone().build::<(), f64>()?.compute(&()),
+            first.clone().build::<(), f64>()?.compute(&())
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_canonical_serialization() -> Result<(), ComputeGraphErrors> {
+        let mut registry = NodeRegistry::new();
+        registry.register::<Constant<f64>, _, _>("constant")?;
+        registry.register::<SubInputs<f64>, _, _>("sub")?;
+        let json = build(false).to_json(&registry)?;
+        assert_eq!(json, build(true).to_json(&registry)?);
+        assert_eq!(
+            Graph::from_json(&json, &registry)?.to_json(&registry)?,
+            json
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use crate::com_graph::ComputeGraph;
+use crate::graph::{ComputeGraphErrors, Graph, NodeHandle};
+use crate::registry::NodeRegistry;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+/// What an op needs from its host, declared with `RegistryEntry::declare`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Output depends only on the inputs. Always granted.
+    Pure,
+    SideEffecting,
+    Network,
+    Filesystem,
+}
+
+/// Capabilities a host grants to the graphs it builds with `Graph::build_with_capabilities`.
+/// Declarations are copied from the registry when the policy is created.
+#[derive(Clone, Debug, Default)]
+pub struct CapabilityPolicy {
+    declared: HashMap<TypeId, (String, Vec<Capability>)>,
+    granted: Vec<Capability>,
+    allow_unregistered: bool,
+}
+
+impl CapabilityPolicy {
+    pub fn new(registry: &NodeRegistry) -> Self {
+        let declared = registry
+            .entries()
+            .map(|entry| {
+                let capabilities = entry.capabilities().to_vec();
+                (entry.type_id(), (entry.name().to_string(), capabilities))
+            })
+            .collect();
+        Self {
+            declared,
+            granted: vec![Capability::Pure],
+            allow_unregistered: false,
+        }
+    }
+
+    pub fn grant(mut self, capability: Capability) -> Self {
+        if !self.granted.contains(&capability) {
+            self.granted.push(capability);
+        }
+        self
+    }
+
+    /// Lets graphs use ops missing from the registry, whose capabilities are unknown.
+    pub fn allow_unregistered(mut self) -> Self {
+        self.allow_unregistered = true;
+        self
+    }
+
+    pub fn is_granted(&self, capability: Capability) -> bool {
+        self.granted.contains(&capability)
+    }
+
+    fn check(&self, node_name: &str, op: TypeId) -> Result<(), ComputeGraphErrors> {
+        let Some((op_name, capabilities)) = self.declared.get(&op) else {
+            if self.allow_unregistered {
+                return Ok(());
+            }
+            return Err(ComputeGraphErrors::CapabilityDenied(
+                format!("'{}' uses an unregistered op", node_name).into(),
+            ));
+        };
+        match capabilities.iter().find(|c| !self.is_granted(**c)) {
+            Some(capability) => Err(ComputeGraphErrors::CapabilityDenied(
+                format!(
+                    "'{}' uses op '{}' which needs {:?}",
+                    node_name, op_name, capability
+                )
+                .into(),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Graph {
+    /// Like `build`, but fails if any node of the graph needs a capability `policy` doesn't grant.
+    pub fn build_with_capabilities<In, Out>(
+        &mut self,
+        policy: &CapabilityPolicy,
+    ) -> Result<ComputeGraph<In, Out>, ComputeGraphErrors>
+    where
+        In: Any + Clone,
+        Out: Any + Clone,
+    {
+        for (key, node) in self.nodes.iter() {
+            let op = Any::type_id(node.inner.as_any());
+            policy.check(&node.name, op).map_err(|err| {
+                err.with_node(NodeHandle {
+                    key,
+                    graph_id: self.id,
+                })
+                .with_type(op, node.inner.type_name())
+            })?;
+        }
+        self.build()
+    }
+}
+
+#[cfg(test)]
+mod capabilities_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_build_with_capabilities() -> Result<(), ComputeGraphErrors> {
+        let mut registry = NodeRegistry::new();
+        registry
+            .register::<Constant<f64>, _, _>("constant")?
+            .declare(Capability::Pure);
+        registry
+            .register::<AddInputs<f64>, _, _>("add")?
+            .declare(Capability::Network)
+            .declare(Capability::SideEffecting);
+        assert!(registry.get("constant").unwrap().is_pure());
+        assert!(!registry.get("add").unwrap().is_pure());
+
+        let mut graph = Graph::new();
+        let one_handle = graph.insert_node("one", Constant(1.0));
+        let add_handle = graph.insert_node("fetch", AddInputs::<f64>::new());
+        graph.add_input(&add_handle, &one_handle)?;
+        graph.connect_to_input(&add_handle)?;
+        graph.set_output_node(&add_handle)?;
+
+        let policy = CapabilityPolicy::new(&registry).grant(Capability::Network);
+        let Err(ComputeGraphErrors::CapabilityDenied(message)) =
+            graph.build_with_capabilities::<f64, f64>(&policy)
+        else {
+            panic!("capability not enforced");
+        };
+        assert_eq!(message, "'fetch' uses op 'add' which needs SideEffecting");
+
+        let policy = policy.grant(Capability::SideEffecting);
+        let compute_graph = graph.build_with_capabilities::<f64, f64>(&policy)?;
+        assert_eq!(compute_graph.compute(&2.0), 3.0);
+
+        graph.insert_node("scale", MulInputs::<f64>::new());
+        assert!(graph.build_with_capabilities::<f64, f64>(&policy).is_err());
+        let policy = policy.allow_unregistered();
+        assert!(graph.build_with_capabilities::<f64, f64>(&policy).is_ok());
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+use crate::com_graph::{ComputeGraph, EvalContext};
+use crate::compute::InnerCompute;
+use crate::graph::ComputeGraphErrors;
+use crate::params::ParamValue;
+use crate::report::ComputeReport;
+use std::any::{Any, TypeId};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Storage for completed node outputs, keyed by node fingerprint.
+/// Implement this over a file or database to make evaluations resumable across processes.
+pub trait CheckpointStore {
+    fn load(&self, key: u64) -> Option<&(dyn Any + Send + Sync)>;
+    fn save(&mut self, key: u64, value: Box<dyn Any + Send + Sync>);
+}
+
+/// In-memory `CheckpointStore`, enough to resume after a panic or an aborted run.
+#[derive(Default)]
+pub struct MemoryCheckpoints {
+    values: HashMap<u64, Box<dyn Any + Send + Sync>>,
+}
+
+impl MemoryCheckpoints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+}
+
+impl CheckpointStore for MemoryCheckpoints {
+    fn load(&self, key: u64) -> Option<&(dyn Any + Send + Sync)> {
+        self.values.get(&key).map(|value| value.as_ref())
+    }
+
+    fn save(&mut self, key: u64, value: Box<dyn Any + Send + Sync>) {
+        self.values.insert(key, value);
+    }
+}
+
+/// Hashes the operation type, its parameters and its `Compute::fingerprint`.
+pub(crate) fn hash_operation<H: Hasher>(func: &dyn InnerCompute, hasher: &mut H) {
+    func.type_name().hash(hasher);
+    if let Some(params) = func.parameters() {
+        for param in params.params() {
+            param.name.hash(hasher);
+            match param.value {
+                ParamValue::Bool(value) => value.hash(hasher),
+                ParamValue::Int(value) => value.hash(hasher),
+                ParamValue::UInt(value) => value.hash(hasher),
+                ParamValue::Float(value) => value.to_bits().hash(hasher),
+            }
+        }
+    }
+    func.fingerprint(hasher);
+}
+
+impl<In, Out> ComputeGraph<In, Out> {
+    /// One hash per node, covering its name, operation, parameters, the revision its operation
+    /// was inserted or replaced at, and everything upstream of it.
+    pub fn node_fingerprints(&self) -> Vec<u64> {
+        let mut fingerprints: Vec<u64> = Vec::with_capacity(self.nodes.len());
+        for node in self.nodes.iter() {
+            let mut hasher = DefaultHasher::new();
+            node.name.hash(&mut hasher);
+            node.connected_to_input.hash(&mut hasher);
+            hash_operation(node.func.as_ref(), &mut hasher);
+            node.op_revision.hash(&mut hasher);
+            for input in node.inputs.iter() {
+                fingerprints[*input].hash(&mut hasher);
+            }
+            fingerprints.push(hasher.finish());
+        }
+        fingerprints
+    }
+
+    /// Like `compute`, but every finished node output is saved to `store`, and nodes whose
+    /// output is already stored are skipped and reported as cached. `input_key` must identify
+    /// `input`; it is mixed into the keys of all nodes that depend on the graph input.
+    pub fn compute_checkpointed(
+        &self,
+        input: &In,
+        input_key: u64,
+        store: &mut dyn CheckpointStore,
+    ) -> Result<ComputeReport<Out>, ComputeGraphErrors>
+    where
+        In: Any + Clone,
+        Out: Any + Clone,
+    {
+        let keys = self.checkpoint_keys(input_key);
+        self.evaluate(input, None, Some((&keys, store)))
+    }
+
+    fn checkpoint_keys(&self, input_key: u64) -> Vec<u64> {
+        let fingerprints = self.node_fingerprints();
+        let mut uses_input = Vec::with_capacity(self.nodes.len());
+        let mut keys = Vec::with_capacity(self.nodes.len());
+        for (i, node) in self.nodes.iter().enumerate() {
+            let reads_input =
+                node.connected_to_input && node.func.input_type() != TypeId::of::<()>();
+            uses_input.push(reads_input || node.inputs.iter().any(|input| uses_input[*input]));
+            keys.push(if uses_input[i] {
+                let mut hasher = DefaultHasher::new();
+                fingerprints[i].hash(&mut hasher);
+                input_key.hash(&mut hasher);
+                hasher.finish()
+            } else {
+                fingerprints[i]
+            });
+        }
+        keys
+    }
+
+    pub(crate) fn load_checkpoint(
+        &self,
+        index: usize,
+        key: u64,
+        store: &dyn CheckpointStore,
+        context: &mut EvalContext,
+    ) -> bool {
+        let func = &self.nodes[index].func;
+        match store.load(key) {
+            Some(saved) if (*saved).type_id() == func.output_type() => {
+                context.set_output(index, func.clone_output(saved));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn save_checkpoint(
+        &self,
+        index: usize,
+        key: u64,
+        store: &mut dyn CheckpointStore,
+        context: &EvalContext,
+    ) {
+        let output = self.nodes[index]
+            .func
+            .clone_output(context.outputs[index].as_ref());
+        store.save(key, output);
+    }
+}
+
+#[cfg(test)]
+mod checkpoint_tests {
+    use crate::prelude::*;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Expensive {
+        calls: Arc<AtomicUsize>,
+        fail: Arc<AtomicBool>,
+    }
+
+    impl Compute for Expensive {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail.load(Ordering::SeqCst) {
+                panic!("interrupted");
+            }
+            inputs.iter().copied().sum::<f64>() * 2.0
+        }
+    }
+
+    #[test]
+    fn test_resume_after_interrupt() -> Result<(), ComputeGraphErrors> {
+        let first = Expensive::default();
+        let second = Expensive::default();
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("the_answer", Constant(42.0));
+        let first_handle = graph.insert_node("first", first.clone());
+        let second_handle = graph.insert_node("second", second.clone());
+        graph.add_input(&first_handle, &const_handle)?;
+        graph.add_input(&second_handle, &first_handle)?;
+        graph.connect_to_input(&first_handle)?;
+        graph.set_output_node(&second_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+
+        let mut store = MemoryCheckpoints::new();
+        second.fail.store(true, Ordering::SeqCst);
+        let interrupted = panic::catch_unwind(AssertUnwindSafe(|| {
+            compute_graph.compute_checkpointed(&1.0, 1, &mut store)
+        }));
+        assert!(interrupted.is_err());
+        assert_eq!(store.len(), 2);
+
+        second.fail.store(false, Ordering::SeqCst);
+        assert_eq!(
+            compute_graph
+                .compute_checkpointed(&1.0, 1, &mut store)?
+                .output,
+            172.0
+        );
+        assert_eq!(first.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(second.calls.load(Ordering::SeqCst), 2);
+
+        assert_eq!(
+            compute_graph
+                .compute_checkpointed(&1.0, 1, &mut store)?
+                .output,
+            172.0
+        );
+        assert_eq!(second.calls.load(Ordering::SeqCst), 2);
+
+        // A new input only reruns the nodes downstream of it
+        assert_eq!(
+            compute_graph
+                .compute_checkpointed(&2.0, 2, &mut store)?
+                .output,
+            176.0
+        );
+        assert_eq!(first.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(store.len(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fingerprints_follow_params() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("the_answer", Constant(42.0));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&add_handle, &const_handle)?;
+        graph.set_output_node(&add_handle)?;
+        let mut compute_graph = graph.build::<(), f64>()?;
+
+        let before = compute_graph.node_fingerprints();
+        compute_graph.set_param(&const_handle, "value", 1.0)?;
+        let after = compute_graph.node_fingerprints();
+        assert_ne!(before[0], after[0]);
+        assert_ne!(before[1], after[1]);
+        assert_eq!(before, graph.build::<(), f64>()?.node_fingerprints());
+        Ok(())
+    }
+
+    /// Configuration outside `parameters` and `fingerprint`.
+    #[derive(Clone)]
+    struct Scale(f64);
+
+    impl Compute for Scale {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            *inputs[0] * self.0
+        }
+    }
+
+    #[test]
+    fn test_replaced_nodes_miss_checkpoints() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let scale_handle = graph.insert_node("scale", Scale(2.0));
+        graph.set_output_node(&scale_handle)?;
+        let mut store = MemoryCheckpoints::new();
+        let compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(
+            compute_graph
+                .compute_checkpointed(&1.0, 1, &mut store)?
+                .output,
+            2.0
+        );
+
+        graph.replace_node(&scale_handle, Scale(3.0))?;
+        let compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(
+            compute_graph
+                .compute_checkpointed(&1.0, 1, &mut store)?
+                .output,
+            3.0
+        );
+        assert_eq!(store.len(), 2);
+        Ok(())
+    }
+}
@@ -1,69 +1,429 @@
-use crate::compute::InnerCompute;
-use std::any::{Any, TypeId};
-use std::cell::RefCell;
+use crate::compute::{InnerCompute, Value, STACK_INPUTS};
+use crate::graph::{ComputeGraphErrors, NodeHandle};
+use crate::operations::Parameter;
+use crate::params::{ParamError, ParamInfo, ParamValue};
+use crate::policy::{NodePolicy, PolicyOutcome};
+use crate::scheduling::Scheduling;
+use std::any::{type_name, Any, TypeId};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::Hasher;
 use std::marker::PhantomData;
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
 #[derive(Clone)]
 pub(crate) struct ComputeNode {
+    pub(crate) handle: NodeHandle,
+    pub(crate) name: String,
     pub(crate) connected_to_input: bool,
     pub(crate) inputs: Vec<usize>,
+    /// Nodes ordered before this one that aren't among its inputs.
+    pub(crate) after: Vec<usize>,
     pub(crate) func: Box<dyn InnerCompute + 'static>,
+    /// See `Node::op_revision`.
+    pub(crate) op_revision: u64,
+    pub(crate) policy: NodePolicy,
+    pub(crate) critical: bool,
+    pub(crate) scheduling: Scheduling,
+    pub(crate) probed: bool,
+}
+
+/// Every node's latest output, the scratch space of an evaluation. `ComputeGraph` keeps a
+/// pool of these, so several threads can evaluate the same graph at once.
+pub struct EvalContext {
+    pub(crate) outputs: Vec<Box<dyn Any + Send + Sync>>,
+    /// Input hash each output of a `Compute::cacheable` node was computed from.
+    input_hashes: Vec<Option<u64>>,
 }
 
 pub struct ComputeGraph<In, Out> {
-    outputs: Vec<RefCell<Box<dyn Any>>>,
-    nodes: Vec<ComputeNode>,
-    _intype: PhantomData<In>,
-    _outtype: PhantomData<Out>,
+    pub(crate) nodes: Vec<ComputeNode>,
+    contexts: Mutex<Vec<EvalContext>>,
+    /// Held while a node that isn't `Scheduling::Free` runs.
+    effects: Mutex<()>,
+    /// Mixed into the input hashes of cacheable nodes, bumped when their parameters change.
+    pub(crate) cache_generation: u64,
+    /// Latest outputs of the probed nodes by index, see `Graph::probe`.
+    pub(crate) probes: Mutex<HashMap<usize, Box<dyn Any + Send + Sync>>>,
+    /// Indices of the nodes by dependency level, for `compute_parallel`.
+    #[cfg(feature = "rayon")]
+    pub(crate) levels: Vec<Vec<usize>>,
+    _types: PhantomData<fn(&In) -> Out>,
 }
 
 impl<In, Out> ComputeGraph<In, Out> {
     pub(crate) fn new(nodes: Vec<ComputeNode>) -> Self {
-        let outputs = nodes
-            .iter()
-            .map(|node| RefCell::new(node.func.init_output()))
-            .collect::<Vec<_>>();
-        Self {
-            outputs,
+        let graph = Self {
+            contexts: Mutex::new(Vec::new()),
+            effects: Mutex::new(()),
+            cache_generation: 0,
+            probes: Mutex::new(HashMap::new()),
+            #[cfg(feature = "rayon")]
+            levels: crate::parallel::dependency_levels(&nodes),
             nodes,
-            _intype: PhantomData,
-            _outtype: PhantomData,
+            _types: PhantomData,
+        };
+        // Allocated up front, so single threaded evaluation doesn't allocate
+        let context = graph.new_context();
+        graph.contexts.lock().unwrap().push(context);
+        graph
+    }
+
+    /// Fresh scratch space for `try_compute_with`, with every node output at its default.
+    pub fn new_context(&self) -> EvalContext {
+        EvalContext {
+            outputs: self
+                .nodes
+                .iter()
+                .map(|node| node.func.init_output())
+                .collect(),
+            input_hashes: vec![None; self.nodes.len()],
+        }
+    }
+
+    /// Runs `f` with a context from the pool, so sequential evaluations see the outputs of
+    /// the previous one, which stale fallbacks and time budgets rely on.
+    pub(crate) fn with_context<R>(&self, f: impl FnOnce(&mut EvalContext) -> R) -> R {
+        let context = self.contexts.lock().unwrap().pop();
+        let mut context = context.unwrap_or_else(|| self.new_context());
+        let result = f(&mut context);
+        self.contexts.lock().unwrap().push(context);
+        result
+    }
+
+    /// Keeps nodes with side effects from running concurrently, see `Scheduling`.
+    pub(crate) fn effect_guard(&self, index: usize) -> Option<MutexGuard<'_, ()>> {
+        let scheduling = self.nodes[index].scheduling;
+        // A panicking node doesn't leave anything behind the lock to be cleaned up
+        (!scheduling.is_free()).then(|| self.effects.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn check_context(&self, context: &EvalContext) -> Result<(), ComputeGraphErrors> {
+        let matches = context.outputs.len() == self.nodes.len()
+            && context
+                .outputs
+                .iter()
+                .zip(self.nodes.iter())
+                .all(|(output, node)| (**output).type_id() == node.func.output_type());
+        if !matches {
+            return Err(ComputeGraphErrors::InvalidParameter(
+                "context was created for another graph".into(),
+            ));
         }
+        Ok(())
     }
 
+    pub fn get_params(&self, node_handle: &NodeHandle) -> Vec<ParamInfo> {
+        self.nodes
+            .iter()
+            .find(|node| node.handle == *node_handle)
+            .and_then(|node| node.func.parameters())
+            .map(|params| params.params())
+            .unwrap_or_default()
+    }
+
+    pub fn set_param<V: Into<ParamValue>>(
+        &mut self,
+        node_handle: &NodeHandle,
+        name: &str,
+        value: V,
+    ) -> Result<(), ComputeGraphErrors> {
+        let node = self
+            .nodes
+            .iter_mut()
+            .find(|node| node.handle == *node_handle)
+            .ok_or(ComputeGraphErrors::NodeMissing)?;
+        self.cache_generation += 1;
+        let result = match node.func.parameters_mut() {
+            Some(params) => params.set_param(name, value.into()),
+            None => Err(ParamError::UnknownParam(name.to_string())),
+        };
+        result.map_err(|err| {
+            ComputeGraphErrors::format_param_error(&node.name, &err).with_node(node.handle)
+        })
+    }
+
+    /// Sets the value of every `Parameter<T>` node called `name`, taking effect from the
+    /// next evaluation without rebuilding the graph.
+    pub fn set_parameter<T: Value>(
+        &mut self,
+        name: &str,
+        value: T,
+    ) -> Result<(), ComputeGraphErrors> {
+        let mut found = false;
+        for node in self.nodes.iter_mut() {
+            if let Some(parameter) = node
+                .func
+                .as_any_mut()
+                .downcast_mut::<Parameter<T>>()
+                .filter(|parameter| parameter.name == name)
+            {
+                parameter.value = value.clone();
+                found = true;
+                self.cache_generation += 1;
+            }
+        }
+        if !found {
+            return Err(ComputeGraphErrors::InvalidParameter(
+                format!("no parameter '{}' of type '{}'", name, type_name::<T>()).into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Evaluates the graph, panicking if a node fails. See `try_compute`.
     pub fn compute(&self, input: &In) -> Out
     where
-        In: Any + Copy,
-        Out: Any + Copy,
+        In: Any + Clone,
+        Out: Any + Clone,
     {
-        for (i, node) in self.nodes.iter().enumerate() {
-            let mut output = self.outputs[i].borrow_mut();
-            if node.func.input_type() == TypeId::of::<()>() {
-                node.func.inner_compute(&[], output.as_mut());
-            } else {
-                let inp = node
-                    .inputs
-                    .iter()
-                    .map(|inp| self.outputs[*inp].borrow())
-                    .collect::<Vec<_>>();
-
-                let mut inp_refs = inp.iter().map(|inp| inp.as_ref()).collect::<Vec<_>>();
-
-                if node.connected_to_input {
-                    inp_refs.push(input);
-                }
-
-                node.func.inner_compute(&inp_refs, output.as_mut());
-            }
+        self.try_compute(input).unwrap()
+    }
+
+    /// Evaluates the graph, applying each node's `NodePolicy` when `Compute::try_compute` fails.
+    /// Can be called from several threads at once.
+    pub fn try_compute(&self, input: &In) -> Result<Out, ComputeGraphErrors>
+    where
+        In: Any + Clone,
+        Out: Any + Clone,
+    {
+        self.with_context(|context| self.try_compute_with(input, context))
+    }
+
+    /// Like `try_compute`, with the node outputs kept in `context`.
+    pub fn try_compute_with(
+        &self,
+        input: &In,
+        context: &mut EvalContext,
+    ) -> Result<Out, ComputeGraphErrors>
+    where
+        In: Any + Clone,
+        Out: Any + Clone,
+    {
+        Ok(self.try_compute_ref(input, context)?.clone())
+    }
+
+    /// Like `try_compute_with`, but borrows the output from `context` instead of cloning it,
+    /// for heavy outputs like buffers and images.
+    pub fn try_compute_ref<'a>(
+        &self,
+        input: &In,
+        context: &'a mut EvalContext,
+    ) -> Result<&'a Out, ComputeGraphErrors>
+    where
+        In: Any,
+        Out: Any,
+    {
+        self.check_context(context)?;
+        for i in 0..self.nodes.len() {
+            self.compute_node(i, input, context)?;
         }
-        *self
-            .outputs
-            .last()
-            .unwrap()
-            .borrow()
-            .as_ref()
-            .downcast_ref::<Out>()
+        Ok(context.output())
+    }
+
+    /// Like `try_compute`, evaluating the nodes through `Compute::try_compute_mut` so
+    /// stateful nodes carry their state to the next call. Clones of the graph copy the state.
+    pub fn try_compute_mut(&mut self, input: &In) -> Result<Out, ComputeGraphErrors>
+    where
+        In: Any + Clone,
+        Out: Any + Clone,
+    {
+        let mut context = self
+            .contexts
+            .get_mut()
             .unwrap()
+            .pop()
+            .unwrap_or_else(|| self.new_context());
+        let result = (0..self.nodes.len())
+            .try_for_each(|i| self.compute_node_mut(i, input, &mut context).map(|_| ()))
+            .map(|()| context.output::<Out>().clone());
+        self.contexts.get_mut().unwrap().push(context);
+        result
+    }
+
+    /// Evaluates the graph with stateful nodes, panicking if a node fails. See `try_compute_mut`.
+    pub fn compute_mut(&mut self, input: &In) -> Out
+    where
+        In: Any + Clone,
+        Out: Any + Clone,
+    {
+        self.try_compute_mut(input).unwrap()
+    }
+
+    /// Evaluates node `index` into `context`. Its inputs come before it in compute order.
+    pub(crate) fn compute_node(
+        &self,
+        index: usize,
+        input: &In,
+        context: &mut EvalContext,
+    ) -> Result<PolicyOutcome, ComputeGraphErrors>
+    where
+        In: Any,
+    {
+        let node = &self.nodes[index];
+        let EvalContext {
+            outputs,
+            input_hashes,
+        } = context;
+        let (earlier, rest) = outputs.split_at_mut(index);
+        let output = rest[0].as_mut();
+        let input_hash = &mut input_hashes[index];
+        let graph_input = node.connected_to_input.then_some(input as &dyn Any);
+        if node.func.input_type() == TypeId::of::<()>() {
+            self.run_node(index, &[], output, input_hash)
+        } else {
+            with_node_inputs(earlier, &node.inputs, graph_input, |inputs| {
+                self.run_node(index, inputs, output, input_hash)
+            })
+        }
+    }
+
+    /// Runs node `index` on `inputs` through its `NodePolicy`, into `output`, skipping it if
+    /// `input_hash` shows `output` is up to date. Records probes and holds the effect lock.
+    pub(crate) fn run_node(
+        &self,
+        index: usize,
+        inputs: &[&dyn Any],
+        output: &mut dyn Any,
+        input_hash: &mut Option<u64>,
+    ) -> Result<PolicyOutcome, ComputeGraphErrors> {
+        let node = &self.nodes[index];
+        let _effect = self.effect_guard(index);
+        let result = self.run_cached(index, inputs, input_hash, || {
+            node.policy
+                .run(&node.name, node.func.as_ref(), inputs, output)
+        });
+        if node.probed && result.is_ok() {
+            self.record_probe(index, output);
+        }
+        result.map_err(|err| err.with_node(node.handle))
+    }
+
+    /// Hash of the inputs of node `index` if it is `Compute::cacheable` and they can be
+    /// hashed, see `Compute::hash_inputs`.
+    fn input_hash(&self, index: usize, inputs: &[&dyn Any]) -> Option<u64> {
+        let func = &self.nodes[index].func;
+        if !func.cacheable() {
+            return None;
+        }
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u64(self.cache_generation);
+        func.hash_inputs(inputs, &mut hasher)
+            .then(|| hasher.finish())
+    }
+
+    /// Calls `run` unless node `index` already holds the output for `inputs`, going by
+    /// `input_hash`, which is updated to the hash of the inputs of the new output.
+    fn run_cached(
+        &self,
+        index: usize,
+        inputs: &[&dyn Any],
+        input_hash: &mut Option<u64>,
+        run: impl FnOnce() -> Result<PolicyOutcome, ComputeGraphErrors>,
+    ) -> Result<PolicyOutcome, ComputeGraphErrors> {
+        let key = self.input_hash(index, inputs);
+        if key.is_some() && *input_hash == key {
+            return Ok(PolicyOutcome::cached());
+        }
+        let result = run();
+        *input_hash = cache_key(key, &result);
+        result
+    }
+
+    fn compute_node_mut(
+        &mut self,
+        index: usize,
+        input: &In,
+        context: &mut EvalContext,
+    ) -> Result<PolicyOutcome, ComputeGraphErrors>
+    where
+        In: Any,
+    {
+        let (handle, probed) = (self.nodes[index].handle, self.nodes[index].probed);
+        let EvalContext {
+            outputs,
+            input_hashes,
+        } = context;
+        let (earlier, rest) = outputs.split_at_mut(index);
+        let output = rest[0].as_mut();
+        let graph_input = self.nodes[index]
+            .connected_to_input
+            .then_some(input as &dyn Any);
+        let key = if self.nodes[index].func.input_type() == TypeId::of::<()>() {
+            self.input_hash(index, &[])
+        } else {
+            with_node_inputs(earlier, &self.nodes[index].inputs, graph_input, |inputs| {
+                self.input_hash(index, inputs)
+            })
+        };
+        let node = &mut self.nodes[index];
+        let result = if key.is_some() && input_hashes[index] == key {
+            Ok(PolicyOutcome::cached())
+        } else if node.func.input_type() == TypeId::of::<()>() {
+            node.policy
+                .run_mut(&node.name, node.func.as_mut(), &[], output)
+        } else {
+            with_node_inputs(earlier, &node.inputs, graph_input, |inputs| {
+                node.policy
+                    .run_mut(&node.name, node.func.as_mut(), inputs, output)
+            })
+        };
+        input_hashes[index] = cache_key(key, &result);
+        if probed && result.is_ok() {
+            self.record_probe(index, rest[0].as_ref());
+        }
+        result.map_err(|err| err.with_node(handle))
+    }
+}
+
+impl EvalContext {
+    /// Output of the last node, the output of the graph.
+    pub(crate) fn output<Out: Any>(&self) -> &Out {
+        self.outputs.last().unwrap().downcast_ref::<Out>().unwrap()
+    }
+
+    /// Replaces the output of node `index` with one computed elsewhere.
+    pub(crate) fn set_output(&mut self, index: usize, output: Box<dyn Any + Send + Sync>) {
+        self.outputs[index] = output;
+        self.input_hashes[index] = None;
+    }
+}
+
+/// Input hash to remember for an output computed with `result`. Fallback outputs aren't
+/// what the node computes for its inputs, so they aren't reused.
+fn cache_key(key: Option<u64>, result: &Result<PolicyOutcome, ComputeGraphErrors>) -> Option<u64> {
+    key.filter(|_| matches!(result, Ok(outcome) if outcome.fallback.is_none()))
+}
+
+/// Calls `f` with the outputs of the nodes at `inputs`, followed by `graph_input`.
+fn with_node_inputs<R>(
+    outputs: &[Box<dyn Any + Send + Sync>],
+    inputs: &[usize],
+    graph_input: Option<&dyn Any>,
+    f: impl FnOnce(&[&dyn Any]) -> R,
+) -> R {
+    if inputs.len() <= STACK_INPUTS {
+        // Nodes with few inputs are evaluated without allocating
+        let mut inp_refs: [&dyn Any; STACK_INPUTS + 1] = [&(); STACK_INPUTS + 1];
+        for (inp_ref, inp) in inp_refs.iter_mut().zip(inputs) {
+            *inp_ref = outputs[*inp].as_ref();
+        }
+        let mut count = inputs.len();
+        if let Some(graph_input) = graph_input {
+            inp_refs[count] = graph_input;
+            count += 1;
+        }
+        f(&inp_refs[..count])
+    } else {
+        let mut inp_refs = inputs
+            .iter()
+            .map(|inp| outputs[*inp].as_ref() as &dyn Any)
+            .collect::<Vec<_>>();
+
+        if let Some(graph_input) = graph_input {
+            inp_refs.push(graph_input);
+        }
+        f(&inp_refs)
     }
 }
 
@@ -72,3 +432,165 @@ impl<In, Out> Clone for ComputeGraph<In, Out> {
         ComputeGraph::new(self.nodes.clone())
     }
 }
+
+#[cfg(test)]
+mod com_graph_tests {
+    use crate::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Running sum of its inputs over all calls.
+    #[derive(Clone, Copy, Default)]
+    struct Accumulator {
+        total: f64,
+    }
+
+    impl Compute for Accumulator {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            self.total + inputs.iter().copied().sum::<f64>()
+        }
+        fn try_compute_mut(&mut self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+            self.total = self.compute(inputs);
+            Ok(self.total)
+        }
+    }
+
+    #[test]
+    fn test_stateful_nodes() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let gain_handle = graph.insert_node("gain", Constant(2.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        let sum_handle = graph.insert_node("sum", Accumulator::default());
+        graph.add_input(&mul_handle, &gain_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.add_input(&sum_handle, &mul_handle)?;
+        graph.set_output_node(&sum_handle)?;
+
+        let mut compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(compute_graph.compute_mut(&1.0), 2.0);
+        assert_eq!(compute_graph.compute_mut(&2.0), 6.0);
+        // `compute` reads the state without changing it
+        assert_eq!(compute_graph.compute(&1.0), 8.0);
+        let mut copy = compute_graph.clone();
+        assert_eq!(compute_graph.compute_mut(&1.0), 8.0);
+        assert_eq!(copy.try_compute_mut(&0.5)?, 7.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_shared_between_threads() -> Result<(), ComputeGraphErrors> {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        let mut graph = Graph::new();
+        let gain_handle = graph.insert_node("gain", Constant(3.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &gain_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.set_output_node(&mul_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+        assert_send_sync(&compute_graph);
+
+        std::thread::scope(|scope| {
+            for thread in 0..4 {
+                let compute_graph = &compute_graph;
+                scope.spawn(move || {
+                    let mut context = compute_graph.new_context();
+                    for x in 0..100 {
+                        let x = (thread * 100 + x) as f64;
+                        assert_eq!(compute_graph.compute(&x), x * 3.0);
+                        assert_eq!(
+                            compute_graph.try_compute_with(&x, &mut context).unwrap(),
+                            x * 3.0
+                        );
+                    }
+                });
+            }
+        });
+
+        let mut other = Graph::new();
+        let handle = other.insert_node("one", Constant(true));
+        other.set_output_node(&handle)?;
+        let mut context = other.build::<(), bool>()?.new_context();
+        assert!(compute_graph.try_compute_with(&1.0, &mut context).is_err());
+        Ok(())
+    }
+
+    /// Pure but expensive node, counting its evaluations.
+    #[derive(Clone)]
+    struct Noise {
+        evaluations: Arc<AtomicUsize>,
+    }
+
+    impl Compute for Noise {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            self.evaluations.fetch_add(1, Ordering::Relaxed);
+            (*inputs[0] * 12.9898).sin().fract()
+        }
+        fn cacheable(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_cacheable_nodes() -> Result<(), ComputeGraphErrors> {
+        let evaluations = Arc::new(AtomicUsize::new(0));
+        let mut graph = Graph::new();
+        let noise_handle = graph.insert_node(
+            "noise",
+            Noise {
+                evaluations: evaluations.clone(),
+            },
+        );
+        let offset_handle = graph.insert_node("offset", Parameter::new("offset", 1.0));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&add_handle, &noise_handle)?;
+        graph.add_input(&add_handle, &offset_handle)?;
+        graph.set_output_node(&add_handle)?;
+        let mut compute_graph = graph.build::<f64, f64>()?;
+
+        let first = compute_graph.compute(&0.5);
+        assert_eq!(compute_graph.compute(&0.5), first);
+        assert_eq!(evaluations.load(Ordering::Relaxed), 1);
+        compute_graph.set_parameter("offset", 2.0)?;
+        assert_eq!(compute_graph.compute(&0.5), first + 1.0);
+        assert_eq!(evaluations.load(Ordering::Relaxed), 2);
+
+        // Only the inputs of the previous evaluation are remembered
+        compute_graph.compute(&0.25);
+        compute_graph.compute(&0.5);
+        assert_eq!(evaluations.load(Ordering::Relaxed), 4);
+        let report = compute_graph.compute_detailed(&0.5)?;
+        assert_eq!(report.with_status(NodeStatus::Cached), [noise_handle]);
+        assert_eq!(compute_graph.compute_mut(&0.5), first + 1.0);
+        assert_eq!(evaluations.load(Ordering::Relaxed), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_parameter() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let rate_handle = graph.insert_node("rate", Parameter::new("rate", 2.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &rate_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.set_output_node(&mul_handle)?;
+        let mut compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(compute_graph.try_compute(&3.0)?, 6.0);
+
+        compute_graph.set_parameter("rate", 5.0)?;
+        assert_eq!(compute_graph.try_compute(&3.0)?, 15.0);
+        assert_eq!(
+            graph.formula(&rate_handle)?,
+            Expr::Symbol("rate".to_string())
+        );
+        assert!(matches!(
+            compute_graph.set_parameter("rate", 5i64),
+            Err(ComputeGraphErrors::InvalidParameter(_))
+        ));
+        assert!(compute_graph.set_parameter("speed", 1.0).is_err());
+        Ok(())
+    }
+}
@@ -1,56 +1,442 @@
+use crate::formula::Expr;
+use crate::params::Parameters;
+use crate::preview::Detail;
 use dyn_clone::DynClone;
-use std::any::{Any, TypeId};
+use std::any::{type_name, Any, TypeId};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
 
-pub trait Compute: Clone {
+/// Inputs, not counting the graph input, a node can have to be evaluated without allocating.
+pub(crate) const STACK_INPUTS: usize = 8;
+
+/// Types that can flow along the edges of a graph.
+pub trait Value: Any + Clone + Default + Send + Sync {}
+impl<T: Any + Clone + Default + Send + Sync> Value for T {}
+
+pub trait Compute: Clone + Send + Sync {
     type In;
     type Out;
     fn compute(&self, inputs: &[&Self::In]) -> Self::Out
     where
-        Self::In: Any + Copy + Default,
-        Self::Out: Any + Copy + Default;
+        Self::In: Value,
+        Self::Out: Value;
+
+    /// Fallible variant of `compute`, used by `ComputeGraph::try_compute`. Nodes wrapping
+    /// unreliable resources override this, and failures are handled by the node's `NodePolicy`.
+    fn try_compute(&self, inputs: &[&Self::In]) -> Result<Self::Out, String>
+    where
+        Self::In: Value,
+        Self::Out: Value,
+    {
+        Ok(self.compute(inputs))
+    }
+
+    /// Stateful variant of `try_compute`, used by `ComputeGraph::try_compute_mut`. Accumulators,
+    /// filters and controllers override this to update their state on every call.
+    fn try_compute_mut(&mut self, inputs: &[&Self::In]) -> Result<Self::Out, String>
+    where
+        Self::In: Value,
+        Self::Out: Value,
+    {
+        self.try_compute(inputs)
+    }
+
+    /// Returns true if `self` is a constant source that yields the same value as `other`.
+    /// Used by `Graph::dedup_constants` to find nodes that can be shared. See `same_primitive`
+    /// for comparing values without requiring `PartialEq` of every output type.
+    fn same_constant(&self, _other: &dyn Any) -> bool {
+        false
+    }
+
+    /// Exposes the node's tunable fields, see `Parameters`.
+    fn parameters(&self) -> Option<&dyn Parameters> {
+        None
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut dyn Parameters> {
+        None
+    }
+
+    /// Fewest inputs the node works with, counting the graph input. `Graph::build` rejects
+    /// nodes wired with fewer with `ComputeGraphErrors::WrongArity`.
+    fn min_inputs(&self) -> usize {
+        0
+    }
+
+    /// Most inputs the node works with, counting the graph input, or `None` for no limit.
+    fn max_inputs(&self) -> Option<usize> {
+        None
+    }
+
+    /// Feeds any configuration not exposed through `parameters` into `state`,
+    /// so node fingerprints change when it does.
+    fn fingerprint(&self, _state: &mut dyn Hasher) {}
+
+    /// Symbolic form of the node given the expressions of its inputs, used by
+    /// `Graph::formula`. Nodes without one are rendered as a function of their inputs.
+    fn formula(&self, _inputs: &[Expr]) -> Option<Expr> {
+        None
+    }
+
+    /// Level of detail the following evaluations should run at, see `ComputeGraph::set_detail`.
+    /// Nodes trading quality for speed, like octaves of noise, do less work at coarse levels.
+    fn set_detail(&mut self, _detail: Detail) {}
+
+    /// Pure nodes that are expensive to evaluate return true to have `ComputeGraph` keep
+    /// their last output while their inputs hash the same, see `hash_inputs`.
+    fn cacheable(&self) -> bool {
+        false
+    }
+
+    /// Feeds `inputs` into `state` for `cacheable` nodes, returning false if they can't be
+    /// hashed, in which case the node is always evaluated. The default handles primitive
+    /// numbers, `bool`, `char` and `String`, and arrays and `Vec`s of floats.
+    fn hash_inputs(&self, inputs: &[&Self::In], state: &mut dyn Hasher) -> bool
+    where
+        Self::In: Value,
+    {
+        state.write_usize(inputs.len());
+        inputs
+            .iter()
+            .all(|input| hash_primitive(*input as &dyn Any, state))
+    }
+}
+
+/// Compares `value` and `other` if both have one of the types `hash_primitive` handles,
+/// returning false for any other type.
+pub(crate) fn same_primitive(value: &dyn Any, other: &dyn Any) -> bool {
+    macro_rules! same {
+        ($($t:ty),*) => {$(
+            if let (Some(value), Some(other)) = (value.downcast_ref::<$t>(), other.downcast_ref::<$t>()) {
+                return value == other;
+            }
+        )*};
+    }
+    // Floats compare by their bits, keeping 0.0 and -0.0 apart
+    macro_rules! same_bits {
+        ($($t:ty),*) => {$(
+            if let (Some(value), Some(other)) = (value.downcast_ref::<$t>(), other.downcast_ref::<$t>()) {
+                return value.to_bits() == other.to_bits();
+            }
+        )*};
+    }
+    macro_rules! same_elements {
+        ($($t:ty),*) => {$(
+            if let (Some(value), Some(other)) = (value.downcast_ref::<$t>(), other.downcast_ref::<$t>()) {
+                return value.len() == other.len()
+                    && value.iter().zip(other.iter()).all(|(a, b)| a.to_bits() == b.to_bits());
+            }
+        )*};
+    }
+    same!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, bool, char, String);
+    same_bits!(f32, f64);
+    same_elements!(
+        [f32; 2],
+        [f32; 3],
+        [f32; 4],
+        [f64; 2],
+        [f64; 3],
+        [f64; 4],
+        Vec<f32>,
+        Vec<f64>
+    );
+    false
+}
+
+/// Hashes `value` if it has one of the types `Compute::hash_inputs` handles by default.
+fn hash_primitive(value: &dyn Any, mut state: &mut dyn Hasher) -> bool {
+    macro_rules! hash {
+        ($($t:ty),*) => {$(
+            if let Some(value) = value.downcast_ref::<$t>() {
+                value.hash(&mut state);
+                return true;
+            }
+        )*};
+    }
+    macro_rules! hash_bits {
+        ($($t:ty),*) => {$(
+            if let Some(value) = value.downcast_ref::<$t>() {
+                // The length keeps `[1], [2, 3]` apart from `[1, 2], [3]`
+                state.write_usize(value.len());
+                value.iter().for_each(|value| value.to_bits().hash(&mut state));
+                return true;
+            }
+        )*};
+    }
+    hash!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, bool, char, String);
+    hash_bits!(
+        [f32; 2],
+        [f32; 3],
+        [f32; 4],
+        [f64; 2],
+        [f64; 3],
+        [f64; 4],
+        Vec<f32>,
+        Vec<f64>
+    );
+    if let Some(value) = value.downcast_ref::<f64>() {
+        state.write_u64(value.to_bits());
+        return true;
+    }
+    if let Some(value) = value.downcast_ref::<f32>() {
+        state.write_u32(value.to_bits());
+        return true;
+    }
+    false
 }
 
 impl<OuterIn, OuterOut> Compute for fn(&[&OuterIn]) -> OuterOut
 where
-    OuterIn: Any + Copy + Default,
-    OuterOut: Any + Copy + Default,
+    OuterIn: Value,
+    OuterOut: Value,
 {
     type In = OuterIn;
     type Out = OuterOut;
     fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
         self(inputs)
     }
+    fn fingerprint(&self, state: &mut dyn Hasher) {
+        state.write_usize(*self as usize);
+    }
+}
+
+/// Wraps a closure as a node, for ad-hoc nodes that capture their configuration.
+/// A blanket impl for closures would overlap with the other `Compute` impls. Captured
+/// values are invisible to fingerprints, so `build_cached` and checkpoints can't tell two
+/// instances of the same closure apart; expose values that change as `Parameters` instead.
+///
+/// ```ignore
+/// let factor = 2.5;
+/// graph.insert_node("scale", Closure::new(move |inputs: &[&f64]| *inputs[0] * factor));
+/// ```
+pub struct Closure<F, In, Out> {
+    func: F,
+    _types: PhantomData<fn(&In) -> Out>,
+}
+
+impl<F, In, Out> Closure<F, In, Out>
+where
+    F: Fn(&[&In]) -> Out,
+{
+    pub fn new(func: F) -> Self {
+        Self {
+            func,
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<F: Clone, In, Out> Clone for Closure<F, In, Out> {
+    fn clone(&self) -> Self {
+        Self {
+            func: self.func.clone(),
+            _types: PhantomData,
+        }
+    }
 }
 
-pub(crate) trait InnerCompute: DynClone {
-    fn init_output(&self) -> Box<dyn Any>;
+impl<F, OuterIn, OuterOut> Compute for Closure<F, OuterIn, OuterOut>
+where
+    F: Fn(&[&OuterIn]) -> OuterOut + Clone + Send + Sync,
+    OuterIn: Value,
+    OuterOut: Value,
+{
+    type In = OuterIn;
+    type Out = OuterOut;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        (self.func)(inputs)
+    }
+}
+
+/// Downcasts `inputs` for `f`, on the stack for up to `STACK_INPUTS` inputs plus the graph input.
+fn with_typed_inputs<In: Value, R>(inputs: &[&dyn Any], f: impl FnOnce(&[&In]) -> R) -> R {
+    if inputs.len() <= STACK_INPUTS + 1 {
+        let placeholder = In::default();
+        let mut typed = [&placeholder; STACK_INPUTS + 1];
+        for (typed, a) in typed.iter_mut().zip(inputs) {
+            *typed = a.downcast_ref::<In>().unwrap();
+        }
+        return f(&typed[..inputs.len()]);
+    }
+    let inputs = inputs
+        .iter()
+        .map(|a| a.downcast_ref::<In>().unwrap())
+        .collect::<Vec<_>>();
+    f(&inputs)
+}
+
+pub(crate) trait InnerCompute: DynClone + Send + Sync {
+    fn init_output(&self) -> Box<dyn Any + Send + Sync>;
+    fn clone_output(&self, output: &dyn Any) -> Box<dyn Any + Send + Sync>;
+    fn copy_output(&self, from: &dyn Any, to: &mut dyn Any);
     fn input_type(&self) -> TypeId;
     fn output_type(&self) -> TypeId;
-    fn inner_compute(&self, inputs: &[&dyn Any], output: &mut dyn Any);
+    fn input_type_name(&self) -> &'static str;
+    fn output_type_name(&self) -> &'static str;
+    fn type_name(&self) -> &'static str;
+    fn inner_try_compute(&self, inputs: &[&dyn Any], output: &mut dyn Any) -> Result<(), String>;
+    fn inner_try_compute_mut(
+        &mut self,
+        inputs: &[&dyn Any],
+        output: &mut dyn Any,
+    ) -> Result<(), String>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn same_constant(&self, other: &dyn InnerCompute) -> bool;
+    fn parameters(&self) -> Option<&dyn Parameters>;
+    fn parameters_mut(&mut self) -> Option<&mut dyn Parameters>;
+    fn fingerprint(&self, state: &mut dyn Hasher);
+    fn formula(&self, inputs: &[Expr]) -> Option<Expr>;
+    fn set_detail(&mut self, detail: Detail);
+    /// Type of each input port of a `PortCompute` node, empty for other nodes.
+    fn port_types(&self) -> Vec<(TypeId, &'static str)>;
+    /// Name of the graph input a node stands for, see `Graph::add_graph_input`.
+    fn input_name(&self) -> Option<&str>;
+    /// `Compute::min_inputs` and `Compute::max_inputs`.
+    fn arity(&self) -> (usize, Option<usize>);
+    fn cacheable(&self) -> bool;
+    fn hash_inputs(&self, inputs: &[&dyn Any], state: &mut dyn Hasher) -> bool;
 }
 dyn_clone::clone_trait_object!(InnerCompute);
 
 impl<T, InnerIn, InnerOut> InnerCompute for T
 where
-    T: Compute<In = InnerIn, Out = InnerOut>,
-    InnerIn: Any + Copy + Default + 'static,
-    InnerOut: Any + Copy + Default + 'static,
+    T: Compute<In = InnerIn, Out = InnerOut> + 'static,
+    InnerIn: Value,
+    InnerOut: Value,
 {
-    fn init_output(&self) -> Box<dyn Any> {
+    fn init_output(&self) -> Box<dyn Any + Send + Sync> {
         Box::new(InnerOut::default())
     }
+    fn clone_output(&self, output: &dyn Any) -> Box<dyn Any + Send + Sync> {
+        Box::new(output.downcast_ref::<InnerOut>().unwrap().clone())
+    }
+    fn copy_output(&self, from: &dyn Any, to: &mut dyn Any) {
+        to.downcast_mut::<InnerOut>()
+            .unwrap()
+            .clone_from(from.downcast_ref::<InnerOut>().unwrap());
+    }
     fn input_type(&self) -> TypeId {
         TypeId::of::<InnerIn>()
     }
     fn output_type(&self) -> TypeId {
         TypeId::of::<InnerOut>()
     }
-    fn inner_compute(&self, inputs: &[&dyn Any], output: &mut dyn Any) {
-        let inputs = inputs
-            .iter()
-            .map(|a| a.downcast_ref::<InnerIn>().unwrap())
-            .collect::<Vec<_>>();
-        let output_val = output.downcast_mut::<InnerOut>().unwrap();
-        *output_val = self.compute(&inputs);
+    fn input_type_name(&self) -> &'static str {
+        type_name::<InnerIn>()
+    }
+    fn output_type_name(&self) -> &'static str {
+        type_name::<InnerOut>()
+    }
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+    fn inner_try_compute(&self, inputs: &[&dyn Any], output: &mut dyn Any) -> Result<(), String> {
+        *output.downcast_mut::<InnerOut>().unwrap() =
+            with_typed_inputs(inputs, |inputs| self.try_compute(inputs))?;
+        Ok(())
+    }
+    fn inner_try_compute_mut(
+        &mut self,
+        inputs: &[&dyn Any],
+        output: &mut dyn Any,
+    ) -> Result<(), String> {
+        *output.downcast_mut::<InnerOut>().unwrap() =
+            with_typed_inputs(inputs, |inputs| self.try_compute_mut(inputs))?;
+        Ok(())
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn same_constant(&self, other: &dyn InnerCompute) -> bool {
+        Compute::same_constant(self, other.as_any())
+    }
+    fn parameters(&self) -> Option<&dyn Parameters> {
+        Compute::parameters(self)
+    }
+    fn parameters_mut(&mut self) -> Option<&mut dyn Parameters> {
+        Compute::parameters_mut(self)
+    }
+    fn fingerprint(&self, state: &mut dyn Hasher) {
+        Compute::fingerprint(self, state)
+    }
+    fn formula(&self, inputs: &[Expr]) -> Option<Expr> {
+        Compute::formula(self, inputs)
+    }
+    fn set_detail(&mut self, detail: Detail) {
+        Compute::set_detail(self, detail)
+    }
+    fn port_types(&self) -> Vec<(TypeId, &'static str)> {
+        Vec::new()
+    }
+    fn input_name(&self) -> Option<&str> {
+        None
+    }
+    fn arity(&self) -> (usize, Option<usize>) {
+        (Compute::min_inputs(self), Compute::max_inputs(self))
+    }
+    fn cacheable(&self) -> bool {
+        Compute::cacheable(self)
+    }
+    fn hash_inputs(&self, inputs: &[&dyn Any], state: &mut dyn Hasher) -> bool {
+        with_typed_inputs(inputs, |inputs| Compute::hash_inputs(self, inputs, state))
+    }
+}
+
+#[cfg(test)]
+mod compute_tests {
+    use crate::prelude::*;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+
+    #[test]
+    fn test_closure_node() -> Result<(), ComputeGraphErrors> {
+        let factor = 2.5;
+        let mut graph = Graph::new();
+        let scale_handle = graph.insert_node(
+            "scale",
+            Closure::new(move |inputs: &[&f64]| inputs.iter().copied().sum::<f64>() * factor),
+        );
+        let label_handle = graph.insert_node(
+            "label",
+            Closure::new(|inputs: &[&f64]| format!("{:.1}", inputs[0])),
+        );
+        graph.add_input(&label_handle, &scale_handle)?;
+        graph.disconnect_from_input(&label_handle)?;
+        graph.set_output_node(&label_handle)?;
+        assert_eq!(graph.build::<f64, String>()?.compute(&2.0), "5.0");
+        Ok(())
+    }
+
+    /// Cacheable node outputting the lengths of its inputs.
+    #[derive(Clone)]
+    struct Len;
+
+    impl Compute for Len {
+        type In = Vec<f64>;
+        type Out = Vec<f64>;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            inputs.iter().map(|input| input.len() as f64).collect()
+        }
+        fn cacheable(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_hash_inputs_keeps_vectors_apart() {
+        let hash = |inputs: &[&Vec<f64>]| {
+            let mut hasher = DefaultHasher::new();
+            assert!(Len.hash_inputs(inputs, &mut hasher));
+            hasher.finish()
+        };
+        assert_ne!(
+            hash(&[&vec![1.0, 2.0], &vec![3.0]]),
+            hash(&[&vec![1.0], &vec![2.0, 3.0]])
+        );
+        assert_ne!(hash(&[&vec![], &vec![1.0]]), hash(&[&vec![1.0]]));
     }
 }
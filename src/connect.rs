@@ -0,0 +1,259 @@
+use crate::graph::{ComputeGraphErrors, Graph, GraphKey, NodeHandle};
+use std::collections::{HashMap, VecDeque};
+
+/// Result of checking whether one node's output can feed another node's input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConnectCheck {
+    Ok,
+    NodeMissing,
+    /// One of the handles belongs to another graph.
+    ForeignHandle,
+    SameNode,
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    WouldCreateCycle,
+}
+
+impl ConnectCheck {
+    pub fn is_ok(&self) -> bool {
+        *self == ConnectCheck::Ok
+    }
+}
+
+impl Graph {
+    /// Checks if `from` can be added as an input of `to`.
+    pub fn can_connect(&self, from: &NodeHandle, to: &NodeHandle) -> ConnectCheck {
+        if from.graph_id != self.id || to.graph_id != self.id {
+            return ConnectCheck::ForeignHandle;
+        }
+        let (Some(from_node), Some(to_node)) = (self.nodes.get(from.key), self.nodes.get(to.key))
+        else {
+            return ConnectCheck::NodeMissing;
+        };
+        if from.key == to.key {
+            return ConnectCheck::SameNode;
+        }
+        if from_node.inner.output_type() != to_node.inner.input_type() {
+            return ConnectCheck::TypeMismatch {
+                expected: to_node.inner.input_type_name(),
+                found: from_node.inner.output_type_name(),
+            };
+        }
+        if self.input_path(from.key, to.key).is_some() {
+            return ConnectCheck::WouldCreateCycle;
+        }
+        ConnectCheck::Ok
+    }
+
+    /// Returns the loop that adding `from` as an input of `to` would close, in data flow
+    /// order starting with `from`, or `None` if the edge is safe.
+    pub fn would_create_cycle(
+        &self,
+        from: &NodeHandle,
+        to: &NodeHandle,
+    ) -> Result<Option<Vec<NodeHandle>>, ComputeGraphErrors> {
+        self.check_handle(from)?;
+        self.check_handle(to)?;
+        let Some(mut path) = self.input_path(from.key, to.key) else {
+            return Ok(None);
+        };
+        path.reverse();
+        path.pop();
+        path.insert(0, from.key);
+        Ok(Some(
+            path.into_iter()
+                .map(|key| NodeHandle {
+                    key,
+                    graph_id: self.id,
+                })
+                .collect(),
+        ))
+    }
+
+    /// Makes `add_input`, `add_input_port` and `add_order` fail with `GraphCycle` on edges
+    /// that would close a cycle, instead of leaving them for `build` to find. Off by default.
+    pub fn reject_cycles(&mut self, reject: bool) {
+        self.reject_cycles = reject;
+        self.touch();
+    }
+
+    pub fn rejects_cycles(&self) -> bool {
+        self.reject_cycles
+    }
+
+    /// Fails if cycles are rejected and feeding `input` into `node` would close one.
+    pub(crate) fn check_new_edge(
+        &self,
+        node: &NodeHandle,
+        input: &NodeHandle,
+    ) -> Result<(), ComputeGraphErrors> {
+        self.check_new_edge_as(node, input, "as an input of")
+    }
+
+    /// Like `check_new_edge`, with `relation` describing the edge in the error.
+    pub(crate) fn check_new_edge_as(
+        &self,
+        node: &NodeHandle,
+        input: &NodeHandle,
+        relation: &str,
+    ) -> Result<(), ComputeGraphErrors> {
+        if !self.reject_cycles || self.input_path(input.key, node.key).is_none() {
+            return Ok(());
+        }
+        Err(ComputeGraphErrors::GraphCycle(
+            format!(
+                "adding '{}' {} '{}' would close a cycle",
+                self.nodes[input.key].name, relation, self.nodes[node.key].name
+            )
+            .into(),
+        )
+        .with_node(*node)
+        .with_node(*input))
+    }
+
+    /// Shortest chain of input and ordering edges leading from `start` back to `target`,
+    /// both included.
+    pub(crate) fn input_path(&self, start: GraphKey, target: GraphKey) -> Option<Vec<GraphKey>> {
+        let mut parents = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        parents.insert(start, start);
+        while let Some(key) = queue.pop_front() {
+            if key == target {
+                let mut path = vec![key];
+                let mut current = key;
+                while current != start {
+                    current = parents[&current];
+                    path.push(current);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            let node = self.nodes.get(key)?;
+            for input in node.inputs.iter().chain(node.after.iter()) {
+                if !parents.contains_key(input) {
+                    parents.insert(*input, key);
+                    queue.push_back(*input);
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod connect_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_can_connect() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("the_answer", Constant(42.0));
+        let flag_handle = graph.insert_node("flag", Constant(true));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&add_handle, &const_handle)?;
+        graph.add_input(&mul_handle, &add_handle)?;
+
+        assert!(graph.can_connect(&add_handle, &mul_handle).is_ok());
+        assert_eq!(
+            graph.can_connect(&add_handle, &add_handle),
+            ConnectCheck::SameNode
+        );
+        assert_eq!(
+            graph.can_connect(&flag_handle, &add_handle),
+            ConnectCheck::TypeMismatch {
+                expected: "f64",
+                found: "bool"
+            }
+        );
+        assert_eq!(
+            graph.can_connect(&mul_handle, &add_handle),
+            ConnectCheck::WouldCreateCycle
+        );
+        assert_eq!(graph.would_create_cycle(&add_handle, &mul_handle)?, None);
+        assert_eq!(
+            graph.would_create_cycle(&mul_handle, &const_handle)?,
+            Some(vec![
+                mul_handle.erase(),
+                const_handle.erase(),
+                add_handle.erase()
+            ])
+        );
+        assert_eq!(
+            graph.would_create_cycle(&add_handle, &add_handle)?,
+            Some(vec![add_handle.erase()])
+        );
+
+        graph.remove_node(&flag_handle)?;
+        assert_eq!(
+            graph.can_connect(&flag_handle, &add_handle),
+            ConnectCheck::NodeMissing
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_reject_cycles() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &add_handle)?;
+        graph.reject_cycles(true);
+
+        let err = graph.add_input(&add_handle, &mul_handle).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "graph has a cycle: adding 'mul' as an input of 'add' would close a cycle"
+        );
+        assert_eq!(err.nodes(), [add_handle.erase(), mul_handle.erase()]);
+        assert!(graph.add_input(&add_handle, &add_handle).is_err());
+        assert!(graph.get_node_meta(&add_handle)?.inputs.is_empty());
+
+        // Without the check the cycle is only found by `build`
+        graph.reject_cycles(false);
+        graph.add_input(&add_handle, &mul_handle)?;
+        graph.set_output_node(&mul_handle)?;
+        assert!(matches!(
+            graph.build::<f64, f64>(),
+            Err(ComputeGraphErrors::GraphCycle(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ordering_edges_close_cycles() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let write_handle = graph.insert_node("write", Constant(1.0));
+        let log_handle = graph.insert_node("log", AddInputs::<f64>::new());
+        graph.add_order(&write_handle, &log_handle)?;
+        graph.reject_cycles(true);
+
+        assert_eq!(
+            graph.can_connect(&log_handle, &write_handle),
+            ConnectCheck::TypeMismatch {
+                expected: "()",
+                found: "f64"
+            }
+        );
+        assert_eq!(
+            graph.would_create_cycle(&log_handle, &write_handle)?,
+            Some(vec![log_handle.erase(), write_handle.erase()])
+        );
+        let err = graph.add_order(&log_handle, &write_handle).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "graph has a cycle: adding 'log' before 'write' would close a cycle"
+        );
+        assert!(graph.add_order(&log_handle, &log_handle).is_err());
+
+        let sum_handle = graph.insert_node("sum", AddInputs::<f64>::new());
+        graph.add_input(&log_handle, &sum_handle)?;
+        assert_eq!(
+            graph.can_connect(&log_handle, &sum_handle),
+            ConnectCheck::WouldCreateCycle
+        );
+        assert!(graph.add_order(&log_handle, &sum_handle).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+use crate::compute::Compute;
+use crate::ports::PortCompute;
+
+/// Follows the first input, changing by at most `max_rate` per evaluation. Under a
+/// `Simulator`, a rate per second times its `dt` gives the rate per step. Starts at the
+/// first input it sees. Evaluated through `ComputeGraph::try_compute_mut`.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SlewLimit {
+    pub max_rate: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    value: Option<f64>,
+}
+
+impl SlewLimit {
+    pub fn new(max_rate: f64) -> Self {
+        Self {
+            max_rate,
+            value: None,
+        }
+    }
+}
+
+impl Compute for SlewLimit {
+    type In = f64;
+    type Out = f64;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let target = inputs.first().map_or(0.0, |target| **target);
+        match self.value {
+            Some(value) => target.clamp(value - self.max_rate, value + self.max_rate),
+            None => target,
+        }
+    }
+
+    fn try_compute_mut(&mut self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        let value = self.compute(inputs);
+        self.value = Some(value);
+        Ok(value)
+    }
+
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+/// Switches on once the first input reaches `on` and off once it falls to `off`, keeping
+/// its state in between, so a noisy signal near a threshold doesn't flicker. `on` should
+/// be above `off`. Starts off. Evaluated through `ComputeGraph::try_compute_mut`.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hysteresis {
+    pub on: f64,
+    pub off: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    state: bool,
+}
+
+impl Hysteresis {
+    pub fn new(on: f64, off: f64) -> Self {
+        Self {
+            on,
+            off,
+            state: false,
+        }
+    }
+}
+
+impl Compute for Hysteresis {
+    type In = f64;
+    type Out = bool;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        match inputs.first() {
+            Some(input) if **input >= self.on => true,
+            Some(input) if **input <= self.off => false,
+            _ => self.state,
+        }
+    }
+
+    fn try_compute_mut(&mut self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        self.state = self.compute(inputs);
+        Ok(self.state)
+    }
+
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+/// Critically damped spring following the target on port 0 as fast as it can without
+/// overshooting, for smoothly interpolating parameters. Reaches about 60% of a step in
+/// `smooth_time` seconds. Port 1 reads the time step in seconds, like the graph input fed by
+/// `Simulator`; every step is solved exactly, so long ones stay stable. Starts at rest at the
+/// first target it sees. Evaluated through `ComputeGraph::try_compute_mut`.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spring {
+    pub smooth_time: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    position: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    velocity: f64,
+}
+
+impl Spring {
+    pub fn new(smooth_time: f64) -> Self {
+        Self {
+            smooth_time,
+            position: None,
+            velocity: 0.0,
+        }
+    }
+}
+
+impl PortCompute for Spring {
+    type Ports = (f64, f64);
+    type Out = f64;
+    fn compute(&self, ports: (&f64, &f64)) -> Self::Out {
+        self.clone().try_compute_mut(ports).unwrap()
+    }
+
+    fn try_compute_mut(&mut self, (target, dt): (&f64, &f64)) -> Result<f64, String> {
+        let position = *self.position.get_or_insert(*target);
+        if self.smooth_time <= 0.0 {
+            self.velocity = 0.0;
+            return Ok(*self.position.insert(*target));
+        }
+        let omega = 2.0 / self.smooth_time;
+        let offset = position - target;
+        let decay = (-omega * dt).exp();
+        let temp = (self.velocity + omega * offset) * dt;
+        self.velocity = (self.velocity - omega * temp) * decay;
+        Ok(*self.position.insert(target + (offset + temp) * decay))
+    }
+}
+
+#[cfg(test)]
+mod control_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_slew_and_hysteresis() -> Result<(), ComputeGraphErrors> {
+        //  Input ── slew ── hysteresis
+        let mut graph = Graph::new();
+        let slew_handle = graph.insert_node("slew", SlewLimit::new(0.5));
+        let switch_handle = graph.insert_node("switch", Hysteresis::new(2.0, 1.0));
+        graph.add_input(&switch_handle, &slew_handle)?;
+        graph.set_output_node(&switch_handle)?;
+        graph.probe(&slew_handle)?;
+        let mut compute_graph = graph.build::<f64, bool>()?;
+
+        let mut outputs = Vec::new();
+        for target in [0.0, 3.0, 3.0, 3.0, 3.0, 1.2, 1.2, 0.0] {
+            let on = compute_graph.compute_mut(&target);
+            let slewed = compute_graph.probed_value::<f64>(&slew_handle).unwrap();
+            outputs.push((slewed, on));
+        }
+        assert_eq!(
+            outputs,
+            [
+                (0.0, false),
+                (0.5, false),
+                (1.0, false),
+                (1.5, false),
+                (2.0, true),
+                (1.5, true),
+                (1.2, true),
+                (0.7, false)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_spring() -> Result<(), ComputeGraphErrors> {
+        //  target ────────── spring (port 0)
+        //  Input (dt) ── dt ── spring (port 1)
+        let mut graph = Graph::new();
+        let target_handle = graph.insert_node("target", Parameter::new("target", 0.0));
+        let dt_handle = graph.insert_node("dt", AddInputs::<f64>::new());
+        let spring_handle = graph.insert_port_node("spring", Spring::new(1.0));
+        graph.add_input_port(&spring_handle, 0, &target_handle)?;
+        graph.add_input_port(&spring_handle, 1, &dt_handle)?;
+        graph.set_output_node(&spring_handle)?;
+        let mut compute_graph = graph.build::<f64, f64>()?;
+
+        assert_eq!(compute_graph.compute_mut(&0.25), 0.0);
+        compute_graph.set_parameter("target", 1.0)?;
+        let positions = (0..40)
+            .map(|_| compute_graph.compute_mut(&0.25))
+            .collect::<Vec<_>>();
+        // Critically damped: 1 - (1 + 2t) e^(-2t) after t seconds, never overshooting
+        assert!((positions[3] - (1.0 - 3.0 * (-2.0f64).exp())).abs() < 1e-9);
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(positions[39] < 1.0 && positions[39] > 0.999);
+
+        // One long step lands close to the target instead of blowing up
+        compute_graph.set_parameter("target", -1.0)?;
+        let position = compute_graph.compute_mut(&10.0);
+        assert!((position + 1.0).abs() < 1e-6);
+        Ok(())
+    }
+}
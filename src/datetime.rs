@@ -0,0 +1,127 @@
+use crate::compute::Compute;
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, Timelike};
+
+/// Converts unix timestamps in seconds to UTC date times.
+/// Out of range timestamps produce the unix epoch.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FromTimestamp;
+impl Compute for FromTimestamp {
+    type In = i64;
+    type Out = NaiveDateTime;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs
+            .first()
+            .and_then(|secs| DateTime::from_timestamp(**secs, 0))
+            .map(|time| time.naive_utc())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ToTimestamp;
+impl Compute for ToTimestamp {
+    type In = NaiveDateTime;
+    type Out = i64;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs.first().map_or(0, |time| time.and_utc().timestamp())
+    }
+}
+
+/// Shifts the first input by a fixed number of seconds.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AddDuration {
+    pub seconds: i64,
+}
+impl AddDuration {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            seconds: duration.num_seconds(),
+        }
+    }
+}
+
+impl Compute for AddDuration {
+    type In = NaiveDateTime;
+    type Out = NaiveDateTime;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let time = inputs.first().map(|time| **time).unwrap_or_default();
+        time.checked_add_signed(Duration::seconds(self.seconds))
+            .unwrap_or(time)
+    }
+}
+
+/// Day of the week of the first input, counted from Monday = 0.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Weekday;
+impl Compute for Weekday {
+    type In = NaiveDateTime;
+    type Out = u32;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs
+            .first()
+            .map_or(0, |time| time.weekday().num_days_from_monday())
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hour;
+impl Compute for Hour {
+    type In = NaiveDateTime;
+    type Out = u32;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs.first().map_or(0, |time| time.hour())
+    }
+}
+
+/// True if the inputs are in strictly increasing time order.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IsBefore;
+impl Compute for IsBefore {
+    type In = NaiveDateTime;
+    type Out = bool;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs.windows(2).all(|pair| pair[0] < pair[1])
+    }
+}
+
+#[cfg(test)]
+mod datetime_tests {
+    use crate::prelude::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_datetime_nodes() -> Result<(), ComputeGraphErrors> {
+        //  Input ── parse ──┬── shift ── weekday
+        //                   ├── shift ── hour
+        //                   └── is_before(parse, deadline)
+        let mut graph = Graph::new();
+        let parse_handle = graph.insert_node("parse", FromTimestamp);
+        let shift_handle = graph.insert_node("shift", AddDuration::new(Duration::hours(30)));
+        let weekday_handle = graph.insert_node("weekday", Weekday);
+        let hour_handle = graph.insert_node("hour", Hour);
+        let deadline_handle = graph.insert_node("deadline", AddDuration::new(Duration::days(1)));
+        let before_handle = graph.insert_node("before", IsBefore);
+        graph.add_input(&shift_handle, &parse_handle)?;
+        graph.add_input(&weekday_handle, &shift_handle)?;
+        graph.add_input(&hour_handle, &shift_handle)?;
+        graph.add_input(&deadline_handle, &parse_handle)?;
+        graph.add_input(&before_handle, &shift_handle)?;
+        graph.add_input(&before_handle, &deadline_handle)?;
+
+        // 2024-01-01 00:00:00 UTC was a Monday
+        let monday = 1_704_067_200;
+        let weekday = graph.build_for_node::<i64, u32>(&weekday_handle)?;
+        assert_eq!(weekday.compute(&monday), 1);
+        let hour = graph.build_for_node::<i64, u32>(&hour_handle)?;
+        assert_eq!(hour.compute(&monday), 6);
+        let before = graph.build_for_node::<i64, bool>(&before_handle)?;
+        assert!(!before.compute(&monday));
+        Ok(())
+    }
+}
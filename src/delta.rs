@@ -0,0 +1,332 @@
+use crate::compute::{Compute, Value};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// Changes to a collection since the previous evaluation, flowing between the nodes of an
+/// incremental pipeline instead of the whole collection. `Diff` turns a collection into
+/// deltas, `MapDelta` and `FilterDelta` process only the changed items, and `Integrate`
+/// applies them to rebuild the collection. Collections are multisets: removing an item
+/// removes one occurrence of it.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Delta<T> {
+    pub added: Vec<T>,
+    pub removed: Vec<T>,
+}
+
+impl<T> Delta<T> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Outputs the items added to and removed from the first input since the last evaluation,
+/// everything being added the first time. Evaluated through `ComputeGraph::try_compute_mut`.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diff<T> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    previous: Vec<T>,
+}
+
+impl<T> Diff<T> {
+    pub fn new() -> Self {
+        Self {
+            previous: Vec::new(),
+        }
+    }
+}
+
+impl<T> Compute for Diff<T>
+where
+    T: Value + Eq + Hash,
+{
+    type In = Vec<T>;
+    type Out = Delta<T>;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let current = inputs.first().map_or(&[][..], |input| input.as_slice());
+        let mut counts = HashMap::<&T, isize>::new();
+        for item in current {
+            *counts.entry(item).or_default() += 1;
+        }
+        for item in self.previous.iter() {
+            *counts.entry(item).or_default() -= 1;
+        }
+        let mut delta = Delta::default();
+        // Walking the collections keeps the items in their order
+        for item in current {
+            if let Some(count) = counts.get_mut(item).filter(|count| **count > 0) {
+                *count -= 1;
+                delta.added.push(item.clone());
+            }
+        }
+        for item in self.previous.iter() {
+            if let Some(count) = counts.get_mut(item).filter(|count| **count < 0) {
+                *count += 1;
+                delta.removed.push(item.clone());
+            }
+        }
+        delta
+    }
+
+    fn try_compute_mut(&mut self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        let delta = self.compute(inputs);
+        self.previous = inputs
+            .first()
+            .map(|input| (*input).clone())
+            .unwrap_or_default();
+        Ok(delta)
+    }
+}
+
+/// Applies `func` to the added and removed items of the first input. `func` must be pure,
+/// so an item maps to what it mapped to when it was added.
+pub struct MapDelta<F, T, U> {
+    func: F,
+    _types: PhantomData<fn(&T) -> U>,
+}
+
+impl<F, T, U> MapDelta<F, T, U>
+where
+    F: Fn(&T) -> U,
+{
+    pub fn new(func: F) -> Self {
+        Self {
+            func,
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<F: Clone, T, U> Clone for MapDelta<F, T, U> {
+    fn clone(&self) -> Self {
+        Self {
+            func: self.func.clone(),
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<F, T, U> Compute for MapDelta<F, T, U>
+where
+    F: Fn(&T) -> U + Clone + Send + Sync,
+    T: Value,
+    U: Value,
+{
+    type In = Delta<T>;
+    type Out = Delta<U>;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let Some(delta) = inputs.first() else {
+            return Delta::default();
+        };
+        Delta {
+            added: delta.added.iter().map(&self.func).collect(),
+            removed: delta.removed.iter().map(&self.func).collect(),
+        }
+    }
+}
+
+/// Keeps the added and removed items of the first input for which `predicate` holds.
+/// `predicate` must be pure, so removed items are dropped exactly when they were added.
+pub struct FilterDelta<F, T> {
+    predicate: F,
+    _types: PhantomData<fn(&T)>,
+}
+
+impl<F, T> FilterDelta<F, T>
+where
+    F: Fn(&T) -> bool,
+{
+    pub fn new(predicate: F) -> Self {
+        Self {
+            predicate,
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<F: Clone, T> Clone for FilterDelta<F, T> {
+    fn clone(&self) -> Self {
+        Self {
+            predicate: self.predicate.clone(),
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<F, T> Compute for FilterDelta<F, T>
+where
+    F: Fn(&T) -> bool + Clone + Send + Sync,
+    T: Value,
+{
+    type In = Delta<T>;
+    type Out = Delta<T>;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let Some(delta) = inputs.first() else {
+            return Delta::default();
+        };
+        let keep = |items: &[T]| {
+            items
+                .iter()
+                .filter(|item| (self.predicate)(item))
+                .cloned()
+                .collect()
+        };
+        Delta {
+            added: keep(&delta.added),
+            removed: keep(&delta.removed),
+        }
+    }
+}
+
+/// Applies the deltas of the first input to the collection it outputs, in the order the
+/// items were added. Evaluated through `ComputeGraph::try_compute_mut`.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Integrate<T> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    items: Vec<T>,
+}
+
+impl<T> Integrate<T> {
+    pub fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+}
+
+impl<T> Compute for Integrate<T>
+where
+    T: Value + PartialEq,
+{
+    type In = Delta<T>;
+    type Out = Vec<T>;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.try_compute(inputs).unwrap()
+    }
+
+    fn try_compute(&self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        self.clone().try_compute_mut(inputs)
+    }
+
+    fn try_compute_mut(&mut self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        if let Some(delta) = inputs.first() {
+            for removed in delta.removed.iter() {
+                let position = self
+                    .items
+                    .iter()
+                    .position(|item| item == removed)
+                    .ok_or("delta removes an item the collection doesn't hold")?;
+                self.items.remove(position);
+            }
+            self.items.extend(delta.added.iter().cloned());
+        }
+        Ok(self.items.clone())
+    }
+}
+
+#[cfg(test)]
+mod delta_tests {
+    use crate::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_only_deltas_propagate() -> Result<(), ComputeGraphErrors> {
+        //  Input ── diff ── square ── even ── integrate
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mut graph = Graph::new();
+        let diff_handle = graph.insert_node("diff", Diff::<i64>::new());
+        let square_handle = graph.insert_node(
+            "square",
+            MapDelta::new(move |x: &i64| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                x * x
+            }),
+        );
+        let even_handle = graph.insert_node("even", FilterDelta::new(|x: &i64| x % 2 == 0));
+        let integrate_handle = graph.insert_node("integrate", Integrate::<i64>::new());
+        graph.add_input(&square_handle, &diff_handle)?;
+        graph.add_input(&even_handle, &square_handle)?;
+        graph.add_input(&integrate_handle, &even_handle)?;
+        graph.connect_to_input(&diff_handle)?;
+        graph.set_output_node(&integrate_handle)?;
+        graph.probe(&square_handle)?;
+        let mut compute_graph = graph.build::<Vec<i64>, Vec<i64>>()?;
+
+        let mut items = (0..1000).collect::<Vec<i64>>();
+        let squares = compute_graph.compute_mut(&items);
+        assert_eq!(squares.len(), 500);
+        assert_eq!(calls.load(Ordering::SeqCst), 1000);
+
+        // One changed item sends one removal and one addition down the pipeline
+        items[4] = 2000;
+        let mut squares = compute_graph.compute_mut(&items);
+        assert_eq!(calls.load(Ordering::SeqCst), 1002);
+        assert_eq!(
+            compute_graph.probed_value::<Delta<i64>>(&square_handle),
+            Some(Delta {
+                added: vec![4_000_000],
+                removed: vec![16],
+            })
+        );
+        let mut expected = items
+            .iter()
+            .map(|x| x * x)
+            .filter(|x| x % 2 == 0)
+            .collect::<Vec<_>>();
+        expected.sort();
+        squares.sort();
+        assert_eq!(squares, expected);
+
+        // Nothing changed, nothing to process
+        compute_graph.compute_mut(&items);
+        assert_eq!(calls.load(Ordering::SeqCst), 1002);
+        assert!(compute_graph
+            .probed_value::<Delta<i64>>(&square_handle)
+            .is_some_and(|delta| delta.is_empty()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_multiset() {
+        let mut diff = Diff::<char>::new();
+        diff.try_compute_mut(&[&vec!['a', 'b', 'a']]).unwrap();
+        assert_eq!(
+            diff.try_compute_mut(&[&vec!['b', 'a', 'c']]).unwrap(),
+            Delta {
+                added: vec!['c'],
+                removed: vec!['a'],
+            }
+        );
+        let mut integrate = Integrate::<char>::new();
+        assert!(integrate
+            .try_compute_mut(&[&Delta {
+                added: vec![],
+                removed: vec!['x'],
+            }])
+            .is_err());
+    }
+
+    #[test]
+    fn test_integrate_reports_bad_deltas() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let delta_handle = graph.insert_node(
+            "delta",
+            Constant(Delta {
+                added: vec![],
+                removed: vec![1i64],
+            }),
+        );
+        let integrate_handle = graph.insert_node("integrate", Integrate::<i64>::new());
+        graph.add_input(&integrate_handle, &delta_handle)?;
+        graph.set_output_node(&integrate_handle)?;
+        let compute_graph = graph.build::<(), Vec<i64>>()?;
+        assert!(matches!(
+            compute_graph.try_compute(&()),
+            Err(ComputeGraphErrors::NodeFailed(message)) if message.contains("doesn't hold")
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,313 @@
+use crate::graph::Graph;
+use crate::params::ParamValue;
+use crate::profile::{NodeProfile, Profiler};
+use crate::registry::NodeRegistry;
+use std::any::{Any, TypeId};
+
+/// Report describing a graph to the people using it rather than programming it: a
+/// diagram, what the graph reads and outputs, and a table of its nodes with their types,
+/// parameters and docs. Created with `Graph::document`.
+pub struct GraphDocument<'a> {
+    graph: &'a Graph,
+    registry: &'a NodeRegistry,
+    title: String,
+    profiles: Vec<NodeProfile>,
+}
+
+/// One row of the node table, already formatted.
+struct NodeRow {
+    name: String,
+    op: String,
+    input_type: &'static str,
+    output_type: &'static str,
+    params: String,
+    doc: String,
+    timing: Option<String>,
+}
+
+struct Content {
+    diagram: String,
+    inputs: Vec<(String, &'static str)>,
+    output: Option<(String, &'static str, String)>,
+    nodes: Vec<NodeRow>,
+}
+
+fn markdown_escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl<'a> GraphDocument<'a> {
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Adds the timings collected by `profiler` to the node table.
+    pub fn with_profile(mut self, profiler: &Profiler) -> Self {
+        self.profiles = profiler.report();
+        self
+    }
+
+    fn content(&self) -> Content {
+        let graph = self.graph;
+        let order = graph.canonical_order();
+        let type_name = |type_id| *graph.type_names.get(&type_id).unwrap_or(&"unknown type");
+
+        let inputs = order
+            .iter()
+            .map(|key| &graph.nodes[*key])
+            .filter(|node| node.connected_to_input && node.inner.input_type() != TypeId::of::<()>())
+            .map(|node| (node.name.clone(), type_name(node.inner.input_type())))
+            .collect();
+
+        let output = graph.output_node.map(|key| {
+            let node = &graph.nodes[key];
+            let doc = self
+                .registry
+                .entry_of(Any::type_id(node.inner.as_any()))
+                .and_then(|entry| entry.output_doc())
+                .unwrap_or_default();
+            (
+                node.name.clone(),
+                type_name(node.inner.output_type()),
+                doc.to_string(),
+            )
+        });
+
+        let nodes = order
+            .iter()
+            .map(|key| {
+                let meta = graph.build_node_meta(*key, &graph.nodes[*key]);
+                let entry = self.registry.entry_of(meta.op_type);
+                let params = meta
+                    .params
+                    .iter()
+                    .map(|param| match param.value {
+                        ParamValue::Bool(value) => format!("{} = {}", param.name, value),
+                        ParamValue::Int(value) => format!("{} = {}", param.name, value),
+                        ParamValue::UInt(value) => format!("{} = {}", param.name, value),
+                        ParamValue::Float(value) => format!("{} = {}", param.name, value),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let timing = self
+                    .profiles
+                    .iter()
+                    .find(|profile| profile.node == meta.this_node)
+                    .map(|profile| {
+                        format!(
+                            "{:.2?} mean over {} samples, {:.2?} max",
+                            profile.mean(),
+                            profile.samples,
+                            profile.max
+                        )
+                    });
+                NodeRow {
+                    name: meta.name,
+                    op: entry
+                        .map_or(meta.op_type_name, |entry| entry.display_name())
+                        .to_string(),
+                    input_type: meta.input_type_name,
+                    output_type: meta.output_type_name,
+                    params,
+                    doc: entry
+                        .and_then(|entry| entry.description())
+                        .unwrap_or_default()
+                        .to_string(),
+                    timing,
+                }
+            })
+            .collect();
+
+        Content {
+            diagram: graph.to_mermaid(),
+            inputs,
+            output,
+            nodes,
+        }
+    }
+
+    /// Markdown report, with the diagram as a `mermaid` code block.
+    pub fn to_markdown(&self) -> String {
+        let content = self.content();
+        let profiled = !self.profiles.is_empty();
+        let mut out = format!("# {}\n\n## Diagram\n\n```mermaid\n", self.title);
+        out += &content.diagram;
+        out += "```\n\n## Input\n\n";
+        if content.inputs.is_empty() {
+            out += "The graph doesn't read its input.\n";
+        }
+        for (name, type_name) in content.inputs.iter() {
+            out += &format!("- `{}` reads `{}`\n", markdown_escape(name), type_name);
+        }
+        out += "\n## Output\n\n";
+        match &content.output {
+            Some((name, type_name, doc)) => {
+                out += &format!("`{}` outputs `{}`", markdown_escape(name), type_name);
+                if !doc.is_empty() {
+                    out += &format!(": {}", markdown_escape(doc));
+                }
+                out += "\n";
+            }
+            None => out += "No output node is set.\n",
+        }
+
+        out += "\n## Nodes\n\n| Node | Op | Input | Output | Parameters | Description |";
+        out += if profiled { " Time |\n" } else { "\n" };
+        out += "|---|---|---|---|---|---|";
+        out += if profiled { "---|\n" } else { "\n" };
+        for row in content.nodes.iter() {
+            out += &format!(
+                "| {} | {} | `{}` | `{}` | {} | {} |",
+                markdown_escape(&row.name),
+                markdown_escape(&row.op),
+                row.input_type,
+                row.output_type,
+                markdown_escape(&row.params),
+                markdown_escape(&row.doc)
+            );
+            if profiled {
+                out += &format!(" {} |", row.timing.as_deref().unwrap_or("not sampled"));
+            }
+            out += "\n";
+        }
+        out
+    }
+
+    /// Standalone HTML page. The diagram is rendered by mermaid.js, loaded from a CDN;
+    /// without it the diagram's source is shown instead.
+    pub fn to_html(&self) -> String {
+        let content = self.content();
+        let profiled = !self.profiles.is_empty();
+        let title = html_escape(&self.title);
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+             <script type=\"module\">import mermaid from \
+             \"https://cdn.jsdelivr.net/npm/mermaid@11/dist/mermaid.esm.min.mjs\";</script>\n\
+             </head>\n<body>\n<h1>{}</h1>\n<h2>Diagram</h2>\n<pre class=\"mermaid\">\n",
+            title, title
+        );
+        out += &html_escape(&content.diagram);
+        out += "</pre>\n<h2>Input</h2>\n";
+        if content.inputs.is_empty() {
+            out += "<p>The graph doesn't read its input.</p>\n";
+        } else {
+            out += "<ul>\n";
+            for (name, type_name) in content.inputs.iter() {
+                out += &format!(
+                    "<li><code>{}</code> reads <code>{}</code></li>\n",
+                    html_escape(name),
+                    html_escape(type_name)
+                );
+            }
+            out += "</ul>\n";
+        }
+        out += "<h2>Output</h2>\n";
+        match &content.output {
+            Some((name, type_name, doc)) => {
+                out += &format!(
+                    "<p><code>{}</code> outputs <code>{}</code>",
+                    html_escape(name),
+                    html_escape(type_name)
+                );
+                if !doc.is_empty() {
+                    out += &format!(": {}", html_escape(doc));
+                }
+                out += "</p>\n";
+            }
+            None => out += "<p>No output node is set.</p>\n",
+        }
+
+        out += "<h2>Nodes</h2>\n<table>\n<tr><th>Node</th><th>Op</th><th>Input</th>\
+                <th>Output</th><th>Parameters</th><th>Description</th>";
+        out += if profiled {
+            "<th>Time</th></tr>\n"
+        } else {
+            "</tr>\n"
+        };
+        for row in content.nodes.iter() {
+            out += &format!(
+                "<tr><td>{}</td><td>{}</td><td><code>{}</code></td><td><code>{}</code></td>\
+                 <td>{}</td><td>{}</td>",
+                html_escape(&row.name),
+                html_escape(&row.op),
+                html_escape(row.input_type),
+                html_escape(row.output_type),
+                html_escape(&row.params),
+                html_escape(&row.doc)
+            );
+            if profiled {
+                out += &format!(
+                    "<td>{}</td>",
+                    row.timing.as_deref().unwrap_or("not sampled")
+                );
+            }
+            out += "</tr>\n";
+        }
+        out += "</table>\n</body>\n</html>\n";
+        out
+    }
+}
+
+impl Graph {
+    /// Starts a report documenting the graph, taking op names and docs from `registry`.
+    pub fn document<'a>(&'a self, registry: &'a NodeRegistry) -> GraphDocument<'a> {
+        GraphDocument {
+            graph: self,
+            registry,
+            title: "Graph".to_string(),
+            profiles: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod document_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_document() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let rate_handle = graph.insert_node("rate", Constant(1.25));
+        let margin_handle = graph.insert_node("margin | net", MulInputs::<f64>::new());
+        graph.add_input(&margin_handle, &rate_handle)?;
+        graph.connect_to_input(&margin_handle)?;
+        graph.set_output_node(&margin_handle)?;
+
+        let mut registry = NodeRegistry::new();
+        registry
+            .register::<MulInputs<f64>, _, _>("pricing.margin")?
+            .display_as("Margin")
+            .describe("Applies the rate to the <cost>")
+            .document_output("price including margin");
+
+        let markdown = graph.document(&registry).title("Pricing").to_markdown();
+        assert!(markdown.starts_with("# Pricing\n\n## Diagram\n\n```mermaid\nflowchart LR\n"));
+        assert!(markdown.contains("    n1[[\"margin | net<br/>f64 → f64\"]]\n"));
+        assert!(markdown.contains("    input --> n1\n    n0 --> n1\n"));
+        assert!(markdown.contains("- `margin \\| net` reads `f64`\n"));
+        assert!(markdown.contains("`margin \\| net` outputs `f64`: price including margin\n"));
+        assert!(markdown.contains(
+            "| margin \\| net | Margin | `f64` | `f64` |  | Applies the rate to the <cost> |\n"
+        ));
+        assert!(!markdown.contains("Time"));
+
+        let profiler = Profiler::new(1);
+        graph
+            .build::<f64, f64>()?
+            .compute_profiled(&2.0, &profiler)?;
+        let html = graph.document(&registry).with_profile(&profiler).to_html();
+        assert!(html.contains("<title>Graph</title>"));
+        assert!(html.contains("<td>Applies the rate to the &lt;cost&gt;</td>"));
+        assert!(html.contains("<th>Time</th>"));
+        assert!(html.contains("mean over 1 samples"));
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use crate::graph::{Graph, GraphKey};
+use std::any::TypeId;
+use std::collections::HashMap;
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl Graph {
+    /// Graphviz DOT description of the graph, with the input and output types of every node.
+    /// Nodes reading the graph input are connected to an `input` node, and the output node
+    /// is drawn with a double border. Groups of `NodeStyle` become clusters and colors fill
+    /// their nodes.
+    pub fn to_dot(&self) -> String {
+        let order = self.canonical_order();
+        let ids = order
+            .iter()
+            .enumerate()
+            .map(|(i, key)| (*key, i))
+            .collect::<HashMap<_, _>>();
+        let type_name = |type_id| *self.type_names.get(&type_id).unwrap_or(&"unknown type");
+
+        let node_line = |key: &GraphKey, indent: &str| {
+            let node = &self.nodes[*key];
+            let output = if self.output_node == Some(*key) {
+                ", peripheries=2"
+            } else {
+                ""
+            };
+            let fill = node.style.color.as_ref().map_or(String::new(), |color| {
+                format!(", style=filled, fillcolor=\"{}\"", dot_escape(color))
+            });
+            format!(
+                "{}n{} [shape=box, label=\"{}\\n{} -> {}\"{}{}];\n",
+                indent,
+                ids[key],
+                dot_escape(&node.name),
+                dot_escape(type_name(node.inner.input_type())),
+                dot_escape(type_name(node.inner.output_type())),
+                output,
+                fill
+            )
+        };
+
+        let mut out = String::from("digraph {\n    input [shape=plaintext];\n");
+        let (ungrouped, groups) = self.split_groups(&order);
+        for key in ungrouped.iter() {
+            out += &node_line(key, "    ");
+        }
+        for (i, (group, members)) in groups.iter().enumerate() {
+            out += &format!(
+                "    subgraph cluster_{} {{\n        label=\"{}\";\n",
+                i,
+                dot_escape(group)
+            );
+            for key in members.iter() {
+                out += &node_line(key, "        ");
+            }
+            out.push_str("    }\n");
+        }
+        for key in order.iter() {
+            let node = &self.nodes[*key];
+            if node.connected_to_input && node.inner.input_type() != TypeId::of::<()>() {
+                out += &format!("    input -> n{};\n", ids[key]);
+            }
+            for input in node.inputs.iter() {
+                out += &format!("    n{} -> n{};\n", ids[input], ids[key]);
+            }
+            for before in node.after.iter() {
+                out += &format!("    n{} -> n{} [style=dashed];\n", ids[before], ids[key]);
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod dot_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_to_dot() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("the \"answer\"", Constant(42.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &const_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.set_output_node(&mul_handle)?;
+
+        assert_eq!(
+            graph.to_dot(),
+            "digraph {\n    input [shape=plaintext];\n    \
+             n0 [shape=box, label=\"the \\\"answer\\\"\\n() -> f64\"];\n    \
+             n1 [shape=box, label=\"mul\\nf64 -> f64\", peripheries=2];\n    \
+             input -> n1;\n    \
+             n0 -> n1;\n\
+             }\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_dot_styles() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("rate", Constant(2.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &const_handle)?;
+        graph.set_style(
+            &const_handle,
+            NodeStyle::group("inputs").with_color("#ff8800"),
+        )?;
+        graph.set_style(&mul_handle, NodeStyle::default().with_color("red"))?;
+
+        assert_eq!(
+            graph.to_dot(),
+            "digraph {\n    input [shape=plaintext];\n    \
+             n1 [shape=box, label=\"mul\\nf64 -> f64\", style=filled, fillcolor=\"red\"];\n    \
+             subgraph cluster_0 {\n        \
+             label=\"inputs\";\n        \
+             n0 [shape=box, label=\"rate\\n() -> f64\", style=filled, fillcolor=\"#ff8800\"];\n    \
+             }\n    \
+             n0 -> n1;\n\
+             }\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+use crate::compute::Compute;
+use crate::params::{ParamKind, ParamType, ParamValue, Parameters};
+use std::f64::consts::TAU;
+
+/// Shape of an easing curve, exposed as an integer parameter in declaration order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Curve {
+    #[default]
+    Linear,
+    Quad,
+    Cubic,
+    Elastic,
+    Bounce,
+}
+
+/// Which end of the curve eases, exposed as an integer parameter in declaration order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EaseMode {
+    #[default]
+    In,
+    Out,
+    InOut,
+}
+
+impl Curve {
+    pub const ALL: [Curve; 5] = [
+        Curve::Linear,
+        Curve::Quad,
+        Curve::Cubic,
+        Curve::Elastic,
+        Curve::Bounce,
+    ];
+
+    /// Eases into `t`, from 0 at 0 to 1 at 1.
+    pub fn ease_in(self, t: f64) -> f64 {
+        match self {
+            Curve::Linear => t,
+            Curve::Quad => t * t,
+            Curve::Cubic => t * t * t,
+            Curve::Elastic if t <= 0.0 || t >= 1.0 => t,
+            Curve::Elastic => -(10.0 * t - 10.0).exp2() * ((10.0 * t - 10.75) * TAU / 3.0).sin(),
+            Curve::Bounce => 1.0 - bounce_out(1.0 - t),
+        }
+    }
+}
+
+fn bounce_out(t: f64) -> f64 {
+    const N: f64 = 7.5625;
+    const D: f64 = 2.75;
+    let (offset, base) = match t {
+        t if t < 1.0 / D => (0.0, 0.0),
+        t if t < 2.0 / D => (1.5 / D, 0.75),
+        t if t < 2.5 / D => (2.25 / D, 0.9375),
+        _ => (2.625 / D, 0.984375),
+    };
+    N * (t - offset) * (t - offset) + base
+}
+
+impl EaseMode {
+    pub const ALL: [EaseMode; 3] = [EaseMode::In, EaseMode::Out, EaseMode::InOut];
+}
+
+macro_rules! enum_param_type {
+    ($($t:ident),*) => {$(
+        impl ParamType for $t {
+            const KIND: ParamKind = ParamKind::Int;
+            fn to_param(&self) -> ParamValue {
+                ParamValue::Int($t::ALL.iter().position(|v| v == self).unwrap() as i64)
+            }
+            fn from_param(value: ParamValue) -> Option<Self> {
+                match value {
+                    ParamValue::Int(v) => $t::ALL.get(usize::try_from(v).ok()?).copied(),
+                    _ => None,
+                }
+            }
+        }
+    )*};
+}
+enum_param_type!(Curve, EaseMode);
+
+/// Applies an easing curve to the first input, for animation graphs. Inputs are clamped to
+/// 0..1; `Elastic` leaves that range in between. Both fields are parameters, so editors
+/// can switch curves without replacing the node.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ease {
+    pub curve: Curve,
+    pub mode: EaseMode,
+}
+
+impl Ease {
+    pub fn new(curve: Curve, mode: EaseMode) -> Self {
+        Self { curve, mode }
+    }
+
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        let curve = self.curve;
+        match self.mode {
+            EaseMode::In => curve.ease_in(t),
+            EaseMode::Out => 1.0 - curve.ease_in(1.0 - t),
+            EaseMode::InOut if t < 0.5 => curve.ease_in(2.0 * t) / 2.0,
+            EaseMode::InOut => 1.0 - curve.ease_in(2.0 - 2.0 * t) / 2.0,
+        }
+    }
+}
+crate::impl_parameters!(Ease {
+    curve: Curve [0, 4],
+    mode: EaseMode [0, 2],
+});
+
+impl Compute for Ease {
+    type In = f64;
+    type Out = f64;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.apply(inputs.first().map(|v| **v).unwrap_or_default())
+    }
+    fn parameters(&self) -> Option<&dyn Parameters> {
+        Some(self)
+    }
+    fn parameters_mut(&mut self) -> Option<&mut dyn Parameters> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod easing_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_easing() -> Result<(), ComputeGraphErrors> {
+        let quad = |mode| Ease::new(Curve::Quad, mode);
+        assert_eq!(quad(EaseMode::In).apply(0.5), 0.25);
+        assert_eq!(quad(EaseMode::Out).apply(0.5), 0.75);
+        assert_eq!(quad(EaseMode::InOut).apply(0.25), 0.125);
+        assert_eq!(Ease::new(Curve::Cubic, EaseMode::InOut).apply(0.75), 0.9375);
+        assert_eq!(Ease::new(Curve::Bounce, EaseMode::Out).apply(0.5), 0.765625);
+        for curve in Curve::ALL {
+            for mode in EaseMode::ALL {
+                let ease = Ease::new(curve, mode);
+                assert_eq!((ease.apply(-1.0), ease.apply(0.0)), (0.0, 0.0));
+                assert_eq!((ease.apply(1.0), ease.apply(2.0)), (1.0, 1.0));
+            }
+        }
+        let elastic = Ease::new(Curve::Elastic, EaseMode::Out);
+        assert!((1..10).any(|i| elastic.apply(i as f64 / 10.0) > 1.0));
+
+        //  Input ── ease
+        let mut graph = Graph::new();
+        let ease_handle = graph.insert_node("ease", Ease::default());
+        graph.set_output_node(&ease_handle)?;
+        assert_eq!(graph.build::<f64, f64>()?.compute(&0.5), 0.5);
+        graph.set_param(&ease_handle, "curve", 2i64)?;
+        graph.set_param(&ease_handle, "mode", 1i64)?;
+        assert_eq!(graph.build::<f64, f64>()?.compute(&0.5), 0.875);
+        assert_eq!(graph.get_params(&ease_handle)?[0].value, ParamValue::Int(2));
+        assert!(graph.set_param(&ease_handle, "curve", 5i64).is_err());
+        Ok(())
+    }
+}
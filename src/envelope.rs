@@ -0,0 +1,144 @@
+use crate::ports::PortCompute;
+
+#[derive(Clone, Copy, Default)]
+enum Stage {
+    #[default]
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Attack-decay-sustain-release envelope from 0 to 1, with linear segments. Opening the
+/// gate on port 0 rises to 1 over `attack` seconds, then falls to the `sustain` level over
+/// `decay` seconds and holds it; closing the gate falls to 0 over `release` seconds. Port 1
+/// reads the time step in seconds, like the graph input fed by `Simulator`. Event gates can
+/// be held as a `bool` with a `Latch`. Evaluated through `ComputeGraph::try_compute_mut`.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Adsr {
+    pub attack: f64,
+    pub decay: f64,
+    pub sustain: f64,
+    pub release: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    stage: Stage,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    level: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    released_from: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    open: bool,
+}
+
+impl Adsr {
+    pub fn new(attack: f64, decay: f64, sustain: f64, release: f64) -> Self {
+        Self {
+            attack,
+            decay,
+            sustain,
+            release,
+            ..Self::default()
+        }
+    }
+}
+
+impl PortCompute for Adsr {
+    type Ports = (bool, f64);
+    type Out = f64;
+    fn compute(&self, ports: (&bool, &f64)) -> Self::Out {
+        self.clone().try_compute_mut(ports).unwrap()
+    }
+
+    fn try_compute_mut(&mut self, (gate, dt): (&bool, &f64)) -> Result<f64, String> {
+        if *gate && !self.open {
+            // Retriggering attacks from the current level, so the output doesn't jump
+            self.stage = Stage::Attack;
+        } else if !*gate && self.open {
+            self.stage = Stage::Release;
+            self.released_from = self.level;
+        }
+        self.open = *gate;
+
+        let sustain = self.sustain.clamp(0.0, 1.0);
+        match self.stage {
+            Stage::Idle => self.level = 0.0,
+            Stage::Attack => {
+                self.level = if self.attack > 0.0 {
+                    self.level + dt / self.attack
+                } else {
+                    1.0
+                };
+                if self.level >= 1.0 {
+                    self.level = 1.0;
+                    self.stage = Stage::Decay;
+                }
+            }
+            Stage::Decay => {
+                self.level = if self.decay > 0.0 {
+                    self.level - dt * (1.0 - sustain) / self.decay
+                } else {
+                    sustain
+                };
+                if self.level <= sustain {
+                    self.level = sustain;
+                    self.stage = Stage::Sustain;
+                }
+            }
+            Stage::Sustain => self.level = sustain,
+            Stage::Release => {
+                self.level = if self.release > 0.0 {
+                    self.level - dt * self.released_from / self.release
+                } else {
+                    0.0
+                };
+                if self.level <= 0.0 {
+                    self.level = 0.0;
+                    self.stage = Stage::Idle;
+                }
+            }
+        }
+        Ok(self.level)
+    }
+}
+
+#[cfg(test)]
+mod envelope_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_adsr() -> Result<(), ComputeGraphErrors> {
+        //  gate ────────── adsr (port 0)
+        //  Input (dt) ── dt ── adsr (port 1)
+        let mut graph = Graph::new();
+        let gate_handle = graph.insert_node("gate", Parameter::new("gate", false));
+        let dt_handle = graph.insert_node("dt", AddInputs::<f64>::new());
+        let adsr_handle = graph.insert_port_node("adsr", Adsr::new(0.5, 0.5, 0.5, 1.0));
+        graph.add_input_port(&adsr_handle, 0, &gate_handle)?;
+        graph.add_input_port(&adsr_handle, 1, &dt_handle)?;
+        graph.set_output_node(&adsr_handle)?;
+        let mut compute_graph = graph.build::<f64, f64>()?;
+
+        let mut levels = vec![compute_graph.compute_mut(&0.25)];
+        compute_graph.set_parameter("gate", true)?;
+        levels.extend((0..5).map(|_| compute_graph.compute_mut(&0.25)));
+        compute_graph.set_parameter("gate", false)?;
+        levels.extend((0..5).map(|_| compute_graph.compute_mut(&0.25)));
+        assert_eq!(
+            levels,
+            [0.0, 0.5, 1.0, 0.75, 0.5, 0.5, 0.375, 0.25, 0.125, 0.0, 0.0]
+        );
+
+        // Retriggering during the release attacks from the current level
+        compute_graph.set_parameter("gate", true)?;
+        levels.clear();
+        levels.extend((0..2).map(|_| compute_graph.compute_mut(&0.25)));
+        compute_graph.set_parameter("gate", false)?;
+        levels.push(compute_graph.compute_mut(&0.25));
+        compute_graph.set_parameter("gate", true)?;
+        levels.push(compute_graph.compute_mut(&0.25));
+        assert_eq!(levels, [0.5, 1.0, 0.75, 1.0]);
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+use crate::graph::{ComputeGraphErrors, NodeHandle};
+use std::any::TypeId;
+use std::fmt;
+use std::ops::Deref;
+
+/// Stable identifier of a kind of `ComputeGraphErrors`. The numbers never change, so
+/// frontends can store them or map them to UI affordances.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ErrorCode {
+    NoInputNodes = 1,
+    ConflictingEdit = 2,
+    NoOutputNode = 3,
+    NodeMissing = 4,
+    IncompatibleNewNode = 5,
+    GraphCycle = 6,
+    WrongTypes = 7,
+    InvalidParameter = 8,
+    Serialization = 9,
+    Import = 10,
+    LimitExceeded = 11,
+    CapabilityDenied = 12,
+    NodeFailed = 13,
+    InvalidSignature = 14,
+    InvalidGraph = 15,
+    BrokenInvariant = 16,
+    WrongArity = 17,
+    ForeignHandle = 18,
+    StaleHandle = 19,
+    NameCollision = 20,
+}
+
+impl ErrorCode {
+    pub fn number(self) -> u16 {
+        self as u16
+    }
+
+    /// Key like `error.graph-cycle`, also used by `Localizer`.
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::NoInputNodes => "error.no-input-nodes",
+            Self::ConflictingEdit => "error.conflicting-edit",
+            Self::NoOutputNode => "error.no-output-node",
+            Self::NodeMissing => "error.node-missing",
+            Self::IncompatibleNewNode => "error.incompatible-new-node",
+            Self::GraphCycle => "error.graph-cycle",
+            Self::WrongTypes => "error.wrong-types",
+            Self::InvalidParameter => "error.invalid-parameter",
+            Self::Serialization => "error.serialization",
+            Self::Import => "error.import",
+            Self::LimitExceeded => "error.limit-exceeded",
+            Self::CapabilityDenied => "error.capability-denied",
+            Self::NodeFailed => "error.node-failed",
+            Self::InvalidSignature => "error.invalid-signature",
+            Self::InvalidGraph => "error.invalid-graph",
+            Self::BrokenInvariant => "error.broken-invariant",
+            Self::WrongArity => "error.wrong-arity",
+            Self::ForeignHandle => "error.foreign-handle",
+            Self::StaleHandle => "error.stale-handle",
+            Self::NameCollision => "error.name-collision",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CG{:04}", self.number())
+    }
+}
+
+/// Message of an error together with the nodes and types it is about, so frontends can
+/// point at them without parsing the message. Derefs to the message.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ErrorDetails {
+    message: String,
+    nodes: Vec<NodeHandle>,
+    types: Vec<(TypeId, &'static str)>,
+}
+
+impl ErrorDetails {
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Nodes involved, most relevant first.
+    pub fn nodes(&self) -> &[NodeHandle] {
+        &self.nodes
+    }
+
+    /// Types involved with their names, e.g. the expected type before the one found.
+    pub fn types(&self) -> &[(TypeId, &'static str)] {
+        &self.types
+    }
+}
+
+impl From<String> for ErrorDetails {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<&str> for ErrorDetails {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl Deref for ErrorDetails {
+    type Target = str;
+    fn deref(&self) -> &str {
+        &self.message
+    }
+}
+
+impl PartialEq<&str> for ErrorDetails {
+    fn eq(&self, other: &&str) -> bool {
+        self.message == *other
+    }
+}
+
+impl fmt::Display for ErrorDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl ComputeGraphErrors {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::NoInputNodes => ErrorCode::NoInputNodes,
+            Self::ConflictingEdit(_) => ErrorCode::ConflictingEdit,
+            Self::NoOutputNode => ErrorCode::NoOutputNode,
+            Self::NodeMissing => ErrorCode::NodeMissing,
+            Self::IncompatibleNewNode(_) => ErrorCode::IncompatibleNewNode,
+            Self::GraphCycle(_) => ErrorCode::GraphCycle,
+            Self::WrongTypes(_) => ErrorCode::WrongTypes,
+            Self::InvalidParameter(_) => ErrorCode::InvalidParameter,
+            Self::Serialization(_) => ErrorCode::Serialization,
+            Self::Import(_) => ErrorCode::Import,
+            Self::LimitExceeded(_) => ErrorCode::LimitExceeded,
+            Self::CapabilityDenied(_) => ErrorCode::CapabilityDenied,
+            Self::NodeFailed(_) => ErrorCode::NodeFailed,
+            Self::BrokenInvariant(_) => ErrorCode::BrokenInvariant,
+            Self::WrongArity(_) => ErrorCode::WrongArity,
+            Self::ForeignHandle(_) => ErrorCode::ForeignHandle,
+            Self::StaleHandle(_) => ErrorCode::StaleHandle,
+            Self::NameCollision(_) => ErrorCode::NameCollision,
+            #[cfg(feature = "signing")]
+            Self::InvalidSignature => ErrorCode::InvalidSignature,
+            #[cfg(feature = "serde")]
+            Self::InvalidGraph(_) => ErrorCode::InvalidGraph,
+        }
+    }
+
+    /// Structured details, for the variants that carry a message.
+    pub fn details(&self) -> Option<&ErrorDetails> {
+        match self {
+            Self::ConflictingEdit(details)
+            | Self::IncompatibleNewNode(details)
+            | Self::GraphCycle(details)
+            | Self::WrongTypes(details)
+            | Self::InvalidParameter(details)
+            | Self::Serialization(details)
+            | Self::Import(details)
+            | Self::LimitExceeded(details)
+            | Self::CapabilityDenied(details)
+            | Self::NodeFailed(details)
+            | Self::BrokenInvariant(details)
+            | Self::WrongArity(details)
+            | Self::ForeignHandle(details)
+            | Self::StaleHandle(details)
+            | Self::NameCollision(details) => Some(details),
+            _ => None,
+        }
+    }
+
+    fn details_mut(&mut self) -> Option<&mut ErrorDetails> {
+        match self {
+            Self::ConflictingEdit(details)
+            | Self::IncompatibleNewNode(details)
+            | Self::GraphCycle(details)
+            | Self::WrongTypes(details)
+            | Self::InvalidParameter(details)
+            | Self::Serialization(details)
+            | Self::Import(details)
+            | Self::LimitExceeded(details)
+            | Self::CapabilityDenied(details)
+            | Self::NodeFailed(details)
+            | Self::BrokenInvariant(details)
+            | Self::WrongArity(details)
+            | Self::ForeignHandle(details)
+            | Self::StaleHandle(details)
+            | Self::NameCollision(details) => Some(details),
+            _ => None,
+        }
+    }
+
+    /// Nodes the error is about, empty when it isn't about particular nodes.
+    pub fn nodes(&self) -> &[NodeHandle] {
+        self.details().map_or(&[], |details| details.nodes())
+    }
+
+    pub(crate) fn with_node(mut self, node: NodeHandle) -> Self {
+        if let Some(details) = self.details_mut() {
+            if !details.nodes.contains(&node) {
+                details.nodes.push(node);
+            }
+        }
+        self
+    }
+
+    pub(crate) fn with_type(mut self, type_id: TypeId, type_name: &'static str) -> Self {
+        if let Some(details) = self.details_mut() {
+            details.types.push((type_id, type_name));
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod errors_tests {
+    use crate::prelude::*;
+    use std::any::TypeId;
+
+    #[test]
+    fn test_structured_errors() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let flag_handle = graph.insert_node("flag", Constant(true));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        let error = graph.add_input(&mul_handle, &flag_handle).err().unwrap();
+        assert_eq!(error.code(), ErrorCode::WrongTypes);
+        assert_eq!(error.code().to_string(), "CG0007");
+        assert_eq!(error.nodes(), [*mul_handle, *flag_handle]);
+        let types = error.details().unwrap().types();
+        assert_eq!(types[0].0, TypeId::of::<f64>());
+        assert_eq!(types[1].0, TypeId::of::<bool>());
+
+        graph.add_input(&mul_handle, &mul_handle).ok();
+        graph.set_output_node(&mul_handle)?;
+        let error = graph.build::<f64, f64>().err().unwrap();
+        assert_eq!(error.code().number(), 6);
+        assert_eq!(error.nodes(), [*mul_handle]);
+        assert_eq!(
+            ComputeGraphErrors::NoOutputNode.code().key(),
+            ComputeGraphErrors::NoOutputNode.key()
+        );
+        assert!(ComputeGraphErrors::NodeMissing.nodes().is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use crate::compute::{Compute, Value};
+
+/// Edge carrying a trigger instead of a continuous value: fired with an optional payload
+/// on the evaluations where something happened, empty on all others. `Event<()>` is a
+/// plain trigger. Event nodes keep their state across `ComputeGraph::try_compute_mut`
+/// calls; `compute` only previews their next output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Event<T = ()>(Option<T>);
+
+impl<T> Default for Event<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T> Event<T> {
+    pub fn fire(payload: T) -> Self {
+        Self(Some(payload))
+    }
+
+    pub fn none() -> Self {
+        Self(None)
+    }
+
+    pub fn is_fired(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub fn payload(&self) -> Option<&T> {
+        self.0.as_ref()
+    }
+}
+
+impl Event {
+    pub fn trigger() -> Self {
+        Self(Some(()))
+    }
+}
+
+/// Payload of the last fired event among `inputs`.
+fn last_fired<T: Clone>(inputs: &[&Event<T>]) -> Option<T> {
+    inputs.iter().rev().find_map(|event| event.0.clone())
+}
+
+/// Fires with the new value when the first input differs from the previous evaluation.
+/// The first value seen doesn't fire.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OnChange<T> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last: Option<T>,
+}
+
+impl<T> OnChange<T> {
+    pub fn new() -> Self {
+        Self { last: None }
+    }
+}
+
+impl<T> Compute for OnChange<T>
+where
+    T: Value + PartialEq,
+{
+    type In = T;
+    type Out = Event<T>;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.clone().try_compute_mut(inputs).unwrap()
+    }
+
+    fn try_compute_mut(&mut self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        let Some(value) = inputs.first() else {
+            return Ok(Event::none());
+        };
+        let changed = self.last.as_ref().is_some_and(|last| last != *value);
+        self.last = Some((*value).clone());
+        Ok(if changed {
+            Event::fire((*value).clone())
+        } else {
+            Event::none()
+        })
+    }
+}
+
+/// Fires the payload of the last event once no event arrived for `quiet` evaluations.
+/// A `quiet` of 0 passes events through unchanged.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Debounce<T> {
+    pub quiet: u32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pending: Option<T>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    since: u32,
+}
+
+impl<T> Debounce<T> {
+    pub fn new(quiet: u32) -> Self {
+        Self {
+            quiet,
+            pending: None,
+            since: 0,
+        }
+    }
+}
+
+impl<T> Compute for Debounce<T>
+where
+    T: Value,
+{
+    type In = Event<T>;
+    type Out = Event<T>;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.clone().try_compute_mut(inputs).unwrap()
+    }
+
+    fn try_compute_mut(&mut self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        match last_fired(inputs) {
+            Some(payload) => {
+                self.pending = Some(payload);
+                self.since = 0;
+            }
+            None => self.since = self.since.saturating_add(1),
+        }
+        if self.since >= self.quiet {
+            return Ok(Event(self.pending.take()));
+        }
+        Ok(Event::none())
+    }
+}
+
+/// Holds the payload of the last fired event as a continuous value, starting at `initial`.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Latch<T> {
+    pub initial: T,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    value: Option<T>,
+}
+
+impl<T> Latch<T> {
+    pub fn new(initial: T) -> Self {
+        Self {
+            initial,
+            value: None,
+        }
+    }
+}
+
+impl<T> Compute for Latch<T>
+where
+    T: Value,
+{
+    type In = Event<T>;
+    type Out = T;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.clone().try_compute_mut(inputs).unwrap()
+    }
+
+    fn try_compute_mut(&mut self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        if let Some(payload) = last_fired(inputs) {
+            self.value = Some(payload);
+        }
+        Ok(self.value.clone().unwrap_or_else(|| self.initial.clone()))
+    }
+}
+
+#[cfg(test)]
+mod events_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_event_nodes() -> Result<(), ComputeGraphErrors> {
+        //  Input ── on_change ── debounce ── latch
+        let mut graph = Graph::new();
+        let on_change_handle = graph.insert_node("on_change", OnChange::<i64>::new());
+        let debounce_handle = graph.insert_node("debounce", Debounce::<i64>::new(2));
+        let latch_handle = graph.insert_node("latch", Latch::new(-1i64));
+        graph.add_input(&debounce_handle, &on_change_handle)?;
+        graph.add_input(&latch_handle, &debounce_handle)?;
+        graph.set_output_node(&latch_handle)?;
+        graph.probe(&on_change_handle)?;
+        let mut compute_graph = graph.build::<i64, i64>()?;
+
+        // The value settles at 3 and then 5; only settled values reach the latch
+        let mut latched = Vec::new();
+        let mut changes = Vec::new();
+        for value in [1, 2, 3, 3, 3, 3, 4, 5, 5, 5] {
+            latched.push(compute_graph.compute_mut(&value));
+            let change = compute_graph.probed_value::<Event<i64>>(&on_change_handle);
+            changes.push(change.unwrap().payload().copied());
+        }
+        assert_eq!(latched, [-1, -1, -1, -1, 3, 3, 3, 3, 3, 5]);
+        assert_eq!(
+            changes,
+            [
+                None,
+                Some(2),
+                Some(3),
+                None,
+                None,
+                None,
+                Some(4),
+                Some(5),
+                None,
+                None
+            ]
+        );
+        // `compute` reads the state without changing it
+        assert_eq!(compute_graph.compute(&5), 5);
+        assert!(Event::trigger().is_fired() && !Event::<f64>::none().is_fired());
+        Ok(())
+    }
+}
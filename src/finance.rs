@@ -0,0 +1,378 @@
+use crate::compute::{Compute, Value};
+use crate::formula::Expr;
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Sub};
+
+/// Number types the financial nodes work with: `f64`, and `Decimal`
+/// with the `rust_decimal` feature.
+pub trait FinanceNumber:
+    Value
+    + Copy
+    + PartialEq
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    fn one() -> Self;
+    fn from_i32(value: i32) -> Self;
+    /// `None` on overflow, or for `f64` a result that isn't finite.
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn checked_div(self, other: Self) -> Option<Self>;
+
+    /// `self` to the power of `exponent` by squaring, `None` on overflow or zero division.
+    fn checked_powi(self, exponent: i32) -> Option<Self> {
+        let mut result = Self::one();
+        let mut base = self;
+        let mut remaining = exponent.unsigned_abs();
+        while remaining > 0 {
+            if remaining & 1 == 1 {
+                result = result.checked_mul(base)?;
+            }
+            remaining >>= 1;
+            if remaining > 0 {
+                base = base.checked_mul(base)?;
+            }
+        }
+        if exponent < 0 {
+            Self::one().checked_div(result)
+        } else {
+            Some(result)
+        }
+    }
+}
+
+impl FinanceNumber for f64 {
+    fn one() -> Self {
+        1.0
+    }
+    fn from_i32(value: i32) -> Self {
+        value as f64
+    }
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Some(self + other).filter(|v| v.is_finite())
+    }
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        Some(self - other).filter(|v| v.is_finite())
+    }
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        Some(self * other).filter(|v| v.is_finite())
+    }
+    fn checked_div(self, other: Self) -> Option<Self> {
+        Some(self / other).filter(|v| v.is_finite())
+    }
+    fn checked_powi(self, exponent: i32) -> Option<Self> {
+        Some(f64::powi(self, exponent)).filter(|v| v.is_finite())
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl FinanceNumber for rust_decimal::Decimal {
+    fn one() -> Self {
+        rust_decimal::Decimal::ONE
+    }
+    fn from_i32(value: i32) -> Self {
+        value.into()
+    }
+    fn checked_add(self, other: Self) -> Option<Self> {
+        rust_decimal::Decimal::checked_add(self, other)
+    }
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        rust_decimal::Decimal::checked_sub(self, other)
+    }
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        rust_decimal::Decimal::checked_mul(self, other)
+    }
+    fn checked_div(self, other: Self) -> Option<Self> {
+        rust_decimal::Decimal::checked_div(self, other)
+    }
+}
+
+fn overflow<T>(value: Option<T>) -> Result<T, String> {
+    value.ok_or_else(|| "arithmetic overflow".to_string())
+}
+
+fn divide<T: FinanceNumber>(dividend: T, divisor: T) -> Result<T, String> {
+    if divisor == T::default() {
+        return Err("division by zero".to_string());
+    }
+    overflow(dividend.checked_div(divisor))
+}
+
+fn power<T: FinanceNumber>(base: T, exponent: i32) -> Result<T, String> {
+    if exponent < 0 && base == T::default() {
+        return Err("division by zero".to_string());
+    }
+    overflow(base.checked_powi(exponent))
+}
+
+/// Grows the first input (the principal) by `rate` per period, compounded over `periods`.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompoundInterest<T> {
+    pub rate: T,
+    pub periods: i32,
+}
+impl<T> CompoundInterest<T> {
+    pub fn new(rate: T, periods: i32) -> Self {
+        Self { rate, periods }
+    }
+}
+
+impl<T: FinanceNumber> Compute for CompoundInterest<T> {
+    type In = T;
+    type Out = T;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.try_compute(inputs).unwrap()
+    }
+    fn try_compute(&self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        let principal = inputs.first().map_or(T::default(), |v| **v);
+        let growth = power(overflow(T::one().checked_add(self.rate))?, self.periods)?;
+        overflow(principal.checked_mul(growth))
+    }
+    fn formula(&self, inputs: &[Expr]) -> Option<Expr> {
+        let growth = Expr::Number(1.0) + Expr::number_of(&self.rate)?;
+        Some(inputs.first()?.clone() * growth.pow(Expr::Number(self.periods as f64)))
+    }
+}
+
+/// Net present value of the inputs, taken as cash flows of periods 0, 1, 2, ...
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetPresentValue<T> {
+    pub rate: T,
+}
+impl<T> NetPresentValue<T> {
+    pub fn new(rate: T) -> Self {
+        Self { rate }
+    }
+}
+
+impl<T: FinanceNumber> Compute for NetPresentValue<T> {
+    type In = T;
+    type Out = T;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.try_compute(inputs).unwrap()
+    }
+    fn try_compute(&self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        let discount = overflow(T::one().checked_add(self.rate))?;
+        let mut factor = T::one();
+        let mut npv = T::default();
+        for (period, cash_flow) in inputs.iter().enumerate() {
+            if period > 0 {
+                factor = overflow(factor.checked_mul(discount))?;
+            }
+            npv = overflow(npv.checked_add(divide(**cash_flow, factor)?))?;
+        }
+        Ok(npv)
+    }
+    fn formula(&self, inputs: &[Expr]) -> Option<Expr> {
+        let discount = Expr::Number(1.0) + Expr::number_of(&self.rate)?;
+        let terms = inputs
+            .iter()
+            .enumerate()
+            .map(|(period, cash_flow)| match period {
+                0 => cash_flow.clone(),
+                1 => cash_flow.clone() / discount.clone(),
+                _ => cash_flow.clone() / discount.clone().pow(Expr::Number(period as f64)),
+            })
+            .collect::<Vec<_>>();
+        Some(match terms.len() {
+            0 => Expr::Number(0.0),
+            1 => terms.into_iter().next()?,
+            _ => Expr::Add(terms),
+        })
+    }
+}
+
+/// Fixed payment per period that pays off the first input (the loan principal)
+/// over `periods` at `rate` per period.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AmortizationPayment<T> {
+    pub rate: T,
+    pub periods: i32,
+}
+impl<T> AmortizationPayment<T> {
+    pub fn new(rate: T, periods: i32) -> Self {
+        Self { rate, periods }
+    }
+}
+
+impl<T: FinanceNumber> Compute for AmortizationPayment<T> {
+    type In = T;
+    type Out = T;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.try_compute(inputs).unwrap()
+    }
+    fn try_compute(&self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        let principal = inputs.first().map_or(T::default(), |v| **v);
+        if self.periods == 0 {
+            return Ok(T::default());
+        }
+        if self.rate == T::default() {
+            return divide(principal, T::from_i32(self.periods));
+        }
+        let growth = power(overflow(T::one().checked_add(self.rate))?, self.periods)?;
+        let interest = overflow(principal.checked_mul(self.rate))?;
+        divide(
+            overflow(interest.checked_mul(growth))?,
+            overflow(growth.checked_sub(T::one()))?,
+        )
+    }
+    fn formula(&self, inputs: &[Expr]) -> Option<Expr> {
+        let principal = inputs.first()?.clone();
+        let periods = Expr::Number(self.periods as f64);
+        if self.periods == 0 {
+            return Some(Expr::Number(0.0));
+        }
+        if self.rate == T::default() {
+            return Some(principal / periods);
+        }
+        let rate = Expr::number_of(&self.rate)?;
+        let growth = (Expr::Number(1.0) + rate.clone()).pow(periods);
+        Some(principal * rate * growth.clone() / (growth - Expr::Number(1.0)))
+    }
+}
+
+/// Change from the first input to the second, in percent of the first.
+/// Yields zero when the first input is zero.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PercentChange<T> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _intype: PhantomData<T>,
+}
+impl<T> PercentChange<T> {
+    pub fn new() -> Self {
+        Self {
+            _intype: PhantomData,
+        }
+    }
+}
+
+impl<T: FinanceNumber> Compute for PercentChange<T> {
+    type In = T;
+    type Out = T;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.try_compute(inputs).unwrap()
+    }
+    fn try_compute(&self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        let (Some(old), Some(new)) = (inputs.first(), inputs.get(1)) else {
+            return Ok(T::default());
+        };
+        if **old == T::default() {
+            return Ok(T::default());
+        }
+        let change = divide(overflow(new.checked_sub(**old))?, **old)?;
+        overflow(change.checked_mul(T::from_i32(100)))
+    }
+    fn formula(&self, inputs: &[Expr]) -> Option<Expr> {
+        let [old, new] = inputs else {
+            return None;
+        };
+        Some((new.clone() - old.clone()) / old.clone() * Expr::Number(100.0))
+    }
+}
+
+#[cfg(test)]
+mod finance_tests {
+    use crate::prelude::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn test_finance_nodes() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let interest_handle = graph.insert_node("interest", CompoundInterest::new(0.05, 10));
+        let payment_handle = graph.insert_node("payment", AmortizationPayment::new(0.01, 12));
+        let change_handle = graph.insert_node("change", PercentChange::<f64>::new());
+        let npv_handle = graph.insert_node("npv", NetPresentValue::new(0.1));
+        let principal_handle = graph.insert_node("principal", Constant(1000.0));
+        let grown_handle = graph.insert_node("grown", Constant(1100.0));
+        for handle in [principal_handle, grown_handle] {
+            graph.add_input(&change_handle, &handle)?;
+            graph.add_input(&npv_handle, &handle)?;
+        }
+
+        let interest = graph.build_for_node::<f64, f64>(&interest_handle)?;
+        assert!(close(interest.compute(&1000.0), 1628.894627));
+        let payment = graph.build_for_node::<f64, f64>(&payment_handle)?;
+        assert!(close(payment.compute(&10000.0), 888.487887));
+        let change = graph.build_for_node::<(), f64>(&change_handle)?;
+        assert!(close(change.compute(&()), 10.0));
+        let npv = graph.build_for_node::<(), f64>(&npv_handle)?;
+        assert!(close(npv.compute(&()), 2000.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_finance_reports_bad_rates() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let npv_handle = graph.insert_node("npv", NetPresentValue::new(-1.0));
+        let interest_handle = graph.insert_node("interest", CompoundInterest::new(-1.0, -2));
+        let grown_handle = graph.insert_node("grown", CompoundInterest::new(1e300, 3));
+        for value in [100.0, 110.0] {
+            let handle = graph.insert_node("flow", Constant(value));
+            graph.add_input(&npv_handle, &handle)?;
+        }
+
+        let npv = graph.build_for_node::<(), f64>(&npv_handle)?;
+        assert!(matches!(
+            npv.try_compute(&()),
+            Err(ComputeGraphErrors::NodeFailed(message)) if message.contains("division by zero")
+        ));
+        let interest = graph.build_for_node::<f64, f64>(&interest_handle)?;
+        assert!(matches!(
+            interest.try_compute(&1.0),
+            Err(ComputeGraphErrors::NodeFailed(message)) if message.contains("division by zero")
+        ));
+        let grown = graph.build_for_node::<f64, f64>(&grown_handle)?;
+        assert!(matches!(
+            grown.try_compute(&1.0),
+            Err(ComputeGraphErrors::NodeFailed(message)) if message.contains("overflow")
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn test_decimal_finance() -> Result<(), ComputeGraphErrors> {
+        use rust_decimal::Decimal;
+
+        let mut graph = Graph::new();
+        let payment_handle =
+            graph.insert_node("payment", AmortizationPayment::new(Decimal::ZERO, 4));
+        let interest_handle =
+            graph.insert_node("interest", CompoundInterest::new(Decimal::new(1, 1), 2));
+        graph.add_input(&interest_handle, &payment_handle)?;
+        graph.set_output_node(&interest_handle)?;
+        let compute_graph = graph.build::<Decimal, Decimal>()?;
+        assert_eq!(
+            compute_graph.compute(&Decimal::from(100)),
+            Decimal::new(3025, 2)
+        );
+
+        assert_eq!(
+            Decimal::new(11, 1).checked_powi(-2),
+            Some(Decimal::ONE / Decimal::new(121, 2))
+        );
+        assert_eq!(Decimal::MAX.checked_powi(2), None);
+        let mut graph = Graph::new();
+        let npv_handle = graph.insert_node("npv", NetPresentValue::new(-Decimal::ONE));
+        for value in [100, 110] {
+            let handle = graph.insert_node("flow", Constant(Decimal::from(value)));
+            graph.add_input(&npv_handle, &handle)?;
+        }
+        let npv = graph.build_for_node::<(), Decimal>(&npv_handle)?;
+        assert!(matches!(
+            npv.try_compute(&()),
+            Err(ComputeGraphErrors::NodeFailed(message)) if message.contains("division by zero")
+        ));
+        Ok(())
+    }
+}
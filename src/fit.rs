@@ -0,0 +1,215 @@
+use crate::com_graph::ComputeGraph;
+use crate::compute::Value;
+use crate::graph::{ComputeGraphErrors, NodeHandle};
+use crate::params::{ParamInfo, ParamKind};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FitOptions {
+    /// Initial step size, adapted while fitting.
+    pub learning_rate: f64,
+    pub max_iterations: usize,
+    /// Stops once an iteration improves the loss by less than this.
+    pub tolerance: f64,
+    /// Relative step of the central finite differences.
+    pub epsilon: f64,
+}
+
+impl Default for FitOptions {
+    fn default() -> Self {
+        Self {
+            learning_rate: 0.1,
+            max_iterations: 1000,
+            tolerance: 1e-12,
+            epsilon: 1e-6,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FitReport {
+    /// Fitted values, in the order of the tuned parameters.
+    pub values: Vec<f64>,
+    /// Mean squared error at `values`.
+    pub loss: f64,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+struct Tuned<'a> {
+    node: NodeHandle,
+    name: &'a str,
+    range: Option<(f64, f64)>,
+}
+
+fn mean_squared_error<In: Value>(
+    graph: &ComputeGraph<In, f64>,
+    dataset: &[(In, f64)],
+) -> Result<f64, ComputeGraphErrors> {
+    let mut sum = 0.0;
+    for (input, expected) in dataset {
+        let error = graph.try_compute(input)? - expected;
+        sum += error * error;
+    }
+    Ok(sum / dataset.len().max(1) as f64)
+}
+
+fn assign<In>(
+    graph: &mut ComputeGraph<In, f64>,
+    tuned: &[Tuned],
+    values: &[f64],
+) -> Result<(), ComputeGraphErrors> {
+    for (param, value) in tuned.iter().zip(values) {
+        graph.set_param(&param.node, param.name, *value)?;
+    }
+    Ok(())
+}
+
+/// Tunes float parameters of `graph`, such as the `value` of `Constant` nodes, to minimize the
+/// mean squared error over `dataset` pairs of inputs and measured outputs.
+/// Gradients come from central finite differences, and the step size halves whenever a step
+/// would increase the loss. The graph is left with the fitted values set.
+pub fn optimize<In: Value>(
+    graph: &mut ComputeGraph<In, f64>,
+    dataset: &[(In, f64)],
+    params_to_tune: &[(NodeHandle, &str)],
+    options: &FitOptions,
+) -> Result<FitReport, ComputeGraphErrors> {
+    let mut tuned = Vec::with_capacity(params_to_tune.len());
+    let mut values = Vec::with_capacity(params_to_tune.len());
+    for (node, name) in params_to_tune {
+        let info: ParamInfo = graph
+            .get_params(node)
+            .into_iter()
+            .find(|info| info.name == *name)
+            .ok_or_else(|| {
+                ComputeGraphErrors::InvalidParameter(
+                    format!("node has no parameter '{}'", name).into(),
+                )
+            })?;
+        if info.kind != ParamKind::Float {
+            return Err(ComputeGraphErrors::InvalidParameter(
+                format!(
+                    "parameter '{}' is {:?}, only floats can be fitted",
+                    name, info.kind
+                )
+                .into(),
+            ));
+        }
+        tuned.push(Tuned {
+            node: *node,
+            name,
+            range: info.range,
+        });
+        values.push(info.value.as_f64());
+    }
+    let clamp = |values: &mut [f64]| {
+        for (value, param) in values.iter_mut().zip(tuned.iter()) {
+            if let Some((min, max)) = param.range {
+                *value = value.clamp(min, max);
+            }
+        }
+    };
+
+    let mut loss = mean_squared_error(graph, dataset)?;
+    let mut learning_rate = options.learning_rate;
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < options.max_iterations && !converged {
+        iterations += 1;
+        let mut gradient = vec![0.0; values.len()];
+        for i in 0..values.len() {
+            let h = options.epsilon * values[i].abs().max(1.0);
+            let mut probe = values.clone();
+            probe[i] = values[i] + h;
+            assign(graph, &tuned, &probe)?;
+            let above = mean_squared_error(graph, dataset)?;
+            probe[i] = values[i] - h;
+            assign(graph, &tuned, &probe)?;
+            let below = mean_squared_error(graph, dataset)?;
+            gradient[i] = (above - below) / (2.0 * h);
+        }
+
+        loop {
+            let mut candidate = values
+                .iter()
+                .zip(gradient.iter())
+                .map(|(value, slope)| value - learning_rate * slope)
+                .collect::<Vec<_>>();
+            clamp(&mut candidate);
+            assign(graph, &tuned, &candidate)?;
+            let candidate_loss = mean_squared_error(graph, dataset)?;
+            if candidate_loss <= loss {
+                converged = loss - candidate_loss < options.tolerance;
+                values = candidate;
+                loss = candidate_loss;
+                learning_rate *= 1.2;
+                break;
+            }
+            learning_rate *= 0.5;
+            if learning_rate < f64::EPSILON {
+                converged = true;
+                break;
+            }
+        }
+    }
+    assign(graph, &tuned, &values)?;
+    Ok(FitReport {
+        values,
+        loss,
+        iterations,
+        converged,
+    })
+}
+
+#[cfg(test)]
+mod fit_tests {
+    use crate::fit::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_fit_line() -> Result<(), ComputeGraphErrors> {
+        //  Input ── mul ── add
+        //  slope ──┘      │
+        //  intercept ─────┘
+        let mut graph = Graph::new();
+        let x_handle = graph.insert_node("x", AddInputs::<f64>::new());
+        let slope_handle = graph.insert_node("slope", Constant(0.0));
+        let intercept_handle = graph.insert_node("intercept", Constant(0.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&mul_handle, &x_handle)?;
+        graph.add_input(&mul_handle, &slope_handle)?;
+        graph.add_input(&add_handle, &mul_handle)?;
+        graph.add_input(&add_handle, &intercept_handle)?;
+        graph.set_output_node(&add_handle)?;
+        let mut compute_graph = graph.build::<f64, f64>()?;
+
+        let dataset = (0..10)
+            .map(|i| (i as f64, 2.0 * i as f64 + 1.0))
+            .collect::<Vec<_>>();
+        let params = [
+            (slope_handle.erase(), "value"),
+            (intercept_handle.erase(), "value"),
+        ];
+        let report = optimize(
+            &mut compute_graph,
+            &dataset,
+            &params,
+            &FitOptions::default(),
+        )?;
+        assert!(report.converged);
+        assert!(report.loss < 1e-8);
+        assert!((report.values[0] - 2.0).abs() < 1e-4);
+        assert!((report.values[1] - 1.0).abs() < 1e-3);
+        assert!((compute_graph.compute(&20.0) - 41.0).abs() < 1e-2);
+
+        assert!(optimize(
+            &mut compute_graph,
+            &dataset,
+            &[(add_handle.erase(), "value")],
+            &FitOptions::default()
+        )
+        .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,364 @@
+use crate::graph::{ComputeGraphErrors, Graph, NodeHandle};
+use std::any::{Any, TypeId};
+use std::ops;
+
+/// Symbolic expression of what a node computes, built through `Compute::formula`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    /// The graph input.
+    Input,
+    Number(f64),
+    Symbol(String),
+    Add(Vec<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Vec<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+    /// Opaque node applied to its inputs.
+    Call(String, Vec<Expr>),
+}
+
+impl Expr {
+    /// The value as a number, if it is one of the primitive numeric types.
+    pub fn number_of(value: &dyn Any) -> Option<Expr> {
+        macro_rules! try_number {
+            ($($t:ty),*) => {
+                $(if let Some(v) = value.downcast_ref::<$t>() {
+                    return Some(Expr::Number(*v as f64));
+                })*
+            };
+        }
+        try_number!(f64, f32, i64, i32, i16, i8, u64, u32, u16, u8, usize, isize);
+        #[cfg(feature = "rust_decimal")]
+        if let Some(v) = value.downcast_ref::<rust_decimal::Decimal>() {
+            use rust_decimal::prelude::ToPrimitive;
+            return v.to_f64().map(Expr::Number);
+        }
+        None
+    }
+
+    pub fn pow(self, exponent: Expr) -> Expr {
+        Expr::Pow(Box::new(self), Box::new(exponent))
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Add(_) | Expr::Sub(..) => 1,
+            Expr::Mul(_) => 2,
+            Expr::Neg(_) => 3,
+            Expr::Number(value) if *value < 0.0 => 3,
+            Expr::Pow(..) => 4,
+            _ => 5,
+        }
+    }
+
+    pub fn to_latex(&self) -> String {
+        let mut out = String::new();
+        self.latex(&mut out);
+        out
+    }
+
+    fn latex_wrapped(&self, out: &mut String, min_precedence: u8) {
+        if self.precedence() < min_precedence {
+            out.push_str("\\left(");
+            self.latex(out);
+            out.push_str("\\right)");
+        } else {
+            self.latex(out);
+        }
+    }
+
+    fn latex(&self, out: &mut String) {
+        match self {
+            Expr::Input => out.push('x'),
+            Expr::Number(value) => out.push_str(&value.to_string()),
+            Expr::Symbol(name) if name.chars().count() == 1 => out.push_str(name),
+            Expr::Symbol(name) => {
+                out.push_str(&format!("\\mathrm{{{}}}", latex_escape(name)));
+            }
+            Expr::Add(terms) => {
+                for (i, term) in terms.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(" + ");
+                    }
+                    term.latex_wrapped(out, 1);
+                }
+            }
+            Expr::Sub(a, b) => {
+                a.latex_wrapped(out, 1);
+                out.push_str(" - ");
+                b.latex_wrapped(out, 2);
+            }
+            Expr::Mul(factors) => {
+                for (i, factor) in factors.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(" \\cdot ");
+                    }
+                    factor.latex_wrapped(out, 2);
+                }
+            }
+            Expr::Div(a, b) => {
+                out.push_str("\\frac{");
+                a.latex(out);
+                out.push_str("}{");
+                b.latex(out);
+                out.push('}');
+            }
+            Expr::Pow(base, exponent) => {
+                out.push('{');
+                base.latex_wrapped(out, 5);
+                out.push_str("}^{");
+                exponent.latex(out);
+                out.push('}');
+            }
+            Expr::Neg(inner) => {
+                out.push('-');
+                inner.latex_wrapped(out, 3);
+            }
+            Expr::Call(name, args) => {
+                out.push_str(&format!("\\operatorname{{{}}}\\left(", latex_escape(name)));
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    arg.latex(out);
+                }
+                out.push_str("\\right)");
+            }
+        }
+    }
+
+    /// Presentation MathML, wrapped in a `<math>` element.
+    pub fn to_mathml(&self) -> String {
+        let mut out = String::from("<math xmlns=\"http://www.w3.org/1998/Math/MathML\">");
+        self.mathml(&mut out);
+        out.push_str("</math>");
+        out
+    }
+
+    fn mathml_wrapped(&self, out: &mut String, min_precedence: u8) {
+        if self.precedence() < min_precedence {
+            out.push_str("<mrow><mo>(</mo>");
+            self.mathml(out);
+            out.push_str("<mo>)</mo></mrow>");
+        } else {
+            self.mathml(out);
+        }
+    }
+
+    fn mathml(&self, out: &mut String) {
+        match self {
+            Expr::Input => out.push_str("<mi>x</mi>"),
+            Expr::Number(value) if *value < 0.0 => {
+                out.push_str(&format!("<mrow><mo>-</mo><mn>{}</mn></mrow>", -value))
+            }
+            Expr::Number(value) => out.push_str(&format!("<mn>{}</mn>", value)),
+            Expr::Symbol(name) => out.push_str(&format!("<mi>{}</mi>", xml_escape(name))),
+            Expr::Add(terms) => {
+                out.push_str("<mrow>");
+                for (i, term) in terms.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str("<mo>+</mo>");
+                    }
+                    term.mathml_wrapped(out, 1);
+                }
+                out.push_str("</mrow>");
+            }
+            Expr::Sub(a, b) => {
+                out.push_str("<mrow>");
+                a.mathml_wrapped(out, 1);
+                out.push_str("<mo>-</mo>");
+                b.mathml_wrapped(out, 2);
+                out.push_str("</mrow>");
+            }
+            Expr::Mul(factors) => {
+                out.push_str("<mrow>");
+                for (i, factor) in factors.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str("<mo>&#x22C5;</mo>");
+                    }
+                    factor.mathml_wrapped(out, 2);
+                }
+                out.push_str("</mrow>");
+            }
+            Expr::Div(a, b) => {
+                out.push_str("<mfrac>");
+                a.mathml(out);
+                b.mathml(out);
+                out.push_str("</mfrac>");
+            }
+            Expr::Pow(base, exponent) => {
+                out.push_str("<msup>");
+                base.mathml_wrapped(out, 5);
+                exponent.mathml(out);
+                out.push_str("</msup>");
+            }
+            Expr::Neg(inner) => {
+                out.push_str("<mrow><mo>-</mo>");
+                inner.mathml_wrapped(out, 3);
+                out.push_str("</mrow>");
+            }
+            Expr::Call(name, args) => {
+                out.push_str(&format!(
+                    "<mrow><mi>{}</mi><mo>&#x2061;</mo><mrow><mo>(</mo>",
+                    xml_escape(name)
+                ));
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str("<mo>,</mo>");
+                    }
+                    arg.mathml(out);
+                }
+                out.push_str("<mo>)</mo></mrow></mrow>");
+            }
+        }
+    }
+}
+
+fn latex_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\{}_^#$%&~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl ops::Add for Expr {
+    type Output = Expr;
+    fn add(self, rhs: Expr) -> Expr {
+        match self {
+            Expr::Add(mut terms) => {
+                terms.push(rhs);
+                Expr::Add(terms)
+            }
+            lhs => Expr::Add(vec![lhs, rhs]),
+        }
+    }
+}
+
+impl ops::Sub for Expr {
+    type Output = Expr;
+    fn sub(self, rhs: Expr) -> Expr {
+        Expr::Sub(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl ops::Mul for Expr {
+    type Output = Expr;
+    fn mul(self, rhs: Expr) -> Expr {
+        match self {
+            Expr::Mul(mut factors) => {
+                factors.push(rhs);
+                Expr::Mul(factors)
+            }
+            lhs => Expr::Mul(vec![lhs, rhs]),
+        }
+    }
+}
+
+impl ops::Div for Expr {
+    type Output = Expr;
+    fn div(self, rhs: Expr) -> Expr {
+        Expr::Div(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl ops::Neg for Expr {
+    type Output = Expr;
+    fn neg(self) -> Expr {
+        Expr::Neg(Box::new(self))
+    }
+}
+
+impl Graph {
+    /// Expression computed by `node_handle`, expanded through all of its inputs.
+    pub fn formula(&self, node_handle: &NodeHandle) -> Result<Expr, ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        let node = &self.nodes[node_handle.key];
+        let mut inputs = node
+            .inputs
+            .iter()
+            .map(|key| {
+                self.formula(&NodeHandle {
+                    key: *key,
+                    graph_id: self.id,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if node.connected_to_input && node.inner.input_type() != TypeId::of::<()>() {
+            inputs.push(Expr::Input);
+        }
+        Ok(node
+            .inner
+            .formula(&inputs)
+            .unwrap_or_else(|| Expr::Call(node.name.clone(), inputs)))
+    }
+
+    /// Formula of the output node, rendered as LaTeX.
+    pub fn to_latex(&self) -> Result<String, ComputeGraphErrors> {
+        Ok(self.output_formula()?.to_latex())
+    }
+
+    /// Formula of the output node, rendered as MathML.
+    pub fn to_mathml(&self) -> Result<String, ComputeGraphErrors> {
+        Ok(self.output_formula()?.to_mathml())
+    }
+
+    fn output_formula(&self) -> Result<Expr, ComputeGraphErrors> {
+        let key = self.output_node.ok_or(ComputeGraphErrors::NoOutputNode)?;
+        self.formula(&NodeHandle {
+            key,
+            graph_id: self.id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod formula_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_render_formula() -> Result<(), ComputeGraphErrors> {
+        //  Input ── growth ── sub ── mul
+        //  fee ──────────────┘      │
+        //  Input ── squash ─────────┘
+        let mut graph = Graph::new();
+        let growth_handle = graph.insert_node("growth", CompoundInterest::new(0.05, 10));
+        let fee_handle = graph.insert_node("fee", Constant(2.5));
+        let sub_handle = graph.insert_node("sub", SubInputs::<f64>::new());
+        let squash_handle = graph.insert_node("squash", Sigmoid::<1>);
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&sub_handle, &fee_handle)?;
+        graph.add_input(&sub_handle, &growth_handle)?;
+        graph.add_input(&mul_handle, &sub_handle)?;
+        graph.add_input(&mul_handle, &fee_handle)?;
+        graph.set_output_node(&mul_handle)?;
+
+        assert_eq!(
+            graph.to_latex()?,
+            "\\left(x \\cdot {\\left(1 + 0.05\\right)}^{10} - 2.5\\right) \\cdot 2.5"
+        );
+        assert_eq!(
+            graph.formula(&squash_handle)?,
+            Expr::Call("squash".to_string(), vec![Expr::Input])
+        );
+        let mathml = graph.to_mathml()?;
+        assert!(mathml.starts_with("<math"));
+        assert!(mathml.contains("<msup><mrow><mo>(</mo><mrow><mn>1</mn><mo>+</mo><mn>0.05</mn>"));
+
+        let npv = Expr::Div(Box::new(Expr::Input), Box::new(Expr::Symbol("r_1".into())));
+        assert_eq!(npv.to_latex(), "\\frac{x}{\\mathrm{r\\_1}}");
+        assert!(Graph::new().to_latex().is_err());
+        Ok(())
+    }
+}
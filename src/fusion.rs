@@ -0,0 +1,206 @@
+use crate::com_graph::{ComputeGraph, ComputeNode};
+use crate::graph::{ComputeGraphErrors, Graph};
+use crate::operations::{AddInputs, Constant, MulAdd, MulInputs, Pow};
+use std::any::{Any, TypeId};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Source {
+    Node(usize),
+    Input,
+}
+
+fn constant(nodes: &[ComputeNode], index: usize) -> Option<f64> {
+    let node = nodes[index].func.as_any().downcast_ref::<Constant<f64>>()?;
+    Some(node.0)
+}
+
+/// Operands of a node, including the graph input when it receives it.
+fn operands(node: &ComputeNode) -> Vec<Source> {
+    let mut operands = node
+        .inputs
+        .iter()
+        .map(|input| Source::Node(*input))
+        .collect::<Vec<_>>();
+    if node.connected_to_input && node.func.input_type() != TypeId::of::<()>() {
+        operands.push(Source::Input);
+    }
+    operands
+}
+
+/// Describes a node as `scale * source + offset` when it is a `MulAdd`, or a two-operand
+/// `MulInputs`/`AddInputs` over `f64` with one constant operand.
+fn linear(nodes: &[ComputeNode], index: usize) -> Option<(f64, f64, Source)> {
+    let node = &nodes[index];
+    let func = node.func.as_any();
+    let operands = operands(node);
+    if let Some(mul_add) = func.downcast_ref::<MulAdd>() {
+        return match operands[..] {
+            [source] => Some((mul_add.scale, mul_add.offset, source)),
+            _ => None,
+        };
+    }
+    let is_mul = func.is::<MulInputs<f64>>();
+    if !is_mul && !func.is::<AddInputs<f64>>() {
+        return None;
+    }
+    let [a, b] = operands[..] else {
+        return None;
+    };
+    let value_of = |source| match source {
+        Source::Node(input) => constant(nodes, input),
+        Source::Input => None,
+    };
+    let (value, source) = match (value_of(a), value_of(b)) {
+        (Some(value), None) => (value, b),
+        (None, Some(value)) => (value, a),
+        _ => return None,
+    };
+    if is_mul {
+        Some((value, 0.0, source))
+    } else {
+        Some((1.0, value, source))
+    }
+}
+
+fn set_source(node: &mut ComputeNode, source: Source) {
+    match source {
+        Source::Node(input) => {
+            node.inputs = vec![input];
+            node.connected_to_input = false;
+        }
+        Source::Input => {
+            node.inputs = Vec::new();
+            node.connected_to_input = true;
+        }
+    }
+}
+
+impl<In, Out> ComputeGraph<In, Out> {
+    /// Rewrites hot numeric patterns over `f64` into fewer nodes: chains of constant
+    /// multiplications and additions become a single `MulAdd`, and `Pow(2)` becomes a `Mul`.
+    /// Nodes left without consumers are dropped, so handles of fused nodes and their
+    /// constants no longer resolve. Returns the number of nodes removed.
+    pub fn fuse(&mut self) -> usize {
+        let mut nodes = std::mem::take(&mut self.nodes);
+        let output = nodes.len() - 1;
+        let mut consumers = vec![0; nodes.len()];
+        consumers[output] += 1;
+        for node in nodes.iter() {
+            for input in node.inputs.iter() {
+                consumers[*input] += 1;
+            }
+        }
+
+        for index in 0..nodes.len() {
+            if let Some((outer_scale, outer_offset, Source::Node(inner))) = linear(&nodes, index) {
+                let fusable = consumers[inner] == 1
+                    && !nodes[inner].critical
+                    && !nodes[inner].probed
+                    && nodes[inner].scheduling.is_free();
+                if let Some((scale, offset, source)) = linear(&nodes, inner).filter(|_| fusable) {
+                    for input in nodes[index].inputs.iter().chain(nodes[inner].inputs.iter()) {
+                        consumers[*input] -= 1;
+                    }
+                    if let Source::Node(source) = source {
+                        consumers[source] += 1;
+                    }
+                    let node = &mut nodes[index];
+                    node.func = Box::new(MulAdd::new(
+                        outer_scale * scale,
+                        outer_scale * offset + outer_offset,
+                    ));
+                    set_source(node, source);
+                }
+            }
+
+            let node = &mut nodes[index];
+            let squares = node
+                .func
+                .as_any()
+                .downcast_ref::<Pow>()
+                .is_some_and(|pow| pow.exponent == 2.0);
+            // A square of the graph input can't be expressed as a two-input `Mul`
+            if let (&[Source::Node(input)], true) = (&operands(node)[..], squares) {
+                consumers[input] += 1;
+                node.inputs = vec![input, input];
+                node.func = Box::new(MulInputs::<f64>::new());
+            }
+        }
+
+        // Drop dead nodes and renumber the survivors
+        let mut new_index = vec![usize::MAX; nodes.len()];
+        let mut kept = Vec::with_capacity(nodes.len());
+        for (index, mut node) in nodes.into_iter().enumerate() {
+            if consumers[index] == 0 {
+                continue;
+            }
+            node.inputs = node.inputs.iter().map(|input| new_index[*input]).collect();
+            new_index[index] = kept.len();
+            kept.push(node);
+        }
+        let removed = consumers.iter().filter(|count| **count == 0).count();
+        *self = ComputeGraph::new(kept);
+        removed
+    }
+}
+
+impl Graph {
+    /// Builds the graph and applies `ComputeGraph::fuse`.
+    pub fn build_fused<In, Out>(&mut self) -> Result<ComputeGraph<In, Out>, ComputeGraphErrors>
+    where
+        In: Any + Clone,
+        Out: Any + Clone,
+    {
+        let mut compute_graph = self.build()?;
+        compute_graph.fuse();
+        Ok(compute_graph)
+    }
+}
+
+#[cfg(test)]
+mod fusion_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_fuse_linear_chain() -> Result<(), ComputeGraphErrors> {
+        //  Input ── mul ── add ── mul ── square ── add
+        //  3.0 ─────┘      │      │                │
+        //  1.0 ────────────┘      │                │
+        //  0.5 ───────────────────┘                │
+        //  Input ── other ─────────────────────────┘
+        let mut graph = Graph::new();
+        let three_handle = graph.insert_node("three", Constant(3.0));
+        let one_handle = graph.insert_node("one", Constant(1.0));
+        let half_handle = graph.insert_node("half", Constant(0.5));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        let scale_handle = graph.insert_node("scale", MulInputs::<f64>::new());
+        let square_handle = graph.insert_node("square", Pow::new(2.0));
+        let other_handle = graph.insert_node("other", Pow::new(2.0));
+        let sum_handle = graph.insert_node("sum", AddInputs::<f64>::new());
+        graph.add_input(&mul_handle, &three_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.add_input(&add_handle, &mul_handle)?;
+        graph.add_input(&add_handle, &one_handle)?;
+        graph.add_input(&scale_handle, &add_handle)?;
+        graph.add_input(&scale_handle, &half_handle)?;
+        graph.add_input(&square_handle, &scale_handle)?;
+        graph.add_input(&sum_handle, &square_handle)?;
+        graph.add_input(&sum_handle, &other_handle)?;
+        graph.set_output_node(&sum_handle)?;
+
+        let plain = graph.build::<f64, f64>()?;
+        let mut fused = graph.build::<f64, f64>()?;
+        assert_eq!(fused.fuse(), 5);
+        // scale ─ square ─ sum, with `other` still reading the graph input
+        assert_eq!(fused.nodes.len(), 4);
+        for x in [-2.0, 0.0, 1.5, 10.0] {
+            assert_eq!(fused.compute(&x), plain.compute(&x));
+        }
+        assert_eq!(fused.compute(&1.0), 4.0 + 1.0);
+
+        let shared = graph.build_fused::<f64, f64>()?;
+        assert_eq!(shared.nodes.len(), 4);
+        Ok(())
+    }
+}
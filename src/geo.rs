@@ -0,0 +1,150 @@
+use crate::compute::Compute;
+use std::f64::consts::PI;
+
+const WEB_MERCATOR_RADIUS: f64 = 6_378_137.0;
+
+/// WGS84 coordinate in degrees.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LatLon {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl LatLon {
+    pub fn new(lat: f64, lon: f64) -> Self {
+        Self { lat, lon }
+    }
+}
+
+impl From<(f64, f64)> for LatLon {
+    fn from((lat, lon): (f64, f64)) -> Self {
+        Self { lat, lon }
+    }
+}
+
+/// Great-circle distance between the first two inputs, in the unit of `radius`.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Haversine {
+    pub radius: f64,
+}
+
+impl Haversine {
+    /// Mean earth radius, giving distances in kilometers.
+    pub const EARTH_KM: f64 = 6371.0088;
+
+    pub fn new(radius: f64) -> Self {
+        Self { radius }
+    }
+}
+
+impl Default for Haversine {
+    fn default() -> Self {
+        Self::new(Self::EARTH_KM)
+    }
+}
+
+impl Compute for Haversine {
+    type In = LatLon;
+    type Out = f64;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let (Some(a), Some(b)) = (inputs.first(), inputs.get(1)) else {
+            return 0.0;
+        };
+        let d_lat = (b.lat - a.lat).to_radians();
+        let d_lon = (b.lon - a.lon).to_radians();
+        let h = (d_lat / 2.0).sin().powi(2)
+            + a.lat.to_radians().cos() * b.lat.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * self.radius * h.sqrt().min(1.0).asin()
+    }
+}
+
+/// True if every input lies within the box spanned by `min` and `max`.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InBoundingBox {
+    pub min: LatLon,
+    pub max: LatLon,
+}
+
+impl InBoundingBox {
+    pub fn new(min: LatLon, max: LatLon) -> Self {
+        Self { min, max }
+    }
+}
+
+impl Compute for InBoundingBox {
+    type In = LatLon;
+    type Out = bool;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs.iter().all(|point| {
+            (self.min.lat..=self.max.lat).contains(&point.lat)
+                && (self.min.lon..=self.max.lon).contains(&point.lon)
+        })
+    }
+}
+
+/// Projects the first input to Web Mercator (EPSG:3857) `(x, y)` in meters.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ToWebMercator;
+impl Compute for ToWebMercator {
+    type In = LatLon;
+    type Out = (f64, f64);
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let point = inputs.first().map(|point| **point).unwrap_or_default();
+        let x = WEB_MERCATOR_RADIUS * point.lon.to_radians();
+        let y = WEB_MERCATOR_RADIUS * (PI / 4.0 + point.lat.to_radians() / 2.0).tan().ln();
+        (x, y)
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FromWebMercator;
+impl Compute for FromWebMercator {
+    type In = (f64, f64);
+    type Out = LatLon;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let (x, y) = inputs.first().map(|xy| **xy).unwrap_or_default();
+        LatLon {
+            lat: (2.0 * (y / WEB_MERCATOR_RADIUS).exp().atan() - PI / 2.0).to_degrees(),
+            lon: (x / WEB_MERCATOR_RADIUS).to_degrees(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod geo_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_geo_nodes() -> Result<(), ComputeGraphErrors> {
+        let oslo = LatLon::new(59.9139, 10.7522);
+        let bergen = LatLon::new(60.3913, 5.3221);
+
+        let mut graph = Graph::new();
+        let oslo_handle = graph.insert_node("oslo", Constant(oslo));
+        let distance_handle = graph.insert_node("distance", Haversine::default());
+        let norway = InBoundingBox::new(LatLon::new(57.0, 4.0), LatLon::new(72.0, 32.0));
+        let inside_handle = graph.insert_node("inside", norway);
+        let project_handle = graph.insert_node("project", ToWebMercator);
+        let unproject_handle = graph.insert_node("unproject", FromWebMercator);
+        graph.add_input(&distance_handle, &oslo_handle)?;
+        graph.connect_to_input(&distance_handle)?;
+        graph.add_input(&unproject_handle, &project_handle)?;
+
+        let distance = graph.build_for_node::<LatLon, f64>(&distance_handle)?;
+        assert!((distance.compute(&bergen) - 305.0).abs() < 5.0);
+        let inside = graph.build_for_node::<LatLon, bool>(&inside_handle)?;
+        assert!(inside.compute(&bergen));
+        assert!(!inside.compute(&LatLon::new(51.5, -0.12)));
+
+        let roundtrip = graph.build_for_node::<LatLon, LatLon>(&unproject_handle)?;
+        let point = roundtrip.compute(&bergen);
+        assert!((point.lat - bergen.lat).abs() < 1e-9);
+        assert!((point.lon - bergen.lon).abs() < 1e-9);
+        Ok(())
+    }
+}
@@ -1,38 +1,171 @@
 use crate::com_graph::*;
 use crate::compute::*;
+use crate::errors::ErrorDetails;
+use crate::limits::GraphLimits;
+use crate::params::{ParamError, ParamInfo, ParamValue};
+use crate::policy::NodePolicy;
+use crate::provenance::Provenance;
+use crate::scheduling::Scheduling;
+use crate::snapshot::GraphState;
+use crate::typed::TypedNodeHandle;
 use slotmap::{new_key_type, SlotMap};
-use std::any::{type_name, Any, TypeId};
+use std::any::{Any, TypeId};
 use std::collections::{HashMap, HashSet};
-new_key_type! {struct GraphKey;}
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+new_key_type! {pub(crate) struct GraphKey;}
 
 #[derive(Clone)]
-struct Node {
-    name: String,
-    inputs: Vec<GraphKey>,
-    inner: Box<dyn InnerCompute + 'static>,
-    connected_to_input: bool,
+pub(crate) struct Node {
+    pub(crate) name: String,
+    pub(crate) inputs: Vec<GraphKey>,
+    /// Port of each input of a `PortCompute` node, empty for other nodes.
+    pub(crate) ports: Vec<usize>,
+    /// Nodes that must run before this one without passing it data, see `Graph::add_order`.
+    pub(crate) after: Vec<GraphKey>,
+    pub(crate) inner: Box<dyn InnerCompute + 'static>,
+    /// `Graph::revision` when `inner` was inserted or last replaced. Checkpoint keys mix it
+    /// in, as they can't see configuration outside `parameters` and `fingerprint`.
+    pub(crate) op_revision: u64,
+    pub(crate) connected_to_input: bool,
+    pub(crate) tags: Vec<String>,
+    pub(crate) style: NodeStyle,
+    pub(crate) policy: NodePolicy,
+    pub(crate) critical: bool,
+    pub(crate) scheduling: Scheduling,
+    /// See `Graph::probe`.
+    pub(crate) probed: bool,
 }
 
-#[derive(Clone, Copy)]
+impl Node {
+    /// Whether both nodes carry the same tags, style, policy, ordering edges, probe and
+    /// scheduling settings.
+    fn same_metadata(&self, other: &Node) -> bool {
+        let sorted = |keys: &[GraphKey]| {
+            let mut keys = keys.to_vec();
+            keys.sort();
+            keys.dedup();
+            keys
+        };
+        self.tags == other.tags
+            && self.style == other.style
+            && self.policy.same_as(&other.policy)
+            && sorted(&self.after) == sorted(&other.after)
+            && self.probed == other.probed
+            && self.critical == other.critical
+            && self.scheduling == other.scheduling
+    }
+
+    /// Keeps the inputs for which `keep` returns true, together with their ports.
+    pub(crate) fn retain_inputs(&mut self, mut keep: impl FnMut(GraphKey) -> bool) {
+        self.retain_inputs_with_ports(|key, _| keep(key))
+    }
+
+    /// Like `retain_inputs`, also passing the port of the input, 0 for nodes without ports.
+    pub(crate) fn retain_inputs_with_ports(
+        &mut self,
+        mut keep: impl FnMut(GraphKey, usize) -> bool,
+    ) {
+        let mut index = 0;
+        while index < self.inputs.len() {
+            if keep(
+                self.inputs[index],
+                self.ports.get(index).copied().unwrap_or(0),
+            ) {
+                index += 1;
+                continue;
+            }
+            self.inputs.remove(index);
+            if !self.ports.is_empty() {
+                self.ports.remove(index);
+            }
+        }
+    }
+}
+
+/// Visual hints for editors and exporters, carried through serialization.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeStyle {
+    pub group: Option<String>,
+    pub color: Option<String>,
+}
+
+impl NodeStyle {
+    pub fn group(group: impl Into<String>) -> Self {
+        Self {
+            group: Some(group.into()),
+            color: None,
+        }
+    }
+
+    pub fn with_color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.group.is_none() && self.color.is_none()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct NodeHandle {
-    key: GraphKey,
-    graph_id: usize,
+    pub(crate) key: GraphKey,
+    pub(crate) graph_id: usize,
 }
 
+#[derive(Clone, Debug)]
 pub struct NodeMeta {
     pub this_node: NodeHandle,
+    pub name: String,
+    /// Type of the compute object, as used by `NodeRegistry::entry_of`.
+    pub op_type: TypeId,
+    pub op_type_name: &'static str,
     pub inputs: Vec<NodeHandle>,
     pub connected_to_input: bool,
     pub input_type: TypeId,
     pub output_type: TypeId,
+    pub input_type_name: &'static str,
+    pub output_type_name: &'static str,
+    pub params: Vec<ParamInfo>,
+    pub tags: Vec<String>,
+    pub style: NodeStyle,
+    pub critical: bool,
+    pub scheduling: Scheduling,
+}
+
+pub struct ConstantMerge {
+    pub kept: NodeHandle,
+    pub removed: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct DedupReport {
+    pub merges: Vec<ConstantMerge>,
+}
+
+impl DedupReport {
+    pub fn removed_count(&self) -> usize {
+        self.merges.iter().map(|merge| merge.removed.len()).sum()
+    }
 }
 
 #[derive(Clone)]
 pub struct Graph {
-    type_names: HashMap<TypeId, &'static str>,
-    nodes: SlotMap<GraphKey, Node>,
-    output_node: Option<GraphKey>,
-    id: usize,
+    pub(crate) type_names: HashMap<TypeId, &'static str>,
+    pub(crate) nodes: SlotMap<GraphKey, Arc<Node>>,
+    pub(crate) output_node: Option<GraphKey>,
+    pub(crate) id: usize,
+    revision: u64,
+    pub(crate) limits: Option<GraphLimits>,
+    pub(crate) provenance: Option<Provenance>,
+    /// State the graph was branched from, see `Graph::merge`.
+    pub(crate) base: Option<Arc<GraphState>>,
+    pub(crate) snapshots: HashMap<String, GraphState>,
+    /// See `Graph::reject_cycles`.
+    pub(crate) reject_cycles: bool,
 }
 
 impl Default for Graph {
@@ -43,49 +176,215 @@ impl Default for Graph {
 
 impl Graph {
     pub fn new() -> Self {
-        let mut g = Self {
+        // Unique per graph, so handles of other graphs can be told apart
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+        Self {
             type_names: HashMap::default(),
             nodes: SlotMap::default(),
             output_node: None,
-            id: 0,
-        };
-
-        g.id = (&g.nodes as *const SlotMap<_, _>) as usize;
-        g
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            revision: 0,
+            limits: None,
+            provenance: None,
+            base: None,
+            snapshots: HashMap::new(),
+            reject_cycles: false,
+        }
     }
 
-    pub fn insert_node<N, Obj, In, Out>(&mut self, name: N, compute_object: Obj) -> NodeHandle
+    pub fn insert_node<N, Obj, In, Out>(
+        &mut self,
+        name: N,
+        compute_object: Obj,
+    ) -> TypedNodeHandle<In, Out>
     where
         N: Into<String>,
         Obj: Compute<In = In, Out = Out> + 'static,
-        In: Any + Copy + Default + 'static,
-        Out: Any + Copy + Default + 'static,
+        In: Value,
+        Out: Value,
     {
-        let node = Node {
-            name: name.into(),
-            inputs: Vec::new(),
-            inner: Box::new(compute_object),
-            connected_to_input: true,
-        };
+        TypedNodeHandle::new(self.insert_inner(name.into(), Box::new(compute_object)))
+    }
 
+    pub(crate) fn insert_inner(
+        &mut self,
+        name: String,
+        inner: Box<dyn InnerCompute>,
+    ) -> NodeHandle {
         self.type_names
-            .insert(TypeId::of::<In>(), type_name::<In>());
+            .insert(inner.input_type(), inner.input_type_name());
         self.type_names
-            .insert(TypeId::of::<Out>(), type_name::<Out>());
+            .insert(inner.output_type(), inner.output_type_name());
 
-        let key = self.nodes.insert(node);
+        let node = Node {
+            name,
+            inputs: Vec::new(),
+            ports: Vec::new(),
+            after: Vec::new(),
+            inner,
+            op_revision: self.revision,
+            connected_to_input: true,
+            tags: Vec::new(),
+            style: NodeStyle::default(),
+            policy: NodePolicy::default(),
+            critical: false,
+            scheduling: Scheduling::Free,
+            probed: false,
+        };
+        let key = self.nodes.insert(Arc::new(node));
+        self.touch();
+        self.record(|graph| {
+            let node = &graph.nodes[key];
+            format!("inserted '{}' ({})", node.name, node.inner.type_name())
+        });
         NodeHandle {
             key,
             graph_id: self.id,
         }
     }
 
-    pub fn remove_node(&mut self, node_handle: &NodeHandle) {
-        self.verify_graphid(node_handle);
-        self.nodes.remove(node_handle.key);
-        for (_, node) in self.nodes.iter_mut() {
-            node.inputs.retain(|key| *key != node_handle.key);
+    pub fn remove_node(&mut self, node_handle: &NodeHandle) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        let removed = self.nodes.remove(node_handle.key);
+        if self.output_node == Some(node_handle.key) {
+            self.output_node = None;
+        }
+        for node in self.nodes.values_mut() {
+            if node.inputs.contains(&node_handle.key) || node.after.contains(&node_handle.key) {
+                let node = Arc::make_mut(node);
+                node.retain_inputs(|key| key != node_handle.key);
+                node.after.retain(|key| *key != node_handle.key);
+            }
+        }
+        self.touch();
+        if let Some(removed) = removed {
+            self.record(|_| format!("removed '{}'", removed.name));
+        }
+        Ok(())
+    }
+
+    /// Merges `Constant` nodes of the same value, see `Compute::same_constant`. Constants
+    /// with different tags, style, policy, ordering edges, probe or scheduling settings are
+    /// kept apart, so merging never drops metadata.
+    pub fn dedup_constants(&mut self) -> DedupReport {
+        let mut groups: Vec<(GraphKey, Vec<GraphKey>)> = Vec::new();
+        for (key, node) in self.nodes.iter() {
+            if !node.inner.same_constant(node.inner.as_ref()) {
+                continue;
+            }
+            match groups.iter_mut().find(|(kept, _)| {
+                let kept = &self.nodes[*kept];
+                kept.inner.same_constant(node.inner.as_ref()) && kept.same_metadata(node)
+            }) {
+                Some((_, duplicates)) => duplicates.push(key),
+                None => groups.push((key, Vec::new())),
+            }
+        }
+
+        let mut report = DedupReport::default();
+        for (kept, duplicates) in groups {
+            if duplicates.is_empty() {
+                continue;
+            }
+            for node in self.nodes.values_mut() {
+                if !node
+                    .inputs
+                    .iter()
+                    .chain(node.after.iter())
+                    .any(|input| duplicates.contains(input))
+                {
+                    continue;
+                }
+                let node = Arc::make_mut(node);
+                for input in node.inputs.iter_mut().chain(node.after.iter_mut()) {
+                    if duplicates.contains(input) {
+                        *input = kept;
+                    }
+                }
+            }
+            if self
+                .output_node
+                .is_some_and(|output| duplicates.contains(&output))
+            {
+                self.output_node = Some(kept);
+            }
+
+            let removed = duplicates
+                .iter()
+                .filter_map(|key| self.nodes.remove(*key))
+                .map(|node| node.name.clone())
+                .collect();
+            report.merges.push(ConstantMerge {
+                kept: NodeHandle {
+                    key: kept,
+                    graph_id: self.id,
+                },
+                removed,
+            });
+        }
+        if report.removed_count() > 0 {
+            self.touch();
+            let removed = report.removed_count();
+            self.record(|_| format!("merged {} duplicate constants", removed));
+        }
+        report
+    }
+
+    /// Nodes that `node_handle` doesn't depend on, through inputs or ordering edges, so an
+    /// editor can highlight them as dead when `node_handle` is the output.
+    pub fn unreachable_from(
+        &self,
+        node_handle: &NodeHandle,
+    ) -> Result<Vec<NodeHandle>, ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        let reachable = self.reachable(node_handle.key);
+        Ok(self
+            .nodes
+            .keys()
+            .filter(|key| !reachable.contains(key))
+            .map(|key| NodeHandle {
+                key,
+                graph_id: self.id,
+            })
+            .collect())
+    }
+
+    /// Removes the nodes the output node doesn't depend on, returning their names.
+    pub fn prune_unreachable(&mut self) -> Result<Vec<String>, ComputeGraphErrors> {
+        let output_node = self.output_node.ok_or(ComputeGraphErrors::NoOutputNode)?;
+        let reachable = self.reachable(output_node);
+        // Reachable nodes can't use unreachable ones, so no edges are left dangling
+        let unreachable = self
+            .nodes
+            .keys()
+            .filter(|key| !reachable.contains(key))
+            .collect::<Vec<_>>();
+        let removed = unreachable
+            .into_iter()
+            .filter_map(|key| self.nodes.remove(key))
+            .map(|node| node.name.clone())
+            .collect::<Vec<_>>();
+        if !removed.is_empty() {
+            self.touch();
+            let count = removed.len();
+            self.record(|_| format!("pruned {} unreachable nodes", count));
         }
+        Ok(removed)
+    }
+
+    /// `node` and everything it depends on. Tolerates cycles.
+    fn reachable(&self, node: GraphKey) -> HashSet<GraphKey> {
+        let mut reachable = HashSet::from([node]);
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            for dependency in node.inputs.iter().chain(node.after.iter()) {
+                if reachable.insert(*dependency) {
+                    stack.push(*dependency);
+                }
+            }
+        }
+        reachable
     }
 
     pub fn replace_node<Obj, In, Out>(
@@ -95,14 +394,11 @@ impl Graph {
     ) -> Result<(), ComputeGraphErrors>
     where
         Obj: Compute<In = In, Out = Out> + 'static,
-        In: Any + Copy + Default + 'static,
-        Out: Any + Copy + Default + 'static,
+        In: Value,
+        Out: Value,
     {
-        self.verify_graphid(node_handle);
-        let node = self
-            .nodes
-            .get_mut(node_handle.key)
-            .ok_or(ComputeGraphErrors::NodeMissing)?;
+        self.check_handle(node_handle)?;
+        let node = &self.nodes[node_handle.key];
 
         let new_inner_compute: Box<dyn InnerCompute> = Box::new(compute_object);
         let mut type_errors = Vec::new();
@@ -127,34 +423,231 @@ impl Graph {
             ))
         }
         if !type_errors.is_empty() {
-            return Err(ComputeGraphErrors::format_incompatible_object(
-                &node.name,
-                &type_errors,
-            ));
+            return Err(
+                ComputeGraphErrors::format_incompatible_object(&node.name, &type_errors)
+                    .with_node(*node_handle),
+            );
         }
 
+        let op_revision = self.revision;
+        let node = self.node_mut(node_handle.key).unwrap();
         node.inner = new_inner_compute;
+        node.op_revision = op_revision;
+        self.touch();
+        let key = node_handle.key;
+        self.record(|graph| {
+            let node = &graph.nodes[key];
+            format!("replaced '{}' with {}", node.name, node.inner.type_name())
+        });
         Ok(())
     }
-    
-    pub fn get_node_meta(&self, node_handle: &NodeHandle) -> NodeMeta {
-        self.verify_graphid(node_handle);
-        let node = self.nodes.get(node_handle.key).unwrap();
-        self.build_node_meta(node_handle.key, node)
+
+    pub fn get_node_meta(&self, node_handle: &NodeHandle) -> Result<NodeMeta, ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        let node = &self.nodes[node_handle.key];
+        Ok(self.build_node_meta(node_handle.key, node))
     }
 
     pub fn get_all_node_metas(&self) -> Vec<NodeMeta> {
-        self.nodes.iter().map(|(key, node)| self.build_node_meta(key, node)).collect()
+        self.nodes
+            .iter()
+            .map(|(key, node)| self.build_node_meta(key, node))
+            .collect()
     }
 
-    fn build_node_meta(&self, key: GraphKey, node: &Node) -> NodeMeta {
+    pub(crate) fn build_node_meta(&self, key: GraphKey, node: &Node) -> NodeMeta {
         NodeMeta {
-            this_node: NodeHandle {key, graph_id: self.id },
-            inputs: node.inputs.iter().map(|key| NodeHandle {key: *key, graph_id: self.id }).collect(),
+            this_node: NodeHandle {
+                key,
+                graph_id: self.id,
+            },
+            name: node.name.clone(),
+            op_type: Any::type_id(node.inner.as_any()),
+            op_type_name: node.inner.type_name(),
+            inputs: node
+                .inputs
+                .iter()
+                .map(|key| NodeHandle {
+                    key: *key,
+                    graph_id: self.id,
+                })
+                .collect(),
             connected_to_input: node.connected_to_input,
             input_type: node.inner.input_type(),
-            output_type: node.inner.output_type()
+            output_type: node.inner.output_type(),
+            input_type_name: node.inner.input_type_name(),
+            output_type_name: node.inner.output_type_name(),
+            params: node
+                .inner
+                .parameters()
+                .map(|params| params.params())
+                .unwrap_or_default(),
+            tags: node.tags.clone(),
+            style: node.style.clone(),
+            critical: node.critical,
+            scheduling: node.scheduling,
+        }
+    }
+
+    pub fn set_style(
+        &mut self,
+        node_handle: &NodeHandle,
+        style: NodeStyle,
+    ) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        if let Some(node) = self.node_mut(node_handle.key) {
+            node.style = style;
+        }
+        self.touch();
+        let key = node_handle.key;
+        self.record(|graph| format!("set style of '{}'", graph.name_for_log(key)));
+        Ok(())
+    }
+
+    /// Critical nodes are always evaluated, even when `ComputeGraph::compute_within`
+    /// runs out of time.
+    pub fn set_critical(
+        &mut self,
+        node_handle: &NodeHandle,
+        critical: bool,
+    ) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        if let Some(node) = self.node_mut(node_handle.key) {
+            node.critical = critical;
+        }
+        self.touch();
+        let key = node_handle.key;
+        self.record(|graph| {
+            format!(
+                "set critical of '{}' to {}",
+                graph.name_for_log(key),
+                critical
+            )
+        });
+        Ok(())
+    }
+
+    /// Splits `order` into the nodes without a group and the members of each group, in the
+    /// order groups first appear. Used by the exporters to draw groups as clusters.
+    pub(crate) fn split_groups<'a>(
+        &'a self,
+        order: &[GraphKey],
+    ) -> (Vec<GraphKey>, Vec<(&'a str, Vec<GraphKey>)>) {
+        let mut ungrouped = Vec::new();
+        let mut groups: Vec<(&str, Vec<GraphKey>)> = Vec::new();
+        for key in order.iter() {
+            let Some(group) = self.nodes[*key].style.group.as_deref() else {
+                ungrouped.push(*key);
+                continue;
+            };
+            match groups.iter_mut().find(|(name, _)| *name == group) {
+                Some((_, members)) => members.push(*key),
+                None => groups.push((group, vec![*key])),
+            }
+        }
+        (ungrouped, groups)
+    }
+
+    /// Groups assigned through `set_style`, with their member nodes.
+    pub fn groups(&self) -> Vec<(String, Vec<NodeHandle>)> {
+        let mut groups: Vec<(String, Vec<NodeHandle>)> = Vec::new();
+        for (key, node) in self.nodes.iter() {
+            let Some(group) = node.style.group.as_ref() else {
+                continue;
+            };
+            let handle = NodeHandle {
+                key,
+                graph_id: self.id,
+            };
+            match groups.iter_mut().find(|(name, _)| name == group) {
+                Some((_, members)) => members.push(handle),
+                None => groups.push((group.clone(), vec![handle])),
+            }
         }
+        groups
+    }
+
+    pub fn add_tag(
+        &mut self,
+        node_handle: &NodeHandle,
+        tag: impl Into<String>,
+    ) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        let tag = tag.into();
+        if let Some(node) = self.node_mut(node_handle.key) {
+            if !node.tags.contains(&tag) {
+                node.tags.push(tag.clone());
+            }
+        }
+        self.touch();
+        let key = node_handle.key;
+        self.record(|graph| format!("tagged '{}' with '{}'", graph.name_for_log(key), tag));
+        Ok(())
+    }
+
+    pub fn remove_tag(
+        &mut self,
+        node_handle: &NodeHandle,
+        tag: &str,
+    ) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        if let Some(node) = self.node_mut(node_handle.key) {
+            node.tags.retain(|t| t != tag);
+        }
+        self.touch();
+        let key = node_handle.key;
+        self.record(|graph| format!("removed tag '{}' from '{}'", tag, graph.name_for_log(key)));
+        Ok(())
+    }
+
+    pub fn get_params(
+        &self,
+        node_handle: &NodeHandle,
+    ) -> Result<Vec<ParamInfo>, ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        Ok(self.nodes[node_handle.key]
+            .inner
+            .parameters()
+            .map(|params| params.params())
+            .unwrap_or_default())
+    }
+
+    pub fn set_param<V: Into<ParamValue>>(
+        &mut self,
+        node_handle: &NodeHandle,
+        name: &str,
+        value: V,
+    ) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        let value = value.into();
+        let set = |inner: &mut Box<dyn InnerCompute>| match inner.parameters_mut() {
+            Some(params) => params.set_param(name, value),
+            None => Err(ParamError::UnknownParam(name.to_string())),
+        };
+        let node = &mut self.nodes[node_handle.key];
+        // A node shared with a branch is only copied once the value was accepted
+        let result = match Arc::get_mut(node) {
+            Some(node) => set(&mut node.inner),
+            None => {
+                let mut inner = node.inner.clone();
+                set(&mut inner).map(|()| self.node_mut(node_handle.key).unwrap().inner = inner)
+            }
+        };
+        result.map_err(|err| {
+            ComputeGraphErrors::format_param_error(&self.nodes[node_handle.key].name, &err)
+                .with_node(*node_handle)
+        })?;
+        self.touch();
+        let key = node_handle.key;
+        self.record(|graph| {
+            format!(
+                "set parameter '{}' of '{}' to {:?}",
+                name,
+                graph.name_for_log(key),
+                value
+            )
+        });
+        Ok(())
     }
 
     pub fn add_input(
@@ -162,69 +655,189 @@ impl Graph {
         node_handle: &NodeHandle,
         input_node_handle: &NodeHandle,
     ) -> Result<(), ComputeGraphErrors> {
-        self.verify_graphid(node_handle);
-        self.verify_graphid(input_node_handle);
+        self.check_handle(node_handle)?;
+        self.check_handle(input_node_handle)?;
+        let node = &self.nodes[node_handle.key];
+        if !node.inner.port_types().is_empty() {
+            return Err(ComputeGraphErrors::WrongTypes(
+                format!(
+                    "'{}' takes its inputs by port, connect them with `add_input_port`",
+                    node.name
+                )
+                .into(),
+            )
+            .with_node(*node_handle));
+        }
         let node_input_type = &self.nodes[node_handle.key].inner.input_type();
         let input_node_output_type = &self.nodes[input_node_handle.key].inner.output_type();
         if *node_input_type == *input_node_output_type {
-            let node = self.nodes.get_mut(node_handle.key).unwrap();
+            self.check_new_edge(node_handle, input_node_handle)?;
+            let node = self.node_mut(node_handle.key).unwrap();
             node.inputs.push(input_node_handle.key);
 
             if node.connected_to_input {
                 node.connected_to_input = false;
             }
 
+            self.touch();
+            let (key, input_key) = (node_handle.key, input_node_handle.key);
+            self.record(|graph| {
+                format!(
+                    "added input '{}' to '{}'",
+                    graph.name_for_log(input_key),
+                    graph.name_for_log(key)
+                )
+            });
             Ok(())
         } else {
+            let input_type_name = *self.type_names.get(node_input_type).unwrap();
+            let output_type_name = *self.type_names.get(input_node_output_type).unwrap();
             Err(ComputeGraphErrors::format_wrong_types(
                 self._get_name(node_handle.key).unwrap(),
-                self.type_names.get(node_input_type).unwrap(),
+                input_type_name,
                 self._get_name(input_node_handle.key).unwrap(),
-                self.type_names.get(input_node_output_type).unwrap(),
-            ))
+                output_type_name,
+            )
+            .with_node(*node_handle)
+            .with_node(*input_node_handle)
+            .with_type(*node_input_type, input_type_name)
+            .with_type(*input_node_output_type, output_type_name))
+        }
+    }
+
+    pub fn remove_input(
+        &mut self,
+        node_handle: &NodeHandle,
+        input_to_remove_handle: &NodeHandle,
+    ) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        self.check_handle(input_to_remove_handle)?;
+        if let Some(node) = self.node_mut(node_handle.key) {
+            node.retain_inputs(|key| key != input_to_remove_handle.key);
         }
+        self.touch();
+        let (key, input_key) = (node_handle.key, input_to_remove_handle.key);
+        self.record(|graph| {
+            format!(
+                "removed input '{}' from '{}'",
+                graph.name_for_log(input_key),
+                graph.name_for_log(key)
+            )
+        });
+        Ok(())
     }
 
-    pub fn remove_input(&mut self, node_handle: &NodeHandle, input_to_remove_handle: &NodeHandle) {
-        self.verify_graphid(node_handle);
-        if let Some(node) = self.nodes.get_mut(node_handle.key) {
-            node.inputs.retain(|key| *key != input_to_remove_handle.key);
+    /// Makes `after_handle` run after `before_handle` without passing it any data, e.g. a
+    /// logging sink after the node writing a file. Every evaluation that includes
+    /// `after_handle` also evaluates `before_handle`, and cycles fail in `build`.
+    pub fn add_order(
+        &mut self,
+        before_handle: &NodeHandle,
+        after_handle: &NodeHandle,
+    ) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(before_handle)?;
+        self.check_handle(after_handle)?;
+        self.check_new_edge_as(after_handle, before_handle, "before")?;
+        let node = self.node_mut(after_handle.key).unwrap();
+        if !node.after.contains(&before_handle.key) {
+            node.after.push(before_handle.key);
         }
+        self.touch();
+        let (key, before_key) = (after_handle.key, before_handle.key);
+        self.record(|graph| {
+            format!(
+                "ordered '{}' after '{}'",
+                graph.name_for_log(key),
+                graph.name_for_log(before_key)
+            )
+        });
+        Ok(())
+    }
+
+    pub fn remove_order(
+        &mut self,
+        before_handle: &NodeHandle,
+        after_handle: &NodeHandle,
+    ) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(before_handle)?;
+        self.check_handle(after_handle)?;
+        if let Some(node) = self.node_mut(after_handle.key) {
+            node.after.retain(|key| *key != before_handle.key);
+        }
+        self.touch();
+        let (key, before_key) = (after_handle.key, before_handle.key);
+        self.record(|graph| {
+            format!(
+                "removed ordering of '{}' after '{}'",
+                graph.name_for_log(key),
+                graph.name_for_log(before_key)
+            )
+        });
+        Ok(())
     }
 
     pub fn get_name(&self, node_handle: &NodeHandle) -> Result<String, ComputeGraphErrors> {
-        self.verify_graphid(node_handle);
+        self.check_handle(node_handle)?;
         let name = self._get_name(node_handle.key)?;
         Ok(name.to_string())
     }
 
     pub fn get_type_name(&self, type_id: TypeId) -> Option<&'static str> {
-        self.type_names.get(&type_id).map(|v| *v)
+        self.type_names.get(&type_id).copied()
     }
 
-    pub fn set_output_node(&mut self, node_handle: &NodeHandle) {
-        self.verify_graphid(node_handle);
+    pub fn set_output_node(&mut self, node_handle: &NodeHandle) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
         self.output_node = Some(node_handle.key);
+        self.touch();
+        let key = node_handle.key;
+        self.record(|graph| format!("set output node to '{}'", graph.name_for_log(key)));
+        Ok(())
     }
 
-    pub fn connect_to_input(&mut self, node_handle: &NodeHandle) {
-        self.verify_graphid(node_handle);
-        if let Some(node) = self.nodes.get_mut(node_handle.key) {
+    pub fn connect_to_input(&mut self, node_handle: &NodeHandle) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        if let Some(node) = self.node_mut(node_handle.key) {
             node.connected_to_input = true;
         }
+        self.touch();
+        let key = node_handle.key;
+        self.record(|graph| format!("connected '{}' to the graph input", graph.name_for_log(key)));
+        Ok(())
     }
 
-    pub fn disconnect_from_input(&mut self, node_handle: &NodeHandle) {
-        self.verify_graphid(node_handle);
-        if let Some(node) = self.nodes.get_mut(node_handle.key) {
+    pub fn disconnect_from_input(
+        &mut self,
+        node_handle: &NodeHandle,
+    ) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        if let Some(node) = self.node_mut(node_handle.key) {
             node.connected_to_input = false;
         }
+        self.touch();
+        let key = node_handle.key;
+        self.record(|graph| {
+            format!(
+                "disconnected '{}' from the graph input",
+                graph.name_for_log(key)
+            )
+        });
+        Ok(())
+    }
+
+    /// Incremented by every mutation of the graph.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub(crate) fn touch(&mut self) {
+        self.revision += 1;
     }
 
     pub fn build<In, Out>(&mut self) -> Result<ComputeGraph<In, Out>, ComputeGraphErrors>
     where
-        In: Any + Copy,
-        Out: Any + Copy,
+        In: Any + Clone,
+        Out: Any + Clone,
     {
         let output_node_key = self.output_node.ok_or(ComputeGraphErrors::NoOutputNode)?;
         self._build_for_node(output_node_key)
@@ -235,10 +848,10 @@ impl Graph {
         output_node_handle: &NodeHandle,
     ) -> Result<ComputeGraph<In, Out>, ComputeGraphErrors>
     where
-        In: Any + Copy,
-        Out: Any + Copy,
+        In: Any + Clone,
+        Out: Any + Clone,
     {
-        self.verify_graphid(output_node_handle);
+        self.check_handle(output_node_handle)?;
         self._build_for_node(output_node_handle.key)
     }
 
@@ -247,23 +860,41 @@ impl Graph {
         output_node_key: GraphKey,
     ) -> Result<ComputeGraph<In, Out>, ComputeGraphErrors>
     where
-        In: Any + Copy,
-        Out: Any + Copy,
+        In: Any + Clone,
+        Out: Any + Clone,
     {
+        self.check_limits()?;
         let output_node_output_typeid = self.nodes[output_node_key].inner.output_type();
         let output_typeid = TypeId::of::<Out>();
         if output_node_output_typeid != output_typeid {
+            let output_type_name = *self
+                .type_names
+                .get(&output_typeid)
+                .unwrap_or(&"unknown type");
+            let node_type_name = *self.type_names.get(&output_node_output_typeid).unwrap();
             return Err(ComputeGraphErrors::format_wrong_types(
                 "compute output",
-                self.type_names
-                    .get(&output_typeid)
-                    .unwrap_or(&"unknown type"),
+                output_type_name,
                 self._get_name(output_node_key).unwrap(),
-                self.type_names.get(&output_node_output_typeid).unwrap(),
-            ));
+                node_type_name,
+            )
+            .with_node(NodeHandle {
+                key: output_node_key,
+                graph_id: self.id,
+            })
+            .with_type(output_typeid, output_type_name)
+            .with_type(output_node_output_typeid, node_type_name));
         }
 
-        let compute_order = self.compute_order(output_node_key)?;
+        let compute_order = self.compute_order(&[output_node_key])?;
+        Ok(ComputeGraph::new(self.compute_nodes::<In>(compute_order)?))
+    }
+
+    /// Compute nodes for `compute_order`, checking they can read the graph input `In`.
+    pub(crate) fn compute_nodes<In: Any>(
+        &self,
+        compute_order: Vec<GraphKey>,
+    ) -> Result<Vec<ComputeNode>, ComputeGraphErrors> {
         let input_typeid = TypeId::of::<In>();
 
         let node_key_to_index = compute_order
@@ -281,27 +912,79 @@ impl Graph {
                 if node.inner.input_type() != TypeId::of::<()>()
                     && node.inner.input_type() != input_typeid
                 {
+                    let node_type_name = *self.type_names.get(&node.inner.input_type()).unwrap();
+                    let input_type_name = *self
+                        .type_names
+                        .get(&input_typeid)
+                        .unwrap_or(&"unknown type");
                     return Err(ComputeGraphErrors::format_wrong_types(
                         self._get_name(node_key).unwrap(),
-                        self.type_names.get(&node.inner.input_type()).unwrap(),
+                        node_type_name,
                         "compute input",
-                        self.type_names
-                            .get(&input_typeid)
-                            .unwrap_or(&"unknown type"),
-                    ));
+                        input_type_name,
+                    )
+                    .with_node(NodeHandle {
+                        key: node_key,
+                        graph_id: self.id,
+                    })
+                    .with_type(node.inner.input_type(), node_type_name)
+                    .with_type(input_typeid, input_type_name));
                 }
             }
 
-            let inputs = node
-                .inputs
+            let inputs = self
+                .port_inputs(node_key)?
                 .iter()
                 .map(|input_key| *node_key_to_index.get(input_key).unwrap())
                 .collect::<Vec<_>>();
+            let reads_input =
+                node.connected_to_input && node.inner.input_type() != TypeId::of::<()>();
+            #[cfg(feature = "serde")]
+            if let Some(op) = crate::serialize::unavailable_op(node.inner.as_ref()) {
+                return Err(ComputeGraphErrors::CapabilityDenied(
+                    format!(
+                        "'{}' uses op '{}', which is not available in this build",
+                        node.name, op
+                    )
+                    .into(),
+                )
+                .with_node(NodeHandle {
+                    key: node_key,
+                    graph_id: self.id,
+                }));
+            }
+            let (min_inputs, max_inputs) = node.inner.arity();
+            let num_inputs = inputs.len() + reads_input as usize;
+            if num_inputs < min_inputs || max_inputs.is_some_and(|max| num_inputs > max) {
+                return Err(ComputeGraphErrors::format_wrong_arity(
+                    &node.name, num_inputs, min_inputs, max_inputs,
+                )
+                .with_node(NodeHandle {
+                    key: node_key,
+                    graph_id: self.id,
+                }));
+            }
+            let after = node
+                .after
+                .iter()
+                .map(|key| *node_key_to_index.get(key).unwrap())
+                .collect();
 
             nodes.push(ComputeNode {
+                handle: NodeHandle {
+                    key: node_key,
+                    graph_id: self.id,
+                },
+                name: node.name.clone(),
                 connected_to_input: node.connected_to_input,
                 inputs,
+                after,
                 func: node.inner.clone(),
+                op_revision: node.op_revision,
+                policy: node.policy.clone(),
+                critical: node.critical,
+                scheduling: node.scheduling,
+                probed: node.probed,
             });
         }
 
@@ -309,41 +992,63 @@ impl Graph {
             return Err(ComputeGraphErrors::NoInputNodes);
         }
 
-        Ok(ComputeGraph::new(nodes))
+        Ok(nodes)
     }
 
-    fn compute_order(&self, node: GraphKey) -> Result<Vec<GraphKey>, ComputeGraphErrors> {
+    /// The nodes `outputs` depend on, each after its dependencies.
+    pub(crate) fn compute_order(
+        &self,
+        outputs: &[GraphKey],
+    ) -> Result<Vec<GraphKey>, ComputeGraphErrors> {
         let mut compute_order = Vec::new();
-        let mut temp_list = HashSet::new();
-        self.toposort_visit(node, &mut compute_order, &mut temp_list)?;
+        let mut done = HashSet::new();
+        let mut visiting = HashSet::new();
+        // Depth-first with an explicit stack of nodes and the index of their next
+        // dependency, as recursing per node overflows on long chains
+        let mut stack: Vec<(GraphKey, usize)> = Vec::new();
+        for output in outputs {
+            if done.contains(output) {
+                continue;
+            }
+            visiting.insert(*output);
+            stack.push((*output, 0));
+            while let Some((node, next)) = stack.last_mut() {
+                let node = *node;
+                let node_ref = &self.nodes[node];
+                let dependency = match *next {
+                    index if index < node_ref.inputs.len() => Some(node_ref.inputs[index]),
+                    index => node_ref.after.get(index - node_ref.inputs.len()).copied(),
+                };
+                *next += 1;
+                let Some(dependency) = dependency else {
+                    stack.pop();
+                    visiting.remove(&node);
+                    done.insert(node);
+                    compute_order.push(node);
+                    continue;
+                };
+                if done.contains(&dependency) {
+                    continue;
+                }
+                if !visiting.insert(dependency) {
+                    return Err(ComputeGraphErrors::GraphCycle(
+                        self._get_name(dependency).unwrap().to_string().into(),
+                    )
+                    .with_node(NodeHandle {
+                        key: dependency,
+                        graph_id: self.id,
+                    }));
+                }
+                stack.push((dependency, 0));
+            }
+        }
         Ok(compute_order)
     }
 
-    fn toposort_visit(
-        &self,
-        node: GraphKey,
-        sorted_list: &mut Vec<GraphKey>,
-        temp_list: &mut HashSet<GraphKey>,
-    ) -> Result<(), ComputeGraphErrors> {
-        if sorted_list.contains(&node) {
-            return Ok(());
-        }
-
-        if temp_list.contains(&node) {
-            return Err(ComputeGraphErrors::GraphCycle(
-                self._get_name(node).unwrap().to_string(),
-            ));
-        }
-
-        temp_list.insert(node);
-
-        for input_node in self.nodes.get(node).unwrap().inputs.iter() {
-            self.toposort_visit(*input_node, sorted_list, temp_list)?;
-        }
-
-        temp_list.remove(&node);
-        sorted_list.push(node);
-        Ok(())
+    /// Node `node_key` for editing, copied first if a branch still shares it. Validate the
+    /// edit before, and `touch` the graph once it succeeded.
+    pub(crate) fn node_mut(&mut self, node_key: GraphKey) -> Option<&mut Node> {
+        self.nodes.get_mut(node_key).map(Arc::make_mut)
     }
 
     fn _get_name(&self, node_key: GraphKey) -> Result<&str, ComputeGraphErrors> {
@@ -354,25 +1059,51 @@ impl Graph {
         Ok(&node.name)
     }
 
-    fn verify_graphid(&self, node_handle: &NodeHandle) {
+    /// Checks that `node_handle` belongs to this graph and its node wasn't removed.
+    pub(crate) fn check_handle(&self, node_handle: &NodeHandle) -> Result<(), ComputeGraphErrors> {
         if node_handle.graph_id != self.id {
-            panic!(
-                "Graph got node_handle with wrong graph_id: {} != {}",
-                node_handle.graph_id, self.id
+            return Err(ComputeGraphErrors::ForeignHandle(
+                "node handle belongs to another graph".into(),
+            ));
+        }
+        if !self.nodes.contains_key(node_handle.key) {
+            return Err(
+                ComputeGraphErrors::StaleHandle("node was removed from the graph".into())
+                    .with_node(*node_handle),
             );
         }
+        Ok(())
     }
 }
 
-
 #[derive(Debug)]
 pub enum ComputeGraphErrors {
     NoInputNodes,
+    ConflictingEdit(ErrorDetails),
     NoOutputNode,
     NodeMissing,
-    IncompatibleNewNode(String),
-    GraphCycle(String),
-    WrongTypes(String),
+    IncompatibleNewNode(ErrorDetails),
+    GraphCycle(ErrorDetails),
+    WrongTypes(ErrorDetails),
+    InvalidParameter(ErrorDetails),
+    Serialization(ErrorDetails),
+    Import(ErrorDetails),
+    LimitExceeded(ErrorDetails),
+    CapabilityDenied(ErrorDetails),
+    NodeFailed(ErrorDetails),
+    /// Internal state is inconsistent, see `Graph::check_invariants`.
+    BrokenInvariant(ErrorDetails),
+    WrongArity(ErrorDetails),
+    /// A handle from another graph was passed in.
+    ForeignHandle(ErrorDetails),
+    /// A handle to a node that was removed was passed in.
+    StaleHandle(ErrorDetails),
+    /// An operation identifier is already taken, see `NodeRegistry::register`.
+    NameCollision(ErrorDetails),
+    #[cfg(feature = "signing")]
+    InvalidSignature,
+    #[cfg(feature = "serde")]
+    InvalidGraph(Vec<crate::serialize::LoadIssue>),
 }
 
 impl ComputeGraphErrors {
@@ -382,10 +1113,32 @@ impl ComputeGraphErrors {
         output_name: &str,
         output_type: &str,
     ) -> Self {
-        Self::WrongTypes(format!(
-            "'{}' input type '{}' does not match '{}' output type '{}'",
-            input_name, input_type, output_name, output_type
-        ))
+        Self::WrongTypes(
+            format!(
+                "'{}' input type '{}' does not match '{}' output type '{}'",
+                input_name, input_type, output_name, output_type
+            )
+            .into(),
+        )
+    }
+    fn format_wrong_arity(
+        node_name: &str,
+        num_inputs: usize,
+        min_inputs: usize,
+        max_inputs: Option<usize>,
+    ) -> Self {
+        let expected = match max_inputs {
+            Some(max) if max == min_inputs => format!("exactly {}", max),
+            Some(max) if num_inputs > max => format!("at most {}", max),
+            _ => format!("at least {}", min_inputs),
+        };
+        Self::WrongArity(
+            format!(
+                "'{}' takes {} inputs, including the graph input, but has {}",
+                node_name, expected, num_inputs
+            )
+            .into(),
+        )
     }
     fn format_incompatible_object(
         input_name: &str,
@@ -402,16 +1155,112 @@ impl ComputeGraphErrors {
                 slot_name, old_type_name, new_type_name
             );
         }
-        Self::IncompatibleNewNode(msg)
+        Self::IncompatibleNewNode(msg.into())
+    }
+    pub(crate) fn format_node_failed(node_name: &str, error: &str) -> Self {
+        Self::NodeFailed(format!("'{}' failed: {}", node_name, error).into())
+    }
+    pub(crate) fn format_param_error(node_name: &str, error: &ParamError) -> Self {
+        let reason = match error {
+            ParamError::UnknownParam(name) => format!("has no parameter '{}'", name),
+            ParamError::WrongKind {
+                name,
+                expected,
+                found,
+            } => format!(
+                "parameter '{}' expects {:?} but got {:?}",
+                name, expected, found
+            ),
+            ParamError::OutOfRange { name, value, range } => format!(
+                "parameter '{}' value {} is outside [{}, {}]",
+                name, value, range.0, range.1
+            ),
+        };
+        Self::InvalidParameter(format!("'{}' {}", node_name, reason).into())
+    }
+
+    /// Stable identifier of the kind of error, used as the key by `Localizer`.
+    pub fn key(&self) -> &'static str {
+        self.code().key()
+    }
+
+    /// Specifics of the error, like the nodes involved.
+    pub fn detail(&self) -> Option<String> {
+        match self {
+            Self::ConflictingEdit(detail)
+            | Self::IncompatibleNewNode(detail)
+            | Self::GraphCycle(detail)
+            | Self::WrongTypes(detail)
+            | Self::InvalidParameter(detail)
+            | Self::Serialization(detail)
+            | Self::Import(detail)
+            | Self::LimitExceeded(detail)
+            | Self::CapabilityDenied(detail)
+            | Self::NodeFailed(detail)
+            | Self::BrokenInvariant(detail)
+            | Self::WrongArity(detail)
+            | Self::ForeignHandle(detail)
+            | Self::StaleHandle(detail)
+            | Self::NameCollision(detail) => Some(detail.to_string()),
+            #[cfg(feature = "serde")]
+            Self::InvalidGraph(issues) => Some(
+                issues
+                    .iter()
+                    .map(|issue| issue.to_string())
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            ),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn summary(&self) -> &'static str {
+        match self {
+            Self::NoInputNodes => "graph has no input nodes",
+            Self::ConflictingEdit(_) => "conflicting edit",
+            Self::NoOutputNode => "graph has no output node",
+            Self::NodeMissing => "node is missing",
+            Self::IncompatibleNewNode(_) => "incompatible node",
+            Self::GraphCycle(_) => "graph has a cycle",
+            Self::WrongTypes(_) => "wrong types",
+            Self::InvalidParameter(_) => "invalid parameter",
+            Self::Serialization(_) => "serialization failed",
+            Self::Import(_) => "import failed",
+            Self::LimitExceeded(_) => "limit exceeded",
+            Self::CapabilityDenied(_) => "capability denied",
+            Self::NodeFailed(_) => "node failed",
+            Self::BrokenInvariant(_) => "broken invariant",
+            Self::WrongArity(_) => "wrong number of inputs",
+            Self::ForeignHandle(_) => "handle from another graph",
+            Self::StaleHandle(_) => "handle to a removed node",
+            Self::NameCollision(_) => "name collision",
+            #[cfg(feature = "signing")]
+            Self::InvalidSignature => "invalid signature",
+            #[cfg(feature = "serde")]
+            Self::InvalidGraph(_) => "invalid graph",
+        }
+    }
+}
+
+impl fmt::Display for ComputeGraphErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.detail() {
+            Some(detail) => write!(f, "{}: {}", self.summary(), detail),
+            None => write!(f, "{}", self.summary()),
+        }
     }
 }
 
+impl std::error::Error for ComputeGraphErrors {}
+
 #[cfg(test)]
 mod graph_tests {
     use crate::{
         graph::*,
-        operations::{AddInputs, Constant, MulInputs},
+        operations::{AddInputs, Constant, MulInputs, SubInputs},
+        params::{ParamKind, Parameters},
     };
+    use std::sync::Mutex;
     #[test]
     fn test_functionality() -> Result<(), ComputeGraphErrors> {
         //  Building this graph:
@@ -433,9 +1282,8 @@ mod graph_tests {
         let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
 
         //Operation fails if output type does not match the input type
-        match graph.add_input(&add_handle, &mul_handle) {
-            Err(msg) => eprintln!("{:?}", msg),
-            _ => {}
+        if let Err(msg) = graph.add_input(&add_handle, &mul_handle) {
+            eprintln!("{:?}", msg)
         };
 
         //Lets setup the rest of the nodes and ignore errors..
@@ -444,10 +1292,10 @@ mod graph_tests {
 
         //By default the graph sends the input to any input that has no other inputs
         //If you want a node to have the input together with other nodes you have to manually assign it
-        graph.connect_to_input(&mul_handle);
+        graph.connect_to_input(&mul_handle)?;
 
         //We can specify an output node:
-        graph.set_output_node(&add_handle);
+        graph.set_output_node(&add_handle)?;
         //We must build a ComputeGraph before we can compute anything
         //Graph fails if input type does not match output type, or there are cycles in the graph.
         let compute_graph = graph.build::<f64, f64>()?;
@@ -470,4 +1318,440 @@ mod graph_tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_dedup_constants() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let first = graph.insert_node("first", Constant(42.0));
+        let second = graph.insert_node("second", Constant(42.0));
+        let other = graph.insert_node("other", Constant(1.0));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&add_handle, &first)?;
+        graph.add_input(&add_handle, &second)?;
+        graph.add_input(&add_handle, &other)?;
+        graph.connect_to_input(&add_handle)?;
+        graph.set_output_node(&add_handle)?;
+
+        let report = graph.dedup_constants();
+        assert_eq!(report.removed_count(), 1);
+        assert_eq!(report.merges[0].removed, vec!["second".to_string()]);
+        assert_eq!(graph.get_all_node_metas().len(), 3);
+        assert_eq!(graph.get_node_meta(&add_handle)?.inputs.len(), 3);
+
+        let compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(compute_graph.compute(&1.0), 86.0);
+
+        // Constants of types without `PartialEq` are allowed, but never merged
+        #[derive(Clone, Default)]
+        struct Opaque;
+        let mut graph = Graph::new();
+        graph.insert_node("first", Constant(Opaque));
+        graph.insert_node("second", Constant(Opaque));
+        assert_eq!(graph.dedup_constants().removed_count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup_keeps_metadata() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        let mut constants = Vec::new();
+        for name in ["plain", "tagged", "styled", "probed", "critical", "twin"] {
+            let handle = graph.insert_node(name, Constant(42.0));
+            graph.add_input(&add_handle, &handle)?;
+            constants.push(handle);
+        }
+        graph.add_tag(&constants[1], "tuning")?;
+        graph.set_style(&constants[2], NodeStyle::group("inputs"))?;
+        graph.probe(&constants[3])?;
+        graph.set_critical(&constants[4], true)?;
+        graph.add_tag(&constants[5], "tuning")?;
+        graph.set_output_node(&add_handle)?;
+
+        // Only the two constants tagged alike are merged
+        let report = graph.dedup_constants();
+        assert_eq!(report.removed_count(), 1);
+        assert_eq!(report.merges[0].kept, constants[1]);
+        assert_eq!(report.merges[0].removed, ["twin"]);
+        assert_eq!(graph.get_node_meta(&constants[1])?.tags, ["tuning"]);
+        assert!(graph.is_probed(&constants[3])?);
+        assert_eq!(graph.build::<(), f64>()?.compute(&()), 6.0 * 42.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup_keeps_ordering_and_policy() -> Result<(), ComputeGraphErrors> {
+        use std::time::Duration;
+        let mut graph = Graph::new();
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        let log_handle = graph.insert_node("log", Constant(()));
+        let constant = |graph: &mut Graph, name: &str, value: f64| {
+            let handle = graph.insert_node(name, Constant(value));
+            graph.add_input(&add_handle, &handle).map(|()| handle)
+        };
+        constant(&mut graph, "zero", 0.0)?;
+        constant(&mut graph, "negative_zero", -0.0)?;
+        let first = constant(&mut graph, "first", 1.0)?;
+        let second = constant(&mut graph, "second", 1.0)?;
+        graph.add_order(&second, &first)?;
+        let retried = constant(&mut graph, "retried", 2.0)?;
+        let plain = constant(&mut graph, "plain", 2.0)?;
+        let retried_twin = constant(&mut graph, "retried_twin", 2.0)?;
+        let retry = NodePolicy::new().retry(1, Duration::ZERO);
+        graph.set_policy(&retried, retry.clone())?;
+        graph.set_policy(&retried_twin, retry)?;
+        let ordered = constant(&mut graph, "ordered", 3.0)?;
+        let ordered_twin = constant(&mut graph, "ordered_twin", 3.0)?;
+        graph.add_order(&log_handle, &ordered)?;
+        graph.add_order(&log_handle, &ordered_twin)?;
+        graph.set_output_node(&add_handle)?;
+
+        let report = graph.dedup_constants();
+        let mut merges = report
+            .merges
+            .iter()
+            .map(|merge| (merge.kept, merge.removed.clone()))
+            .collect::<Vec<_>>();
+        merges.sort_by_key(|(_, removed)| removed.clone());
+        assert_eq!(
+            merges,
+            [
+                (ordered.erase(), vec!["ordered_twin".to_string()]),
+                (retried.erase(), vec!["retried_twin".to_string()])
+            ]
+        );
+        assert!(graph.nodes.contains_key(plain.key));
+        assert!(graph.nodes.contains_key(second.key));
+        assert_eq!(graph.nodes[ordered.key].after, [log_handle.key]);
+        assert_eq!(
+            graph.build::<(), f64>()?.compute(&()),
+            1.0 + 1.0 + 2.0 * 3.0 + 3.0 * 2.0
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_unreachable() -> Result<(), ComputeGraphErrors> {
+        //  Input ── mul ── add (output)    orphan ── dead
+        //  const ──┘  log ┄┘
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("const", Constant(2.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        let log_handle = graph.insert_node("log", Constant(0.0));
+        let orphan_handle = graph.insert_node("orphan", Constant(1.0));
+        let dead_handle = graph.insert_node("dead", SubInputs::<f64>::new());
+        graph.add_input(&mul_handle, &const_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.add_input(&add_handle, &mul_handle)?;
+        graph.add_order(&log_handle, &add_handle)?;
+        graph.add_input(&dead_handle, &orphan_handle)?;
+        graph.add_input(&dead_handle, &add_handle)?;
+        assert!(matches!(
+            graph.prune_unreachable(),
+            Err(ComputeGraphErrors::NoOutputNode)
+        ));
+        graph.set_output_node(&add_handle)?;
+
+        let unreachable = graph.unreachable_from(&add_handle)?;
+        assert_eq!(unreachable.len(), 2);
+        assert!(unreachable.contains(&orphan_handle) && unreachable.contains(&dead_handle));
+        assert_eq!(graph.unreachable_from(&mul_handle)?.len(), 4);
+
+        let mut removed = graph.prune_unreachable()?;
+        removed.sort();
+        assert_eq!(removed, ["dead", "orphan"]);
+        assert_eq!(graph.get_all_node_metas().len(), 4);
+        assert!(graph.unreachable_from(&dead_handle).is_err());
+        assert!(graph.prune_unreachable()?.is_empty());
+        assert_eq!(graph.build::<f64, f64>()?.compute(&3.0), 6.0);
+        Ok(())
+    }
+
+    #[derive(Clone)]
+    struct Gain {
+        factor: f64,
+        enabled: bool,
+    }
+    crate::impl_parameters!(Gain {
+        factor: f64 [0.0, 10.0],
+        enabled: bool,
+    });
+    impl Compute for Gain {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&f64]) -> f64 {
+            let sum: f64 = inputs.iter().copied().sum();
+            if self.enabled {
+                sum * self.factor
+            } else {
+                sum
+            }
+        }
+        fn parameters(&self) -> Option<&dyn Parameters> {
+            Some(self)
+        }
+        fn parameters_mut(&mut self) -> Option<&mut dyn Parameters> {
+            Some(self)
+        }
+    }
+
+    #[test]
+    fn test_params() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("const", Constant(2.0));
+        let gain_handle = graph.insert_node(
+            "gain",
+            Gain {
+                factor: 3.0,
+                enabled: true,
+            },
+        );
+        graph.add_input(&gain_handle, &const_handle)?;
+        graph.set_output_node(&gain_handle)?;
+
+        let params = graph.get_params(&gain_handle)?;
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0].name, "factor");
+        assert_eq!(params[0].value, ParamValue::Float(3.0));
+        assert_eq!(params[0].range, Some((0.0, 10.0)));
+        assert_eq!(params[1].kind, ParamKind::Bool);
+
+        assert!(graph.set_param(&gain_handle, "factor", 11.0).is_err());
+        assert!(graph.set_param(&gain_handle, "factor", f64::NAN).is_err());
+        assert!(graph.set_param(&gain_handle, "factor", true).is_err());
+        assert!(graph.set_param(&gain_handle, "missing", 1.0).is_err());
+        graph.set_param(&gain_handle, "factor", 5.0)?;
+        graph.set_param(&const_handle, "value", 4.0)?;
+        assert_eq!(
+            graph.get_params(&const_handle)?[0].value,
+            ParamValue::Float(4.0)
+        );
+
+        let compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(compute_graph.compute(&0.0), 20.0);
+
+        graph.add_tag(&gain_handle, "audio")?;
+        let meta = graph.get_node_meta(&gain_handle)?;
+        assert_eq!(meta.name, "gain");
+        assert_eq!(meta.input_type_name, "f64");
+        assert_eq!(meta.params[0].value, ParamValue::Float(5.0));
+        assert_eq!(meta.tags, vec!["audio".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wide_int_params() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("const", Constant(u64::MAX));
+        let value = graph.get_params(&const_handle)?[0].value;
+        assert_eq!(value, ParamValue::UInt(u64::MAX));
+        graph.set_param(&const_handle, "value", value)?;
+        assert_eq!(graph.get_params(&const_handle)?[0].value, value);
+        graph.set_param(&const_handle, "value", 7i64)?;
+        assert_eq!(
+            graph.get_params(&const_handle)?[0].value,
+            ParamValue::Int(7)
+        );
+        assert!(graph.set_param(&const_handle, "value", -1i64).is_err());
+
+        let small_handle = graph.insert_node("small", Constant(1u8));
+        assert!(graph.set_param(&small_handle, "value", value).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hot_params() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("const", Constant(2.0));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        let unused_handle = graph.insert_node("unused", Constant(1.0));
+        graph.add_input(&add_handle, &const_handle)?;
+        graph.connect_to_input(&add_handle)?;
+        graph.set_output_node(&add_handle)?;
+
+        let mut compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(compute_graph.compute(&1.0), 3.0);
+
+        compute_graph.set_param(&const_handle, "value", 5.0)?;
+        assert_eq!(compute_graph.compute(&1.0), 6.0);
+        assert!(compute_graph.set_param(&const_handle, "value", 5).is_err());
+        assert!(compute_graph
+            .set_param(&unused_handle, "value", 5.0)
+            .is_err());
+
+        // The editable graph is untouched by hot updates
+        assert_eq!(
+            graph.get_params(&const_handle)?[0].value,
+            ParamValue::Float(2.0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_copy_values() -> Result<(), ComputeGraphErrors> {
+        fn concat(inputs: &[&Vec<f64>]) -> Vec<f64> {
+            inputs.iter().flat_map(|v| v.iter().copied()).collect()
+        }
+        fn label(inputs: &[&Vec<f64>]) -> String {
+            format!("{} samples", inputs[0].len())
+        }
+        let mut graph = Graph::new();
+        let samples_handle = graph.insert_node("samples", Constant(vec![1.0, 2.0]));
+        let concat_handle = graph.insert_node("concat", concat as fn(&[&Vec<f64>]) -> Vec<f64>);
+        let label_handle = graph.insert_node("label", label as fn(&[&Vec<f64>]) -> String);
+        graph.add_input(&concat_handle, &samples_handle)?;
+        graph.connect_to_input(&concat_handle)?;
+        graph.add_input(&label_handle, &concat_handle)?;
+        graph.set_output_node(&concat_handle)?;
+
+        let compute_graph = graph.build::<Vec<f64>, Vec<f64>>()?;
+        assert_eq!(compute_graph.compute(&vec![3.0]), [1.0, 2.0, 3.0]);
+        // Borrowing the output avoids cloning it
+        let mut context = compute_graph.new_context();
+        assert_eq!(
+            compute_graph.try_compute_ref(&vec![], &mut context)?.len(),
+            2
+        );
+
+        graph.set_output_node(&label_handle)?;
+        let compute_graph = graph.build::<Vec<f64>, String>()?;
+        assert_eq!(compute_graph.compute(&vec![3.0, 4.0]), "4 samples");
+        Ok(())
+    }
+
+    #[test]
+    fn test_branch() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("rate", Constant(2.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &const_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.set_output_node(&mul_handle)?;
+
+        let mut branch = graph.branch();
+        assert!(Arc::ptr_eq(
+            &graph.nodes[const_handle.key],
+            &branch.nodes[const_handle.key]
+        ));
+        branch.set_param(&const_handle, "value", 5.0)?;
+        assert!(!Arc::ptr_eq(
+            &graph.nodes[const_handle.key],
+            &branch.nodes[const_handle.key]
+        ));
+        assert!(Arc::ptr_eq(
+            &graph.nodes[mul_handle.key],
+            &branch.nodes[mul_handle.key]
+        ));
+
+        assert_eq!(branch.build::<f64, f64>()?.compute(&3.0), 15.0);
+        assert_eq!(graph.build::<f64, f64>()?.compute(&3.0), 6.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrong_arity() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let rate_handle = graph.insert_node("rate", Constant(2.0));
+        let sub_handle = graph.insert_node("sub", SubInputs::<f64>::new());
+        graph.add_input(&sub_handle, &rate_handle)?;
+        graph.disconnect_from_input(&sub_handle)?;
+        graph.set_output_node(&sub_handle)?;
+        let Err(err) = graph.build::<f64, f64>() else {
+            panic!("subtraction with one input was built");
+        };
+        assert!(matches!(err, ComputeGraphErrors::WrongArity(_)));
+        assert_eq!(err.nodes(), [*sub_handle]);
+        assert_eq!(
+            err.detail().unwrap(),
+            "'sub' takes at least 2 inputs, including the graph input, but has 1"
+        );
+
+        // The graph input counts as the second operand
+        graph.connect_to_input(&sub_handle)?;
+        assert_eq!(graph.build::<f64, f64>()?.compute(&5.0), 3.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deep_chain() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let one_handle = graph.insert_node("one", Constant(1.0));
+        let mut previous = one_handle.erase();
+        for _ in 0..100_000 {
+            let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+            graph.add_input(&add_handle, &previous)?;
+            graph.add_input(&add_handle, &one_handle)?;
+            previous = add_handle.erase();
+        }
+        graph.set_output_node(&previous)?;
+        assert_eq!(graph.build::<f64, f64>()?.compute(&0.0), 100_001.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_handles() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("const", Constant(1.0));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        let mut other = Graph::new();
+        let foreign_handle = other.insert_node("foreign", Constant(2.0));
+
+        assert!(matches!(
+            graph.add_input(&add_handle, &foreign_handle),
+            Err(ComputeGraphErrors::ForeignHandle(_))
+        ));
+        assert!(matches!(
+            graph.set_output_node(&foreign_handle),
+            Err(ComputeGraphErrors::ForeignHandle(_))
+        ));
+
+        graph.remove_node(&const_handle)?;
+        let err = graph.get_node_meta(&const_handle).unwrap_err();
+        assert!(matches!(err, ComputeGraphErrors::StaleHandle(_)));
+        assert_eq!(err.nodes(), [const_handle.erase()]);
+        assert!(matches!(
+            graph.remove_node(&const_handle),
+            Err(ComputeGraphErrors::StaleHandle(_))
+        ));
+        // Failed edits leave the graph untouched
+        assert_eq!(graph.get_node_meta(&add_handle)?.inputs.len(), 0);
+        assert!(graph.output_node.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ordering_edges() -> Result<(), ComputeGraphErrors> {
+        static RUNS: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+        fn write(_: &[&f64]) -> f64 {
+            RUNS.lock().unwrap().push("write");
+            0.0
+        }
+        fn log(_: &[&f64]) -> f64 {
+            RUNS.lock().unwrap().push("log");
+            0.0
+        }
+
+        //  Input ── log ┄┄ write ┄┄ output
+        let mut graph = Graph::new();
+        let write_handle = graph.insert_node("write", write as fn(&[&f64]) -> f64);
+        let log_handle = graph.insert_node("log", log as fn(&[&f64]) -> f64);
+        let output_handle = graph.insert_node("output", AddInputs::<f64>::new());
+        graph.add_order(&write_handle, &log_handle)?;
+        graph.add_order(&log_handle, &output_handle)?;
+        graph.set_output_node(&output_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+        compute_graph.compute(&1.0);
+        assert_eq!(*RUNS.lock().unwrap(), ["write", "log"]);
+
+        graph.add_order(&output_handle, &write_handle)?;
+        assert!(matches!(
+            graph.build::<f64, f64>(),
+            Err(ComputeGraphErrors::GraphCycle(_))
+        ));
+        graph.remove_order(&output_handle, &write_handle)?;
+        graph.remove_node(&write_handle)?;
+        graph.check_invariants()?;
+        Ok(())
+    }
 }
@@ -0,0 +1,331 @@
+use crate::com_graph::ComputeGraph;
+use crate::compute::Value;
+use crate::graph::ComputeGraphErrors;
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Regular 2D grid of sample points, `width` columns by `height` rows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Grid {
+    pub origin: [f64; 2],
+    pub step: [f64; 2],
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Grid {
+    pub fn new(origin: [f64; 2], step: [f64; 2], width: usize, height: usize) -> Self {
+        Self {
+            origin,
+            step,
+            width,
+            height,
+        }
+    }
+
+    pub fn point(&self, column: usize, row: usize) -> [f64; 2] {
+        [
+            self.origin[0] + column as f64 * self.step[0],
+            self.origin[1] + row as f64 * self.step[1],
+        ]
+    }
+
+    pub fn len(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Tiles of at most `tile_size` by `tile_size` points covering the grid, row by row.
+    pub fn tiles(&self, tile_size: usize) -> Vec<Tile> {
+        let tile_size = tile_size.max(1);
+        let mut tiles = Vec::new();
+        for row in (0..self.height).step_by(tile_size) {
+            for column in (0..self.width).step_by(tile_size) {
+                tiles.push(Tile {
+                    column,
+                    row,
+                    width: tile_size.min(self.width - column),
+                    height: tile_size.min(self.height - row),
+                });
+            }
+        }
+        tiles
+    }
+}
+
+/// Rectangle of grid points, given by its top left point and size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tile {
+    pub column: usize,
+    pub row: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Passed to the progress callback of `ComputeGraph::compute_grid` after every tile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileProgress {
+    pub tile: Tile,
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Shared flag stopping a `ComputeGraph::render_grid` call, for example from a UI thread.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Clears the flag so the token can be used for the next render.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Outputs of a grid evaluation that can be stopped and resumed tile by tile.
+#[derive(Clone, Debug)]
+pub struct GridRender<Out> {
+    grid: Grid,
+    tiles: Vec<Tile>,
+    done: Vec<bool>,
+    outputs: Vec<Out>,
+}
+
+impl<Out: Value> GridRender<Out> {
+    pub fn new(grid: Grid, tile_size: usize) -> Self {
+        let tiles = grid.tiles(tile_size);
+        Self {
+            grid,
+            done: vec![false; tiles.len()],
+            tiles,
+            outputs: vec![Out::default(); grid.len()],
+        }
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    pub fn completed_tiles(&self) -> usize {
+        self.done.iter().filter(|done| **done).count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.done.iter().all(|done| *done)
+    }
+
+    pub fn is_tile_complete(&self, index: usize) -> bool {
+        self.done[index]
+    }
+
+    /// Outputs row by row. Points of tiles not rendered yet keep their last value.
+    pub fn outputs(&self) -> &[Out] {
+        &self.outputs
+    }
+
+    pub fn into_outputs(self) -> Vec<Out> {
+        self.outputs
+    }
+
+    /// Marks every tile as pending, e.g. after a parameter change. The old outputs stay
+    /// visible until their tiles are rendered again.
+    pub fn invalidate(&mut self) {
+        self.done.fill(false);
+    }
+
+    /// Marks the tiles from `index` on as pending, to resume a render from that tile.
+    pub fn invalidate_from(&mut self, index: usize) {
+        for done in self.done.iter_mut().skip(index) {
+            *done = false;
+        }
+    }
+
+    fn store(&mut self, index: usize, values: &[Out]) {
+        let tile = self.tiles[index];
+        for (offset, row) in values.chunks(tile.width).enumerate() {
+            let start = (tile.row + offset) * self.grid.width + tile.column;
+            self.outputs[start..start + tile.width].clone_from_slice(row);
+        }
+        self.done[index] = true;
+    }
+}
+
+impl<Out> ComputeGraph<[f64; 2], Out>
+where
+    Out: Value,
+{
+    /// Evaluates the graph at every point of `grid`, returning the outputs row by row.
+    /// Tiles are evaluated on the rayon pool, each worker with its own `EvalContext`,
+    /// and `progress` is called as each tile completes, from the thread that computed it.
+    pub fn compute_grid<F>(
+        &self,
+        grid: &Grid,
+        tile_size: usize,
+        progress: F,
+    ) -> Result<Vec<Out>, ComputeGraphErrors>
+    where
+        F: Fn(TileProgress) + Sync,
+    {
+        let mut render = GridRender::new(*grid, tile_size);
+        self.render_grid(&mut render, &CancelToken::new(), progress)?;
+        Ok(render.into_outputs())
+    }
+
+    /// Renders the pending tiles of `render` like `compute_grid`. Once `cancel` is set no new
+    /// tile is started, and the tiles finished so far are kept, so calling this again resumes
+    /// the render. Returns whether the render is complete.
+    pub fn render_grid<F>(
+        &self,
+        render: &mut GridRender<Out>,
+        cancel: &CancelToken,
+        progress: F,
+    ) -> Result<bool, ComputeGraphErrors>
+    where
+        F: Fn(TileProgress) + Sync,
+    {
+        let pending = (0..render.tiles.len())
+            .filter(|index| !render.done[*index])
+            .collect::<Vec<_>>();
+        let completed = AtomicUsize::new(render.completed_tiles());
+        let total = render.tiles.len();
+        let (grid, tiles) = (&render.grid, &render.tiles);
+        let results = pending
+            .par_iter()
+            .map_init(
+                || self.new_context(),
+                |context, index| {
+                    if cancel.is_cancelled() {
+                        return Ok(None);
+                    }
+                    let tile = tiles[*index];
+                    let mut values = Vec::with_capacity(tile.width * tile.height);
+                    for row in tile.row..tile.row + tile.height {
+                        for column in tile.column..tile.column + tile.width {
+                            values.push(self.try_compute_with(&grid.point(column, row), context)?);
+                        }
+                    }
+                    progress(TileProgress {
+                        tile,
+                        completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+                        total,
+                    });
+                    Ok(Some((*index, values)))
+                },
+            )
+            .collect::<Vec<Result<_, ComputeGraphErrors>>>();
+
+        let mut error = None;
+        for result in results {
+            match result {
+                Ok(Some((index, values))) => render.store(index, &values),
+                Ok(None) => {}
+                Err(err) => error = error.or(Some(err)),
+            }
+        }
+        match error {
+            Some(err) => Err(err),
+            None => Ok(render.is_complete()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod grid_tests {
+    use crate::prelude::*;
+    use std::sync::Mutex;
+
+    #[derive(Clone, Copy)]
+    struct Height;
+
+    impl Compute for Height {
+        type In = [f64; 2];
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            let [x, y] = *inputs[0];
+            x * 100.0 + y
+        }
+    }
+
+    #[test]
+    fn test_tiled_grid() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let height_handle = graph.insert_node("height", Height);
+        graph.set_output_node(&height_handle)?;
+        let compute_graph = graph.build::<[f64; 2], f64>()?;
+
+        let grid = Grid::new([0.0, 0.0], [1.0, 1.0], 7, 5);
+        assert_eq!(grid.tiles(3).len(), 6);
+        let seen = Mutex::new(Vec::new());
+        let values = compute_graph.compute_grid(&grid, 3, |progress| {
+            assert_eq!(progress.total, 6);
+            seen.lock().unwrap().push(progress.completed);
+        })?;
+
+        let expected = (0..5)
+            .flat_map(|y| (0..7).map(move |x| x as f64 * 100.0 + y as f64))
+            .collect::<Vec<_>>();
+        assert_eq!(values, expected);
+        let mut seen = seen.into_inner().unwrap();
+        seen.sort();
+        assert_eq!(seen, [1, 2, 3, 4, 5, 6]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancel_and_resume() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let height_handle = graph.insert_node("height", Height);
+        graph.set_output_node(&height_handle)?;
+        let compute_graph = graph.build::<[f64; 2], f64>()?;
+
+        let grid = Grid::new([0.0, 0.0], [1.0, 1.0], 64, 64);
+        let mut render = GridRender::new(grid, 4);
+        let cancel = CancelToken::new();
+        let complete = compute_graph.render_grid(&mut render, &cancel, |progress| {
+            if progress.completed == 3 {
+                cancel.cancel();
+            }
+        })?;
+        assert!(!complete);
+        assert!(render.completed_tiles() >= 3 && render.completed_tiles() < 256);
+
+        cancel.reset();
+        let resumed_from = render.completed_tiles();
+        let rendered = std::sync::atomic::AtomicUsize::new(0);
+        let complete = compute_graph.render_grid(&mut render, &cancel, |progress| {
+            assert!(progress.completed > resumed_from);
+            rendered.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        })?;
+        assert!(complete);
+        assert_eq!(rendered.into_inner(), 256 - resumed_from);
+        assert_eq!(
+            render.outputs(),
+            compute_graph.compute_grid(&grid, 5, |_| {})?
+        );
+
+        render.invalidate_from(200);
+        assert_eq!(render.completed_tiles(), 200);
+        render.invalidate();
+        assert!(!render.is_tile_complete(0));
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+use crate::com_graph::ComputeGraph;
+use crate::compute::{InnerCompute, Value};
+use crate::formula::Expr;
+use crate::graph::{ComputeGraphErrors, Graph};
+use crate::params::Parameters;
+use crate::preview::Detail;
+use crate::typed::TypedNodeHandle;
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::hash::Hasher;
+use std::marker::PhantomData;
+
+/// Values for the named inputs of a graph, see `Graph::add_graph_input`.
+#[derive(Default)]
+pub struct Inputs {
+    values: HashMap<String, Box<dyn Any + Send + Sync>>,
+}
+
+impl Inputs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<T: Value>(mut self, name: impl Into<String>, value: T) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    pub fn set<T: Value>(&mut self, name: impl Into<String>, value: T) {
+        self.values.insert(name.into(), Box::new(value));
+    }
+
+    pub fn get<T: Value>(&self, name: &str) -> Option<&T> {
+        self.values.get(name)?.downcast_ref()
+    }
+}
+
+/// Source node whose output is the input of that name passed to `ComputeGraph::compute_named`.
+#[derive(Clone)]
+struct NamedInput<T> {
+    name: String,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T: Value> InnerCompute for NamedInput<T> {
+    fn init_output(&self) -> Box<dyn Any + Send + Sync> {
+        Box::new(T::default())
+    }
+    fn clone_output(&self, output: &dyn Any) -> Box<dyn Any + Send + Sync> {
+        Box::new(output.downcast_ref::<T>().unwrap().clone())
+    }
+    fn copy_output(&self, from: &dyn Any, to: &mut dyn Any) {
+        to.downcast_mut::<T>()
+            .unwrap()
+            .clone_from(from.downcast_ref::<T>().unwrap());
+    }
+    fn input_type(&self) -> TypeId {
+        TypeId::of::<()>()
+    }
+    fn output_type(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+    fn input_type_name(&self) -> &'static str {
+        type_name::<()>()
+    }
+    fn output_type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+    fn type_name(&self) -> &'static str {
+        type_name::<Self>()
+    }
+    fn inner_try_compute(&self, _inputs: &[&dyn Any], _output: &mut dyn Any) -> Result<(), String> {
+        Err(format!(
+            "graph input '{}' is only supplied by compute_named",
+            self.name
+        ))
+    }
+    fn inner_try_compute_mut(
+        &mut self,
+        inputs: &[&dyn Any],
+        output: &mut dyn Any,
+    ) -> Result<(), String> {
+        self.inner_try_compute(inputs, output)
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn same_constant(&self, _other: &dyn InnerCompute) -> bool {
+        false
+    }
+    fn parameters(&self) -> Option<&dyn Parameters> {
+        None
+    }
+    fn parameters_mut(&mut self) -> Option<&mut dyn Parameters> {
+        None
+    }
+    fn fingerprint(&self, state: &mut dyn Hasher) {
+        state.write(self.name.as_bytes());
+    }
+    fn formula(&self, _inputs: &[Expr]) -> Option<Expr> {
+        Some(Expr::Symbol(self.name.clone()))
+    }
+    fn set_detail(&mut self, _detail: Detail) {}
+    fn port_types(&self) -> Vec<(TypeId, &'static str)> {
+        Vec::new()
+    }
+    fn input_name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+    fn arity(&self) -> (usize, Option<usize>) {
+        (0, Some(0))
+    }
+    fn cacheable(&self) -> bool {
+        false
+    }
+    fn hash_inputs(&self, _inputs: &[&dyn Any], _state: &mut dyn Hasher) -> bool {
+        false
+    }
+}
+
+impl Graph {
+    /// Adds a named input of type `T`. Nodes read it by taking the returned node as input,
+    /// which checks its type like any other edge, and its value is passed to
+    /// `ComputeGraph::compute_named`. Graphs using only named inputs are built with `In = ()`.
+    pub fn add_graph_input<T: Value>(&mut self, name: impl Into<String>) -> TypedNodeHandle<(), T> {
+        let name = name.into();
+        let input = NamedInput::<T> {
+            name: name.clone(),
+            _type: PhantomData,
+        };
+        TypedNodeHandle::new(self.insert_inner(name, Box::new(input)))
+    }
+}
+
+impl<Out: Value> ComputeGraph<(), Out> {
+    /// Names and type names of the inputs `compute_named` needs.
+    pub fn graph_inputs(&self) -> Vec<(&str, &'static str)> {
+        self.nodes
+            .iter()
+            .filter_map(|node| Some((node.func.input_name()?, node.func.output_type_name())))
+            .collect()
+    }
+
+    /// Evaluates the graph with the named inputs taken from `inputs`. Fails if one is
+    /// missing or has another type than its `Graph::add_graph_input`. This is the only way
+    /// to supply named inputs: `compute`, `compute_parallel`, `compute_pipelined`,
+    /// `compute_sandboxed`, `compute_within` and `Simulator` fail with `NodeFailed` on them.
+    pub fn compute_named(&self, inputs: &Inputs) -> Result<Out, ComputeGraphErrors> {
+        self.with_context(|context| {
+            for (index, node) in self.nodes.iter().enumerate() {
+                let Some(name) = node.func.input_name() else {
+                    self.compute_node(index, &(), context)?;
+                    continue;
+                };
+                let value = inputs.values.get(name).ok_or_else(|| {
+                    ComputeGraphErrors::InvalidParameter(
+                        format!("graph input '{}' is missing", name).into(),
+                    )
+                    .with_node(node.handle)
+                })?;
+                if value.as_ref().type_id() != node.func.output_type() {
+                    return Err(ComputeGraphErrors::WrongTypes(
+                        format!(
+                            "graph input '{}' must be '{}'",
+                            name,
+                            node.func.output_type_name()
+                        )
+                        .into(),
+                    )
+                    .with_node(node.handle)
+                    .with_type(node.func.output_type(), node.func.output_type_name()));
+                }
+                node.func
+                    .copy_output(value.as_ref(), context.outputs[index].as_mut());
+            }
+            Ok(context.output::<Out>().clone())
+        })
+    }
+}
+
+#[cfg(test)]
+mod inputs_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_named_inputs() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let price_handle = graph.add_graph_input::<f64>("price");
+        let count_handle = graph.add_graph_input::<i64>("count");
+        let tax_handle = graph.add_graph_input::<f64>("tax");
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &price_handle)?;
+        graph.add_input(&mul_handle, &tax_handle)?;
+        assert!(matches!(
+            graph.add_input(&mul_handle, &count_handle),
+            Err(ComputeGraphErrors::WrongTypes(_))
+        ));
+        graph.set_output_node(&mul_handle)?;
+        let compute_graph = graph.build::<(), f64>()?;
+        assert_eq!(
+            compute_graph.graph_inputs(),
+            [("price", "f64"), ("tax", "f64")]
+        );
+
+        let inputs = Inputs::new().with("price", 10.0).with("tax", 1.25);
+        assert_eq!(compute_graph.compute_named(&inputs)?, 12.5);
+        assert!(matches!(
+            compute_graph.compute_named(&Inputs::new().with("price", 10.0)),
+            Err(ComputeGraphErrors::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            compute_graph.compute_named(&Inputs::new().with("price", 10.0).with("tax", 1)),
+            Err(ComputeGraphErrors::WrongTypes(_))
+        ));
+        assert!(matches!(
+            compute_graph.try_compute(&()),
+            Err(ComputeGraphErrors::NodeFailed(message)) if message.contains("compute_named")
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use crate::graph::{ComputeGraphErrors, Graph, NodeHandle};
+
+impl Graph {
+    /// Verifies the internal consistency of the graph: edges and the output node refer
+    /// to live nodes and every node type has a name. A failure is a bug in the graph
+    /// editing code, not in the graph being edited.
+    pub fn check_invariants(&self) -> Result<(), ComputeGraphErrors> {
+        let broken = |message: String, key| {
+            ComputeGraphErrors::BrokenInvariant(message.into()).with_node(NodeHandle {
+                key,
+                graph_id: self.id,
+            })
+        };
+        for (key, node) in self.nodes.iter() {
+            if let Some(input) = node
+                .inputs
+                .iter()
+                .chain(node.after.iter())
+                .find(|input| !self.nodes.contains_key(**input))
+            {
+                return Err(broken(
+                    format!("'{}' has removed node {:?} as input", node.name, input),
+                    key,
+                ));
+            }
+            if !node.ports.is_empty() && node.ports.len() != node.inputs.len() {
+                return Err(broken(
+                    format!("'{}' has ports for only some inputs", node.name),
+                    key,
+                ));
+            }
+            for (type_id, type_name) in [
+                (node.inner.input_type(), node.inner.input_type_name()),
+                (node.inner.output_type(), node.inner.output_type_name()),
+            ] {
+                if !self.type_names.contains_key(&type_id) {
+                    return Err(broken(
+                        format!("type '{}' of '{}' has no name", type_name, node.name),
+                        key,
+                    )
+                    .with_type(type_id, type_name));
+                }
+            }
+        }
+        if let Some(output) = self
+            .output_node
+            .filter(|key| !self.nodes.contains_key(*key))
+        {
+            return Err(broken(
+                format!("output node {:?} was removed", output),
+                output,
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod invariants_tests {
+    use crate::prelude::*;
+    use std::any::TypeId;
+
+    #[test]
+    fn test_check_invariants() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let rate_handle = graph.insert_node("rate", Constant(2.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &rate_handle)?;
+        graph.set_output_node(&mul_handle)?;
+        graph.check_invariants()?;
+
+        graph.remove_node(&mul_handle)?;
+        graph.check_invariants()?;
+        assert!(matches!(
+            graph.build::<f64, f64>(),
+            Err(ComputeGraphErrors::NoOutputNode)
+        ));
+
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&add_handle, &rate_handle)?;
+        graph.nodes.remove(rate_handle.key);
+        let error = graph.check_invariants().err().unwrap();
+        assert_eq!(error.code(), ErrorCode::BrokenInvariant);
+        assert_eq!(error.nodes(), [*add_handle]);
+
+        graph.node_mut(add_handle.key).unwrap().inputs.clear();
+        graph.type_names.remove(&TypeId::of::<f64>());
+        assert!(matches!(
+            graph.check_invariants(),
+            Err(ComputeGraphErrors::BrokenInvariant(message)) if message.contains("has no name")
+        ));
+        Ok(())
+    }
+}
@@ -1,10 +1,159 @@
+pub mod analysis;
+mod batch;
+mod buffered;
+mod canonical;
+mod capabilities;
+mod checkpoint;
 mod com_graph;
 mod compute;
+mod connect;
+mod control;
+#[cfg(feature = "chrono")]
+mod datetime;
+mod delta;
+mod document;
+mod dot;
+mod easing;
+mod envelope;
+mod errors;
+mod events;
+mod finance;
+pub mod fit;
+mod formula;
+mod fusion;
+#[cfg(feature = "geo")]
+mod geo;
 mod graph;
+#[cfg(feature = "rayon")]
+mod grid;
+mod inputs;
+mod invariants;
+mod limits;
+mod lint;
+mod locale;
+mod merge;
+mod mermaid;
+mod multi;
+mod nn;
+#[cfg(feature = "onnx")]
+mod onnx;
 mod operations;
+mod oscillator;
+#[cfg(feature = "serde")]
+mod pack;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod params;
+#[cfg(feature = "parser")]
+mod parser;
+mod pipeline;
+mod plan_cache;
+mod plugin;
+mod policy;
+mod ports;
+mod preview;
+mod probe;
+mod profile;
+mod provenance;
+mod registry;
+mod report;
+mod resample;
+mod rules;
+mod sandbox;
+mod scheduling;
+#[cfg(feature = "rhai")]
+mod script;
+#[cfg(feature = "serde")]
+mod serialize;
+mod session;
+#[cfg(feature = "signing")]
+mod signing;
+mod simulation;
+mod snapshot;
+mod speculate;
+mod subgraph;
+mod trace;
+mod typed;
+mod view;
+
+/// Used by `register_operation!`.
+#[cfg(feature = "inventory")]
+#[doc(hidden)]
+pub use inventory;
+
+/// Key and signature types used by `Graph::sign` and `Graph::verify`.
+#[cfg(feature = "signing")]
+pub use ed25519_dalek;
 
 pub mod prelude {
-    pub use crate::compute::Compute;
-    pub use crate::graph::{Graph, NodeHandle};
+    pub use crate::buffered::{DoubleBuffered, Frame, FrameReader};
+    pub use crate::capabilities::{Capability, CapabilityPolicy};
+    pub use crate::checkpoint::{CheckpointStore, MemoryCheckpoints};
+    pub use crate::com_graph::EvalContext;
+    pub use crate::compute::{Closure, Compute, Value};
+    pub use crate::connect::ConnectCheck;
+    pub use crate::control::{Hysteresis, SlewLimit, Spring};
+    #[cfg(feature = "chrono")]
+    pub use crate::datetime::*;
+    pub use crate::delta::{Delta, Diff, FilterDelta, Integrate, MapDelta};
+    pub use crate::document::GraphDocument;
+    pub use crate::easing::{Curve, Ease, EaseMode};
+    pub use crate::envelope::Adsr;
+    pub use crate::errors::{ErrorCode, ErrorDetails};
+    pub use crate::events::{Debounce, Event, Latch, OnChange};
+    pub use crate::finance::*;
+    pub use crate::formula::Expr;
+    #[cfg(feature = "geo")]
+    pub use crate::geo::*;
+    pub use crate::graph::{
+        ComputeGraphErrors, ConstantMerge, DedupReport, Graph, NodeHandle, NodeMeta, NodeStyle,
+    };
+    #[cfg(feature = "rayon")]
+    pub use crate::grid::{CancelToken, Grid, GridRender, Tile, TileProgress};
+    pub use crate::inputs::Inputs;
+    pub use crate::limits::GraphLimits;
+    pub use crate::lint::{LintFinding, Linter};
+    pub use crate::locale::{Localizer, Message};
+    pub use crate::merge::{MergeChoice, MergeConflict, MergeReport, MergeStrategy};
+    pub use crate::multi::{MultiComputeGraph, MultiOutput};
+    pub use crate::nn::*;
+    #[cfg(feature = "onnx")]
+    pub use crate::onnx::{Activation, Affine, BinaryOp, SoftmaxAt};
     pub use crate::operations::*;
+    pub use crate::oscillator::{Oscillator, Waveform};
+    #[cfg(feature = "serde")]
+    pub use crate::pack::{GraphPack, NamespaceRequirement, PackMetadata, PACK_VERSION};
+    pub use crate::params::{
+        assign_param, param_info, ParamError, ParamInfo, ParamKind, ParamType, ParamValue,
+        Parameters,
+    };
+    pub use crate::plan_cache::PlanCache;
+    pub use crate::plugin::{PluginNode, PLUGIN_ABI_VERSION, PLUGIN_OK, PLUGIN_PANICKED};
+    pub use crate::policy::NodePolicy;
+    pub use crate::ports::{PortCompute, Ports};
+    pub use crate::preview::Detail;
+    pub use crate::profile::{NodeProfile, Profiler};
+    pub use crate::provenance::{ProvenanceEntry, UNKNOWN_AUTHOR};
+    #[cfg(feature = "inventory")]
+    pub use crate::registry::OperationRegistration;
+    pub use crate::registry::{NodeRegistry, Registrable, RegistryEntry};
+    pub use crate::report::{ComputeReport, NodeReport, NodeStatus};
+    pub use crate::resample::{Clock, Sample, SampleHold, SampleLerp};
+    pub use crate::rules::{Condition, Rule, RuleGraph, RuleSet};
+    pub use crate::sandbox::{Sandbox, TrackingAllocator};
+    pub use crate::scheduling::Scheduling;
+    #[cfg(feature = "rhai")]
+    pub use crate::script::ScriptNode;
+    #[cfg(feature = "serde")]
+    pub use crate::serialize::{
+        GraphEnvelope, LoadIssue, Migration, MissingOps, RenameOp, SerializedGraph, SerializedNode,
+        UnavailableOp, FORMAT_VERSION,
+    };
+    pub use crate::session::EditSession;
+    pub use crate::simulation::Simulator;
+    pub use crate::speculate::Speculative;
+    pub use crate::subgraph::Subgraph;
+    pub use crate::trace::Trace;
+    pub use crate::typed::TypedNodeHandle;
+    pub use crate::view::GraphView;
 }
@@ -0,0 +1,295 @@
+use crate::compute::Compute;
+use crate::graph::{ComputeGraphErrors, Graph};
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+
+/// Guardrails for graphs from untrusted sources, enforced by `Graph::build` once set with
+/// `Graph::set_limits`, and by `Graph::deserialize_with_limits` before any node is created.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GraphLimits {
+    max_nodes: Option<usize>,
+    max_edges: Option<usize>,
+    max_depth: Option<usize>,
+    banned: Vec<(TypeId, &'static str)>,
+}
+
+impl GraphLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    pub fn max_edges(mut self, max_edges: usize) -> Self {
+        self.max_edges = Some(max_edges);
+        self
+    }
+
+    /// Longest chain of nodes from a source to a sink, counting both ends.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Rejects graphs containing nodes of type `Obj`.
+    pub fn ban<Obj: Compute + 'static>(mut self) -> Self {
+        self.banned.push((TypeId::of::<Obj>(), type_name::<Obj>()));
+        self
+    }
+
+    /// Checks the node and edge counts alone, which is cheap enough to run before anything
+    /// else is done with a graph.
+    pub(crate) fn check_size(&self, nodes: usize, edges: usize) -> Result<(), ComputeGraphErrors> {
+        if let Some(limit) = self.max_nodes.filter(|limit| nodes > *limit) {
+            return Err(exceeded(format!("{} nodes", nodes), limit));
+        }
+        if let Some(limit) = self.max_edges.filter(|limit| edges > *limit) {
+            return Err(exceeded(format!("{} edges", edges), limit));
+        }
+        Ok(())
+    }
+
+    /// Checks a graph given as the name, operation type and the indices of the inputs and
+    /// ordering edges of every node. Depth is found without recursion so hostile graphs
+    /// can't overflow the stack, and nodes on a cycle are left for the cycle check of `build`.
+    pub(crate) fn check(
+        &self,
+        nodes: &[(&str, Option<TypeId>, Vec<usize>)],
+    ) -> Result<(), ComputeGraphErrors> {
+        let edges = nodes
+            .iter()
+            .map(|(_, _, inputs)| inputs.len())
+            .sum::<usize>();
+        self.check_size(nodes.len(), edges)?;
+        for (name, op, _) in nodes.iter() {
+            if let Some((id, banned)) = self.banned.iter().find(|(id, _)| Some(*id) == *op) {
+                return Err(ComputeGraphErrors::LimitExceeded(
+                    format!("'{}' uses banned op '{}'", name, banned).into(),
+                )
+                .with_type(*id, banned));
+            }
+        }
+
+        let Some(limit) = self.max_depth else {
+            return Ok(());
+        };
+        let mut consumers = vec![Vec::new(); nodes.len()];
+        let mut pending = Vec::with_capacity(nodes.len());
+        for (index, (_, _, inputs)) in nodes.iter().enumerate() {
+            for input in inputs.iter().filter(|input| **input < nodes.len()) {
+                consumers[*input].push(index);
+            }
+            pending.push(inputs.len());
+        }
+        let mut depth = vec![1; nodes.len()];
+        let mut ready = (0..nodes.len())
+            .filter(|index| pending[*index] == 0)
+            .collect::<Vec<_>>();
+        while let Some(index) = ready.pop() {
+            if depth[index] > limit {
+                return Err(exceeded(
+                    format!("a chain of {} nodes", depth[index]),
+                    limit,
+                ));
+            }
+            for consumer in consumers[index].iter() {
+                depth[*consumer] = depth[*consumer].max(depth[index] + 1);
+                pending[*consumer] -= 1;
+                if pending[*consumer] == 0 {
+                    ready.push(*consumer);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn exceeded(what: String, limit: usize) -> ComputeGraphErrors {
+    ComputeGraphErrors::LimitExceeded(
+        format!("graph has {}, more than the limit of {}", what, limit).into(),
+    )
+}
+
+impl Graph {
+    /// Limits checked on every `build` of this graph.
+    pub fn set_limits(&mut self, limits: GraphLimits) {
+        self.limits = Some(limits);
+        self.touch();
+    }
+
+    pub fn limits(&self) -> Option<&GraphLimits> {
+        self.limits.as_ref()
+    }
+
+    pub(crate) fn check_limits(&self) -> Result<(), ComputeGraphErrors> {
+        let Some(limits) = self.limits.as_ref() else {
+            return Ok(());
+        };
+        let positions = self
+            .nodes
+            .keys()
+            .enumerate()
+            .map(|(position, key)| (key, position))
+            .collect::<HashMap<_, _>>();
+        let nodes = self
+            .nodes
+            .values()
+            .map(|node| {
+                let inputs = node
+                    .inputs
+                    .iter()
+                    .chain(node.after.iter())
+                    .map(|input| positions[input])
+                    .collect();
+                (
+                    node.name.as_str(),
+                    Some(Any::type_id(node.inner.as_any())),
+                    inputs,
+                )
+            })
+            .collect::<Vec<_>>();
+        limits.check(&nodes)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod deserialize_limits {
+    use super::GraphLimits;
+    use crate::graph::{ComputeGraphErrors, Graph};
+    use crate::registry::NodeRegistry;
+    use crate::serialize::GraphEnvelope;
+    use std::collections::HashMap;
+
+    impl Graph {
+        /// Like `Graph::deserialize`, but rejects envelopes breaking `limits` before creating
+        /// any node, and sets the limits on the loaded graph. Node and edge counts are also
+        /// checked before running migrations.
+        pub fn deserialize_with_limits(
+            mut envelope: GraphEnvelope,
+            registry: &NodeRegistry,
+            limits: &GraphLimits,
+        ) -> Result<Graph, ComputeGraphErrors> {
+            let edges = envelope
+                .graph
+                .nodes
+                .iter()
+                .map(|node| node.inputs.len() + node.after.len())
+                .sum::<usize>();
+            limits.check_size(envelope.graph.nodes.len(), edges)?;
+            envelope.migrate(registry)?;
+            let positions = envelope
+                .graph
+                .nodes
+                .iter()
+                .enumerate()
+                .map(|(position, node)| (node.id, position))
+                .collect::<HashMap<_, _>>();
+            let nodes = envelope
+                .graph
+                .nodes
+                .iter()
+                .map(|node| {
+                    let op = registry.get(&node.op).map(|entry| entry.type_id());
+                    // Missing inputs are reported by `deserialize`
+                    let inputs = node
+                        .inputs
+                        .iter()
+                        .chain(node.after.iter())
+                        .map(|input| positions.get(input).copied().unwrap_or(usize::MAX))
+                        .collect();
+                    (node.name.as_str(), op, inputs)
+                })
+                .collect::<Vec<_>>();
+            limits.check(&nodes)?;
+            let mut graph = Graph::deserialize(envelope, registry)?;
+            graph.set_limits(limits.clone());
+            Ok(graph)
+        }
+    }
+}
+
+#[cfg(test)]
+mod limits_tests {
+    use crate::prelude::*;
+
+    fn chain(length: usize) -> Graph {
+        let mut graph = Graph::new();
+        let mut previous = graph.insert_node("start", AddInputs::<f64>::new());
+        for i in 1..length {
+            let handle = graph.insert_node(format!("add_{}", i), AddInputs::<f64>::new());
+            graph.add_input(&handle, &previous).unwrap();
+            previous = handle;
+        }
+        graph.set_output_node(&previous).unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_build_limits() -> Result<(), ComputeGraphErrors> {
+        let mut graph = chain(10);
+        graph.set_limits(GraphLimits::new().max_nodes(10).max_depth(10));
+        assert!(graph.build::<f64, f64>().is_ok());
+
+        graph.set_limits(GraphLimits::new().max_depth(9));
+        let Err(ComputeGraphErrors::LimitExceeded(message)) = graph.build::<f64, f64>() else {
+            panic!("depth limit not enforced");
+        };
+        assert_eq!(
+            message,
+            "graph has a chain of 10 nodes, more than the limit of 9"
+        );
+
+        graph.set_limits(GraphLimits::new().max_edges(8));
+        assert!(graph.build::<f64, f64>().is_err());
+
+        // Ordering edges count as edges and lengthen chains
+        let mut graph = chain(3);
+        let log_handle = graph.insert_node("log", Constant(()));
+        let first = graph.get_all_node_metas()[0].this_node;
+        graph.add_order(&log_handle, &first)?;
+        graph.set_limits(GraphLimits::new().max_edges(2));
+        assert!(graph.build::<f64, f64>().is_err());
+        graph.set_limits(GraphLimits::new().max_depth(3));
+        assert!(graph.build::<f64, f64>().is_err());
+        graph.set_limits(GraphLimits::new().max_edges(3).max_depth(4));
+        assert!(graph.build::<f64, f64>().is_ok());
+        graph.set_limits(GraphLimits::new().ban::<AddInputs<f64>>());
+        assert!(matches!(
+            graph.build::<f64, f64>(),
+            Err(ComputeGraphErrors::LimitExceeded(message)) if message.contains("banned op")
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_limits() -> Result<(), ComputeGraphErrors> {
+        let mut registry = NodeRegistry::new();
+        registry.register::<AddInputs<f64>, _, _>("add")?;
+        let envelope = chain(50).serialize(&registry)?;
+
+        let limits = GraphLimits::new().max_nodes(20);
+        assert!(matches!(
+            Graph::deserialize_with_limits(envelope.clone(), &registry, &limits),
+            Err(ComputeGraphErrors::LimitExceeded(_))
+        ));
+        // Oversized envelopes are rejected before migrations run
+        registry
+            .set_version(1)
+            .add_migration((0, |_: &mut SerializedGraph| Err("migrated".to_string())));
+        assert!(matches!(
+            Graph::deserialize_with_limits(envelope.clone(), &registry, &limits),
+            Err(ComputeGraphErrors::LimitExceeded(_))
+        ));
+        registry.set_version(0);
+
+        let limits = GraphLimits::new().max_nodes(50).max_depth(50);
+        let mut graph = Graph::deserialize_with_limits(envelope, &registry, &limits)?;
+        assert_eq!(graph.limits(), Some(&limits));
+        assert_eq!(graph.build::<f64, f64>()?.compute(&2.0), 2.0);
+        Ok(())
+    }
+}
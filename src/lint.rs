@@ -0,0 +1,162 @@
+use crate::graph::{Graph, NodeHandle, NodeMeta};
+use crate::registry::NodeRegistry;
+use crate::view::GraphView;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt;
+
+type NodeRule = Box<dyn Fn(&NodeMeta, &GraphView) -> Option<String> + Send + Sync>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LintFinding {
+    pub rule: String,
+    pub node: NodeHandle,
+    pub name: String,
+    pub message: String,
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] '{}' {}", self.rule, self.name, self.message)
+    }
+}
+
+/// A set of named rules checked against every node of a graph.
+/// A rule returns a message when the node breaks it.
+#[derive(Default)]
+pub struct Linter {
+    rules: Vec<(String, NodeRule)>,
+}
+
+impl Linter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Linter with the built-in `unused-constant` and `max-fan-in` (32) rules.
+    pub fn with_defaults() -> Self {
+        let mut linter = Self::new();
+        linter.unused_constants().max_fan_in(32);
+        linter
+    }
+
+    pub fn add_rule<F>(&mut self, name: impl Into<String>, rule: F) -> &mut Self
+    where
+        F: Fn(&NodeMeta, &GraphView) -> Option<String> + Send + Sync + 'static,
+    {
+        self.rules.push((name.into(), Box::new(rule)));
+        self
+    }
+
+    /// Flags source nodes whose output is neither consumed nor the graph output.
+    pub fn unused_constants(&mut self) -> &mut Self {
+        self.add_rule("unused-constant", |node, view| {
+            let unused = node.input_type == TypeId::of::<()>()
+                && view.output_node() != Some(node.this_node)
+                && view.consumers(&node.this_node).is_empty();
+            unused.then(|| "feeds nothing".to_string())
+        })
+    }
+
+    pub fn max_fan_in(&mut self, limit: usize) -> &mut Self {
+        self.add_rule("max-fan-in", move |node, _| {
+            (node.inputs.len() > limit)
+                .then(|| format!("has {} inputs, more than {}", node.inputs.len(), limit))
+        })
+    }
+
+    /// Flags nodes whose op is marked deprecated in `registry`.
+    pub fn deprecated_ops(&mut self, registry: &NodeRegistry) -> &mut Self {
+        let deprecated = registry
+            .entries()
+            .filter_map(|entry| {
+                let hint = entry.deprecation()?;
+                Some((
+                    entry.type_id(),
+                    format!("uses deprecated op '{}': {}", entry.name(), hint),
+                ))
+            })
+            .collect::<HashMap<_, _>>();
+        self.add_rule("deprecated-op", move |node, _| {
+            deprecated.get(&node.op_type).cloned()
+        })
+    }
+
+    pub fn rule_names(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn lint(&self, graph: &Graph) -> Vec<LintFinding> {
+        let view = graph.view();
+        let mut findings = Vec::new();
+        for node in view.nodes() {
+            for (rule, check) in self.rules.iter() {
+                if let Some(message) = check(node, &view) {
+                    findings.push(LintFinding {
+                        rule: rule.clone(),
+                        node: node.this_node,
+                        name: node.name.clone(),
+                        message,
+                    });
+                }
+            }
+        }
+        findings
+    }
+}
+
+impl Graph {
+    pub fn lint(&self, linter: &Linter) -> Vec<LintFinding> {
+        linter.lint(self)
+    }
+}
+
+#[cfg(test)]
+mod lint_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_lint() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("the_answer", Constant(42.0));
+        let unused_handle = graph.insert_node("unused", Constant(1.0));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        for _ in 0..3 {
+            graph.add_input(&add_handle, &const_handle)?;
+        }
+        graph.set_output_node(&add_handle)?;
+
+        let mut linter = Linter::new();
+        linter.unused_constants().max_fan_in(2);
+        linter.add_rule("snake-case", |node, _| {
+            node.name
+                .contains(char::is_uppercase)
+                .then(|| "is not snake_case".to_string())
+        });
+        let findings = graph.lint(&linter);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(
+            findings.iter().filter(|f| f.node == unused_handle).count(),
+            1
+        );
+        let fan_in = findings.iter().find(|f| f.node == add_handle).unwrap();
+        assert_eq!(
+            fan_in.to_string(),
+            "[max-fan-in] 'add' has 3 inputs, more than 2"
+        );
+
+        assert_eq!(graph.lint(&Linter::with_defaults()).len(), 1);
+
+        let mut registry = NodeRegistry::new();
+        registry
+            .register::<AddInputs<f64>, _, _>("add")?
+            .deprecate("use 'sum' instead");
+        let findings = graph.lint(Linter::new().deprecated_ops(&registry));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(
+            findings[0].message,
+            "uses deprecated op 'add': use 'sum' instead"
+        );
+        Ok(())
+    }
+}
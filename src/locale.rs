@@ -0,0 +1,112 @@
+use crate::graph::ComputeGraphErrors;
+use crate::registry::RegistryEntry;
+use std::sync::Arc;
+
+/// User-facing text handed to the callback of a `Localizer`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Message<'a> {
+    /// Stable identifier, like `error.graph-cycle` for errors or `op.mul` for op names.
+    pub key: &'a str,
+    /// Specifics filled in by the library, like node names.
+    pub detail: Option<&'a str>,
+    /// English text, shown when the callback has no translation.
+    pub english: &'a str,
+}
+
+type TranslateFn = dyn Fn(&Message) -> Option<String> + Send + Sync;
+
+/// Turns errors and op names into text for end users through a translation callback.
+/// The default localizer returns the English text.
+#[derive(Clone, Default)]
+pub struct Localizer {
+    translate: Option<Arc<TranslateFn>>,
+}
+
+impl Localizer {
+    /// `translate` returns `None` for messages it has no translation for.
+    pub fn new<F>(translate: F) -> Self
+    where
+        F: Fn(&Message) -> Option<String> + Send + Sync + 'static,
+    {
+        Self {
+            translate: Some(Arc::new(translate)),
+        }
+    }
+
+    pub fn translate(&self, message: &Message) -> String {
+        self.translate
+            .as_ref()
+            .and_then(|translate| translate(message))
+            .unwrap_or_else(|| message.english.to_string())
+    }
+
+    pub fn error(&self, error: &ComputeGraphErrors) -> String {
+        let detail = error.detail();
+        self.translate(&Message {
+            key: error.key(),
+            detail: detail.as_deref(),
+            english: &error.to_string(),
+        })
+    }
+
+    /// Name of the op shown to users, see `RegistryEntry::display_as`.
+    pub fn op_name(&self, entry: &RegistryEntry) -> String {
+        self.translate(&Message {
+            key: &format!("op.{}", entry.name()),
+            detail: None,
+            english: entry.display_name(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod locale_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_localized_messages() -> Result<(), ComputeGraphErrors> {
+        let german = Localizer::new(|message| match message.key {
+            "error.graph-cycle" => Some(format!("Zyklus im Graphen: {}", message.detail?)),
+            "error.no-output-node" => Some("Kein Ausgabeknoten".to_string()),
+            "op.mul" => Some("Multiplizieren".to_string()),
+            _ => None,
+        });
+
+        let mut graph = Graph::new();
+        let a_handle = graph.insert_node("a", AddInputs::<f64>::new());
+        let b_handle = graph.insert_node("b", AddInputs::<f64>::new());
+        assert_eq!(
+            german.error(&graph.build::<f64, f64>().err().unwrap()),
+            "Kein Ausgabeknoten"
+        );
+        graph.add_input(&a_handle, &b_handle)?;
+        graph.add_input(&b_handle, &a_handle)?;
+        graph.set_output_node(&a_handle)?;
+        let error = graph.build::<f64, f64>().err().unwrap();
+        assert!(german.error(&error).starts_with("Zyklus im Graphen: "));
+        assert_eq!(
+            Localizer::default().error(&error),
+            format!("graph has a cycle: {}", error.detail().unwrap())
+        );
+        assert_eq!(
+            german.error(&ComputeGraphErrors::NodeMissing),
+            "node is missing"
+        );
+
+        let mut registry = NodeRegistry::new();
+        registry
+            .register::<MulInputs<f64>, _, _>("mul")?
+            .display_as("Multiply");
+        registry.register::<AddInputs<f64>, _, _>("add")?;
+        assert_eq!(
+            german.op_name(registry.get("mul").unwrap()),
+            "Multiplizieren"
+        );
+        assert_eq!(
+            Localizer::default().op_name(registry.get("mul").unwrap()),
+            "Multiply"
+        );
+        assert_eq!(german.op_name(registry.get("add").unwrap()), "add");
+        Ok(())
+    }
+}
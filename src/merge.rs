@@ -0,0 +1,242 @@
+use crate::graph::{ComputeGraphErrors, Graph, Node, NodeHandle, NodeMeta};
+use crate::snapshot::GraphState;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// A node changed differently in the graph and in the branch being merged into it.
+/// A missing side means that side removed the node.
+#[derive(Clone, Debug)]
+pub struct MergeConflict {
+    pub node: NodeHandle,
+    pub ours: Option<NodeMeta>,
+    /// As in the branch, so its inputs may not resolve in the merged graph.
+    pub theirs: Option<NodeMeta>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeChoice {
+    Ours,
+    Theirs,
+}
+
+/// How `Graph::merge` resolves nodes both sides changed.
+pub enum MergeStrategy {
+    Ours,
+    Theirs,
+    Manual(Box<dyn FnMut(&MergeConflict) -> MergeChoice>),
+}
+
+impl MergeStrategy {
+    pub fn manual(resolve: impl FnMut(&MergeConflict) -> MergeChoice + 'static) -> Self {
+        Self::Manual(Box::new(resolve))
+    }
+
+    fn resolve(&mut self, conflict: &MergeConflict) -> MergeChoice {
+        match self {
+            Self::Ours => MergeChoice::Ours,
+            Self::Theirs => MergeChoice::Theirs,
+            Self::Manual(resolve) => resolve(conflict),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MergeReport {
+    /// Nodes whose state, or removal, was taken from the branch.
+    pub applied: Vec<NodeHandle>,
+    /// Nodes changed on both sides, resolved by the `MergeStrategy`.
+    pub conflicts: Vec<NodeHandle>,
+    /// Nodes added in the branch, by their handle in the branch and in the merged graph.
+    pub added: Vec<(NodeHandle, NodeHandle)>,
+}
+
+fn changed(before: Option<&Arc<Node>>, after: Option<&Arc<Node>>) -> bool {
+    match (before, after) {
+        (Some(before), Some(after)) => !Arc::ptr_eq(before, after),
+        (before, after) => before.is_some() != after.is_some(),
+    }
+}
+
+impl Graph {
+    /// Fork of the graph for speculative edits, like previews in an editor. Nodes are shared
+    /// with `self` until either graph edits them, so branching is cheap even for large graphs.
+    /// Handles of `self` stay valid in the branch, and `merge` brings its edits back.
+    pub fn branch(&self) -> Graph {
+        let mut branch = self.clone();
+        branch.base = Some(Arc::new(GraphState::capture(self)));
+        branch
+    }
+
+    /// Applies the edits made in `branch` since it was created with `branch()`. Edits of
+    /// nodes only one side changed are kept, and `strategy` picks a side for nodes both
+    /// changed. Nodes added in the branch get new handles here, listed in the report.
+    /// Conflicting output nodes are resolved as `Theirs` only by `MergeStrategy::Theirs`.
+    pub fn merge(
+        &mut self,
+        branch: &Graph,
+        mut strategy: MergeStrategy,
+    ) -> Result<MergeReport, ComputeGraphErrors> {
+        let base = match &branch.base {
+            Some(base) if branch.id == self.id => base.clone(),
+            _ => {
+                return Err(ComputeGraphErrors::InvalidParameter(
+                    "graph was not branched from this graph".into(),
+                ))
+            }
+        };
+        let graph_id = self.id;
+        let handle = |key| NodeHandle { key, graph_id };
+        let mut report = MergeReport::default();
+
+        let mut taken = Vec::new();
+        for (key, base_node) in base.nodes.iter() {
+            let ours = self.nodes.get(key);
+            let theirs = branch.nodes.get(key);
+            if !changed(Some(base_node), theirs) {
+                continue;
+            }
+            if changed(Some(base_node), ours) {
+                if ours.is_none() && theirs.is_none() {
+                    continue;
+                }
+                report.conflicts.push(handle(key));
+                let conflict = MergeConflict {
+                    node: handle(key),
+                    ours: ours.map(|node| self.build_node_meta(key, node)),
+                    theirs: theirs.map(|node| branch.build_node_meta(key, node)),
+                };
+                if strategy.resolve(&conflict) == MergeChoice::Ours {
+                    continue;
+                }
+            }
+            report.applied.push(handle(key));
+            taken.push((key, theirs.cloned()));
+        }
+
+        // Our graph may have used the same slots for its own nodes, so take fresh keys
+        let mut keys = HashMap::new();
+        let mut inserted = HashSet::new();
+        for (key, node) in branch.nodes.iter() {
+            if !base.nodes.contains_key(key) {
+                let new_key = self.nodes.insert(node.clone());
+                keys.insert(key, new_key);
+                inserted.insert(new_key);
+                report.added.push((handle(key), handle(new_key)));
+            }
+        }
+        for (key, node) in taken {
+            match (node, self.nodes.get_mut(key)) {
+                (Some(node), Some(ours)) => {
+                    *ours = node;
+                    inserted.insert(key);
+                }
+                (Some(node), None) => {
+                    let new_key = self.nodes.insert(node);
+                    keys.insert(key, new_key);
+                    inserted.insert(new_key);
+                }
+                (None, _) => {
+                    self.nodes.remove(key);
+                }
+            }
+        }
+        let theirs_output = branch
+            .output_node
+            .map(|key| *keys.get(&key).unwrap_or(&key));
+        if branch.output_node != base.output_node
+            && (self.output_node == base.output_node || matches!(strategy, MergeStrategy::Theirs))
+        {
+            self.output_node = theirs_output;
+        }
+
+        // Point nodes from the branch at the new keys and drop edges to removed nodes
+        let live = self.nodes.keys().collect::<Vec<_>>();
+        for key in live {
+            let node = &self.nodes[key];
+            let edges = || node.inputs.iter().chain(node.after.iter());
+            let remap = inserted.contains(&key) && edges().any(|i| keys.contains_key(i));
+            let dangling = edges().any(|i| !self.nodes.contains_key(*i));
+            if !remap && !dangling {
+                continue;
+            }
+            let mut node = self.nodes[key].as_ref().clone();
+            if remap {
+                for input in node.inputs.iter_mut().chain(node.after.iter_mut()) {
+                    *input = *keys.get(input).unwrap_or(input);
+                }
+            }
+            node.retain_inputs(|input| self.nodes.contains_key(input));
+            node.after.retain(|key| self.nodes.contains_key(*key));
+            self.nodes[key] = Arc::new(node);
+        }
+        if self
+            .output_node
+            .is_some_and(|key| !self.nodes.contains_key(key))
+        {
+            self.output_node = None;
+        }
+        for (type_id, type_name) in branch.type_names.iter() {
+            self.type_names.entry(*type_id).or_insert(type_name);
+        }
+
+        self.touch();
+        let (applied, conflicts) = (report.applied.len(), report.conflicts.len());
+        let added = report.added.len();
+        self.record(|_| {
+            format!(
+                "merged a branch: {} changed, {} added, {} conflicts",
+                applied, added, conflicts
+            )
+        });
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod merge_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_merge_branch() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let rate_handle = graph.insert_node("rate", Constant(2.0));
+        let offset_handle = graph.insert_node("offset", Constant(1.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&mul_handle, &rate_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.add_input(&add_handle, &mul_handle)?;
+        graph.add_input(&add_handle, &offset_handle)?;
+        graph.set_output_node(&add_handle)?;
+
+        let mut branch = graph.branch();
+        branch.set_param(&rate_handle, "value", 3.0)?;
+        branch.set_param(&offset_handle, "value", 10.0)?;
+        let bias_handle = branch.insert_node("bias", Constant(100.0));
+        branch.add_input(&add_handle, &bias_handle)?;
+        graph.set_param(&offset_handle, "value", 20.0)?;
+        let unused_handle = graph.insert_node("unused", Constant(0.0));
+
+        let report = graph.merge(
+            &branch,
+            MergeStrategy::manual(|conflict| {
+                assert_eq!(conflict.ours.as_ref().unwrap().name, "offset");
+                MergeChoice::Ours
+            }),
+        )?;
+        assert_eq!(report.conflicts, [*offset_handle]);
+        assert_eq!(report.applied, [*rate_handle, *add_handle]);
+        let merged_bias = report.added[0].1;
+        assert_ne!(merged_bias, *unused_handle);
+        assert_eq!(graph.get_name(&merged_bias)?, "bias");
+        graph.check_invariants()?;
+        // 3 * 2 + 20 + 100
+        assert_eq!(graph.build::<f64, f64>()?.compute(&2.0), 126.0);
+
+        assert!(matches!(
+            graph.merge(&Graph::new(), MergeStrategy::Theirs),
+            Err(ComputeGraphErrors::InvalidParameter(_))
+        ));
+        Ok(())
+    }
+}
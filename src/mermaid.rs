@@ -0,0 +1,123 @@
+use crate::graph::{Graph, GraphKey};
+use std::any::TypeId;
+use std::collections::HashMap;
+
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+impl Graph {
+    /// Mermaid flowchart of the graph, drawn like `Graph::to_dot`, for pasting into markdown
+    /// documents and issues. The output node is drawn as a subroutine box and ordering edges
+    /// as dotted arrows. Groups of `NodeStyle` become subgraphs and colors fill their nodes.
+    pub fn to_mermaid(&self) -> String {
+        let order = self.canonical_order();
+        let ids = order
+            .iter()
+            .enumerate()
+            .map(|(i, key)| (*key, i))
+            .collect::<HashMap<_, _>>();
+        let type_name = |type_id| *self.type_names.get(&type_id).unwrap_or(&"unknown type");
+
+        let node_line = |key: &GraphKey, indent: &str| {
+            let node = &self.nodes[*key];
+            let label = format!(
+                "{}<br/>{} → {}",
+                mermaid_escape(&node.name),
+                mermaid_escape(type_name(node.inner.input_type())),
+                mermaid_escape(type_name(node.inner.output_type()))
+            );
+            if self.output_node == Some(*key) {
+                format!("{}n{}[[\"{}\"]]\n", indent, ids[key], label)
+            } else {
+                format!("{}n{}[\"{}\"]\n", indent, ids[key], label)
+            }
+        };
+
+        let mut out = String::from("flowchart LR\n    input([input])\n");
+        let (ungrouped, groups) = self.split_groups(&order);
+        for key in ungrouped.iter() {
+            out += &node_line(key, "    ");
+        }
+        for (i, (group, members)) in groups.iter().enumerate() {
+            out += &format!("    subgraph g{}[\"{}\"]\n", i, mermaid_escape(group));
+            for key in members.iter() {
+                out += &node_line(key, "        ");
+            }
+            out.push_str("    end\n");
+        }
+        for key in order.iter() {
+            let node = &self.nodes[*key];
+            if node.connected_to_input && node.inner.input_type() != TypeId::of::<()>() {
+                out += &format!("    input --> n{}\n", ids[key]);
+            }
+            for input in node.inputs.iter() {
+                out += &format!("    n{} --> n{}\n", ids[input], ids[key]);
+            }
+            for before in node.after.iter() {
+                out += &format!("    n{} -.-> n{}\n", ids[before], ids[key]);
+            }
+        }
+        for key in order.iter() {
+            if let Some(color) = self.nodes[*key].style.color.as_ref() {
+                out += &format!("    style n{} fill:{}\n", ids[key], color);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod mermaid_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_to_mermaid() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("the \"answer\"", Constant(vec![42.0]));
+        let sum_handle = graph.insert_node("sum", Closure::new(|v: &[&Vec<f64>]| v.len()));
+        let log_handle = graph.insert_node("log", Constant(0.0));
+        graph.add_input(&sum_handle, &const_handle)?;
+        graph.connect_to_input(&sum_handle)?;
+        graph.add_order(&log_handle, &sum_handle)?;
+        graph.set_output_node(&sum_handle)?;
+
+        assert_eq!(
+            graph.to_mermaid(),
+            "flowchart LR\n    input([input])\n    \
+             n0[\"the #quot;answer#quot;<br/>() → alloc::vec::Vec#lt;f64#gt;\"]\n    \
+             n1[[\"sum<br/>alloc::vec::Vec#lt;f64#gt; → usize\"]]\n    \
+             n2[\"log<br/>() → f64\"]\n    \
+             input --> n1\n    \
+             n0 --> n1\n    \
+             n2 -.-> n1\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_mermaid_styles() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("rate", Constant(2.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &const_handle)?;
+        graph.set_style(
+            &const_handle,
+            NodeStyle::group("inputs").with_color("#ff8800"),
+        )?;
+
+        assert_eq!(
+            graph.to_mermaid(),
+            "flowchart LR\n    input([input])\n    \
+             n1[\"mul<br/>f64 → f64\"]\n    \
+             subgraph g0[\"inputs\"]\n        \
+             n0[\"rate<br/>() → f64\"]\n    \
+             end\n    \
+             n0 --> n1\n    \
+             style n0 fill:#ff8800\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+use crate::com_graph::ComputeGraph;
+use crate::graph::{ComputeGraphErrors, Graph, NodeHandle};
+use crate::scheduling::previous_effects;
+use std::any::Any;
+
+/// Compute object for several output nodes of one graph, see `Graph::build_multi`.
+/// Nodes shared by the outputs are evaluated once per call.
+pub struct MultiComputeGraph<In> {
+    graph: ComputeGraph<In, ()>,
+    /// Index of each output node in compute order.
+    outputs: Vec<usize>,
+}
+
+/// Outputs of one `MultiComputeGraph` evaluation.
+pub struct MultiOutput {
+    handles: Vec<NodeHandle>,
+    values: Vec<Box<dyn Any + Send + Sync>>,
+}
+
+impl MultiOutput {
+    /// Output of `node_handle`, if it was requested and has type `T`.
+    pub fn get<T: Any>(&self, node_handle: &NodeHandle) -> Option<&T> {
+        let index = self
+            .handles
+            .iter()
+            .position(|handle| handle == node_handle)?;
+        self.values[index].downcast_ref()
+    }
+
+    pub fn handles(&self) -> &[NodeHandle] {
+        &self.handles
+    }
+}
+
+impl Graph {
+    /// Like `build_for_node`, for several output nodes at once.
+    pub fn build_multi<In>(
+        &mut self,
+        outputs: &[&NodeHandle],
+    ) -> Result<MultiComputeGraph<In>, ComputeGraphErrors>
+    where
+        In: Any + Clone,
+    {
+        if outputs.is_empty() {
+            return Err(ComputeGraphErrors::NoOutputNode);
+        }
+        self.check_limits()?;
+        let mut keys = Vec::with_capacity(outputs.len());
+        for output in outputs {
+            self.check_handle(output)?;
+            keys.push(output.key);
+        }
+        let compute_order = self.compute_order(&keys)?;
+        let outputs = keys
+            .iter()
+            .map(|key| compute_order.iter().position(|k| k == key).unwrap())
+            .collect();
+        let nodes = self.compute_nodes::<In>(compute_order)?;
+        Ok(MultiComputeGraph {
+            graph: ComputeGraph::new(nodes),
+            outputs,
+        })
+    }
+}
+
+impl<In: Any> MultiComputeGraph<In> {
+    /// Handles of the output nodes, in the order given to `build_multi`.
+    pub fn outputs(&self) -> Vec<NodeHandle> {
+        self.outputs
+            .iter()
+            .map(|index| self.graph.nodes[*index].handle)
+            .collect()
+    }
+
+    /// Makes `output` latency-critical: the nodes it depends on are evaluated first and
+    /// everything else after it, so `try_compute_each` delivers it as early as possible.
+    pub fn set_priority(&mut self, output: &NodeHandle) -> Result<(), ComputeGraphErrors> {
+        let Some(position) = self.outputs().iter().position(|handle| handle == output) else {
+            return Err(ComputeGraphErrors::InvalidParameter(
+                "priority node is not an output of the graph".into(),
+            ));
+        };
+        let nodes = &self.graph.nodes;
+        // Nodes only depend on earlier ones, so one backwards pass finds the whole cone.
+        // Effects stay in order by pulling in the ones before them as well.
+        let previous_effects = previous_effects(nodes);
+        let mut in_cone = vec![false; nodes.len()];
+        in_cone[self.outputs[position]] = true;
+        for index in (0..nodes.len()).rev() {
+            if !in_cone[index] {
+                continue;
+            }
+            let node = &nodes[index];
+            for dependency in node
+                .inputs
+                .iter()
+                .chain(node.after.iter())
+                .chain(previous_effects[index].iter())
+            {
+                in_cone[*dependency] = true;
+            }
+        }
+
+        let order = (0..nodes.len())
+            .filter(|index| in_cone[*index])
+            .chain((0..nodes.len()).filter(|index| !in_cone[*index]))
+            .collect::<Vec<_>>();
+        let mut new_index = vec![0; nodes.len()];
+        for (new, old) in order.iter().enumerate() {
+            new_index[*old] = new;
+        }
+        let reordered = order
+            .iter()
+            .map(|old| {
+                let mut node = nodes[*old].clone();
+                for dependency in node.inputs.iter_mut().chain(node.after.iter_mut()) {
+                    *dependency = new_index[*dependency];
+                }
+                node
+            })
+            .collect();
+        self.graph = ComputeGraph::new(reordered);
+        for output in self.outputs.iter_mut() {
+            *output = new_index[*output];
+        }
+        Ok(())
+    }
+
+    pub fn try_compute(&self, input: &In) -> Result<MultiOutput, ComputeGraphErrors> {
+        self.try_compute_each(input, |_, _| {})
+    }
+
+    /// Like `try_compute`, also passing every output to `ready` as soon as it is computed.
+    pub fn try_compute_each(
+        &self,
+        input: &In,
+        mut ready: impl FnMut(&NodeHandle, &dyn Any),
+    ) -> Result<MultiOutput, ComputeGraphErrors> {
+        self.graph.with_context(|context| {
+            for (index, node) in self.graph.nodes.iter().enumerate() {
+                self.graph.compute_node(index, input, context)?;
+                if self.outputs.contains(&index) {
+                    ready(&node.handle, context.outputs[index].as_ref());
+                }
+            }
+            let values = self
+                .outputs
+                .iter()
+                .map(|index| {
+                    self.graph.nodes[*index]
+                        .func
+                        .clone_output(context.outputs[*index].as_ref())
+                })
+                .collect();
+            Ok(MultiOutput {
+                handles: self.outputs(),
+                values,
+            })
+        })
+    }
+
+    /// Evaluates all outputs, panicking if a node fails. See `try_compute`.
+    pub fn compute(&self, input: &In) -> MultiOutput {
+        self.try_compute(input).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod multi_tests {
+    use crate::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Clone)]
+    struct Counted(Arc<AtomicUsize>);
+
+    impl Compute for Counted {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            inputs.iter().copied().sum()
+        }
+    }
+
+    #[test]
+    fn test_build_multi() -> Result<(), ComputeGraphErrors> {
+        //  Input ── shared ── mul ── add
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut graph = Graph::new();
+        let shared_handle = graph.insert_node("shared", Counted(calls.clone()));
+        let rate_handle = graph.insert_node("rate", Constant(3.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&mul_handle, &shared_handle)?;
+        graph.add_input(&mul_handle, &rate_handle)?;
+        graph.add_input(&add_handle, &mul_handle)?;
+        graph.add_input(&add_handle, &shared_handle)?;
+        graph.disconnect_from_input(&mul_handle)?;
+        graph.disconnect_from_input(&add_handle)?;
+
+        let multi = graph.build_multi::<f64>(&[&add_handle, &mul_handle])?;
+        assert_eq!(multi.outputs(), [*add_handle, *mul_handle]);
+        let outputs = multi.compute(&2.0);
+        assert_eq!(outputs.get::<f64>(&mul_handle), Some(&6.0));
+        assert_eq!(outputs.get::<f64>(&add_handle), Some(&8.0));
+        assert_eq!(outputs.get::<bool>(&add_handle), None);
+        assert_eq!(outputs.get::<f64>(&rate_handle), None);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        assert!(matches!(
+            graph.build_multi::<f64>(&[]),
+            Err(ComputeGraphErrors::NoOutputNode)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_priority_output() -> Result<(), ComputeGraphErrors> {
+        //  Input ── report ── summary
+        //  Input ── alarm
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut graph = Graph::new();
+        let report_handle = graph.insert_node("report", Counted(calls.clone()));
+        let summary_handle = graph.insert_node("summary", AddInputs::<f64>::new());
+        let alarm_handle = graph.insert_node("alarm", AddInputs::<f64>::new());
+        graph.add_input(&summary_handle, &report_handle)?;
+
+        let mut multi = graph.build_multi::<f64>(&[&summary_handle, &alarm_handle])?;
+        assert!(multi.set_priority(&report_handle).is_err());
+        multi.set_priority(&alarm_handle)?;
+        let mut ready = Vec::new();
+        let outputs = multi.try_compute_each(&1.0, |handle, value| {
+            let computed = calls.load(Ordering::SeqCst);
+            ready.push((*handle, *value.downcast_ref::<f64>().unwrap(), computed));
+        })?;
+        // The alarm is delivered before the report runs
+        assert_eq!(ready, [(*alarm_handle, 1.0, 0), (*summary_handle, 1.0, 1)]);
+        assert_eq!(outputs.get::<f64>(&summary_handle), Some(&1.0));
+        assert_eq!(multi.outputs(), [*summary_handle, *alarm_handle]);
+        Ok(())
+    }
+}
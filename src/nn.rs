@@ -0,0 +1,157 @@
+use crate::compute::{Compute, Value};
+
+/// Fully connected layer computing `weights × input + bias` over the first input.
+/// Weights are stored row-major, one row of `I` weights per output.
+/// Edge arrays need `Default`, which the standard library provides up to 32 elements.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dense<const I: usize, const O: usize> {
+    weights: Vec<f64>,
+    bias: Vec<f64>,
+}
+
+impl<const I: usize, const O: usize> Dense<I, O> {
+    pub fn new(weights: [[f64; I]; O], bias: [f64; O]) -> Self {
+        Self {
+            weights: weights.iter().flatten().copied().collect(),
+            bias: bias.to_vec(),
+        }
+    }
+
+    /// Builds the layer from flat row-major weights, as exported by most training frameworks.
+    pub fn from_slices(weights: &[f64], bias: &[f64]) -> Result<Self, String> {
+        if weights.len() != I * O || bias.len() != O {
+            return Err(format!(
+                "expected {} weights and {} biases, got {} and {}",
+                I * O,
+                O,
+                weights.len(),
+                bias.len()
+            ));
+        }
+        Ok(Self {
+            weights: weights.to_vec(),
+            bias: bias.to_vec(),
+        })
+    }
+}
+
+impl<const I: usize, const O: usize> Compute for Dense<I, O>
+where
+    [f64; I]: Value,
+    [f64; O]: Value,
+{
+    type In = [f64; I];
+    type Out = [f64; O];
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let input = inputs.first().map(|v| **v).unwrap_or_default();
+        let mut output = [0.0; O];
+        for (o, out) in output.iter_mut().enumerate() {
+            let row = &self.weights[o * I..(o + 1) * I];
+            *out = self.bias[o]
+                + row
+                    .iter()
+                    .zip(input.iter())
+                    .map(|(w, x)| w * x)
+                    .sum::<f64>();
+        }
+        output
+    }
+}
+
+macro_rules! activation {
+    ($(#[$meta:meta])* $name:ident, |$x:ident| $body:expr) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Default)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct $name<const N: usize>;
+
+        impl<const N: usize> Compute for $name<N>
+        where
+            [f64; N]: Value,
+        {
+            type In = [f64; N];
+            type Out = [f64; N];
+            fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+                inputs
+                    .first()
+                    .map(|v| **v)
+                    .unwrap_or_default()
+                    .map(|$x: f64| $body)
+            }
+        }
+    };
+}
+
+activation!(Relu, |x| x.max(0.0));
+activation!(Sigmoid, |x| 1.0 / (1.0 + (-x).exp()));
+activation!(Tanh, |x| x.tanh());
+
+/// Normalizes the first input into probabilities.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Softmax<const N: usize>;
+
+impl<const N: usize> Compute for Softmax<N>
+where
+    [f64; N]: Value,
+{
+    type In = [f64; N];
+    type Out = [f64; N];
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let input = inputs.first().map(|v| **v).unwrap_or_default();
+        // Shifting by the max keeps exp from overflowing
+        let max = input.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let exp = input.map(|x| (x - max).exp());
+        let sum = exp.iter().sum::<f64>();
+        exp.map(|x| x / sum)
+    }
+}
+
+#[cfg(test)]
+mod nn_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_small_network() -> Result<(), ComputeGraphErrors> {
+        //  Input ── dense ── relu ── dense ── softmax
+        let mut graph = Graph::new();
+        let hidden_handle = graph.insert_node(
+            "hidden",
+            Dense::new([[1.0, -1.0], [0.5, 0.5], [-1.0, 0.0]], [0.0, 0.0, 1.0]),
+        );
+        let relu_handle = graph.insert_node("relu", Relu::<3>);
+        let output_handle = graph.insert_node(
+            "output",
+            Dense::<3, 2>::from_slices(&[1.0, 0.0, 0.0, 0.0, 1.0, 1.0], &[0.0, 0.0]).unwrap(),
+        );
+        let softmax_handle = graph.insert_node("softmax", Softmax::<2>);
+        graph.add_input(&relu_handle, &hidden_handle)?;
+        graph.add_input(&output_handle, &relu_handle)?;
+        graph.add_input(&softmax_handle, &output_handle)?;
+        graph.set_output_node(&softmax_handle)?;
+
+        let logits = graph.build_for_node::<[f64; 2], [f64; 2]>(&output_handle)?;
+        assert_eq!(logits.compute(&[2.0, 1.0]), [1.0, 1.5]);
+
+        let network = graph.build::<[f64; 2], [f64; 2]>()?;
+        let [a, b] = network.compute(&[2.0, 1.0]);
+        assert!((a + b - 1.0).abs() < 1e-12);
+        assert!((b - 1.0 / (1.0 + (-0.5f64).exp())).abs() < 1e-12);
+
+        assert!(Dense::<2, 2>::from_slices(&[1.0], &[0.0, 0.0]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_activations() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let sigmoid_handle = graph.insert_node("sigmoid", Sigmoid::<2>);
+        let tanh_handle = graph.insert_node("tanh", Tanh::<2>);
+        let sigmoid = graph.build_for_node::<[f64; 2], [f64; 2]>(&sigmoid_handle)?;
+        assert_eq!(sigmoid.compute(&[0.0, 0.0]), [0.5, 0.5]);
+        let tanh = graph.build_for_node::<[f64; 2], [f64; 2]>(&tanh_handle)?;
+        assert_eq!(tanh.compute(&[0.0, 1.0])[1], 1.0f64.tanh());
+        Ok(())
+    }
+}
@@ -0,0 +1,898 @@
+use crate::compute::{Compute, Value};
+use crate::graph::{ComputeGraphErrors, Graph, NodeHandle};
+use crate::operations::{Collect, Constant, Element};
+use std::collections::HashMap;
+
+/// Scalar `weights · inputs + bias`, the lowering of one output of `Gemm` and `MatMul`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Affine {
+    pub weights: Vec<f64>,
+    pub bias: f64,
+}
+
+impl Compute for Affine {
+    type In = f64;
+    type Out = f64;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.bias
+            + self
+                .weights
+                .iter()
+                .zip(inputs)
+                .map(|(w, x)| w * **x)
+                .sum::<f64>()
+    }
+}
+
+/// Elementwise arithmetic between the first two inputs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BinaryOp {
+    #[default]
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinaryOp {
+    pub fn apply(&self, a: f64, b: f64) -> f64 {
+        match self {
+            BinaryOp::Add => a + b,
+            BinaryOp::Sub => a - b,
+            BinaryOp::Mul => a * b,
+            BinaryOp::Div => a / b,
+        }
+    }
+}
+
+impl Compute for BinaryOp {
+    type In = f64;
+    type Out = f64;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let a = inputs.first().map(|v| **v).unwrap_or_default();
+        let b = inputs.get(1).map(|v| **v).unwrap_or_default();
+        self.apply(a, b)
+    }
+}
+
+/// Scalar activation of the first input.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Activation {
+    #[default]
+    Relu,
+    Sigmoid,
+    Tanh,
+}
+
+impl Activation {
+    pub fn apply(&self, x: f64) -> f64 {
+        match self {
+            Activation::Relu => x.max(0.0),
+            Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            Activation::Tanh => x.tanh(),
+        }
+    }
+}
+
+impl Compute for Activation {
+    type In = f64;
+    type Out = f64;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.apply(inputs.first().map(|v| **v).unwrap_or_default())
+    }
+}
+
+/// Softmax of all inputs, taken at input `index`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SoftmaxAt {
+    pub index: usize,
+}
+
+impl Compute for SoftmaxAt {
+    type In = f64;
+    type Out = f64;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let max = inputs.iter().map(|v| **v).fold(f64::NEG_INFINITY, f64::max);
+        let sum = inputs.iter().map(|v| (**v - max).exp()).sum::<f64>();
+        inputs
+            .get(self.index)
+            .map_or(0.0, |v| (**v - max).exp() / sum)
+    }
+}
+
+enum Field<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<u64, String> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos).ok_or("truncated varint")?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("varint is too long".to_string())
+}
+
+fn take<'a>(bytes: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], String> {
+    let end = pos
+        .checked_add(len)
+        .filter(|end| *end <= bytes.len())
+        .ok_or("truncated field")?;
+    let slice = &bytes[*pos..end];
+    *pos = end;
+    Ok(slice)
+}
+
+/// Splits a protobuf message into its `(field number, value)` pairs.
+fn fields(bytes: &[u8]) -> Result<Vec<(u64, Field<'_>)>, String> {
+    let mut pos = 0;
+    let mut fields = Vec::new();
+    while pos < bytes.len() {
+        let key = read_varint(bytes, &mut pos)?;
+        let field = match key & 7 {
+            0 => Field::Varint(read_varint(bytes, &mut pos)?),
+            1 => Field::Fixed64(u64::from_le_bytes(
+                take(bytes, &mut pos, 8)?.try_into().unwrap(),
+            )),
+            2 => {
+                let len = read_varint(bytes, &mut pos)? as usize;
+                Field::Bytes(take(bytes, &mut pos, len)?)
+            }
+            5 => Field::Fixed32(u32::from_le_bytes(
+                take(bytes, &mut pos, 4)?.try_into().unwrap(),
+            )),
+            wire_type => return Err(format!("unsupported wire type {}", wire_type)),
+        };
+        fields.push((key >> 3, field));
+    }
+    Ok(fields)
+}
+
+fn string(field: &Field) -> Result<String, String> {
+    match field {
+        Field::Bytes(bytes) => String::from_utf8(bytes.to_vec()).map_err(|e| e.to_string()),
+        _ => Err("expected a string field".to_string()),
+    }
+}
+
+/// Reads a repeated integer field, packed or not.
+fn varints(field: &Field) -> Result<Vec<u64>, String> {
+    match field {
+        Field::Varint(value) => Ok(vec![*value]),
+        Field::Bytes(bytes) => {
+            let mut pos = 0;
+            let mut values = Vec::new();
+            while pos < bytes.len() {
+                values.push(read_varint(bytes, &mut pos)?);
+            }
+            Ok(values)
+        }
+        _ => Err("expected an integer field".to_string()),
+    }
+}
+
+fn le_chunks<const W: usize>(bytes: &[u8]) -> impl Iterator<Item = [u8; W]> + '_ {
+    bytes.chunks_exact(W).map(|chunk| chunk.try_into().unwrap())
+}
+
+/// All sub-messages with field `number` in any of `messages`.
+fn submessages<'a>(messages: &[&'a [u8]], number: u64) -> Result<Vec<&'a [u8]>, String> {
+    let mut found = Vec::new();
+    for message in messages {
+        for (field_number, field) in fields(message)? {
+            if let (true, Field::Bytes(bytes)) = (field_number == number, field) {
+                found.push(bytes);
+            }
+        }
+    }
+    Ok(found)
+}
+
+struct OnnxNode {
+    name: String,
+    op_type: String,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    attributes: HashMap<String, f64>,
+}
+
+impl OnnxNode {
+    fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let mut node = OnnxNode {
+            name: String::new(),
+            op_type: String::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            attributes: HashMap::new(),
+        };
+        for (number, field) in fields(bytes)? {
+            match number {
+                1 => node.inputs.push(string(&field)?),
+                2 => node.outputs.push(string(&field)?),
+                3 => node.name = string(&field)?,
+                4 => node.op_type = string(&field)?,
+                5 => {
+                    let Field::Bytes(attribute) = field else {
+                        return Err("expected an attribute".to_string());
+                    };
+                    let mut name = String::new();
+                    let mut value = 0.0;
+                    for (number, field) in fields(attribute)? {
+                        match (number, field) {
+                            (1, field) => name = string(&field)?,
+                            (2, Field::Fixed32(bits)) => value = f32::from_bits(bits) as f64,
+                            (3, Field::Varint(int)) => value = int as i64 as f64,
+                            _ => {}
+                        }
+                    }
+                    node.attributes.insert(name, value);
+                }
+                _ => {}
+            }
+        }
+        if node.name.is_empty() {
+            node.name = node.outputs.first().cloned().unwrap_or_default();
+        }
+        Ok(node)
+    }
+
+    fn attribute(&self, name: &str, default: f64) -> f64 {
+        self.attributes.get(name).copied().unwrap_or(default)
+    }
+}
+
+fn parse_tensor(bytes: &[u8]) -> Result<(String, Vec<usize>, Vec<f64>), String> {
+    let mut name = String::new();
+    let mut dims = Vec::new();
+    let mut data_type = 0;
+    let mut values = Vec::new();
+    let mut raw_data = None;
+    for (number, field) in fields(bytes)? {
+        match (number, field) {
+            (1, field) => dims.extend(varints(&field)?.into_iter().map(|d| d as usize)),
+            (2, Field::Varint(value)) => data_type = value,
+            (4, Field::Fixed32(bits)) => values.push(f32::from_bits(bits) as f64),
+            (4, Field::Bytes(bytes)) => {
+                values.extend(le_chunks(bytes).map(|c| f32::from_le_bytes(c) as f64))
+            }
+            (7, field) => values.extend(varints(&field)?.into_iter().map(|v| v as i64 as f64)),
+            (8, field) => name = string(&field)?,
+            (9, Field::Bytes(bytes)) => raw_data = Some(bytes),
+            (10, Field::Fixed64(bits)) => values.push(f64::from_bits(bits)),
+            (10, Field::Bytes(bytes)) => values.extend(le_chunks(bytes).map(f64::from_le_bytes)),
+            _ => {}
+        }
+    }
+    if let Some(bytes) = raw_data {
+        values = match data_type {
+            1 => le_chunks(bytes)
+                .map(|c| f32::from_le_bytes(c) as f64)
+                .collect(),
+            7 => le_chunks(bytes)
+                .map(|c| i64::from_le_bytes(c) as f64)
+                .collect(),
+            11 => le_chunks(bytes).map(f64::from_le_bytes).collect(),
+            other => {
+                return Err(format!(
+                    "tensor '{}' has unsupported data type {}",
+                    name, other
+                ))
+            }
+        };
+    }
+    let size = dims
+        .iter()
+        .try_fold(1usize, |size, dim| size.checked_mul(*dim))
+        .ok_or_else(|| format!("tensor '{}' has too many elements: {:?}", name, dims))?;
+    if values.len() != size {
+        return Err(format!(
+            "tensor '{}' has {} values for shape {:?}",
+            name,
+            values.len(),
+            dims
+        ));
+    }
+    Ok((name, dims, values))
+}
+
+/// Name and element count of a graph input or output. The count is `None` without shape
+/// information, and symbolic dimensions such as the batch size count as one.
+fn parse_value_info(bytes: &[u8]) -> Result<(String, Option<usize>), String> {
+    let mut name = String::new();
+    for (number, field) in fields(bytes)? {
+        if number == 1 {
+            name = string(&field)?;
+        }
+    }
+    // type -> tensor_type -> shape -> dim
+    let mut dims = vec![bytes];
+    for number in [2, 1, 2, 1] {
+        dims = submessages(&dims, number)?;
+    }
+    let mut size = None;
+    for dim in dims {
+        let mut value = 1;
+        for (number, field) in fields(dim)? {
+            if let (1, Field::Varint(dim_value)) = (number, field) {
+                value = dim_value as usize;
+            }
+        }
+        size = Some(
+            size.unwrap_or(1usize)
+                .checked_mul(value)
+                .ok_or_else(|| format!("'{}' has too many elements", name))?,
+        );
+    }
+    Ok((name, size))
+}
+
+struct OnnxModel {
+    nodes: Vec<OnnxNode>,
+    initializers: Vec<(String, Vec<usize>, Vec<f64>)>,
+    inputs: Vec<(String, Option<usize>)>,
+    outputs: Vec<String>,
+}
+
+impl OnnxModel {
+    fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let graph = submessages(&[bytes], 7)?
+            .pop()
+            .ok_or("model has no graph")?;
+        let mut model = OnnxModel {
+            nodes: Vec::new(),
+            initializers: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        };
+        for (number, field) in fields(graph)? {
+            let Field::Bytes(bytes) = field else {
+                continue;
+            };
+            match number {
+                1 => model.nodes.push(OnnxNode::parse(bytes)?),
+                5 => model.initializers.push(parse_tensor(bytes)?),
+                11 => model.inputs.push(parse_value_info(bytes)?),
+                12 => model.outputs.push(parse_value_info(bytes)?.0),
+                _ => {}
+            }
+        }
+        Ok(model)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Operand {
+    Node(NodeHandle),
+    Const(f64),
+}
+
+#[derive(Clone)]
+struct Tensor {
+    dims: Vec<usize>,
+    values: Vec<Operand>,
+}
+
+/// Lowers ONNX tensors to one scalar node per element, folding constants on the way.
+struct Importer {
+    graph: Graph,
+    tensors: HashMap<String, Tensor>,
+}
+
+fn import_error(message: String) -> ComputeGraphErrors {
+    ComputeGraphErrors::Import(message.into())
+}
+
+impl Importer {
+    fn input(&self, node: &OnnxNode, index: usize) -> Result<&Tensor, ComputeGraphErrors> {
+        let name = node
+            .inputs
+            .get(index)
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| import_error(format!("'{}' is missing input {}", node.name, index)))?;
+        self.tensors
+            .get(name)
+            .ok_or_else(|| import_error(format!("'{}' uses unknown tensor '{}'", node.name, name)))
+    }
+
+    fn constant(
+        &self,
+        node: &OnnxNode,
+        index: usize,
+    ) -> Result<(Vec<usize>, Vec<f64>), ComputeGraphErrors> {
+        let tensor = self.input(node, index)?;
+        let values = tensor
+            .values
+            .iter()
+            .map(|operand| match operand {
+                Operand::Const(value) => Some(*value),
+                Operand::Node(_) => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                import_error(format!("'{}' needs a constant input {}", node.name, index))
+            })?;
+        Ok((tensor.dims.clone(), values))
+    }
+
+    fn node(&mut self, operand: Operand) -> NodeHandle {
+        match operand {
+            Operand::Node(handle) => handle,
+            Operand::Const(value) => self.graph.insert_node("constant", Constant(value)).erase(),
+        }
+    }
+
+    fn insert<Obj>(
+        &mut self,
+        name: String,
+        compute_object: Obj,
+        inputs: &[Operand],
+    ) -> Result<Operand, ComputeGraphErrors>
+    where
+        Obj: Compute<In = f64, Out = f64> + 'static,
+    {
+        let handle = self.graph.insert_node(name, compute_object);
+        for input in inputs {
+            let input_handle = self.node(*input);
+            self.graph.add_input(&handle, &input_handle)?;
+        }
+        self.graph.disconnect_from_input(&handle)?;
+        Ok(Operand::Node(handle.erase()))
+    }
+
+    fn affine(
+        &mut self,
+        name: String,
+        terms: impl Iterator<Item = (f64, Operand)>,
+        mut bias: f64,
+    ) -> Result<Operand, ComputeGraphErrors> {
+        let mut weights = Vec::new();
+        let mut inputs = Vec::new();
+        for (weight, operand) in terms {
+            match operand {
+                Operand::Const(value) => bias += weight * value,
+                Operand::Node(_) => {
+                    weights.push(weight);
+                    inputs.push(operand);
+                }
+            }
+        }
+        if inputs.is_empty() {
+            return Ok(Operand::Const(bias));
+        }
+        self.insert(name, Affine { weights, bias }, &inputs)
+    }
+
+    fn binary(
+        &mut self,
+        name: String,
+        op: BinaryOp,
+        a: Operand,
+        b: Operand,
+    ) -> Result<Operand, ComputeGraphErrors> {
+        let (weight, bias, operand) = match (op, a, b) {
+            (op, Operand::Const(a), Operand::Const(b)) => {
+                return Ok(Operand::Const(op.apply(a, b)))
+            }
+            (BinaryOp::Add, x, Operand::Const(c)) | (BinaryOp::Add, Operand::Const(c), x) => {
+                (1.0, c, x)
+            }
+            (BinaryOp::Mul, x, Operand::Const(c)) | (BinaryOp::Mul, Operand::Const(c), x) => {
+                (c, 0.0, x)
+            }
+            (BinaryOp::Sub, x, Operand::Const(c)) => (1.0, -c, x),
+            (BinaryOp::Sub, Operand::Const(c), x) => (-1.0, c, x),
+            (BinaryOp::Div, x, Operand::Const(c)) if c != 0.0 => (1.0 / c, 0.0, x),
+            (op, a, b) => return self.insert(name, op, &[a, b]),
+        };
+        self.affine(name, std::iter::once((weight, operand)), bias)
+    }
+
+    /// `alpha * a × b + bias`, with `b` a constant `[K, N]` matrix, or `[N, K]` if transposed.
+    fn dense(
+        &mut self,
+        node: &OnnxNode,
+        alpha: f64,
+        transpose: bool,
+        bias: &[f64],
+    ) -> Result<Tensor, ComputeGraphErrors> {
+        let a = self.input(node, 0)?.values.clone();
+        let (dims, b) = self.constant(node, 1)?;
+        let [rows, cols] = dims[..] else {
+            return Err(import_error(format!(
+                "'{}' needs a 2-D weight matrix, got shape {:?}",
+                node.name, dims
+            )));
+        };
+        let (k, n) = if transpose {
+            (cols, rows)
+        } else {
+            (rows, cols)
+        };
+        if a.len() != k || !(bias.len() == n || bias.len() <= 1) {
+            return Err(import_error(format!(
+                "'{}' can't multiply {} inputs by shape {:?} with {} biases",
+                node.name,
+                a.len(),
+                dims,
+                bias.len()
+            )));
+        }
+        let mut values = Vec::with_capacity(n);
+        for j in 0..n {
+            let terms = a.iter().enumerate().map(|(i, operand)| {
+                let weight = if transpose {
+                    b[j * k + i]
+                } else {
+                    b[i * n + j]
+                };
+                (alpha * weight, *operand)
+            });
+            let bias = bias.get(j).or(bias.first()).copied().unwrap_or_default();
+            values.push(self.affine(format!("{}[{}]", node.name, j), terms, bias)?);
+        }
+        Ok(Tensor {
+            dims: vec![n],
+            values,
+        })
+    }
+
+    fn lower(&mut self, node: &OnnxNode) -> Result<(), ComputeGraphErrors> {
+        let name = |index: usize| format!("{}[{}]", node.name, index);
+        let output = match node.op_type.as_str() {
+            "Add" | "Sub" | "Mul" | "Div" => {
+                let op = match node.op_type.as_str() {
+                    "Add" => BinaryOp::Add,
+                    "Sub" => BinaryOp::Sub,
+                    "Mul" => BinaryOp::Mul,
+                    _ => BinaryOp::Div,
+                };
+                let a = self.input(node, 0)?.clone();
+                let b = self.input(node, 1)?.clone();
+                let len = a.values.len().max(b.values.len());
+                if ![1, len].contains(&a.values.len()) || ![1, len].contains(&b.values.len()) {
+                    return Err(import_error(format!(
+                        "'{}' can't broadcast shapes {:?} and {:?}",
+                        node.name, a.dims, b.dims
+                    )));
+                }
+                let mut values = Vec::with_capacity(len);
+                for i in 0..len {
+                    let x = a.values[i % a.values.len()];
+                    let y = b.values[i % b.values.len()];
+                    values.push(self.binary(name(i), op, x, y)?);
+                }
+                let dims = if a.values.len() == len {
+                    a.dims
+                } else {
+                    b.dims
+                };
+                Tensor { dims, values }
+            }
+            "Relu" | "Sigmoid" | "Tanh" => {
+                let activation = match node.op_type.as_str() {
+                    "Relu" => Activation::Relu,
+                    "Sigmoid" => Activation::Sigmoid,
+                    _ => Activation::Tanh,
+                };
+                let input = self.input(node, 0)?.clone();
+                let mut values = Vec::with_capacity(input.values.len());
+                for (i, operand) in input.values.into_iter().enumerate() {
+                    values.push(match operand {
+                        Operand::Const(x) => Operand::Const(activation.apply(x)),
+                        operand => self.insert(name(i), activation, &[operand])?,
+                    });
+                }
+                Tensor {
+                    dims: input.dims,
+                    values,
+                }
+            }
+            "Softmax" => {
+                let input = self.input(node, 0)?.clone();
+                let mut values = Vec::with_capacity(input.values.len());
+                for index in 0..input.values.len() {
+                    values.push(self.insert(name(index), SoftmaxAt { index }, &input.values)?);
+                }
+                Tensor {
+                    dims: input.dims,
+                    values,
+                }
+            }
+            "Gemm" => {
+                if node.attribute("transA", 0.0) != 0.0 {
+                    return Err(import_error(format!(
+                        "'{}' uses transA, which is not supported",
+                        node.name
+                    )));
+                }
+                let beta = node.attribute("beta", 1.0);
+                let bias = match node.inputs.get(2).filter(|name| !name.is_empty()) {
+                    Some(_) => self.constant(node, 2)?.1,
+                    None => Vec::new(),
+                };
+                let bias = bias.iter().map(|c| beta * c).collect::<Vec<_>>();
+                let alpha = node.attribute("alpha", 1.0);
+                let transpose = node.attribute("transB", 0.0) != 0.0;
+                self.dense(node, alpha, transpose, &bias)?
+            }
+            "MatMul" => self.dense(node, 1.0, false, &[])?,
+            "Identity" | "Flatten" | "Reshape" | "Dropout" => {
+                let values = self.input(node, 0)?.values.clone();
+                Tensor {
+                    dims: vec![values.len()],
+                    values,
+                }
+            }
+            op => {
+                return Err(import_error(format!(
+                    "'{}' uses unsupported op '{}'",
+                    node.name, op
+                )))
+            }
+        };
+        let output_name = node
+            .outputs
+            .first()
+            .ok_or_else(|| import_error(format!("'{}' has no output", node.name)))?;
+        self.tensors.insert(output_name.clone(), output);
+        Ok(())
+    }
+}
+
+impl Graph {
+    /// Imports an ONNX model built from elementwise `Add`/`Sub`/`Mul`/`Div`, `Gemm`, `MatMul`,
+    /// `Relu`/`Sigmoid`/`Tanh`, `Softmax` and shape-only ops, for a batch size of one.
+    /// Tensors are lowered to scalar nodes; the graph takes `[f64; IN]` and outputs `[f64; OUT]`.
+    pub fn from_onnx<const IN: usize, const OUT: usize>(
+        bytes: &[u8],
+    ) -> Result<Graph, ComputeGraphErrors>
+    where
+        [f64; IN]: Value,
+        [f64; OUT]: Value,
+    {
+        let model = OnnxModel::parse(bytes).map_err(import_error)?;
+        let mut importer = Importer {
+            graph: Graph::new(),
+            tensors: HashMap::new(),
+        };
+        for (name, dims, values) in model.initializers {
+            let values = values.into_iter().map(Operand::Const).collect();
+            importer.tensors.insert(name, Tensor { dims, values });
+        }
+
+        let inputs = model
+            .inputs
+            .iter()
+            .filter(|(name, _)| !importer.tensors.contains_key(name))
+            .collect::<Vec<_>>();
+        let [(input_name, input_size)] = inputs[..] else {
+            return Err(import_error(format!(
+                "expected one graph input, found {}",
+                inputs.len()
+            )));
+        };
+        if input_size.is_some_and(|size| size != IN) {
+            return Err(import_error(format!(
+                "input '{}' has {} elements, expected {}",
+                input_name,
+                input_size.unwrap_or_default(),
+                IN
+            )));
+        }
+        let values = (0..IN)
+            .map(|i| {
+                let name = format!("{}[{}]", input_name, i);
+                Operand::Node(
+                    importer
+                        .graph
+                        .insert_node(name, Element::<f64, IN>::new(i))
+                        .erase(),
+                )
+            })
+            .collect();
+        importer.tensors.insert(
+            input_name.clone(),
+            Tensor {
+                dims: vec![IN],
+                values,
+            },
+        );
+
+        // ONNX requires nodes to be stored in topological order
+        for node in model.nodes.iter() {
+            importer.lower(node)?;
+        }
+
+        let [output_name] = &model.outputs[..] else {
+            return Err(import_error(format!(
+                "expected one graph output, found {}",
+                model.outputs.len()
+            )));
+        };
+        let output = importer
+            .tensors
+            .get(output_name)
+            .ok_or_else(|| import_error(format!("output '{}' is never computed", output_name)))?
+            .values
+            .clone();
+        if output.len() != OUT {
+            return Err(import_error(format!(
+                "output '{}' has {} elements, expected {}",
+                output_name,
+                output.len(),
+                OUT
+            )));
+        }
+        let collect_handle = importer
+            .graph
+            .insert_node(output_name.clone(), Collect::<f64, OUT>::new());
+        for operand in output {
+            let handle = importer.node(operand);
+            importer.graph.add_input(&collect_handle, &handle)?;
+        }
+        importer.graph.disconnect_from_input(&collect_handle)?;
+        importer.graph.set_output_node(&collect_handle)?;
+        Ok(importer.graph)
+    }
+}
+
+#[cfg(test)]
+mod onnx_tests {
+    use crate::prelude::*;
+
+    fn varint(out: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            out.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn bytes_field(out: &mut Vec<u8>, number: u64, bytes: &[u8]) {
+        varint(out, number << 3 | 2);
+        varint(out, bytes.len() as u64);
+        out.extend_from_slice(bytes);
+    }
+
+    fn tensor(name: &str, dims: &[u64], values: &[f32]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for dim in dims {
+            varint(&mut out, 1 << 3);
+            varint(&mut out, *dim);
+        }
+        varint(&mut out, 2 << 3);
+        varint(&mut out, 1);
+        let data = values
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+        bytes_field(&mut out, 4, &data);
+        bytes_field(&mut out, 8, name.as_bytes());
+        out
+    }
+
+    fn node(op: &str, inputs: &[&str], output: &str, int_attributes: &[(&str, u64)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for input in inputs {
+            bytes_field(&mut out, 1, input.as_bytes());
+        }
+        bytes_field(&mut out, 2, output.as_bytes());
+        bytes_field(&mut out, 4, op.as_bytes());
+        for (name, value) in int_attributes {
+            let mut attribute = Vec::new();
+            bytes_field(&mut attribute, 1, name.as_bytes());
+            varint(&mut attribute, 3 << 3);
+            varint(&mut attribute, *value);
+            bytes_field(&mut out, 5, &attribute);
+        }
+        out
+    }
+
+    fn value_info(name: &str, dims: &[u64]) -> Vec<u8> {
+        let mut shape = Vec::new();
+        for dim in dims {
+            let mut dimension = Vec::new();
+            varint(&mut dimension, 1 << 3);
+            varint(&mut dimension, *dim);
+            bytes_field(&mut shape, 1, &dimension);
+        }
+        let mut tensor_type = Vec::new();
+        bytes_field(&mut tensor_type, 2, &shape);
+        let mut type_proto = Vec::new();
+        bytes_field(&mut type_proto, 1, &tensor_type);
+        let mut out = Vec::new();
+        bytes_field(&mut out, 1, name.as_bytes());
+        bytes_field(&mut out, 2, &type_proto);
+        out
+    }
+
+    fn model(nodes: &[Vec<u8>]) -> Vec<u8> {
+        let initializers = [
+            tensor("w1", &[3, 2], &[1.0, -1.0, 0.5, 0.5, -1.0, 0.0]),
+            tensor("b1", &[3], &[0.0, 0.0, 1.0]),
+            tensor("w2", &[3, 2], &[1.0, 0.0, 0.0, 1.0, 0.0, 1.0]),
+            tensor("shift", &[1], &[0.5]),
+        ];
+        model_with(nodes, &initializers, &[1, 2])
+    }
+
+    fn model_with(nodes: &[Vec<u8>], initializers: &[Vec<u8>], input_dims: &[u64]) -> Vec<u8> {
+        let mut graph = Vec::new();
+        for node in nodes {
+            bytes_field(&mut graph, 1, node);
+        }
+        for initializer in initializers.iter() {
+            bytes_field(&mut graph, 5, initializer);
+        }
+        bytes_field(&mut graph, 11, &value_info("x", input_dims));
+        bytes_field(&mut graph, 12, &value_info("y", &[1, 2]));
+        let mut out = Vec::new();
+        bytes_field(&mut out, 7, &graph);
+        out
+    }
+
+    #[test]
+    fn test_import_mlp() -> Result<(), ComputeGraphErrors> {
+        //  x ── gemm ── relu ── matmul ── sub ── softmax
+        let bytes = model(&[
+            node("Gemm", &["x", "w1", "b1"], "h", &[("transB", 1)]),
+            node("Relu", &["h"], "r", &[]),
+            node("MatMul", &["r", "w2"], "m", &[]),
+            node("Sub", &["m", "shift"], "s", &[]),
+            node("Softmax", &["s"], "y", &[]),
+        ]);
+        let mut graph = Graph::from_onnx::<2, 2>(&bytes)?;
+        let network = graph.build::<[f64; 2], [f64; 2]>()?;
+
+        let expected = |x: [f64; 2]| {
+            let h = [x[0] - x[1], 0.5 * (x[0] + x[1]), 1.0 - x[0]].map(|v| v.max(0.0));
+            let s = [h[0] - 0.5, h[1] + h[2] - 0.5];
+            let sum = s[0].exp() + s[1].exp();
+            s.map(|v| v.exp() / sum)
+        };
+        for x in [[2.0, 1.0], [-1.0, 3.0], [0.0, 0.0]] {
+            let [a, b] = network.compute(&x);
+            let [ea, eb] = expected(x);
+            assert!((a - ea).abs() < 1e-12 && (b - eb).abs() < 1e-12);
+        }
+
+        assert!(matches!(
+            Graph::from_onnx::<3, 2>(&bytes),
+            Err(ComputeGraphErrors::Import(_))
+        ));
+        let unsupported = model(&[node("Conv", &["x", "w1"], "y", &[])]);
+        assert!(matches!(
+            Graph::from_onnx::<2, 2>(&unsupported),
+            Err(ComputeGraphErrors::Import(msg)) if msg.contains("'Conv'")
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_huge_dims() -> Result<(), ComputeGraphErrors> {
+        let relu = [node("Relu", &["x"], "y", &[])];
+        let huge = 1 << 40;
+        let tensor = model_with(&relu, &[tensor("w", &[huge, huge], &[1.0])], &[1, 2]);
+        assert!(matches!(
+            Graph::from_onnx::<2, 2>(&tensor),
+            Err(ComputeGraphErrors::Import(msg)) if msg.contains("too many elements")
+        ));
+        let input = model_with(&relu, &[], &[huge, huge]);
+        assert!(matches!(
+            Graph::from_onnx::<2, 2>(&input),
+            Err(ComputeGraphErrors::Import(msg)) if msg.contains("too many elements")
+        ));
+        Ok(())
+    }
+}
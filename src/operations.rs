@@ -1,25 +1,111 @@
-use crate::compute::Compute;
+use crate::compute::{same_primitive, Compute, Value};
+use crate::formula::Expr;
+#[cfg(feature = "regex")]
+use crate::graph::ComputeGraphErrors;
+use crate::params::{
+    dyn_assign_param, dyn_param_info, ParamError, ParamInfo, ParamValue, Parameters,
+};
 use std::{
     any::Any,
+    cmp::Ordering,
+    hash::Hasher,
     marker::PhantomData,
     ops::{Add, Mul, Sub},
 };
 
 #[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Constant<T>(pub T);
 impl<T> Compute for Constant<T>
 where
-    T: Any + Copy + Default,
+    T: Value,
 {
     type In = ();
     type Out = T;
     fn compute(&self, _: &[&Self::In]) -> Self::Out {
-        self.0
+        self.0.clone()
+    }
+    fn same_constant(&self, other: &dyn Any) -> bool {
+        other
+            .downcast_ref::<Self>()
+            .is_some_and(|other| same_primitive(&self.0, &other.0))
+    }
+    fn parameters(&self) -> Option<&dyn Parameters> {
+        Some(self)
+    }
+    fn parameters_mut(&mut self) -> Option<&mut dyn Parameters> {
+        Some(self)
+    }
+    fn formula(&self, _inputs: &[Expr]) -> Option<Expr> {
+        Expr::number_of(&self.0)
+    }
+}
+
+impl<T: Any> Parameters for Constant<T> {
+    fn params(&self) -> Vec<ParamInfo> {
+        dyn_param_info("value", &self.0).into_iter().collect()
+    }
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        if name != "value" {
+            return Err(ParamError::UnknownParam(name.to_string()));
+        }
+        dyn_assign_param(&mut self.0, name, value)
+    }
+}
+
+/// Named source that, unlike `Constant`, can be changed on a built graph with
+/// `ComputeGraph::set_parameter`.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Parameter<T> {
+    pub name: String,
+    pub value: T,
+}
+impl<T> Parameter<T> {
+    pub fn new(name: impl Into<String>, value: T) -> Self {
+        Self {
+            name: name.into(),
+            value,
+        }
+    }
+}
+
+impl<T: Value> Compute for Parameter<T> {
+    type In = ();
+    type Out = T;
+    fn compute(&self, _: &[&Self::In]) -> Self::Out {
+        self.value.clone()
+    }
+    fn parameters(&self) -> Option<&dyn Parameters> {
+        Some(self)
+    }
+    fn parameters_mut(&mut self) -> Option<&mut dyn Parameters> {
+        Some(self)
+    }
+    fn fingerprint(&self, state: &mut dyn Hasher) {
+        state.write(self.name.as_bytes());
+    }
+    fn formula(&self, _inputs: &[Expr]) -> Option<Expr> {
+        Some(Expr::Symbol(self.name.clone()))
+    }
+}
+
+impl<T: Any> Parameters for Parameter<T> {
+    fn params(&self) -> Vec<ParamInfo> {
+        dyn_param_info("value", &self.value).into_iter().collect()
+    }
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        if name != "value" {
+            return Err(ParamError::UnknownParam(name.to_string()));
+        }
+        dyn_assign_param(&mut self.value, name, value)
     }
 }
 
 #[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AddInputs<In> {
+    #[cfg_attr(feature = "serde", serde(skip))]
     _intype: PhantomData<In>,
 }
 impl<T> AddInputs<T> {
@@ -32,17 +118,28 @@ impl<T> AddInputs<T> {
 
 impl<T> Compute for AddInputs<T>
 where
-    T: Add<Output = T> + Any + Copy + Default,
+    T: Add<Output = T> + Value,
 {
     type In = T;
     type Out = T;
     fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
-        inputs.iter().fold(Self::In::default(), |acc, &v| *v + acc)
+        inputs
+            .iter()
+            .fold(Self::In::default(), |acc, v| (*v).clone() + acc)
+    }
+    fn formula(&self, inputs: &[Expr]) -> Option<Expr> {
+        Some(match inputs {
+            [] => Expr::Number(0.0),
+            [input] => input.clone(),
+            inputs => Expr::Add(inputs.to_vec()),
+        })
     }
 }
 
 #[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SubInputs<In> {
+    #[cfg_attr(feature = "serde", serde(skip))]
     _intype: PhantomData<In>,
 }
 impl<T> SubInputs<T> {
@@ -55,17 +152,30 @@ impl<T> SubInputs<T> {
 
 impl<T> Compute for SubInputs<T>
 where
-    T: Sub<Output = T> + Any + Copy + Default,
+    T: Sub<Output = T> + Value,
 {
     type In = T;
     type Out = T;
     fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
-        inputs.iter().fold(Self::In::default(), |acc, &v| *v - acc)
+        inputs
+            .iter()
+            .fold(Self::In::default(), |acc, v| (*v).clone() - acc)
+    }
+    fn min_inputs(&self) -> usize {
+        2
+    }
+    fn formula(&self, inputs: &[Expr]) -> Option<Expr> {
+        // Each input subtracts the running result, matching `compute`
+        let mut inputs = inputs.iter().cloned();
+        let first = inputs.next().unwrap_or(Expr::Number(0.0));
+        Some(inputs.fold(first, |acc, input| input - acc))
     }
 }
 
 #[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MulInputs<T> {
+    #[cfg_attr(feature = "serde", serde(skip))]
     _intype: PhantomData<T>,
 }
 impl<T> MulInputs<T> {
@@ -78,15 +188,905 @@ impl<T> MulInputs<T> {
 
 impl<T> Compute for MulInputs<T>
 where
-    T: Mul<Output = T> + Any + Copy + Default,
+    T: Mul<Output = T> + Value,
 {
     type In = T;
     type Out = T;
     fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
         if inputs.len() == 1 {
-            *inputs[0]
+            inputs[0].clone()
+        } else {
+            inputs
+                .iter()
+                .skip(1)
+                .fold(inputs[0].clone(), |prod, v| (*v).clone() * prod)
+        }
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+    fn formula(&self, inputs: &[Expr]) -> Option<Expr> {
+        match inputs {
+            [] => None,
+            [input] => Some(input.clone()),
+            inputs => Some(Expr::Mul(inputs.to_vec())),
+        }
+    }
+}
+
+/// `scale * input + offset` in one node. `ComputeGraph::fuse` produces these from chains
+/// of constant multiplications and additions.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MulAdd {
+    pub scale: f64,
+    pub offset: f64,
+}
+impl MulAdd {
+    pub fn new(scale: f64, offset: f64) -> Self {
+        Self { scale, offset }
+    }
+}
+crate::impl_parameters!(MulAdd {
+    scale: f64,
+    offset: f64
+});
+
+impl Compute for MulAdd {
+    type In = f64;
+    type Out = f64;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let input = inputs.first().map_or(0.0, |v| **v);
+        self.scale.mul_add(input, self.offset)
+    }
+    fn parameters(&self) -> Option<&dyn Parameters> {
+        Some(self)
+    }
+    fn parameters_mut(&mut self) -> Option<&mut dyn Parameters> {
+        Some(self)
+    }
+    fn formula(&self, inputs: &[Expr]) -> Option<Expr> {
+        let input = inputs.first()?.clone();
+        Some(Expr::Number(self.scale) * input + Expr::Number(self.offset))
+    }
+}
+
+/// Raises the first input to `exponent`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pow {
+    pub exponent: f64,
+}
+impl Pow {
+    pub fn new(exponent: f64) -> Self {
+        Self { exponent }
+    }
+}
+crate::impl_parameters!(Pow { exponent: f64 });
+
+impl Compute for Pow {
+    type In = f64;
+    type Out = f64;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs.first().map_or(0.0, |v| **v).powf(self.exponent)
+    }
+    fn parameters(&self) -> Option<&dyn Parameters> {
+        Some(self)
+    }
+    fn parameters_mut(&mut self) -> Option<&mut dyn Parameters> {
+        Some(self)
+    }
+    fn formula(&self, inputs: &[Expr]) -> Option<Expr> {
+        Some(inputs.first()?.clone().pow(Expr::Number(self.exponent)))
+    }
+}
+
+/// Maps an enum (or any comparable value) to an output value, such as a
+/// per-mode gain. Inputs without a matching case produce `default`.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MapEnum<E, T> {
+    cases: Vec<(E, T)>,
+    default: T,
+}
+impl<E, T> MapEnum<E, T> {
+    pub fn new(default: T) -> Self {
+        Self {
+            cases: Vec::new(),
+            default,
+        }
+    }
+
+    pub fn case(mut self, variant: E, value: T) -> Self {
+        self.cases.push((variant, value));
+        self
+    }
+}
+
+impl<E, T> Compute for MapEnum<E, T>
+where
+    E: Value + PartialEq,
+    T: Value,
+{
+    type In = E;
+    type Out = T;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let Some(variant) = inputs.first() else {
+            return self.default.clone();
+        };
+        self.cases
+            .iter()
+            .find(|(case, _)| case == *variant)
+            .map_or_else(|| self.default.clone(), |(_, value)| value.clone())
+    }
+}
+
+/// Replaces a missing first input with the wrapped value.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnwrapOr<T>(pub T);
+impl<T> Compute for UnwrapOr<T>
+where
+    T: Value,
+{
+    type In = Option<T>;
+    type Out = T;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs
+            .first()
+            .and_then(|v| (*v).clone())
+            .unwrap_or_else(|| self.0.clone())
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IsSome<T> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _intype: PhantomData<T>,
+}
+impl<T> IsSome<T> {
+    pub fn new() -> Self {
+        Self {
+            _intype: PhantomData,
+        }
+    }
+}
+
+impl<T> Compute for IsSome<T>
+where
+    T: Value,
+{
+    type In = Option<T>;
+    type Out = bool;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs.first().is_some_and(|v| v.is_some())
+    }
+}
+
+/// Passes the first input through only if it lies within `min..=max`.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Filter<T> {
+    pub min: T,
+    pub max: T,
+}
+impl<T> Filter<T> {
+    pub fn new(min: T, max: T) -> Self {
+        Self { min, max }
+    }
+}
+
+impl<T> Compute for Filter<T>
+where
+    T: Value + PartialOrd,
+{
+    type In = T;
+    type Out = Option<T>;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs
+            .first()
+            .map(|v| (*v).clone())
+            .filter(|v| self.min <= *v && *v <= self.max)
+    }
+}
+
+/// Packs the first inputs into a tuple, using defaults for missing ones.
+/// Inputs share one type; tuples of mixed types come from custom nodes and
+/// can be taken apart with the `Unpack` nodes.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pack2<T> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _intype: PhantomData<T>,
+}
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pack3<T> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _intype: PhantomData<T>,
+}
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pack4<T> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _intype: PhantomData<T>,
+}
+
+/// Outputs element `I` of a tuple input.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Unpack2<A, B, const I: usize> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _intype: PhantomData<(A, B)>,
+}
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Unpack3<A, B, C, const I: usize> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _intype: PhantomData<(A, B, C)>,
+}
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Unpack4<A, B, C, D, const I: usize> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _intype: PhantomData<(A, B, C, D)>,
+}
+
+macro_rules! impl_pack {
+    ($name:ident, ($($index:tt),+)) => {
+        impl<T> $name<T> {
+            pub fn new() -> Self {
+                Self {
+                    _intype: PhantomData,
+                }
+            }
+        }
+
+        impl<T: Value> Compute for $name<T> {
+            type In = T;
+            type Out = ($(impl_pack!(@ty $index T),)+);
+            fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+                ($(inputs.get($index).map_or(T::default(), |v| (*v).clone()),)+)
+            }
+        }
+    };
+    (@ty $index:tt $t:ident) => { $t };
+}
+
+macro_rules! impl_unpack {
+    ($name:ident, $types:tt, $($index:tt => $out:ident),+) => {
+        impl_unpack!(@new $name $types);
+        $(impl_unpack!(@compute $name $types $index $out);)+
+    };
+    (@new $name:ident [$($t:ident),+]) => {
+        impl<$($t),+, const I: usize> $name<$($t),+, I> {
+            pub fn new() -> Self {
+                Self {
+                    _intype: PhantomData,
+                }
+            }
+        }
+    };
+    (@compute $name:ident [$($t:ident),+] $index:tt $out:ident) => {
+        impl<$($t: Value),+> Compute for $name<$($t),+, $index> {
+            type In = ($($t,)+);
+            type Out = $out;
+            fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+                inputs.first().map_or($out::default(), |v| v.$index.clone())
+            }
+        }
+    };
+}
+
+impl_pack!(Pack2, (0, 1));
+impl_pack!(Pack3, (0, 1, 2));
+impl_pack!(Pack4, (0, 1, 2, 3));
+impl_unpack!(Unpack2, [A, B], 0 => A, 1 => B);
+impl_unpack!(Unpack3, [A, B, C], 0 => A, 1 => B, 2 => C);
+impl_unpack!(Unpack4, [A, B, C, D], 0 => A, 1 => B, 2 => C, 3 => D);
+
+/// Emits the `K` largest of its inputs in descending order, padding with
+/// defaults when there are fewer than `K` inputs.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TopK<T, const K: usize> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _intype: PhantomData<T>,
+}
+impl<T, const K: usize> TopK<T, K> {
+    pub fn new() -> Self {
+        Self {
+            _intype: PhantomData,
+        }
+    }
+}
+
+impl<T, const K: usize> Compute for TopK<T, K>
+where
+    T: Value + PartialOrd,
+    [T; K]: Value,
+{
+    type In = T;
+    type Out = [T; K];
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        top_k(inputs.iter().map(|v| (*v).clone()))
+    }
+}
+
+/// `TopK` over the elements of an array input.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TopKOf<T, const N: usize, const K: usize> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _intype: PhantomData<T>,
+}
+impl<T, const N: usize, const K: usize> TopKOf<T, N, K> {
+    pub fn new() -> Self {
+        Self {
+            _intype: PhantomData,
+        }
+    }
+}
+
+impl<T, const N: usize, const K: usize> Compute for TopKOf<T, N, K>
+where
+    T: Value + PartialOrd,
+    [T; N]: Value,
+    [T; K]: Value,
+{
+    type In = [T; N];
+    type Out = [T; K];
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        top_k(inputs.iter().flat_map(|v| v.iter().cloned()))
+    }
+}
+
+/// Keeps the best `K` values seen so far sorted in `top[..len]`, so the smallest
+/// kept value is always last and is the only one compared against.
+fn top_k<T, const K: usize>(values: impl Iterator<Item = T>) -> [T; K]
+where
+    T: Value + PartialOrd,
+    [T; K]: Value,
+{
+    let mut top = <[T; K]>::default();
+    let mut len = 0;
+    for value in values {
+        if len == K {
+            if K == 0 || value.partial_cmp(&top[K - 1]) != Some(Ordering::Greater) {
+                continue;
+            }
+            len -= 1;
+        }
+        let mut i = len;
+        while i > 0 && value > top[i - 1] {
+            top.swap(i, i - 1);
+            i -= 1;
+        }
+        top[i] = value;
+        len += 1;
+    }
+    top
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    #[default]
+    Eq,
+    Ne,
+}
+
+impl CompareOp {
+    pub fn apply<T: PartialOrd>(&self, lhs: &T, rhs: &T) -> bool {
+        match self {
+            CompareOp::Lt => lhs < rhs,
+            CompareOp::Le => lhs <= rhs,
+            CompareOp::Gt => lhs > rhs,
+            CompareOp::Ge => lhs >= rhs,
+            CompareOp::Eq => lhs == rhs,
+            CompareOp::Ne => lhs != rhs,
+        }
+    }
+}
+
+/// Compares the first input against `value`, e.g. `input > value` for `CompareOp::Gt`.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Compare<T> {
+    pub op: CompareOp,
+    pub value: T,
+}
+impl<T> Compare<T> {
+    pub fn new(op: CompareOp, value: T) -> Self {
+        Self { op, value }
+    }
+}
+
+impl<T> Compute for Compare<T>
+where
+    T: Value + PartialOrd,
+{
+    type In = T;
+    type Out = bool;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs
+            .first()
+            .is_some_and(|v| self.op.apply(*v, &self.value))
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct And;
+impl Compute for And {
+    type In = bool;
+    type Out = bool;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs.iter().all(|v| **v)
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Or;
+impl Compute for Or {
+    type In = bool;
+    type Out = bool;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs.iter().any(|v| **v)
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Not;
+impl Compute for Not {
+    type In = bool;
+    type Out = bool;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        !inputs.first().is_some_and(|v| **v)
+    }
+}
+
+/// Outputs `if_true` or `if_false` depending on the first input.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Select<T> {
+    pub if_true: T,
+    pub if_false: T,
+}
+impl<T> Select<T> {
+    pub fn new(if_true: T, if_false: T) -> Self {
+        Self { if_true, if_false }
+    }
+}
+
+impl<T: Value> Compute for Select<T> {
+    type In = bool;
+    type Out = T;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        if inputs.first().is_some_and(|v| **v) {
+            self.if_true.clone()
         } else {
-            inputs.iter().skip(1).fold(*inputs[0], |prod, &v| *v * prod)
+            self.if_false.clone()
+        }
+    }
+}
+
+/// Outputs element `index` of an array input.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Element<T, const N: usize> {
+    pub index: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _intype: PhantomData<T>,
+}
+impl<T, const N: usize> Element<T, N> {
+    pub fn new(index: usize) -> Self {
+        Self {
+            index,
+            _intype: PhantomData,
+        }
+    }
+}
+
+impl<T, const N: usize> Compute for Element<T, N>
+where
+    T: Value,
+    [T; N]: Value,
+{
+    type In = [T; N];
+    type Out = T;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs
+            .first()
+            .and_then(|v| v.get(self.index).cloned())
+            .unwrap_or_default()
+    }
+}
+
+/// Gathers its first `N` inputs into an array, padding with defaults.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Collect<T, const N: usize> {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _intype: PhantomData<T>,
+}
+impl<T, const N: usize> Collect<T, N> {
+    pub fn new() -> Self {
+        Self {
+            _intype: PhantomData,
+        }
+    }
+}
+
+impl<T, const N: usize> Compute for Collect<T, N>
+where
+    T: Value,
+    [T; N]: Value,
+{
+    type In = T;
+    type Out = [T; N];
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let mut output = <[T; N]>::default();
+        for (out, input) in output.iter_mut().zip(inputs) {
+            *out = (*input).clone();
+        }
+        output
+    }
+}
+
+/// Joins its inputs, with `separator` between them.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Concat {
+    pub separator: String,
+}
+impl Concat {
+    pub fn new(separator: impl Into<String>) -> Self {
+        Self {
+            separator: separator.into(),
+        }
+    }
+}
+
+impl Compute for Concat {
+    type In = String;
+    type Out = String;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        let mut output = String::new();
+        for (i, input) in inputs.iter().enumerate() {
+            if i > 0 {
+                output.push_str(&self.separator);
+            }
+            output.push_str(input);
+        }
+        output
+    }
+    fn fingerprint(&self, state: &mut dyn Hasher) {
+        state.write(self.separator.as_bytes());
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ToUpper;
+impl Compute for ToUpper {
+    type In = String;
+    type Out = String;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs.first().map(|v| v.to_uppercase()).unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ToLower;
+impl Compute for ToLower {
+    type In = String;
+    type Out = String;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs.first().map(|v| v.to_lowercase()).unwrap_or_default()
+    }
+}
+
+/// Checks whether the first input contains `pattern`.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Contains {
+    pub pattern: String,
+}
+impl Contains {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+        }
+    }
+}
+
+impl Compute for Contains {
+    type In = String;
+    type Out = bool;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs.first().is_some_and(|v| v.contains(&self.pattern))
+    }
+    fn fingerprint(&self, state: &mut dyn Hasher) {
+        state.write(self.pattern.as_bytes());
+    }
+}
+
+#[cfg(feature = "regex")]
+fn compile_regex(pattern: &str) -> Result<regex::Regex, ComputeGraphErrors> {
+    regex::Regex::new(pattern).map_err(|err| {
+        ComputeGraphErrors::InvalidParameter(format!("invalid regex: {}", err).into())
+    })
+}
+
+/// Regexes serialize as their pattern and are compiled again on load.
+#[cfg(all(feature = "regex", feature = "serde"))]
+mod regex_serde {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        regex: &regex::Regex,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(regex.as_str())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<regex::Regex, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        super::compile_regex(&pattern).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Checks whether the first input matches `regex` anywhere.
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegexMatch {
+    #[cfg_attr(feature = "serde", serde(with = "regex_serde"))]
+    regex: regex::Regex,
+}
+
+#[cfg(feature = "regex")]
+impl RegexMatch {
+    /// Compiles `pattern`, failing with `ComputeGraphErrors::InvalidParameter` if it's invalid.
+    pub fn new(pattern: &str) -> Result<Self, ComputeGraphErrors> {
+        Ok(Self {
+            regex: compile_regex(pattern)?,
+        })
+    }
+}
+
+#[cfg(feature = "regex")]
+impl Compute for RegexMatch {
+    type In = String;
+    type Out = bool;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs.first().is_some_and(|v| self.regex.is_match(v))
+    }
+    fn fingerprint(&self, state: &mut dyn Hasher) {
+        state.write(self.regex.as_str().as_bytes());
+    }
+}
+
+/// Extracts capture group `group` of the first match of `regex` in the first input, with
+/// group 0 being the whole match. Outputs an empty string if nothing matches.
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegexExtract {
+    #[cfg_attr(feature = "serde", serde(with = "regex_serde"))]
+    regex: regex::Regex,
+    pub group: usize,
+}
+
+#[cfg(feature = "regex")]
+impl RegexExtract {
+    /// Compiles `pattern`, failing with `ComputeGraphErrors::InvalidParameter` if it's invalid.
+    pub fn new(pattern: &str, group: usize) -> Result<Self, ComputeGraphErrors> {
+        Ok(Self {
+            regex: compile_regex(pattern)?,
+            group,
+        })
+    }
+}
+
+#[cfg(feature = "regex")]
+impl Compute for RegexExtract {
+    type In = String;
+    type Out = String;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        inputs
+            .first()
+            .and_then(|v| self.regex.captures(v))
+            .and_then(|captures| captures.get(self.group))
+            .map(|group| group.as_str().to_string())
+            .unwrap_or_default()
+    }
+    fn fingerprint(&self, state: &mut dyn Hasher) {
+        state.write(self.regex.as_str().as_bytes());
+        state.write_usize(self.group);
+    }
+}
+
+#[cfg(test)]
+mod operations_tests {
+    use crate::prelude::*;
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    enum Mode {
+        #[default]
+        Off,
+        Eco,
+        Boost,
+    }
+
+    #[test]
+    fn test_map_enum() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let mode_handle = graph.insert_node("mode", Constant(Mode::Eco));
+        let gain_handle = graph.insert_node(
+            "gain",
+            MapEnum::new(0.0)
+                .case(Mode::Eco, 0.5)
+                .case(Mode::Boost, 2.0),
+        );
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&gain_handle, &mode_handle)?;
+        graph.add_input(&add_handle, &gain_handle)?;
+        graph.set_output_node(&add_handle)?;
+
+        assert_eq!(graph.build::<(), f64>()?.compute(&()), 0.5);
+
+        let mut graph = Graph::new();
+        let gain_handle = graph.insert_node("gain", MapEnum::new(1.0).case(Mode::Boost, 2.0));
+        graph.set_output_node(&gain_handle)?;
+        let compute_graph = graph.build::<Mode, f64>()?;
+        assert_eq!(compute_graph.compute(&Mode::Boost), 2.0);
+        assert_eq!(compute_graph.compute(&Mode::Off), 1.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_option_edges() -> Result<(), ComputeGraphErrors> {
+        //  Input ── filter ──┬── unwrap_or
+        //                    └── is_some
+        let mut graph = Graph::new();
+        let filter_handle = graph.insert_node("filter", Filter::new(0.0, 10.0));
+        let unwrap_handle = graph.insert_node("unwrap_or", UnwrapOr(-1.0));
+        let is_some_handle = graph.insert_node("is_some", IsSome::<f64>::new());
+        graph.add_input(&unwrap_handle, &filter_handle)?;
+        graph.add_input(&is_some_handle, &filter_handle)?;
+
+        let unwrap_graph = graph.build_for_node::<f64, f64>(&unwrap_handle)?;
+        assert_eq!(unwrap_graph.compute(&4.0), 4.0);
+        assert_eq!(unwrap_graph.compute(&11.0), -1.0);
+
+        let is_some_graph = graph.build_for_node::<f64, bool>(&is_some_handle)?;
+        assert!(is_some_graph.compute(&10.0));
+        assert!(!is_some_graph.compute(&-0.5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pack_unpack() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let a_handle = graph.insert_node("a", Constant(1.0));
+        let b_handle = graph.insert_node("b", Constant(2.0));
+        let pack_handle = graph.insert_node("pack", Pack3::<f64>::new());
+        let second_handle = graph.insert_node("second", Unpack3::<f64, f64, f64, 1>::new());
+        graph.add_input(&pack_handle, &a_handle)?;
+        graph.add_input(&pack_handle, &b_handle)?;
+        graph.add_input(&second_handle, &pack_handle)?;
+
+        let pack_graph = graph.build_for_node::<(), (f64, f64, f64)>(&pack_handle)?;
+        assert_eq!(pack_graph.compute(&()), (1.0, 2.0, 0.0));
+        let second_graph = graph.build_for_node::<(), f64>(&second_handle)?;
+        assert_eq!(second_graph.compute(&()), 2.0);
+
+        let mut graph = Graph::new();
+        let flag_handle = graph.insert_node("flag", Unpack2::<f64, bool, 1>::new());
+        graph.set_output_node(&flag_handle)?;
+        let compute_graph = graph.build::<(f64, bool), bool>()?;
+        assert!(compute_graph.compute(&(0.5, true)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_k() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let top_handle = graph.insert_node("top", TopK::<f64, 3>::new());
+        for (i, score) in [0.2, 0.9, 0.4, 0.7, 0.1].into_iter().enumerate() {
+            let score_handle = graph.insert_node(format!("score{}", i), Constant(score));
+            graph.add_input(&top_handle, &score_handle)?;
+        }
+        let top_graph = graph.build_for_node::<(), [f64; 3]>(&top_handle)?;
+        assert_eq!(top_graph.compute(&()), [0.9, 0.7, 0.4]);
+
+        let mut graph = Graph::new();
+        let top_handle = graph.insert_node("top", TopKOf::<i32, 4, 2>::new());
+        graph.set_output_node(&top_handle)?;
+        let top_graph = graph.build::<[i32; 4], [i32; 2]>()?;
+        assert_eq!(top_graph.compute(&[3, -1, 8, 3]), [8, 3]);
+        assert_eq!(top_graph.compute(&[5, 5, 5, 5]), [5, 5]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_operations() -> Result<(), ComputeGraphErrors> {
+        //  greeting, Input ── concat ──┬── upper
+        //                              ├── lower
+        //                              └── contains
+        let mut graph = Graph::new();
+        let greeting_handle = graph.insert_node("greeting", Constant("Hello".to_string()));
+        let concat_handle = graph.insert_node("concat", Concat::new(", "));
+        let upper_handle = graph.insert_node("upper", ToUpper);
+        let lower_handle = graph.insert_node("lower", ToLower);
+        let contains_handle = graph.insert_node("contains", Contains::new("World"));
+        graph.add_input(&concat_handle, &greeting_handle)?;
+        graph.connect_to_input(&concat_handle)?;
+        graph.add_input(&upper_handle, &concat_handle)?;
+        graph.add_input(&lower_handle, &concat_handle)?;
+        graph.add_input(&contains_handle, &concat_handle)?;
+
+        let name = "World".to_string();
+        let concat_graph = graph.build_for_node::<String, String>(&concat_handle)?;
+        assert_eq!(concat_graph.compute(&name), "Hello, World");
+        let upper_graph = graph.build_for_node::<String, String>(&upper_handle)?;
+        assert_eq!(upper_graph.compute(&name), "HELLO, WORLD");
+        let lower_graph = graph.build_for_node::<String, String>(&lower_handle)?;
+        assert_eq!(lower_graph.compute(&name), "hello, world");
+        let contains_graph = graph.build_for_node::<String, bool>(&contains_handle)?;
+        assert!(contains_graph.compute(&name));
+        assert!(!contains_graph.compute(&"world".to_string()));
+        assert_eq!(Concat::default().compute(&[&name, &name]), "WorldWorld");
+        Ok(())
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_regex_operations() -> Result<(), ComputeGraphErrors> {
+        //  Input ──┬── is_order
+        //          └── order_id
+        let mut graph = Graph::new();
+        let match_handle = graph.insert_node("is_order", RegexMatch::new(r"order-\d+")?);
+        let extract_handle = graph.insert_node("order_id", RegexExtract::new(r"order-(\d+)", 1)?);
+        graph.connect_to_input(&match_handle)?;
+        graph.connect_to_input(&extract_handle)?;
+
+        let line = "shipped order-1234 today".to_string();
+        let match_graph = graph.build_for_node::<String, bool>(&match_handle)?;
+        assert!(match_graph.compute(&line));
+        assert!(!match_graph.compute(&"no orders".to_string()));
+        let extract_graph = graph.build_for_node::<String, String>(&extract_handle)?;
+        assert_eq!(extract_graph.compute(&line), "1234");
+        assert_eq!(extract_graph.compute(&"no orders".to_string()), "");
+
+        assert!(matches!(
+            RegexMatch::new("order-("),
+            Err(ComputeGraphErrors::InvalidParameter(_))
+        ));
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&RegexExtract::new(r"(\w+)@", 1)?).unwrap();
+            let loaded: RegexExtract = serde_json::from_str(&json).unwrap();
+            assert_eq!(loaded.compute(&[&"me@example.com".to_string()]), "me");
+            assert!(serde_json::from_str::<RegexMatch>(r#"{"regex": "("}"#).is_err());
         }
+        Ok(())
     }
 }
@@ -0,0 +1,120 @@
+use crate::ports::PortCompute;
+use std::f64::consts::TAU;
+
+/// Shape of an `Oscillator`, each ranging from -1 to 1 and, except `Square`, starting at 0
+/// and rising like `Sine`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Waveform {
+    #[default]
+    Sine,
+    Saw,
+    Square,
+    Triangle,
+}
+
+impl Waveform {
+    /// Value at `phase`, in cycles.
+    pub fn at(self, phase: f64) -> f64 {
+        let phase = phase.rem_euclid(1.0);
+        match self {
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Saw => 2.0 * (phase + 0.5).rem_euclid(1.0) - 1.0,
+            Waveform::Square if phase < 0.5 => 1.0,
+            Waveform::Square => -1.0,
+            Waveform::Triangle => 1.0 - 4.0 * ((phase + 0.25).rem_euclid(1.0) - 0.5).abs(),
+        }
+    }
+}
+
+/// Periodic signal for synths and for modulating other nodes, as an LFO. Port 0 reads the
+/// frequency in hertz, port 1 a phase offset in cycles and port 2 the time step in seconds,
+/// like the graph input fed by `Simulator`. The phase advances after every evaluation, so
+/// changing the frequency doesn't make the output jump. Evaluated through
+/// `ComputeGraph::try_compute_mut`.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oscillator {
+    pub waveform: Waveform,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    phase: f64,
+}
+
+impl Oscillator {
+    pub fn new(waveform: Waveform) -> Self {
+        Self {
+            waveform,
+            phase: 0.0,
+        }
+    }
+}
+
+impl PortCompute for Oscillator {
+    type Ports = (f64, f64, f64);
+    type Out = f64;
+    fn compute(&self, (_, offset, _): (&f64, &f64, &f64)) -> Self::Out {
+        self.waveform.at(self.phase + offset)
+    }
+
+    fn try_compute_mut(&mut self, ports: (&f64, &f64, &f64)) -> Result<f64, String> {
+        let value = self.compute(ports);
+        let (frequency, _, dt) = ports;
+        self.phase = (self.phase + frequency * dt).rem_euclid(1.0);
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod oscillator_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_oscillators() -> Result<(), ComputeGraphErrors> {
+        //  frequency, phase, Input (dt) ── sine, saw, square, triangle ── pack
+        let mut graph = Graph::new();
+        let frequency_handle = graph.insert_node("frequency", Constant(1.0));
+        let phase_handle = graph.insert_node("phase", Parameter::new("phase", 0.0));
+        let dt_handle = graph.insert_node("dt", AddInputs::<f64>::new());
+        let pack_handle = graph.insert_node("pack", Pack4::<f64>::new());
+        for waveform in [
+            Waveform::Sine,
+            Waveform::Saw,
+            Waveform::Square,
+            Waveform::Triangle,
+        ] {
+            let name = format!("{:?}", waveform);
+            let oscillator_handle = graph.insert_port_node(name, Oscillator::new(waveform));
+            graph.add_input_port(&oscillator_handle, 0, &frequency_handle)?;
+            graph.add_input_port(&oscillator_handle, 1, &phase_handle)?;
+            graph.add_input_port(&oscillator_handle, 2, &dt_handle)?;
+            graph.add_input(&pack_handle, &oscillator_handle)?;
+        }
+        graph.set_output_node(&pack_handle)?;
+        let mut compute_graph = graph.build::<f64, (f64, f64, f64, f64)>()?;
+
+        let mut samples = (0..5)
+            .map(|_| compute_graph.compute_mut(&0.25))
+            .collect::<Vec<_>>();
+        // A phase offset of half a cycle inverts every waveform
+        compute_graph.set_parameter("phase", 0.5)?;
+        samples.push(compute_graph.compute_mut(&0.25));
+        let rounded = samples
+            .iter()
+            .map(|(sine, saw, square, triangle)| {
+                [*sine, *saw, *square, *triangle].map(|value| (value * 1e9).round() / 1e9)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rounded,
+            [
+                [0.0, 0.0, 1.0, 0.0],
+                [1.0, 0.5, 1.0, 1.0],
+                [0.0, -1.0, -1.0, 0.0],
+                [-1.0, -0.5, -1.0, -1.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [-1.0, -0.5, -1.0, -1.0]
+            ]
+        );
+        Ok(())
+    }
+}
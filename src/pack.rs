@@ -0,0 +1,276 @@
+use crate::graph::{ComputeGraphErrors, Graph};
+use crate::registry::NodeRegistry;
+use crate::serialize::{GraphEnvelope, LoadIssue};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Version of the pack layout, bumped when `GraphPack` changes shape.
+pub const PACK_VERSION: u32 = 1;
+
+/// Describes a packed graph to the people using it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PackMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Version of the packed graph itself, in whatever scheme its author uses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Op namespace a packed graph uses, with the minimum version of its library.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NamespaceRequirement {
+    pub namespace: String,
+    pub version: u32,
+}
+
+/// A graph bundled into a single `.cgpack` file with its metadata, the assets its nodes
+/// refer to, like LUTs or curves, and the op namespaces it needs to load.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GraphPack {
+    pub pack_version: u32,
+    #[serde(default)]
+    pub metadata: PackMetadata,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<NamespaceRequirement>,
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        with = "hex_assets"
+    )]
+    assets: BTreeMap<String, Vec<u8>>,
+    pub graph: GraphEnvelope,
+}
+
+impl GraphPack {
+    /// Bundles `bytes` as `name`, replacing an asset of the same name.
+    pub fn add_asset(&mut self, name: impl Into<String>, bytes: impl Into<Vec<u8>>) -> &mut Self {
+        self.assets.insert(name.into(), bytes.into());
+        self
+    }
+
+    pub fn asset(&self, name: &str) -> Option<&[u8]> {
+        self.assets.get(name).map(|bytes| bytes.as_slice())
+    }
+
+    /// Names of the bundled assets, sorted.
+    pub fn asset_names(&self) -> impl Iterator<Item = &str> {
+        self.assets.keys().map(|name| name.as_str())
+    }
+
+    /// Checks the required namespaces and the graph against `registry`, reporting every
+    /// issue found.
+    pub fn validate(&self, registry: &NodeRegistry) -> Vec<LoadIssue> {
+        let mut issues = self.missing_dependencies(registry);
+        issues.extend(self.graph.validate(registry));
+        issues
+    }
+
+    fn missing_dependencies(&self, registry: &NodeRegistry) -> Vec<LoadIssue> {
+        let mut issues = Vec::new();
+        for requirement in self.requires.iter() {
+            let found = registry.namespace_version(&requirement.namespace);
+            if found.is_none() && registry.namespace(&requirement.namespace).is_empty() {
+                issues.push(LoadIssue::MissingNamespace {
+                    namespace: requirement.namespace.clone(),
+                    version: requirement.version,
+                });
+            } else if found.unwrap_or(0) < requirement.version {
+                issues.push(LoadIssue::OutdatedNamespace {
+                    namespace: requirement.namespace.clone(),
+                    required: requirement.version,
+                    found: found.unwrap_or(0),
+                });
+            }
+        }
+        issues
+    }
+
+    /// Loads the packed graph, failing with `InvalidGraph` if `registry` lacks a required
+    /// namespace or has an older version of it.
+    pub fn to_graph(&self, registry: &NodeRegistry) -> Result<Graph, ComputeGraphErrors> {
+        let issues = self.missing_dependencies(registry);
+        if !issues.is_empty() {
+            return Err(ComputeGraphErrors::InvalidGraph(issues));
+        }
+        Graph::deserialize(self.graph.clone(), registry)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ComputeGraphErrors> {
+        serde_json::to_vec_pretty(self)
+            .map_err(|err| ComputeGraphErrors::Serialization(err.to_string().into()))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ComputeGraphErrors> {
+        let pack: GraphPack = serde_json::from_slice(bytes)
+            .map_err(|err| ComputeGraphErrors::Serialization(err.to_string().into()))?;
+        if pack.pack_version > PACK_VERSION {
+            return Err(ComputeGraphErrors::Serialization(
+                format!(
+                    "unsupported pack version {}, expected at most {}",
+                    pack.pack_version, PACK_VERSION
+                )
+                .into(),
+            ));
+        }
+        Ok(pack)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ComputeGraphErrors> {
+        std::fs::write(path, self.to_bytes()?)
+            .map_err(|err| ComputeGraphErrors::Serialization(err.to_string().into()))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ComputeGraphErrors> {
+        let bytes = std::fs::read(path)
+            .map_err(|err| ComputeGraphErrors::Serialization(err.to_string().into()))?;
+        GraphPack::from_bytes(&bytes)
+    }
+}
+
+impl Graph {
+    /// Serializes the graph into a pack requiring the namespaces of the ops it uses, at the
+    /// versions declared in `registry`. Assets are added afterwards.
+    pub fn pack(
+        &self,
+        registry: &NodeRegistry,
+        metadata: PackMetadata,
+    ) -> Result<GraphPack, ComputeGraphErrors> {
+        let graph = self.serialize(registry)?;
+        let mut requires = graph
+            .graph
+            .nodes
+            .iter()
+            .filter_map(|node| registry.library_of(&node.op))
+            .map(|(namespace, version)| NamespaceRequirement {
+                namespace: namespace.to_string(),
+                version,
+            })
+            .collect::<Vec<_>>();
+        requires.sort();
+        requires.dedup();
+        Ok(GraphPack {
+            pack_version: PACK_VERSION,
+            metadata,
+            requires,
+            assets: BTreeMap::new(),
+            graph,
+        })
+    }
+}
+
+/// Stores assets as hex strings, keeping packs readable as JSON.
+mod hex_assets {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::BTreeMap;
+
+    pub fn serialize<S: Serializer>(
+        assets: &BTreeMap<String, Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(assets.iter().map(|(name, bytes)| {
+            let hex = bytes
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>();
+            (name, hex)
+        }))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<String, Vec<u8>>, D::Error> {
+        BTreeMap::<String, String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(name, hex)| {
+                let bytes = (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+                    .collect::<Option<Vec<_>>>();
+                match bytes {
+                    Some(bytes) => Ok((name, bytes)),
+                    None => Err(D::Error::custom(format!(
+                        "asset '{}' is not valid hex",
+                        name
+                    ))),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod pack_tests {
+    use crate::prelude::*;
+
+    fn registry(pricing_version: u32) -> Result<NodeRegistry, ComputeGraphErrors> {
+        let mut registry = NodeRegistry::new();
+        registry.register::<Constant<f64>, _, _>("std.constant")?;
+        registry.register::<MulInputs<f64>, _, _>("mycorp.pricing.margin")?;
+        registry.set_namespace_version("mycorp", pricing_version);
+        Ok(registry)
+    }
+
+    #[test]
+    fn test_pack_roundtrip() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let rate_handle = graph.insert_node("rate", Constant(1.25));
+        let margin_handle = graph.insert_node("margin", MulInputs::<f64>::new());
+        graph.add_input(&margin_handle, &rate_handle)?;
+        graph.connect_to_input(&margin_handle)?;
+        graph.set_output_node(&margin_handle)?;
+
+        let metadata = PackMetadata {
+            author: Some("pricing team".to_string()),
+            version: Some("2.1".to_string()),
+            description: None,
+        };
+        let mut pack = graph.pack(&registry(3)?, metadata.clone())?;
+        pack.add_asset("curves/discount.bin", [0u8, 17, 255]);
+        let path = std::env::temp_dir().join(format!("pack_{}.cgpack", std::process::id()));
+        pack.save(&path)?;
+        let loaded = GraphPack::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded?;
+
+        assert_eq!(loaded, pack);
+        assert_eq!(loaded.metadata, metadata);
+        assert_eq!(
+            loaded.asset("curves/discount.bin"),
+            Some(&[0u8, 17, 255][..])
+        );
+        let requires = loaded
+            .requires
+            .iter()
+            .map(|requirement| (requirement.namespace.as_str(), requirement.version))
+            .collect::<Vec<_>>();
+        assert_eq!(requires, [("mycorp", 3), ("std", 0)]);
+        assert_eq!(
+            loaded
+                .to_graph(&registry(4)?)?
+                .build::<f64, f64>()?
+                .compute(&2.0),
+            2.5
+        );
+
+        assert!(matches!(
+            loaded.to_graph(&registry(2)?),
+            Err(ComputeGraphErrors::InvalidGraph(issues))
+                if issues == [LoadIssue::OutdatedNamespace {
+                    namespace: "mycorp".to_string(),
+                    required: 3,
+                    found: 2,
+                }]
+        ));
+        let issues = loaded.validate(&NodeRegistry::new());
+        assert_eq!(
+            issues[0].to_string(),
+            "requires ops from 'mycorp' version 3, which are not registered"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use crate::com_graph::{ComputeGraph, ComputeNode};
+use crate::compute::Value;
+use crate::graph::ComputeGraphErrors;
+use crate::scheduling::{previous_effects, Scheduling};
+use rayon::prelude::*;
+use std::any::{Any, TypeId};
+
+/// Groups the nodes, which are in compute order, so every node only reads outputs
+/// of earlier levels. Nodes that aren't `Scheduling::Free` follow each other level by
+/// level, and `Scheduling::Exclusive` nodes get a level of their own between everything
+/// before and after them.
+pub(crate) fn dependency_levels(nodes: &[ComputeNode]) -> Vec<Vec<usize>> {
+    let previous_effects = previous_effects(nodes);
+    let mut node_levels: Vec<usize> = Vec::with_capacity(nodes.len());
+    let mut levels: Vec<Vec<usize>> = Vec::new();
+    let mut floor = 0;
+    for (index, node) in nodes.iter().enumerate() {
+        let mut level = node
+            .inputs
+            .iter()
+            .chain(node.after.iter())
+            .chain(previous_effects[index].iter())
+            .map(|input| node_levels[*input] + 1)
+            .max()
+            .unwrap_or(0)
+            .max(floor);
+        if node.scheduling == Scheduling::Exclusive {
+            level = level.max(levels.len());
+            floor = level + 1;
+        }
+        node_levels.push(level);
+        if level == levels.len() {
+            levels.push(Vec::new());
+        }
+        levels[level].push(index);
+    }
+    levels
+}
+
+impl<In, Out> ComputeGraph<In, Out>
+where
+    In: Value,
+    Out: Value,
+{
+    /// Like `try_compute`, evaluating the nodes of each dependency level concurrently on
+    /// the rayon pool. Worth it for wide graphs with expensive nodes.
+    pub fn compute_parallel(&self, input: &In) -> Result<Out, ComputeGraphErrors> {
+        self.with_context(|context| {
+            for level in &self.levels {
+                if let [index] = level.as_slice() {
+                    self.compute_node(*index, input, context)?;
+                    continue;
+                }
+                let current = &context.outputs;
+                let computed = level
+                    .par_iter()
+                    .map(|index| self.compute_detached(*index, current, input))
+                    .collect::<Result<Vec<_>, _>>()?;
+                for (index, output) in level.iter().zip(computed) {
+                    context.set_output(*index, output);
+                }
+            }
+            Ok(context.output::<Out>().clone())
+        })
+    }
+
+    /// Evaluates node `index` into a new output, reading its inputs from `current`.
+    fn compute_detached(
+        &self,
+        index: usize,
+        current: &[Box<dyn Any + Send + Sync>],
+        input: &In,
+    ) -> Result<Box<dyn Any + Send + Sync>, ComputeGraphErrors> {
+        let node = &self.nodes[index];
+        let _effect = self.effect_guard(index);
+        // Starting from the last output keeps stale fallbacks working
+        let mut output = node.func.clone_output(current[index].as_ref());
+        let mut inputs = Vec::new();
+        if node.func.input_type() != TypeId::of::<()>() {
+            inputs.extend(
+                node.inputs
+                    .iter()
+                    .map(|input| current[*input].as_ref() as &dyn Any),
+            );
+            if node.connected_to_input {
+                inputs.push(input as &dyn Any);
+            }
+        }
+        node.policy
+            .run(&node.name, node.func.as_ref(), &inputs, output.as_mut())
+            .map_err(|err| err.with_node(node.handle))?;
+        if node.probed {
+            self.record_probe(index, output.as_ref());
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod parallel_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_parallel_levels() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let sum_handle = graph.insert_node("sum", AddInputs::<f64>::new());
+        for i in 0..6 {
+            let rate_handle = graph.insert_node(format!("rate{}", i), Constant(i as f64));
+            let mul_handle = graph.insert_node(format!("mul{}", i), MulInputs::<f64>::new());
+            graph.add_input(&mul_handle, &rate_handle)?;
+            graph.connect_to_input(&mul_handle)?;
+            graph.add_input(&sum_handle, &mul_handle)?;
+        }
+        graph.set_output_node(&sum_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+
+        let widths = compute_graph
+            .levels
+            .iter()
+            .map(|level| level.len())
+            .collect::<Vec<_>>();
+        assert_eq!(widths, [6, 6, 1]);
+        for x in [1.0, 2.5] {
+            assert_eq!(compute_graph.compute_parallel(&x)?, 15.0 * x);
+            assert_eq!(
+                compute_graph.compute_parallel(&x)?,
+                compute_graph.compute(&x)
+            );
+        }
+        Ok(())
+    }
+}
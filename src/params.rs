@@ -0,0 +1,249 @@
+use std::any::Any;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParamKind {
+    Bool,
+    Int,
+    Float,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParamValue {
+    Bool(bool),
+    Int(i64),
+    /// Unsigned integers beyond `i64::MAX`, smaller ones are `Int`. Of kind `ParamKind::Int`.
+    UInt(u64),
+    Float(f64),
+}
+
+impl ParamValue {
+    pub fn kind(&self) -> ParamKind {
+        match self {
+            ParamValue::Bool(_) => ParamKind::Bool,
+            ParamValue::Int(_) | ParamValue::UInt(_) => ParamKind::Int,
+            ParamValue::Float(_) => ParamKind::Float,
+        }
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            ParamValue::Bool(v) => v as u8 as f64,
+            ParamValue::Int(v) => v as f64,
+            ParamValue::UInt(v) => v as f64,
+            ParamValue::Float(v) => v,
+        }
+    }
+}
+
+impl From<bool> for ParamValue {
+    fn from(value: bool) -> Self {
+        ParamValue::Bool(value)
+    }
+}
+
+impl From<i64> for ParamValue {
+    fn from(value: i64) -> Self {
+        ParamValue::Int(value)
+    }
+}
+
+impl From<f64> for ParamValue {
+    fn from(value: f64) -> Self {
+        ParamValue::Float(value)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParamInfo {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub value: ParamValue,
+    pub range: Option<(f64, f64)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParamError {
+    UnknownParam(String),
+    WrongKind {
+        name: String,
+        expected: ParamKind,
+        found: ParamKind,
+    },
+    OutOfRange {
+        name: String,
+        value: f64,
+        range: (f64, f64),
+    },
+}
+
+/// Exposes the fields of a compute object as named, typed parameters.
+/// Usually implemented with `impl_parameters!` and returned from `Compute::parameters`.
+pub trait Parameters {
+    fn params(&self) -> Vec<ParamInfo>;
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError>;
+}
+
+/// Field types that can be exposed as a parameter.
+pub trait ParamType: Sized {
+    const KIND: ParamKind;
+    fn to_param(&self) -> ParamValue;
+    fn from_param(value: ParamValue) -> Option<Self>;
+}
+
+impl ParamType for bool {
+    const KIND: ParamKind = ParamKind::Bool;
+    fn to_param(&self) -> ParamValue {
+        ParamValue::Bool(*self)
+    }
+    fn from_param(value: ParamValue) -> Option<Self> {
+        match value {
+            ParamValue::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+// Only unsigned values beyond the range of `i64` end up as `ParamValue::UInt`
+macro_rules! int_param_type {
+    ($($t:ty),*) => {$(
+        impl ParamType for $t {
+            const KIND: ParamKind = ParamKind::Int;
+            fn to_param(&self) -> ParamValue {
+                match i64::try_from(*self) {
+                    Ok(v) => ParamValue::Int(v),
+                    Err(_) => ParamValue::UInt(u64::try_from(*self).unwrap()),
+                }
+            }
+            fn from_param(value: ParamValue) -> Option<Self> {
+                match value {
+                    ParamValue::Int(v) => <$t>::try_from(v).ok(),
+                    ParamValue::UInt(v) => <$t>::try_from(v).ok(),
+                    _ => None,
+                }
+            }
+        }
+    )*};
+}
+int_param_type!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! float_param_type {
+    ($($t:ty),*) => {$(
+        impl ParamType for $t {
+            const KIND: ParamKind = ParamKind::Float;
+            fn to_param(&self) -> ParamValue {
+                ParamValue::Float(*self as f64)
+            }
+            fn from_param(value: ParamValue) -> Option<Self> {
+                match value {
+                    ParamValue::Float(v) => Some(v as $t),
+                    _ => None,
+                }
+            }
+        }
+    )*};
+}
+float_param_type!(f32, f64);
+
+pub fn param_info<T: ParamType>(
+    name: &'static str,
+    value: &T,
+    range: Option<(f64, f64)>,
+) -> ParamInfo {
+    ParamInfo {
+        name,
+        kind: T::KIND,
+        value: value.to_param(),
+        range,
+    }
+}
+
+pub fn assign_param<T: ParamType>(
+    target: &mut T,
+    name: &str,
+    value: ParamValue,
+    range: Option<(f64, f64)>,
+) -> Result<(), ParamError> {
+    let new_value = T::from_param(value).ok_or_else(|| ParamError::WrongKind {
+        name: name.to_string(),
+        expected: T::KIND,
+        found: value.kind(),
+    })?;
+    if let Some((min, max)) = range {
+        let v = value.as_f64();
+        if v.is_nan() || v < min || v > max {
+            return Err(ParamError::OutOfRange {
+                name: name.to_string(),
+                value: v,
+                range: (min, max),
+            });
+        }
+    }
+    *target = new_value;
+    Ok(())
+}
+
+macro_rules! dyn_param_types {
+    ($($t:ty),*) => {
+        pub(crate) fn dyn_param_info(name: &'static str, value: &dyn Any) -> Option<ParamInfo> {
+            $(
+                if let Some(v) = value.downcast_ref::<$t>() {
+                    return Some(param_info(name, v, None));
+                }
+            )*
+            None
+        }
+
+        pub(crate) fn dyn_assign_param(
+            target: &mut dyn Any,
+            name: &str,
+            value: ParamValue,
+        ) -> Result<(), ParamError> {
+            $(
+                if let Some(v) = target.downcast_mut::<$t>() {
+                    return assign_param(v, name, value, None);
+                }
+            )*
+            Err(ParamError::UnknownParam(name.to_string()))
+        }
+    };
+}
+dyn_param_types!(bool, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+/// Implements `Parameters` for a struct by listing the fields to expose,
+/// optionally followed by an inclusive `[min, max]` range.
+///
+/// ```ignore
+/// impl_parameters!(Gain { factor: f64 [0.0, 10.0], enabled: bool });
+/// ```
+#[macro_export]
+macro_rules! impl_parameters {
+    ($ty:ident { $($field:ident : $fty:ty $([$min:expr, $max:expr])?),* $(,)? }) => {
+        impl $crate::prelude::Parameters for $ty {
+            fn params(&self) -> Vec<$crate::prelude::ParamInfo> {
+                vec![$(
+                    $crate::prelude::param_info::<$fty>(
+                        stringify!($field),
+                        &self.$field,
+                        None$(.or(Some(($min as f64, $max as f64))))?,
+                    )
+                ),*]
+            }
+
+            fn set_param(
+                &mut self,
+                name: &str,
+                value: $crate::prelude::ParamValue,
+            ) -> Result<(), $crate::prelude::ParamError> {
+                match name {
+                    $(stringify!($field) => $crate::prelude::assign_param::<$fty>(
+                        &mut self.$field,
+                        name,
+                        value,
+                        None$(.or(Some(($min as f64, $max as f64))))?,
+                    ),)*
+                    _ => Err($crate::prelude::ParamError::UnknownParam(name.to_string())),
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,296 @@
+use crate::formula::Expr;
+use crate::graph::{ComputeGraphErrors, Graph, NodeHandle};
+use crate::operations::{AddInputs, Constant, MulAdd, MulInputs, Pow, SubInputs};
+use std::collections::HashMap;
+
+fn parse_error(message: String) -> ComputeGraphErrors {
+    ComputeGraphErrors::Import(message.into())
+}
+
+/// Recursive descent over a formula, one method per precedence level.
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&mut self) -> Option<char> {
+        self.source[self.pos..].chars().find(|c| !c.is_whitespace())
+    }
+
+    /// Skips whitespace and takes `expected` if it comes next.
+    fn eat(&mut self, expected: char) -> bool {
+        let rest = &self.source[self.pos..];
+        let skipped = rest.len() - rest.trim_start().len();
+        if rest[skipped..].starts_with(expected) {
+            self.pos += skipped + expected.len_utf8();
+            return true;
+        }
+        false
+    }
+
+    fn unexpected(&mut self) -> ComputeGraphErrors {
+        let rest = &self.source[self.pos..];
+        let pos = self.pos + rest.len() - rest.trim_start().len();
+        match self.peek() {
+            Some(c) => parse_error(format!("unexpected '{}' at {} in formula", c, pos)),
+            None => parse_error("formula ended unexpectedly".to_string()),
+        }
+    }
+
+    fn take_while(&mut self, mut keep: impl FnMut(char) -> bool) -> &str {
+        let rest = &self.source[self.pos..];
+        let start = self.pos + rest.len() - rest.trim_start().len();
+        let len = self.source[start..]
+            .find(|c| !keep(c))
+            .unwrap_or(self.source.len() - start);
+        self.pos = start + len;
+        &self.source[start..self.pos]
+    }
+
+    fn sum(&mut self) -> Result<Expr, ComputeGraphErrors> {
+        let mut expr = self.product()?;
+        loop {
+            if self.eat('+') {
+                expr = expr + self.product()?;
+            } else if self.eat('-') {
+                expr = expr - self.product()?;
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, ComputeGraphErrors> {
+        let mut expr = self.unary()?;
+        loop {
+            if self.eat('*') {
+                expr = expr * self.unary()?;
+            } else if self.eat('/') {
+                expr = expr / self.unary()?;
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, ComputeGraphErrors> {
+        if self.eat('-') {
+            return Ok(-self.unary()?);
+        }
+        let base = self.atom()?;
+        // Right associative and binding tighter than negation, so `-a^b^c` is `-(a^(b^c))`
+        if self.eat('^') {
+            return Ok(base.pow(self.unary()?));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, ComputeGraphErrors> {
+        match self.peek() {
+            Some('(') => {
+                self.eat('(');
+                let expr = self.sum()?;
+                if !self.eat(')') {
+                    return Err(self.unexpected());
+                }
+                Ok(expr)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let mut exponent = false;
+                let number = self.take_while(|c| {
+                    let sign = exponent && (c == '+' || c == '-');
+                    exponent = c == 'e' || c == 'E';
+                    c.is_ascii_digit() || c == '.' || exponent || sign
+                });
+                number
+                    .parse()
+                    .map(Expr::Number)
+                    .map_err(|_| parse_error(format!("invalid number '{}' in formula", number)))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let name = self
+                    .take_while(|c| c.is_alphanumeric() || c == '_')
+                    .to_string();
+                if !self.eat('(') {
+                    return Ok(match name.as_str() {
+                        "x" => Expr::Input,
+                        _ => Expr::Symbol(name),
+                    });
+                }
+                let mut args = Vec::new();
+                if !self.eat(')') {
+                    loop {
+                        args.push(self.sum()?);
+                        if self.eat(')') {
+                            break;
+                        }
+                        if !self.eat(',') {
+                            return Err(self.unexpected());
+                        }
+                    }
+                }
+                Ok(Expr::Call(name, args))
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+}
+
+impl Expr {
+    /// Parses a formula like `(x * 42.0) + 42.0`, with `+ - * / ^`, unary minus, parentheses
+    /// and calls like `f(a, b)`. `x` is the graph input, as in `Expr::to_latex`, and other
+    /// names become symbols.
+    pub fn parse(source: &str) -> Result<Expr, ComputeGraphErrors> {
+        let mut parser = Parser { source, pos: 0 };
+        let expr = parser.sum()?;
+        if parser.peek().is_some() {
+            return Err(parser.unexpected());
+        }
+        Ok(expr)
+    }
+}
+
+/// Builds the nodes of an `Expr`, sharing one node per variable.
+struct Lowering {
+    graph: Graph,
+    input: Option<NodeHandle>,
+    symbols: HashMap<String, NodeHandle>,
+}
+
+impl Lowering {
+    fn node(&mut self, expr: &Expr) -> Result<NodeHandle, ComputeGraphErrors> {
+        let graph = &mut self.graph;
+        Ok(match expr {
+            Expr::Input => match self.input {
+                Some(handle) => handle,
+                None => *self
+                    .input
+                    .insert(*graph.insert_node("x", AddInputs::<f64>::new())),
+            },
+            Expr::Number(value) => *graph.insert_node(value.to_string(), Constant(*value)),
+            Expr::Symbol(name) => match self.symbols.get(name) {
+                Some(handle) => *handle,
+                None => {
+                    let handle = *graph.add_graph_input::<f64>(name.clone());
+                    self.symbols.insert(name.clone(), handle);
+                    handle
+                }
+            },
+            Expr::Add(terms) => {
+                let handle = *graph.insert_node("add", AddInputs::<f64>::new());
+                self.inputs(&handle, terms)?
+            }
+            Expr::Mul(factors) => {
+                let handle = *graph.insert_node("mul", MulInputs::<f64>::new());
+                self.inputs(&handle, factors)?
+            }
+            // `SubInputs` subtracts the earlier inputs from the later ones
+            Expr::Sub(lhs, rhs) => {
+                let handle = *graph.insert_node("sub", SubInputs::<f64>::new());
+                self.inputs(&handle, &[rhs.as_ref().clone(), lhs.as_ref().clone()])?
+            }
+            Expr::Div(lhs, rhs) => {
+                let handle = *graph.insert_node("div", MulInputs::<f64>::new());
+                let reciprocal = rhs.as_ref().clone().pow(Expr::Number(-1.0));
+                self.inputs(&handle, &[lhs.as_ref().clone(), reciprocal])?
+            }
+            Expr::Pow(base, exponent) => {
+                let Expr::Number(exponent) = exponent.as_ref() else {
+                    return Err(parse_error(
+                        "exponents must be numbers in formulas".to_string(),
+                    ));
+                };
+                let handle = *graph.insert_node("pow", Pow::new(*exponent));
+                self.inputs(&handle, std::slice::from_ref(base.as_ref()))?
+            }
+            Expr::Neg(operand) => {
+                let handle = *graph.insert_node("neg", MulAdd::new(-1.0, 0.0));
+                self.inputs(&handle, std::slice::from_ref(operand.as_ref()))?
+            }
+            Expr::Call(name, _) => {
+                return Err(parse_error(format!(
+                    "unknown function '{}' in formula",
+                    name
+                )))
+            }
+        })
+    }
+
+    fn inputs(
+        &mut self,
+        handle: &NodeHandle,
+        inputs: &[Expr],
+    ) -> Result<NodeHandle, ComputeGraphErrors> {
+        for input in inputs {
+            let input_handle = self.node(input)?;
+            self.graph.add_input(handle, &input_handle)?;
+        }
+        Ok(*handle)
+    }
+}
+
+impl Graph {
+    /// Builds a graph computing `formula`, see `Expr::parse`, from `Constant`, `AddInputs`,
+    /// `SubInputs`, `MulInputs`, `Pow` and `MulAdd` nodes, with the root as output node.
+    /// A graph using `x` is built with `In = f64`. Other variables become named inputs of
+    /// type `f64` and are passed to `ComputeGraph::compute_named`, so they can't be mixed with
+    /// `x`. Exponents must be numbers and calls are rejected.
+    pub fn from_formula(formula: &str) -> Result<Graph, ComputeGraphErrors> {
+        Graph::from_expr(&Expr::parse(formula)?)
+    }
+
+    /// Like `Graph::from_formula`, for an already parsed expression.
+    pub fn from_expr(expr: &Expr) -> Result<Graph, ComputeGraphErrors> {
+        let mut lowering = Lowering {
+            graph: Graph::new(),
+            input: None,
+            symbols: HashMap::new(),
+        };
+        let output = lowering.node(expr)?;
+        if lowering.input.is_some() && !lowering.symbols.is_empty() {
+            return Err(parse_error(
+                "formulas can't use both x and named inputs".to_string(),
+            ));
+        }
+        lowering.graph.set_output_node(&output)?;
+        Ok(lowering.graph)
+    }
+}
+
+#[cfg(test)]
+mod parser_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_from_formula() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::from_formula("(x * 42.0) + 42.0")?;
+        assert_eq!(graph.build::<f64, f64>()?.compute(&2.0), 126.0);
+        assert_eq!(graph.to_latex()?, "x \\cdot 42 + 42");
+
+        let mut graph = Graph::from_formula("-price * (1 - discount / 2)^2 + 1.5e1")?;
+        let compute_graph = graph.build::<(), f64>()?;
+        let inputs = Inputs::new().with("price", 10.0).with("discount", 0.5);
+        assert_eq!(
+            compute_graph.compute_named(&inputs)?,
+            -10.0 * 0.75f64.powi(2) + 15.0
+        );
+        assert_eq!(compute_graph.graph_inputs().len(), 2);
+
+        assert_eq!(Expr::parse("2^-x")?, Expr::Number(2.0).pow(-Expr::Input));
+        for (formula, message) in [
+            ("x * (2 + 3", "formula ended unexpectedly"),
+            ("x + * 2", "unexpected '*' at 4 in formula"),
+            ("x y", "unexpected 'y' at 2 in formula"),
+            ("sqrt(x)", "unknown function 'sqrt' in formula"),
+            ("x ^ a", "exponents must be numbers in formulas"),
+            ("x + a", "formulas can't use both x and named inputs"),
+        ] {
+            match Graph::from_formula(formula) {
+                Err(ComputeGraphErrors::Import(detail)) => assert_eq!(detail.to_string(), message),
+                _ => panic!("'{}' should not parse", formula),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+use crate::com_graph::ComputeGraph;
+use crate::compute::Value;
+use crate::graph::ComputeGraphErrors;
+use crate::scheduling::previous_effects;
+use std::any::{Any, TypeId};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::thread;
+
+type Item = Box<dyn Any + Send + Sync>;
+
+struct StageChannels<In> {
+    inputs: Vec<Receiver<Item>>,
+    graph_input: Option<Receiver<In>>,
+    outputs: Vec<SyncSender<Item>>,
+    /// Ticks of the nodes ordered before this one, see `Graph::add_order`.
+    waits: Vec<Receiver<()>>,
+    signals: Vec<SyncSender<()>>,
+}
+
+impl<In, Out> ComputeGraph<In, Out>
+where
+    In: Value,
+    Out: Value,
+{
+    /// Evaluates a stream of inputs with every node running as its own thread, connected
+    /// by channels holding at most `capacity` values. A slow node applies backpressure
+    /// to everything upstream. `sink` receives the outputs in input order. Nodes that aren't
+    /// `Scheduling::Free` never overlap and keep their order for every item, but one of
+    /// them may already see the next item before the others are done with the current one.
+    ///
+    /// Nodes run through their `NodePolicy`, as in `try_compute`. Once a node fails, the
+    /// pipeline shuts down and its error is returned; `sink` keeps the outputs of the items
+    /// finished before.
+    pub fn compute_pipelined<I, F>(
+        &self,
+        inputs: I,
+        capacity: usize,
+        mut sink: F,
+    ) -> Result<(), ComputeGraphErrors>
+    where
+        I: IntoIterator<Item = In>,
+        I::IntoIter: Send,
+        F: FnMut(Out),
+    {
+        let mut stages = Vec::new();
+        let mut input_senders = Vec::new();
+        let previous_effects = previous_effects(&self.nodes);
+        for (node, previous_effect) in self.nodes.iter().zip(previous_effects) {
+            let mut inputs = Vec::new();
+            for input in node.inputs.iter() {
+                let (tx, rx) = sync_channel(capacity);
+                let producer: &mut StageChannels<In> = &mut stages[*input];
+                producer.outputs.push(tx);
+                inputs.push(rx);
+            }
+            let mut waits = Vec::new();
+            for before in node.after.iter().chain(previous_effect.iter()) {
+                let (tx, rx) = sync_channel(capacity);
+                stages[*before].signals.push(tx);
+                waits.push(rx);
+            }
+            // Nodes without inputs still need one tick per streamed item
+            let graph_input = (node.connected_to_input || node.inputs.is_empty()).then(|| {
+                let (tx, rx) = sync_channel(capacity);
+                input_senders.push(tx);
+                rx
+            });
+            stages.push(StageChannels {
+                inputs,
+                graph_input,
+                outputs: Vec::new(),
+                waits,
+                signals: Vec::new(),
+            });
+        }
+        let (output_sender, output_receiver) = sync_channel(capacity);
+        stages
+            .last_mut()
+            .expect("compute graph has no nodes")
+            .outputs
+            .push(output_sender);
+
+        thread::scope(|scope| {
+            let inputs = inputs.into_iter();
+            scope.spawn(move || {
+                for input in inputs {
+                    for sender in input_senders.iter() {
+                        if sender.send(input.clone()).is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+
+            // A stage returns when a channel closes, so a failing node shuts down the others
+            let mut handles = Vec::with_capacity(self.nodes.len());
+            for (index, (node, stage)) in self.nodes.iter().zip(stages).enumerate() {
+                handles.push(scope.spawn(move || {
+                    let mut output = node.func.init_output();
+                    let mut input_hash = None;
+                    loop {
+                        let mut values = Vec::with_capacity(stage.inputs.len());
+                        for receiver in stage.inputs.iter() {
+                            match receiver.recv() {
+                                Ok(value) => values.push(value),
+                                Err(_) => return Ok(()),
+                            }
+                        }
+                        if stage.waits.iter().any(|receiver| receiver.recv().is_err()) {
+                            return Ok(());
+                        }
+                        let graph_input = match stage.graph_input.as_ref().map(|rx| rx.recv()) {
+                            Some(Ok(input)) => Some(input),
+                            Some(Err(_)) => return Ok(()),
+                            None => None,
+                        };
+
+                        if node.func.input_type() == TypeId::of::<()>() {
+                            self.run_node(index, &[], output.as_mut(), &mut input_hash)?;
+                        } else {
+                            let mut refs = values
+                                .iter()
+                                .map(|value| value.as_ref() as &dyn Any)
+                                .collect::<Vec<_>>();
+                            if node.connected_to_input {
+                                refs.extend(graph_input.as_ref().map(|input| input as &dyn Any));
+                            }
+                            self.run_node(index, &refs, output.as_mut(), &mut input_hash)?;
+                        }
+
+                        for sender in stage.outputs.iter() {
+                            if sender
+                                .send(node.func.clone_output(output.as_ref()))
+                                .is_err()
+                            {
+                                return Ok(());
+                            }
+                        }
+                        if stage.signals.iter().any(|sender| sender.send(()).is_err()) {
+                            return Ok(());
+                        }
+                    }
+                }));
+            }
+
+            for output in output_receiver {
+                sink(*output.downcast::<Out>().unwrap());
+            }
+            handles
+                .into_iter()
+                .try_for_each(|handle| handle.join().unwrap())
+        })
+    }
+
+    /// Convenience wrapper around `compute_pipelined` collecting all outputs.
+    pub fn compute_stream<I>(
+        &self,
+        inputs: I,
+        capacity: usize,
+    ) -> Result<Vec<Out>, ComputeGraphErrors>
+    where
+        I: IntoIterator<Item = In>,
+        I::IntoIter: Send,
+    {
+        let mut outputs = Vec::new();
+        self.compute_pipelined(inputs, capacity, |output| outputs.push(output))?;
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod pipeline_tests {
+    use crate::prelude::*;
+
+    #[derive(Clone)]
+    struct Reciprocal;
+
+    impl Compute for Reciprocal {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            self.try_compute(inputs).unwrap()
+        }
+        fn try_compute(&self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+            match *inputs[0] {
+                0.0 => Err("division by zero".to_string()),
+                value => Ok(1.0 / value),
+            }
+        }
+    }
+
+    #[test]
+    fn test_pipeline_matches_compute() -> Result<(), ComputeGraphErrors> {
+        //  Input ──┬── mul ── add ── output
+        //  Constant┴──────────┘
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("the_answer", Constant(42.0));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&add_handle, &mul_handle)?;
+        graph.add_input(&add_handle, &const_handle)?;
+        graph.add_input(&mul_handle, &const_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.set_output_node(&add_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+
+        let inputs = (0..200).map(|i| i as f64).collect::<Vec<_>>();
+        let expected = inputs
+            .iter()
+            .map(|input| compute_graph.compute(input))
+            .collect::<Vec<_>>();
+        for capacity in [1, 4] {
+            let outputs = compute_graph.compute_stream(inputs.iter().copied(), capacity)?;
+            assert_eq!(outputs, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_pipeline_failing_node() -> Result<(), ComputeGraphErrors> {
+        //  Input ── reciprocal ── add ── output
+        let mut graph = Graph::new();
+        let reciprocal_handle = graph.insert_node("reciprocal", Reciprocal);
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&add_handle, &reciprocal_handle)?;
+        graph.connect_to_input(&reciprocal_handle)?;
+        graph.set_output_node(&add_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+
+        let mut outputs = Vec::new();
+        let result =
+            compute_graph.compute_pipelined([4.0, 2.0, 0.0, 1.0], 1, |output| outputs.push(output));
+        assert!(matches!(result, Err(ComputeGraphErrors::NodeFailed(_))));
+        assert_eq!(outputs, [0.25, 0.5]);
+
+        // A fallback keeps the stream going, as with `try_compute`
+        graph.set_policy(&reciprocal_handle, NodePolicy::new().fallback_to(0.0))?;
+        let compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(
+            compute_graph.compute_stream([4.0, 0.0, 1.0], 1)?,
+            [0.25, 0.0, 1.0]
+        );
+        Ok(())
+    }
+}
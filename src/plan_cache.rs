@@ -0,0 +1,236 @@
+use crate::checkpoint::hash_operation;
+use crate::com_graph::{ComputeGraph, ComputeNode};
+use crate::graph::{ComputeGraphErrors, Graph, NodeHandle};
+use std::any::{Any, TypeId};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// The compute order of a built plan, with nodes referring to graph nodes by their canonical
+/// position. The compute objects always come from the graph being built, as the fingerprint
+/// doesn't cover configuration they keep outside their parameters.
+struct CachedPlan {
+    positions: Vec<usize>,
+    inputs: Vec<Vec<usize>>,
+    after: Vec<Vec<usize>>,
+}
+
+/// Built plans keyed by `Graph::fingerprint` and the edge types, so graphs rebuilt from the
+/// same template skip `build`. Use `PlanCache::global` or keep your own.
+#[derive(Default)]
+pub struct PlanCache {
+    plans: Mutex<HashMap<(u64, TypeId, TypeId), CachedPlan>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl PlanCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process-wide cache shared by every caller.
+    pub fn global() -> &'static PlanCache {
+        static GLOBAL: OnceLock<PlanCache> = OnceLock::new();
+        GLOBAL.get_or_init(PlanCache::new)
+    }
+
+    pub fn len(&self) -> usize {
+        self.plans.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.plans.lock().unwrap().clear();
+    }
+
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+impl Graph {
+    /// Hash of every node's name, operation, parameters and connections, and of the output
+    /// node. Graphs built from the same template share a fingerprint, whatever the order
+    /// their nodes were inserted in.
+    /// Configuration a node keeps outside its parameters only counts if the node
+    /// implements `Compute::fingerprint`.
+    pub fn fingerprint(&self) -> u64 {
+        let order = self.canonical_order();
+        let positions = order
+            .iter()
+            .enumerate()
+            .map(|(position, key)| (*key, position))
+            .collect::<HashMap<_, _>>();
+        let mut hasher = DefaultHasher::new();
+        for node in order.iter().map(|key| &self.nodes[*key]) {
+            node.name.hash(&mut hasher);
+            node.connected_to_input.hash(&mut hasher);
+            hash_operation(node.inner.as_ref(), &mut hasher);
+            for input in node.inputs.iter() {
+                positions[input].hash(&mut hasher);
+            }
+            node.ports.hash(&mut hasher);
+            for before in node.after.iter() {
+                positions[before].hash(&mut hasher);
+            }
+        }
+        self.output_node
+            .map(|key| positions[&key])
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Like `build`, but reuses the compute order from `cache` when a graph with the same
+    /// fingerprint was built before. The nodes themselves are always taken from `self`.
+    pub fn build_cached<In, Out>(
+        &mut self,
+        cache: &PlanCache,
+    ) -> Result<ComputeGraph<In, Out>, ComputeGraphErrors>
+    where
+        In: Any + Clone,
+        Out: Any + Clone,
+    {
+        self.check_limits()?;
+        let key = (self.fingerprint(), TypeId::of::<In>(), TypeId::of::<Out>());
+        let graph_keys = self.canonical_order();
+        if let Some(plan) = cache.plans.lock().unwrap().get(&key) {
+            cache.hits.fetch_add(1, Ordering::Relaxed);
+            let nodes = plan
+                .positions
+                .iter()
+                .zip(plan.inputs.iter().zip(plan.after.iter()))
+                .map(|(position, (inputs, after))| {
+                    let graph_key = graph_keys[*position];
+                    let node = &self.nodes[graph_key];
+                    ComputeNode {
+                        handle: NodeHandle {
+                            key: graph_key,
+                            graph_id: self.id,
+                        },
+                        name: node.name.clone(),
+                        connected_to_input: node.connected_to_input,
+                        inputs: inputs.clone(),
+                        after: after.clone(),
+                        func: node.inner.clone(),
+                        op_revision: node.op_revision,
+                        policy: node.policy.clone(),
+                        critical: node.critical,
+                        scheduling: node.scheduling,
+                        probed: node.probed,
+                    }
+                })
+                .collect();
+            return Ok(ComputeGraph::new(nodes));
+        }
+
+        cache.misses.fetch_add(1, Ordering::Relaxed);
+        let compute_graph = self.build::<In, Out>()?;
+        let graph_positions = graph_keys
+            .iter()
+            .enumerate()
+            .map(|(position, key)| (*key, position))
+            .collect::<HashMap<_, _>>();
+        let positions = compute_graph
+            .nodes
+            .iter()
+            .map(|node| graph_positions[&node.handle.key])
+            .collect();
+        cache.plans.lock().unwrap().insert(
+            key,
+            CachedPlan {
+                positions,
+                inputs: compute_graph
+                    .nodes
+                    .iter()
+                    .map(|node| node.inputs.clone())
+                    .collect(),
+                after: compute_graph
+                    .nodes
+                    .iter()
+                    .map(|node| node.after.clone())
+                    .collect(),
+            },
+        );
+        Ok(compute_graph)
+    }
+}
+
+#[cfg(test)]
+mod plan_cache_tests {
+    use crate::prelude::*;
+
+    fn template(gain: f64) -> (Graph, NodeHandle) {
+        let mut graph = Graph::new();
+        let gain_handle = graph.insert_node("gain", Constant(gain));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &gain_handle).unwrap();
+        graph.connect_to_input(&mul_handle).unwrap();
+        graph.set_output_node(&mul_handle).unwrap();
+        (graph, gain_handle.erase())
+    }
+
+    #[test]
+    fn test_build_cached() -> Result<(), ComputeGraphErrors> {
+        let cache = PlanCache::new();
+        let (mut first, _) = template(2.0);
+        let (mut second, gain_handle) = template(2.0);
+        let (mut other, _) = template(3.0);
+        assert_eq!(first.fingerprint(), second.fingerprint());
+        assert_ne!(first.fingerprint(), other.fingerprint());
+
+        assert_eq!(first.build_cached::<f64, f64>(&cache)?.compute(&4.0), 8.0);
+        let mut reused = second.build_cached::<f64, f64>(&cache)?;
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+        assert_eq!(reused.compute(&4.0), 8.0);
+        // Handles of the requesting graph resolve in the reused plan
+        reused.set_param(&gain_handle, "value", 5.0)?;
+        assert_eq!(reused.compute(&4.0), 20.0);
+
+        assert_eq!(other.build_cached::<f64, f64>(&cache)?.compute(&4.0), 12.0);
+        assert_eq!(cache.len(), 2);
+        assert!(first.build_cached::<f64, bool>(&cache).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_cached_takes_nodes_from_graph() -> Result<(), ComputeGraphErrors> {
+        // Same fingerprint, as string constants have no parameters
+        let shout = |word: &str| {
+            let mut graph = Graph::new();
+            let word_handle = graph.insert_node("word", Constant(word.to_string()));
+            let upper_handle = graph.insert_node("upper", ToUpper);
+            graph.add_input(&upper_handle, &word_handle).unwrap();
+            graph.set_output_node(&upper_handle).unwrap();
+            graph
+        };
+        let cache = PlanCache::new();
+        let (mut alpha, mut beta) = (shout("alpha"), shout("beta"));
+        assert_eq!(
+            alpha.build_cached::<(), String>(&cache)?.compute(&()),
+            "ALPHA"
+        );
+        assert_eq!(
+            beta.build_cached::<(), String>(&cache)?.compute(&()),
+            "BETA"
+        );
+        assert_eq!(cache.hits(), 1);
+
+        // Limits are checked on hits as well
+        beta.set_limits(GraphLimits::new().max_nodes(1));
+        assert!(matches!(
+            beta.build_cached::<(), String>(&cache),
+            Err(ComputeGraphErrors::LimitExceeded(_))
+        ));
+        Ok(())
+    }
+}
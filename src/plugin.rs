@@ -0,0 +1,208 @@
+use crate::compute::Compute;
+use crate::graph::{ComputeGraphErrors, Graph};
+use crate::typed::TypedNodeHandle;
+use std::ffi::c_void;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Version of the `PluginNode` layout. Bumped on every incompatible change, so hosts can
+/// turn away plugins built against another version instead of crashing.
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Returned by `PluginNode::compute` when the node succeeded.
+pub const PLUGIN_OK: i32 = 0;
+/// Returned by `PluginNode::compute` when the node panicked.
+pub const PLUGIN_PANICKED: i32 = -1;
+
+/// C-compatible node for plugins, so nodes built with another compiler version, or in
+/// another language, can be loaded. Plugin nodes read and write `f64`s. A plugin library
+/// exports a function returning one of these, which the host finds with its loader of
+/// choice and passes to `Graph::insert_plugin_node`.
+///
+/// The state must be safe to use from several threads at once.
+#[repr(C)]
+pub struct PluginNode {
+    pub abi_version: u32,
+    /// Opaque state owned by the plugin.
+    pub state: *mut c_void,
+    /// Writes the output for the `len` inputs at `inputs` to `output`. Returns `PLUGIN_OK`,
+    /// or any other value as an error code.
+    pub compute: unsafe extern "C" fn(
+        state: *const c_void,
+        inputs: *const f64,
+        len: usize,
+        output: *mut f64,
+    ) -> i32,
+    pub clone: unsafe extern "C" fn(state: *const c_void) -> *mut c_void,
+    pub drop: unsafe extern "C" fn(state: *mut c_void),
+}
+
+impl PluginNode {
+    /// Exports `node` through the plugin ABI, for plugins written in Rust.
+    pub fn new<C>(node: C) -> Self
+    where
+        C: Compute<In = f64, Out = f64> + 'static,
+    {
+        Self {
+            abi_version: PLUGIN_ABI_VERSION,
+            state: Box::into_raw(Box::new(node)) as *mut c_void,
+            compute: compute_exported::<C>,
+            clone: clone_exported::<C>,
+            drop: drop_exported::<C>,
+        }
+    }
+}
+
+unsafe extern "C" fn compute_exported<C: Compute<In = f64, Out = f64>>(
+    state: *const c_void,
+    inputs: *const f64,
+    len: usize,
+    output: *mut f64,
+) -> i32 {
+    let node = &*(state as *const C);
+    let inputs = std::slice::from_raw_parts(inputs, len);
+    // Unwinding across the ABI boundary would abort the host
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        node.try_compute(&inputs.iter().collect::<Vec<_>>())
+    }));
+    match result {
+        Ok(Ok(value)) => {
+            *output = value;
+            PLUGIN_OK
+        }
+        Ok(Err(_)) => 1,
+        Err(_) => PLUGIN_PANICKED,
+    }
+}
+
+unsafe extern "C" fn clone_exported<C: Compute>(state: *const c_void) -> *mut c_void {
+    let node = &*(state as *const C);
+    Box::into_raw(Box::new(node.clone())) as *mut c_void
+}
+
+unsafe extern "C" fn drop_exported<C>(state: *mut c_void) {
+    drop(Box::from_raw(state as *mut C));
+}
+
+/// Host side of a `PluginNode`.
+struct PluginCompute {
+    node: PluginNode,
+}
+
+// `PluginNode` requires thread-safe state
+unsafe impl Send for PluginCompute {}
+unsafe impl Sync for PluginCompute {}
+
+impl Clone for PluginCompute {
+    fn clone(&self) -> Self {
+        Self {
+            node: PluginNode {
+                state: unsafe { (self.node.clone)(self.node.state) },
+                ..self.node
+            },
+        }
+    }
+}
+
+impl Drop for PluginCompute {
+    fn drop(&mut self) {
+        unsafe { (self.node.drop)(self.node.state) }
+    }
+}
+
+impl Compute for PluginCompute {
+    type In = f64;
+    type Out = f64;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.try_compute(inputs).unwrap()
+    }
+
+    fn try_compute(&self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        let inputs = inputs.iter().map(|input| **input).collect::<Vec<_>>();
+        let mut output = 0.0;
+        let code = unsafe {
+            (self.node.compute)(self.node.state, inputs.as_ptr(), inputs.len(), &mut output)
+        };
+        match code {
+            PLUGIN_OK => Ok(output),
+            PLUGIN_PANICKED => Err("plugin node panicked".to_string()),
+            code => Err(format!("plugin node failed with code {}", code)),
+        }
+    }
+}
+
+impl Graph {
+    /// Inserts a node from a plugin, after checking it was built for this version of the
+    /// plugin ABI. Incompatible nodes are rejected without calling into them.
+    pub fn insert_plugin_node<N: Into<String>>(
+        &mut self,
+        name: N,
+        node: PluginNode,
+    ) -> Result<TypedNodeHandle<f64, f64>, ComputeGraphErrors> {
+        // `abi_version` comes first in every version of the layout, so it can always be read.
+        // The state of an incompatible node is leaked, as its `drop` can't be trusted either.
+        if node.abi_version != PLUGIN_ABI_VERSION {
+            return Err(ComputeGraphErrors::IncompatibleNewNode(
+                format!(
+                    "plugin node '{}' uses ABI version {}, expected {}",
+                    name.into(),
+                    node.abi_version,
+                    PLUGIN_ABI_VERSION
+                )
+                .into(),
+            ));
+        }
+        Ok(self.insert_node(name, PluginCompute { node }))
+    }
+}
+
+#[cfg(test)]
+mod plugin_tests {
+    use crate::prelude::*;
+
+    #[derive(Clone)]
+    struct Checked;
+
+    impl Compute for Checked {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            self.try_compute(inputs).unwrap()
+        }
+        fn try_compute(&self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+            match inputs {
+                [input] if **input >= 0.0 => Ok(input.sqrt()),
+                _ => Err("expected one non-negative input".to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_plugin_nodes() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let add_handle =
+            graph.insert_plugin_node("add", PluginNode::new(AddInputs::<f64>::new()))?;
+        let sqrt_handle = graph.insert_plugin_node("sqrt", PluginNode::new(Checked))?;
+        let rate_handle = graph.insert_node("rate", Constant(7.0));
+        graph.add_input(&add_handle, &rate_handle)?;
+        graph.connect_to_input(&add_handle)?;
+        graph.add_input(&sqrt_handle, &add_handle)?;
+        graph.disconnect_from_input(&sqrt_handle)?;
+        graph.set_output_node(&sqrt_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(compute_graph.try_compute(&2.0)?, 3.0);
+        assert!(matches!(
+            compute_graph.try_compute(&-8.0),
+            Err(ComputeGraphErrors::NodeFailed(message)) if message.contains("code 1")
+        ));
+        // Clones get their own copy of the plugin state
+        assert_eq!(compute_graph.clone().try_compute(&9.0)?, 4.0);
+
+        let mut future = PluginNode::new(Checked);
+        future.abi_version = PLUGIN_ABI_VERSION + 1;
+        assert!(matches!(
+            graph.insert_plugin_node("future", future),
+            Err(ComputeGraphErrors::IncompatibleNewNode(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,283 @@
+use crate::compute::{same_primitive, InnerCompute, Value};
+use crate::graph::{ComputeGraphErrors, Graph, NodeHandle};
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// How a node evaluation went under its policy. `fallback` holds the error
+/// of the last attempt when a fallback output was used.
+pub(crate) struct PolicyOutcome {
+    pub(crate) retries: u32,
+    pub(crate) fallback: Option<String>,
+    /// The node's output was kept from the previous evaluation, see `Compute::cacheable`.
+    pub(crate) cached: bool,
+}
+
+impl PolicyOutcome {
+    pub(crate) fn cached() -> Self {
+        Self {
+            retries: 0,
+            fallback: None,
+            cached: true,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+enum Fallback {
+    #[default]
+    Error,
+    Stale,
+    Value(Arc<dyn Any + Send + Sync>),
+}
+
+/// What the executor does when a node's `Compute::try_compute` fails.
+/// By default the error is returned without retrying.
+#[derive(Clone, Default)]
+pub struct NodePolicy {
+    retries: u32,
+    backoff: Duration,
+    fallback: Fallback,
+}
+
+impl NodePolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retries up to `retries` times, sleeping `backoff` before the first retry and
+    /// doubling it before each following one.
+    pub fn retry(mut self, retries: u32, backoff: Duration) -> Self {
+        self.retries = retries;
+        self.backoff = backoff;
+        self
+    }
+
+    /// Keeps the node's output from the previous evaluation when all attempts fail.
+    pub fn fallback_to_stale(mut self) -> Self {
+        self.fallback = Fallback::Stale;
+        self
+    }
+
+    /// Outputs `value` when all attempts fail.
+    pub fn fallback_to<T: Value>(mut self, value: T) -> Self {
+        self.fallback = Fallback::Value(Arc::new(value));
+        self
+    }
+
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Whether both policies behave the same, comparing fallback values with `same_primitive`.
+    pub(crate) fn same_as(&self, other: &NodePolicy) -> bool {
+        let same_fallback = match (&self.fallback, &other.fallback) {
+            (Fallback::Error, Fallback::Error) | (Fallback::Stale, Fallback::Stale) => true,
+            (Fallback::Value(value), Fallback::Value(other)) => {
+                Arc::ptr_eq(value, other) || same_primitive(value.as_ref(), other.as_ref())
+            }
+            _ => false,
+        };
+        self.retries == other.retries && self.backoff == other.backoff && same_fallback
+    }
+
+    pub(crate) fn fallback_type(&self) -> Option<TypeId> {
+        match &self.fallback {
+            Fallback::Value(value) => Some((**value).type_id()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn run(
+        &self,
+        node_name: &str,
+        func: &dyn InnerCompute,
+        inputs: &[&dyn Any],
+        output: &mut dyn Any,
+    ) -> Result<PolicyOutcome, ComputeGraphErrors> {
+        let (retries, error) =
+            self.attempt(output, |output| func.inner_try_compute(inputs, output));
+        self.finish(node_name, func, retries, error, output)
+    }
+
+    /// Like `run`, through the stateful `Compute::try_compute_mut`.
+    pub(crate) fn run_mut(
+        &self,
+        node_name: &str,
+        func: &mut dyn InnerCompute,
+        inputs: &[&dyn Any],
+        output: &mut dyn Any,
+    ) -> Result<PolicyOutcome, ComputeGraphErrors> {
+        let (retries, error) =
+            self.attempt(output, |output| func.inner_try_compute_mut(inputs, output));
+        self.finish(node_name, func, retries, error, output)
+    }
+
+    /// Calls `compute` until it succeeds or the retries run out, returning the number of
+    /// retries and the last error.
+    fn attempt(
+        &self,
+        output: &mut dyn Any,
+        mut compute: impl FnMut(&mut dyn Any) -> Result<(), String>,
+    ) -> (u32, Option<String>) {
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+        loop {
+            match compute(output) {
+                Ok(()) => return (attempt, None),
+                Err(error) if attempt == self.retries => return (attempt, Some(error)),
+                Err(_) => {
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    fn finish(
+        &self,
+        node_name: &str,
+        func: &dyn InnerCompute,
+        retries: u32,
+        error: Option<String>,
+        output: &mut dyn Any,
+    ) -> Result<PolicyOutcome, ComputeGraphErrors> {
+        let Some(error) = error else {
+            return Ok(PolicyOutcome {
+                retries,
+                fallback: None,
+                cached: false,
+            });
+        };
+        match &self.fallback {
+            Fallback::Error => {
+                return Err(ComputeGraphErrors::format_node_failed(node_name, &error))
+            }
+            Fallback::Stale => {}
+            Fallback::Value(value) => func.copy_output(value.as_ref(), output),
+        }
+        Ok(PolicyOutcome {
+            retries,
+            fallback: Some(error),
+            cached: false,
+        })
+    }
+}
+
+impl Graph {
+    /// Sets how the executor handles failures of `node_handle`.
+    pub fn set_policy(
+        &mut self,
+        node_handle: &NodeHandle,
+        policy: NodePolicy,
+    ) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        let node = &self.nodes[node_handle.key];
+        if let Some(fallback_type) = policy.fallback_type() {
+            if fallback_type != node.inner.output_type() {
+                return Err(ComputeGraphErrors::WrongTypes(
+                    format!(
+                        "fallback value of '{}' does not match output type '{}'",
+                        node.name,
+                        node.inner.output_type_name()
+                    )
+                    .into(),
+                ));
+            }
+        }
+        self.node_mut(node_handle.key).unwrap().policy = policy;
+        self.touch();
+        let key = node_handle.key;
+        self.record(|graph| format!("set policy of '{}'", graph.name_for_log(key)));
+        Ok(())
+    }
+
+    pub fn get_policy(&self, node_handle: &NodeHandle) -> Result<&NodePolicy, ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        Ok(&self.nodes[node_handle.key].policy)
+    }
+}
+
+#[cfg(test)]
+mod policy_tests {
+    use crate::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[derive(Clone)]
+    struct Flaky {
+        failures: usize,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Compute for Flaky {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            self.try_compute(inputs).unwrap()
+        }
+        fn try_compute(&self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err("service unavailable".to_string());
+            }
+            Ok(*inputs[0] + 1.0)
+        }
+    }
+
+    fn flaky_graph(failures: usize) -> (Graph, NodeHandle, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut graph = Graph::new();
+        let flaky_handle = graph.insert_node(
+            "flaky",
+            Flaky {
+                failures,
+                calls: calls.clone(),
+            },
+        );
+        graph.set_output_node(&flaky_handle).unwrap();
+        (graph, flaky_handle.erase(), calls)
+    }
+
+    #[test]
+    fn test_retry() -> Result<(), ComputeGraphErrors> {
+        let (mut graph, flaky_handle, calls) = flaky_graph(2);
+        graph.set_policy(
+            &flaky_handle,
+            NodePolicy::new().retry(2, Duration::from_millis(1)),
+        )?;
+        let compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(compute_graph.try_compute(&1.0)?, 2.0);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let (mut graph, flaky_handle, _) = flaky_graph(2);
+        graph.set_policy(&flaky_handle, NodePolicy::new().retry(1, Duration::ZERO))?;
+        assert!(matches!(
+            graph.build::<f64, f64>()?.try_compute(&1.0),
+            Err(ComputeGraphErrors::NodeFailed(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback() -> Result<(), ComputeGraphErrors> {
+        let (mut graph, flaky_handle, _) = flaky_graph(1);
+        graph.set_policy(&flaky_handle, NodePolicy::new().fallback_to(-1.0))?;
+        let compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(compute_graph.try_compute(&1.0)?, -1.0);
+        assert_eq!(compute_graph.try_compute(&1.0)?, 2.0);
+
+        let (mut graph, flaky_handle, _) = flaky_graph(2);
+        assert!(graph
+            .set_policy(&flaky_handle, NodePolicy::new().fallback_to(true))
+            .is_err());
+        graph.set_policy(&flaky_handle, NodePolicy::new().fallback_to_stale())?;
+        let compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(compute_graph.try_compute(&1.0)?, 0.0);
+        assert_eq!(compute_graph.try_compute(&1.0)?, 0.0);
+        assert_eq!(compute_graph.try_compute(&3.0)?, 4.0);
+        Ok(())
+    }
+}
@@ -0,0 +1,328 @@
+use crate::compute::{InnerCompute, Value};
+use crate::formula::Expr;
+use crate::graph::{ComputeGraphErrors, Graph, GraphKey, NodeHandle};
+use crate::params::Parameters;
+use crate::preview::Detail;
+use crate::typed::TypedNodeHandle;
+use std::any::{type_name, Any, TypeId};
+use std::hash::Hasher;
+
+/// Tuple of the input types of a `PortCompute` node, one element per port.
+pub trait Ports: Value {
+    /// The ports as references to the connected outputs.
+    type Refs<'a>;
+    fn types() -> Vec<(TypeId, &'static str)>;
+    fn downcast<'a>(inputs: &[&'a dyn Any]) -> Self::Refs<'a>;
+}
+
+macro_rules! tuple_ports {
+    ($(($($t:ident $i:tt),+))*) => {$(
+        impl<$($t: Value),+> Ports for ($($t,)+) {
+            type Refs<'a> = ($(&'a $t,)+);
+            fn types() -> Vec<(TypeId, &'static str)> {
+                vec![$((TypeId::of::<$t>(), type_name::<$t>())),+]
+            }
+            fn downcast<'a>(inputs: &[&'a dyn Any]) -> Self::Refs<'a> {
+                ($(inputs[$i].downcast_ref::<$t>().unwrap(),)+)
+            }
+        }
+    )*};
+}
+tuple_ports! {
+    (A 0)
+    (A 0, B 1)
+    (A 0, B 1, C 2)
+    (A 0, B 1, C 2, D 3)
+    (A 0, B 1, C 2, D 3, E 4)
+    (A 0, B 1, C 2, D 3, E 4, F 5)
+}
+
+/// Node with one input per port, each of its own type, like `Lerp(a: f64, b: f64, t: f32)`.
+/// Inserted with `Graph::insert_port_node` and wired with `Graph::add_input_port`.
+pub trait PortCompute: Clone + Send + Sync {
+    type Ports: Ports;
+    type Out: Value;
+    fn compute(&self, ports: <Self::Ports as Ports>::Refs<'_>) -> Self::Out;
+
+    /// Fallible variant of `compute`, see `Compute::try_compute`.
+    fn try_compute(&self, ports: <Self::Ports as Ports>::Refs<'_>) -> Result<Self::Out, String> {
+        Ok(self.compute(ports))
+    }
+
+    /// Stateful variant of `try_compute`, see `Compute::try_compute_mut`.
+    fn try_compute_mut(
+        &mut self,
+        ports: <Self::Ports as Ports>::Refs<'_>,
+    ) -> Result<Self::Out, String> {
+        self.try_compute(ports)
+    }
+
+    /// See `Compute::fingerprint`.
+    fn fingerprint(&self, _state: &mut dyn Hasher) {}
+}
+
+#[derive(Clone)]
+struct Ported<P>(P);
+
+impl<P: PortCompute + 'static> InnerCompute for Ported<P> {
+    fn init_output(&self) -> Box<dyn Any + Send + Sync> {
+        Box::new(P::Out::default())
+    }
+    fn clone_output(&self, output: &dyn Any) -> Box<dyn Any + Send + Sync> {
+        Box::new(output.downcast_ref::<P::Out>().unwrap().clone())
+    }
+    fn copy_output(&self, from: &dyn Any, to: &mut dyn Any) {
+        to.downcast_mut::<P::Out>()
+            .unwrap()
+            .clone_from(from.downcast_ref::<P::Out>().unwrap());
+    }
+    fn input_type(&self) -> TypeId {
+        TypeId::of::<P::Ports>()
+    }
+    fn output_type(&self) -> TypeId {
+        TypeId::of::<P::Out>()
+    }
+    fn input_type_name(&self) -> &'static str {
+        type_name::<P::Ports>()
+    }
+    fn output_type_name(&self) -> &'static str {
+        type_name::<P::Out>()
+    }
+    fn type_name(&self) -> &'static str {
+        type_name::<P>()
+    }
+    fn inner_try_compute(&self, inputs: &[&dyn Any], output: &mut dyn Any) -> Result<(), String> {
+        *output.downcast_mut::<P::Out>().unwrap() =
+            self.0.try_compute(P::Ports::downcast(inputs))?;
+        Ok(())
+    }
+    fn inner_try_compute_mut(
+        &mut self,
+        inputs: &[&dyn Any],
+        output: &mut dyn Any,
+    ) -> Result<(), String> {
+        *output.downcast_mut::<P::Out>().unwrap() =
+            self.0.try_compute_mut(P::Ports::downcast(inputs))?;
+        Ok(())
+    }
+    fn as_any(&self) -> &dyn Any {
+        &self.0
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        &mut self.0
+    }
+    fn same_constant(&self, _other: &dyn InnerCompute) -> bool {
+        false
+    }
+    fn parameters(&self) -> Option<&dyn Parameters> {
+        None
+    }
+    fn parameters_mut(&mut self) -> Option<&mut dyn Parameters> {
+        None
+    }
+    fn fingerprint(&self, state: &mut dyn Hasher) {
+        self.0.fingerprint(state)
+    }
+    fn formula(&self, _inputs: &[Expr]) -> Option<Expr> {
+        None
+    }
+    fn set_detail(&mut self, _detail: Detail) {}
+    fn port_types(&self) -> Vec<(TypeId, &'static str)> {
+        P::Ports::types()
+    }
+    fn input_name(&self) -> Option<&str> {
+        None
+    }
+    fn arity(&self) -> (usize, Option<usize>) {
+        // Every port is checked on its own by `Graph::port_inputs`
+        (0, None)
+    }
+    fn cacheable(&self) -> bool {
+        false
+    }
+    fn hash_inputs(&self, _inputs: &[&dyn Any], _state: &mut dyn Hasher) -> bool {
+        false
+    }
+}
+
+impl Graph {
+    pub fn insert_port_node<N, P>(
+        &mut self,
+        name: N,
+        compute_object: P,
+    ) -> TypedNodeHandle<P::Ports, P::Out>
+    where
+        N: Into<String>,
+        P: PortCompute + 'static,
+    {
+        for (type_id, type_name) in P::Ports::types() {
+            self.type_names.insert(type_id, type_name);
+        }
+        TypedNodeHandle::new(self.insert_inner(name.into(), Box::new(Ported(compute_object))))
+    }
+
+    /// Connects the output of `input_node_handle` to input `port` of a `PortCompute` node,
+    /// replacing what was connected to that port.
+    pub fn add_input_port(
+        &mut self,
+        node_handle: &NodeHandle,
+        port: usize,
+        input_node_handle: &NodeHandle,
+    ) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        self.check_handle(input_node_handle)?;
+        let node = &self.nodes[node_handle.key];
+        let port_types = node.inner.port_types();
+        let Some((port_type, port_type_name)) = port_types.get(port).copied() else {
+            return Err(ComputeGraphErrors::InvalidParameter(
+                format!("'{}' has no input port {}", node.name, port).into(),
+            )
+            .with_node(*node_handle));
+        };
+        let input_node = &self.nodes[input_node_handle.key];
+        let output_type = input_node.inner.output_type();
+        if output_type != port_type {
+            let output_type_name = input_node.inner.output_type_name();
+            return Err(ComputeGraphErrors::WrongTypes(
+                format!(
+                    "'{}' port {} type '{}' does not match '{}' output type '{}'",
+                    node.name, port, port_type_name, input_node.name, output_type_name
+                )
+                .into(),
+            )
+            .with_node(*node_handle)
+            .with_node(*input_node_handle)
+            .with_type(port_type, port_type_name)
+            .with_type(output_type, output_type_name));
+        }
+        self.check_new_edge(node_handle, input_node_handle)?;
+
+        let node = self.node_mut(node_handle.key).unwrap();
+        node.retain_inputs_with_ports(|_, input_port| input_port != port);
+        node.inputs.push(input_node_handle.key);
+        node.ports.push(port);
+        node.connected_to_input = false;
+        self.touch();
+        let (key, input_key) = (node_handle.key, input_node_handle.key);
+        self.record(|graph| {
+            format!(
+                "connected '{}' to port {} of '{}'",
+                graph.name_for_log(input_key),
+                port,
+                graph.name_for_log(key)
+            )
+        });
+        Ok(())
+    }
+
+    /// Inputs of `key` in the order its compute object reads them, which for a
+    /// `PortCompute` node is by port. Fails if a port isn't connected.
+    pub(crate) fn port_inputs(&self, key: GraphKey) -> Result<Vec<GraphKey>, ComputeGraphErrors> {
+        let node = &self.nodes[key];
+        let port_count = node.inner.port_types().len();
+        if port_count == 0 {
+            return Ok(node.inputs.clone());
+        }
+        let mut inputs = Vec::with_capacity(port_count);
+        for port in 0..port_count {
+            match node.ports.iter().position(|input_port| *input_port == port) {
+                Some(index) => inputs.push(node.inputs[index]),
+                None => {
+                    return Err(ComputeGraphErrors::InvalidParameter(
+                        format!("port {} of '{}' is not connected", port, node.name).into(),
+                    )
+                    .with_node(NodeHandle {
+                        key,
+                        graph_id: self.id,
+                    }))
+                }
+            }
+        }
+        Ok(inputs)
+    }
+}
+
+#[cfg(test)]
+mod ports_tests {
+    use crate::prelude::*;
+
+    #[derive(Clone)]
+    struct Lerp;
+
+    impl PortCompute for Lerp {
+        type Ports = (f64, f64, f32);
+        type Out = f64;
+        fn compute(&self, (a, b, t): (&f64, &f64, &f32)) -> f64 {
+            a + (b - a) * *t as f64
+        }
+    }
+
+    #[test]
+    fn test_port_inputs() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let from_handle = graph.insert_node("from", Constant(10.0));
+        let to_handle = graph.insert_node("to", AddInputs::<f64>::new());
+        let t_handle = graph.insert_node("t", Constant(0.25f32));
+        let lerp_handle = graph.insert_port_node("lerp", Lerp);
+        graph.add_input_port(&lerp_handle, 2, &t_handle)?;
+        graph.add_input_port(&lerp_handle, 0, &from_handle)?;
+        assert!(matches!(
+            graph.add_input_port(&lerp_handle, 1, &t_handle),
+            Err(ComputeGraphErrors::WrongTypes(_))
+        ));
+        assert!(graph.add_input_port(&lerp_handle, 3, &to_handle).is_err());
+        graph.set_output_node(&lerp_handle)?;
+        assert!(matches!(
+            graph.build::<f64, f64>(),
+            Err(ComputeGraphErrors::InvalidParameter(message)) if message.contains("port 1")
+        ));
+
+        graph.add_input_port(&lerp_handle, 1, &from_handle)?;
+        graph.add_input_port(&lerp_handle, 1, &to_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(compute_graph.compute(&30.0), 15.0);
+        graph.check_invariants()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_port_nodes_reject_plain_inputs() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let pack_handle = graph.insert_node("pack", Constant((10.0, 20.0, 0.5f32)));
+        let lerp_handle = graph.insert_port_node("lerp", Lerp);
+        assert!(matches!(
+            graph.add_input(&lerp_handle, &pack_handle),
+            Err(ComputeGraphErrors::WrongTypes(_))
+        ));
+        graph.check_invariants()?;
+        Ok(())
+    }
+
+    #[derive(Clone)]
+    struct Offset(f64);
+
+    impl PortCompute for Offset {
+        type Ports = (f64,);
+        type Out = f64;
+        fn compute(&self, (x,): (&f64,)) -> f64 {
+            x + self.0
+        }
+        fn fingerprint(&self, state: &mut dyn std::hash::Hasher) {
+            state.write_u64(self.0.to_bits());
+        }
+    }
+
+    #[test]
+    fn test_port_node_fingerprints() -> Result<(), ComputeGraphErrors> {
+        let fingerprint = |offset| -> Result<u64, ComputeGraphErrors> {
+            let mut graph = Graph::new();
+            let offset_handle = graph.insert_port_node("offset", Offset(offset));
+            let x_handle = graph.insert_node("x", Constant(1.0));
+            graph.add_input_port(&offset_handle, 0, &x_handle)?;
+            graph.set_output_node(&offset_handle)?;
+            Ok(graph.build::<(), f64>()?.node_fingerprints()[1])
+        };
+        assert_ne!(fingerprint(1.0)?, fingerprint(2.0)?);
+        Ok(())
+    }
+}
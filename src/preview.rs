@@ -0,0 +1,167 @@
+use crate::com_graph::ComputeGraph;
+#[cfg(feature = "rayon")]
+use crate::compute::Value;
+#[cfg(feature = "rayon")]
+use crate::graph::ComputeGraphErrors;
+#[cfg(feature = "rayon")]
+use crate::grid::{CancelToken, Grid, GridRender};
+
+/// Level of detail hint passed to every node by `ComputeGraph::set_detail`. Level 0 is full
+/// detail, and every level above it halves the sampling resolution of a preview.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Detail {
+    pub level: u32,
+}
+
+impl Detail {
+    pub const FULL: Detail = Detail { level: 0 };
+
+    pub fn new(level: u32) -> Self {
+        Self { level }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.level == 0
+    }
+
+    /// Fraction of the full resolution sampled at this level.
+    pub fn scale(&self) -> f64 {
+        0.5f64.powi(self.level as i32)
+    }
+
+    /// `full` octaves reduced by one per level, keeping at least one.
+    pub fn octaves(&self, full: u32) -> u32 {
+        full.saturating_sub(self.level).max(1)
+    }
+}
+
+impl<In, Out> ComputeGraph<In, Out> {
+    /// Passes `detail` to every node, see `Compute::set_detail`.
+    pub fn set_detail(&mut self, detail: Detail) {
+        self.cache_generation += 1;
+        for node in self.nodes.iter_mut() {
+            node.func.set_detail(detail);
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<Out> ComputeGraph<[f64; 2], Out>
+where
+    Out: Value,
+{
+    /// Evaluates `grid` progressively, from every `2^coarsest`th point at detail `coarsest`
+    /// down to every point at full detail. After each level `on_level` gets the outputs
+    /// upsampled to the full grid, so an editor can show coarse feedback right away.
+    /// Stops before the next tile once `cancel` is set, returning false.
+    /// The nodes are left at full detail.
+    pub fn preview_grid<F>(
+        &mut self,
+        grid: &Grid,
+        coarsest: u32,
+        tile_size: usize,
+        cancel: &CancelToken,
+        mut on_level: F,
+    ) -> Result<bool, ComputeGraphErrors>
+    where
+        F: FnMut(Detail, &[Out]),
+    {
+        let mut complete = true;
+        for level in (0..=coarsest).rev() {
+            let detail = Detail::new(level);
+            let stride = 1 << level;
+            let coarse = Grid::new(
+                grid.origin,
+                [grid.step[0] * stride as f64, grid.step[1] * stride as f64],
+                grid.width.div_ceil(stride),
+                grid.height.div_ceil(stride),
+            );
+            self.set_detail(detail);
+            let mut render = GridRender::new(coarse, tile_size);
+            let rendered = self.render_grid(&mut render, cancel, |_| {});
+            complete = match rendered {
+                Ok(complete) => complete,
+                Err(err) => {
+                    self.set_detail(Detail::FULL);
+                    return Err(err);
+                }
+            };
+            if !complete {
+                break;
+            }
+            if stride == 1 {
+                on_level(detail, render.outputs());
+                continue;
+            }
+            let values = render.outputs();
+            let upsampled = (0..grid.height)
+                .flat_map(|row| {
+                    (0..grid.width).map(move |column| {
+                        values[row / stride * coarse.width + column / stride].clone()
+                    })
+                })
+                .collect::<Vec<_>>();
+            on_level(detail, &upsampled);
+        }
+        self.set_detail(Detail::FULL);
+        Ok(complete)
+    }
+}
+
+#[cfg(all(test, feature = "rayon"))]
+mod preview_tests {
+    use crate::prelude::*;
+
+    /// Height field whose detail shows up in its output.
+    #[derive(Clone, Copy, Default)]
+    struct Terrain {
+        octaves: u32,
+    }
+
+    impl Compute for Terrain {
+        type In = [f64; 2];
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            let [x, y] = *inputs[0];
+            x * 100.0 + y + self.octaves as f64 * 0.001
+        }
+        fn set_detail(&mut self, detail: Detail) {
+            self.octaves = detail.octaves(4);
+        }
+    }
+
+    #[test]
+    fn test_progressive_preview() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let terrain_handle = graph.insert_node("terrain", Terrain { octaves: 4 });
+        graph.set_output_node(&terrain_handle)?;
+        let mut compute_graph = graph.build::<[f64; 2], f64>()?;
+
+        let grid = Grid::new([0.0, 0.0], [1.0, 1.0], 6, 5);
+        let mut levels = Vec::new();
+        let complete =
+            compute_graph.preview_grid(&grid, 2, 2, &CancelToken::new(), |detail, values| {
+                assert_eq!(values.len(), 30);
+                levels.push((detail.level, values[grid.width + 5]));
+            })?;
+        assert!(complete);
+        // Point (5, 1) is first covered by the sample at (4, 0), then (4, 0), then itself
+        assert_eq!(levels, [(2, 400.002), (1, 400.003), (0, 501.004)]);
+        assert_eq!(
+            compute_graph.compute_grid(&grid, 2, |_| {})?,
+            (0..5)
+                .flat_map(|y| (0..6).map(move |x| x as f64 * 100.0 + y as f64 + 0.004))
+                .collect::<Vec<_>>()
+        );
+
+        let cancel = CancelToken::new();
+        let mut shown = 0;
+        let complete = compute_graph.preview_grid(&grid, 2, 2, &cancel, |_, _| {
+            shown += 1;
+            cancel.cancel();
+        })?;
+        assert!(!complete);
+        assert_eq!(shown, 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use crate::com_graph::ComputeGraph;
+use crate::graph::{ComputeGraphErrors, Graph, NodeHandle};
+use std::any::Any;
+
+impl Graph {
+    /// Keeps the output of `node_handle` after every evaluation of graphs built from now on,
+    /// to be read with `ComputeGraph::probed_value` when debugging intermediate results.
+    /// Probed nodes are never fused away.
+    pub fn probe(&mut self, node_handle: &NodeHandle) -> Result<(), ComputeGraphErrors> {
+        self.set_probed(node_handle, true)
+    }
+
+    pub fn unprobe(&mut self, node_handle: &NodeHandle) -> Result<(), ComputeGraphErrors> {
+        self.set_probed(node_handle, false)
+    }
+
+    pub fn is_probed(&self, node_handle: &NodeHandle) -> Result<bool, ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        Ok(self.nodes[node_handle.key].probed)
+    }
+
+    fn set_probed(
+        &mut self,
+        node_handle: &NodeHandle,
+        probed: bool,
+    ) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        if let Some(node) = self.node_mut(node_handle.key) {
+            node.probed = probed;
+        }
+        self.touch();
+        Ok(())
+    }
+}
+
+impl<In, Out> ComputeGraph<In, Out> {
+    /// Output of the probed node `node_handle` from the last evaluation that computed it.
+    /// `None` if the node isn't probed, hasn't been computed yet or doesn't output a `T`.
+    pub fn probed_value<T: Any + Clone>(&self, node_handle: &NodeHandle) -> Option<T> {
+        let index = self
+            .nodes
+            .iter()
+            .position(|node| node.handle == *node_handle)?;
+        self.probes
+            .lock()
+            .unwrap()
+            .get(&index)?
+            .downcast_ref::<T>()
+            .cloned()
+    }
+
+    pub(crate) fn record_probe(&self, index: usize, output: &dyn Any) {
+        let func = &self.nodes[index].func;
+        let mut probes = self.probes.lock().unwrap();
+        match probes.get_mut(&index) {
+            Some(probe) => func.copy_output(output, probe.as_mut()),
+            None => {
+                probes.insert(index, func.clone_output(output));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod probe_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_probed_values() -> Result<(), ComputeGraphErrors> {
+        //  Input ── mul ── add
+        //  2.0 ─────┘      │
+        //  1.0 ────────────┘
+        let mut graph = Graph::new();
+        let two_handle = graph.insert_node("two", Constant(2.0));
+        let one_handle = graph.insert_node("one", Constant(1.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&mul_handle, &two_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.add_input(&add_handle, &mul_handle)?;
+        graph.add_input(&add_handle, &one_handle)?;
+        graph.set_output_node(&add_handle)?;
+        graph.probe(&mul_handle)?;
+        assert!(graph.is_probed(&mul_handle)?);
+
+        let compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(compute_graph.probed_value::<f64>(&mul_handle), None);
+        assert_eq!(compute_graph.compute(&3.0), 7.0);
+        assert_eq!(compute_graph.probed_value::<f64>(&mul_handle), Some(6.0));
+        assert_eq!(compute_graph.probed_value::<f64>(&one_handle), None);
+        assert_eq!(compute_graph.probed_value::<i64>(&mul_handle), None);
+        compute_graph.compute_detailed(&4.0)?;
+        assert_eq!(compute_graph.probed_value::<f64>(&mul_handle), Some(8.0));
+
+        // Fusing would otherwise fold `mul` into `add`
+        let fused = graph.build_fused::<f64, f64>()?;
+        assert_eq!(fused.compute(&5.0), 11.0);
+        assert_eq!(fused.probed_value::<f64>(&mul_handle), Some(10.0));
+
+        graph.unprobe(&mul_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+        compute_graph.compute(&3.0);
+        assert_eq!(compute_graph.probed_value::<f64>(&mul_handle), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use crate::com_graph::ComputeGraph;
+use crate::graph::{ComputeGraphErrors, NodeHandle};
+use std::any::Any;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Timings of one node aggregated over the sampled calls.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeProfile {
+    pub node: NodeHandle,
+    pub name: String,
+    pub samples: u64,
+    pub total: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl NodeProfile {
+    pub fn mean(&self) -> Duration {
+        Duration::from_nanos((self.total.as_nanos() / self.samples.max(1) as u128) as u64)
+    }
+}
+
+/// Always-on profiler for `ComputeGraph::compute_profiled`. Only every `sample_every`th call
+/// is timed, the others run at the cost of one atomic increment.
+/// One profiler can be shared by several graphs and threads.
+pub struct Profiler {
+    sample_every: u64,
+    calls: AtomicU64,
+    profiles: Mutex<HashMap<NodeHandle, NodeProfile>>,
+}
+
+impl Profiler {
+    /// `sample_every` of 1 times every call, 0 is treated as 1.
+    pub fn new(sample_every: u64) -> Self {
+        Self {
+            sample_every: sample_every.max(1),
+            calls: AtomicU64::new(0),
+            profiles: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn sample_every(&self) -> u64 {
+        self.sample_every
+    }
+
+    /// Calls seen so far, sampled or not.
+    pub fn calls(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    /// Aggregated timings, slowest node in total first.
+    pub fn report(&self) -> Vec<NodeProfile> {
+        let mut profiles = self
+            .profiles
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        profiles.sort_by_key(|profile| Reverse(profile.total));
+        profiles
+    }
+
+    pub fn reset(&self) {
+        self.profiles.lock().unwrap().clear();
+        self.calls.store(0, Ordering::Relaxed);
+    }
+
+    fn should_sample(&self) -> bool {
+        self.calls
+            .fetch_add(1, Ordering::Relaxed)
+            .is_multiple_of(self.sample_every)
+    }
+}
+
+impl<In, Out> ComputeGraph<In, Out> {
+    /// Like `try_compute`, timing every node when `profiler` samples this call.
+    pub fn compute_profiled(
+        &self,
+        input: &In,
+        profiler: &Profiler,
+    ) -> Result<Out, ComputeGraphErrors>
+    where
+        In: Any + Clone,
+        Out: Any + Clone,
+    {
+        if !profiler.should_sample() {
+            return self.try_compute(input);
+        }
+        let mut durations = Vec::with_capacity(self.nodes.len());
+        let output = self.with_context(|context| {
+            for i in 0..self.nodes.len() {
+                let start = Instant::now();
+                self.compute_node(i, input, context)?;
+                durations.push(start.elapsed());
+            }
+            Ok::<_, ComputeGraphErrors>(context.output::<Out>().clone())
+        })?;
+
+        let mut profiles = profiler.profiles.lock().unwrap();
+        for (node, duration) in self.nodes.iter().zip(durations) {
+            let profile = profiles.entry(node.handle).or_insert_with(|| NodeProfile {
+                node: node.handle,
+                name: node.name.clone(),
+                samples: 0,
+                total: Duration::ZERO,
+                min: Duration::MAX,
+                max: Duration::ZERO,
+            });
+            profile.samples += 1;
+            profile.total += duration;
+            profile.min = profile.min.min(duration);
+            profile.max = profile.max.max(duration);
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod profile_tests {
+    use crate::prelude::*;
+    use std::time::Duration;
+
+    #[derive(Clone, Copy)]
+    struct Slow;
+
+    impl Compute for Slow {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            std::thread::sleep(Duration::from_millis(2));
+            inputs.iter().copied().sum()
+        }
+    }
+
+    #[test]
+    fn test_sampled_profiling() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let slow_handle = graph.insert_node("slow", Slow);
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&add_handle, &slow_handle)?;
+        graph.set_output_node(&add_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+
+        let profiler = Profiler::new(4);
+        for x in 0..10 {
+            assert_eq!(
+                compute_graph.compute_profiled(&(x as f64), &profiler)?,
+                x as f64
+            );
+        }
+        assert_eq!(profiler.calls(), 10);
+        let report = profiler.report();
+        assert_eq!(report.len(), 2);
+        // Calls 0, 4 and 8 were timed
+        assert_eq!(report[0].node, slow_handle);
+        assert_eq!(report[0].samples, 3);
+        assert!(report[0].min >= Duration::from_millis(2));
+        assert!(report[0].mean() >= report[0].min && report[0].mean() <= report[0].max);
+        let busy = NodeProfile {
+            samples: 1 << 33,
+            total: Duration::from_secs(1 << 33),
+            ..report[0].clone()
+        };
+        assert_eq!(busy.mean(), Duration::from_secs(1));
+
+        profiler.reset();
+        assert!(profiler.report().is_empty());
+        Ok(())
+    }
+}
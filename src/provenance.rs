@@ -0,0 +1,148 @@
+use crate::graph::{Graph, GraphKey};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One recorded mutation of a graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProvenanceEntry {
+    /// `Graph::revision` right after the mutation.
+    pub revision: u64,
+    pub author: String,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub action: String,
+}
+
+/// Author used for mutations of a loaded graph until `Graph::track_provenance` is called.
+pub const UNKNOWN_AUTHOR: &str = "unknown";
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Provenance {
+    pub(crate) author: String,
+    pub(crate) entries: Vec<ProvenanceEntry>,
+}
+
+impl Graph {
+    /// Records every following mutation of the graph as made by `author`.
+    /// Calling it again switches the author and keeps the log.
+    pub fn track_provenance(&mut self, author: impl Into<String>) {
+        let provenance = self.provenance.get_or_insert_with(Provenance::default);
+        provenance.author = author.into();
+    }
+
+    pub fn is_tracking_provenance(&self) -> bool {
+        self.provenance.is_some()
+    }
+
+    /// Mutations recorded since provenance tracking started, oldest first.
+    pub fn provenance(&self) -> &[ProvenanceEntry] {
+        self.provenance
+            .as_ref()
+            .map(|provenance| provenance.entries.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Logs a mutation, describing it only when provenance is tracked.
+    pub(crate) fn record(&mut self, action: impl FnOnce(&Graph) -> String) {
+        if self.provenance.is_none() {
+            return;
+        }
+        let action = action(self);
+        let revision = self.revision();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+        let provenance = self.provenance.as_mut().unwrap();
+        provenance.entries.push(ProvenanceEntry {
+            revision,
+            author: provenance.author.clone(),
+            timestamp,
+            action,
+        });
+    }
+
+    /// Restores a log loaded with the graph, tracking later mutations as `UNKNOWN_AUTHOR`.
+    #[cfg(feature = "serde")]
+    pub(crate) fn restore_provenance(&mut self, entries: Vec<ProvenanceEntry>) {
+        if !entries.is_empty() {
+            self.provenance = Some(Provenance {
+                author: UNKNOWN_AUTHOR.to_string(),
+                entries,
+            });
+        }
+    }
+
+    pub(crate) fn name_for_log(&self, key: GraphKey) -> &str {
+        self.nodes
+            .get(key)
+            .map_or("<removed>", |node| node.name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod provenance_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_provenance_log() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let rate_handle = graph.insert_node("rate", Constant(0.05));
+        assert!(graph.provenance().is_empty());
+
+        graph.track_provenance("alice");
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &rate_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.track_provenance("bob");
+        graph.set_param(&rate_handle, "value", 0.07)?;
+        graph.set_output_node(&mul_handle)?;
+        graph.remove_node(&rate_handle)?;
+
+        let log = graph
+            .provenance()
+            .iter()
+            .map(|entry| (entry.author.as_str(), entry.action.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            log,
+            [
+                (
+                    "alice",
+                    "inserted 'mul' (compute_graph::operations::MulInputs<f64>)"
+                ),
+                ("alice", "added input 'rate' to 'mul'"),
+                ("alice", "connected 'mul' to the graph input"),
+                ("bob", "set parameter 'value' of 'rate' to Float(0.07)"),
+                ("bob", "set output node to 'mul'"),
+                ("bob", "removed 'rate'"),
+            ]
+        );
+        assert_eq!(
+            graph.provenance().last().unwrap().revision,
+            graph.revision()
+        );
+        assert!(graph.provenance()[0].timestamp > 0);
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_provenance_roundtrip() -> Result<(), ComputeGraphErrors> {
+        let mut registry = NodeRegistry::new();
+        registry.register::<Constant<f64>, _, _>("constant")?;
+        let mut graph = Graph::new();
+        graph.track_provenance("alice");
+        let rate_handle = graph.insert_node("rate", Constant(0.05));
+        graph.set_output_node(&rate_handle)?;
+
+        let json = graph.to_json(&registry)?;
+        let mut loaded = Graph::from_json(&json, &registry)?;
+        assert_eq!(loaded.provenance(), graph.provenance());
+        let handle = loaded.get_all_node_metas()[0].this_node;
+        loaded.add_tag(&handle, "reviewed")?;
+        let last = loaded.provenance().last().unwrap();
+        assert_eq!(last.author, "unknown");
+        assert_eq!(last.action, "tagged 'rate' with 'reviewed'");
+        Ok(())
+    }
+}
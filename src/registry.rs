@@ -0,0 +1,599 @@
+use crate::capabilities::Capability;
+use crate::compute::{Compute, Value};
+use crate::graph::ComputeGraphErrors;
+use std::any::{type_name, TypeId};
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use crate::compute::InnerCompute;
+#[cfg(feature = "serde")]
+use crate::serialize::Migration;
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(feature = "serde")]
+use std::any::Any;
+
+/// Compute objects that can be registered in a `NodeRegistry`.
+/// With the `serde` feature enabled they must also be (de)serializable,
+/// since the registry is what turns saved configs back into nodes.
+#[cfg(not(feature = "serde"))]
+pub trait Registrable: Compute + 'static {}
+#[cfg(not(feature = "serde"))]
+impl<T: Compute + 'static> Registrable for T {}
+
+#[cfg(feature = "serde")]
+pub trait Registrable: Compute + Serialize + DeserializeOwned + 'static {}
+#[cfg(feature = "serde")]
+impl<T: Compute + Serialize + DeserializeOwned + 'static> Registrable for T {}
+
+#[cfg(feature = "serde")]
+type SerializeFn = fn(&dyn Any) -> Result<serde_json::Value, serde_json::Error>;
+#[cfg(feature = "serde")]
+type DeserializeFn = fn(serde_json::Value) -> Result<Box<dyn InnerCompute>, serde_json::Error>;
+
+pub struct RegistryEntry {
+    name: String,
+    type_id: TypeId,
+    input_type: TypeId,
+    output_type: TypeId,
+    input_type_name: &'static str,
+    output_type_name: &'static str,
+    deprecation: Option<String>,
+    capabilities: Vec<Capability>,
+    display_name: Option<String>,
+    description: Option<String>,
+    input_doc: Option<String>,
+    output_doc: Option<String>,
+    #[cfg(feature = "serde")]
+    serialize: SerializeFn,
+    #[cfg(feature = "serde")]
+    deserialize: DeserializeFn,
+}
+
+impl RegistryEntry {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Namespace of the identifier, e.g. `std.math` for `std.math.add`.
+    pub fn namespace(&self) -> Option<&str> {
+        self.name.rsplit_once('.').map(|(namespace, _)| namespace)
+    }
+
+    /// Identifier without its namespace.
+    pub fn short_name(&self) -> &str {
+        self.name
+            .rsplit_once('.')
+            .map_or(&self.name, |(_, name)| name)
+    }
+
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    pub fn input_type(&self) -> TypeId {
+        self.input_type
+    }
+
+    pub fn output_type(&self) -> TypeId {
+        self.output_type
+    }
+
+    pub fn input_type_name(&self) -> &'static str {
+        self.input_type_name
+    }
+
+    pub fn output_type_name(&self) -> &'static str {
+        self.output_type_name
+    }
+
+    /// Flags the op as deprecated. `hint` should tell users what to use instead.
+    pub fn deprecate(&mut self, hint: impl Into<String>) -> &mut Self {
+        self.deprecation = Some(hint.into());
+        self
+    }
+
+    pub fn deprecation(&self) -> Option<&str> {
+        self.deprecation.as_deref()
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecation.is_some()
+    }
+
+    /// Declares that the op needs `capability` to run.
+    pub fn declare(&mut self, capability: Capability) -> &mut Self {
+        if !self.capabilities.contains(&capability) {
+            self.capabilities.push(capability);
+        }
+        self
+    }
+
+    pub fn capabilities(&self) -> &[Capability] {
+        &self.capabilities
+    }
+
+    /// True when the op declares nothing beyond `Capability::Pure`.
+    pub fn is_pure(&self) -> bool {
+        self.capabilities
+            .iter()
+            .all(|capability| *capability == Capability::Pure)
+    }
+
+    /// Name shown to users instead of the op name, which stays the stable identifier.
+    pub fn display_as(&mut self, display_name: impl Into<String>) -> &mut Self {
+        self.display_name = Some(display_name.into());
+        self
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Human-readable summary of what the op does, for tooltips and generated docs.
+    pub fn describe(&mut self, description: impl Into<String>) -> &mut Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Documents what the op expects on its inputs.
+    pub fn document_input(&mut self, doc: impl Into<String>) -> &mut Self {
+        self.input_doc = Some(doc.into());
+        self
+    }
+
+    pub fn input_doc(&self) -> Option<&str> {
+        self.input_doc.as_deref()
+    }
+
+    /// Documents what the op outputs.
+    pub fn document_output(&mut self, doc: impl Into<String>) -> &mut Self {
+        self.output_doc = Some(doc.into());
+        self
+    }
+
+    pub fn output_doc(&self) -> Option<&str> {
+        self.output_doc.as_deref()
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn serialize(
+        &self,
+        object: &dyn Any,
+    ) -> Result<serde_json::Value, serde_json::Error> {
+        (self.serialize)(object)
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn deserialize(
+        &self,
+        config: serde_json::Value,
+    ) -> Result<Box<dyn InnerCompute>, serde_json::Error> {
+        (self.deserialize)(config)
+    }
+}
+
+/// Maps operation names to compute object types, so graphs can refer to
+/// node types by a stable string identifier. Identifiers can be namespaced by
+/// their library, like `std.math.add` or `mycorp.pricing.margin`.
+#[derive(Default)]
+pub struct NodeRegistry {
+    entries: HashMap<String, RegistryEntry>,
+    names: HashMap<TypeId, String>,
+    aliases: HashMap<String, String>,
+    #[cfg(feature = "serde")]
+    version: u32,
+    #[cfg(feature = "serde")]
+    migrations: Vec<Box<dyn Migration>>,
+    #[cfg(feature = "serde")]
+    namespace_versions: HashMap<String, u32>,
+}
+
+impl NodeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `T` as `name`. Fails if `name` isn't made of dot separated segments, is
+    /// taken by another type or an alias, or `T` is already registered under another
+    /// name. Registering `T` as the same name again replaces its entry.
+    pub fn register<T, In, Out>(
+        &mut self,
+        name: impl Into<String>,
+    ) -> Result<&mut RegistryEntry, ComputeGraphErrors>
+    where
+        T: Registrable + Compute<In = In, Out = Out>,
+        In: Value,
+        Out: Value,
+    {
+        let name = name.into();
+        check_identifier(&name)?;
+        if self
+            .entries
+            .get(&name)
+            .is_some_and(|entry| entry.type_id != TypeId::of::<T>())
+        {
+            return Err(ComputeGraphErrors::NameCollision(
+                format!("'{}' is already registered for another type", name).into(),
+            ));
+        }
+        if let Some(target) = self.aliases.get(&name) {
+            return Err(ComputeGraphErrors::NameCollision(
+                format!("'{}' is already an alias of '{}'", name, target).into(),
+            ));
+        }
+        if let Some(existing) = self.names.get(&TypeId::of::<T>()) {
+            if *existing != name {
+                return Err(ComputeGraphErrors::NameCollision(
+                    format!(
+                        "'{}' is already registered as '{}', add '{}' as an alias instead",
+                        type_name::<T>(),
+                        existing,
+                        name
+                    )
+                    .into(),
+                ));
+            }
+        }
+        let entry = RegistryEntry {
+            name: name.clone(),
+            type_id: TypeId::of::<T>(),
+            input_type: TypeId::of::<In>(),
+            output_type: TypeId::of::<Out>(),
+            input_type_name: type_name::<In>(),
+            output_type_name: type_name::<Out>(),
+            deprecation: None,
+            capabilities: Vec::new(),
+            display_name: None,
+            description: None,
+            input_doc: None,
+            output_doc: None,
+            #[cfg(feature = "serde")]
+            serialize: |object| {
+                serde_json::to_value(object.downcast_ref::<T>().expect("registry type mismatch"))
+            },
+            #[cfg(feature = "serde")]
+            deserialize: |config| {
+                let object: Box<dyn InnerCompute> = Box::new(serde_json::from_value::<T>(config)?);
+                Ok(object)
+            },
+        };
+        self.entries.insert(name.clone(), entry);
+        self.names.insert(TypeId::of::<T>(), name.clone());
+        Ok(self.entries.get_mut(&name).unwrap())
+    }
+
+    /// Makes `alias` refer to the op registered as `name`, e.g. to keep loading graphs
+    /// saved before the op moved into a namespace. Graphs are always saved with the
+    /// registered name.
+    pub fn alias(
+        &mut self,
+        alias: impl Into<String>,
+        name: &str,
+    ) -> Result<&mut Self, ComputeGraphErrors> {
+        let alias = alias.into();
+        check_identifier(&alias)?;
+        let Some(name) = self.resolve(name).map(str::to_string) else {
+            return Err(ComputeGraphErrors::InvalidParameter(
+                format!("can't alias unknown op '{}'", name).into(),
+            ));
+        };
+        if self.entries.contains_key(&alias) {
+            return Err(ComputeGraphErrors::NameCollision(
+                format!("'{}' is already a registered op", alias).into(),
+            ));
+        }
+        if let Some(target) = self.aliases.get(&alias).filter(|target| **target != name) {
+            return Err(ComputeGraphErrors::NameCollision(
+                format!("'{}' is already an alias of '{}'", alias, target).into(),
+            ));
+        }
+        self.aliases.insert(alias, name);
+        Ok(self)
+    }
+
+    /// Registered name of the op `name` refers to, following aliases.
+    pub fn resolve(&self, name: &str) -> Option<&str> {
+        match self.entries.get_key_value(name) {
+            Some((name, _)) => Some(name),
+            None => self.aliases.get(name).map(|name| name.as_str()),
+        }
+    }
+
+    /// Entry of the op registered as `name`, or aliased by it.
+    pub fn get(&self, name: &str) -> Option<&RegistryEntry> {
+        self.entries.get(self.resolve(name)?)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.resolve(name).is_some()
+    }
+
+    /// Entries in `namespace` and the namespaces nested in it, sorted by name.
+    pub fn namespace(&self, namespace: &str) -> Vec<&RegistryEntry> {
+        let mut entries = self
+            .entries
+            .values()
+            .filter(|entry| {
+                entry
+                    .name
+                    .strip_prefix(namespace)
+                    .is_some_and(|rest| rest.starts_with('.'))
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        entries
+    }
+
+    pub fn name_of(&self, type_id: TypeId) -> Option<&str> {
+        self.names.get(&type_id).map(|name| name.as_str())
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &RegistryEntry> {
+        self.entries.values()
+    }
+
+    pub fn entry_of(&self, type_id: TypeId) -> Option<&RegistryEntry> {
+        self.entries.get(self.names.get(&type_id)?)
+    }
+
+    /// Markdown reference of every registered op, sorted by name.
+    pub fn reference_docs(&self) -> String {
+        let mut entries = self.entries.values().collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut docs = String::new();
+        for entry in entries {
+            docs.push_str(&format!("## {}\n\n", entry.name));
+            if let Some(hint) = &entry.deprecation {
+                docs.push_str(&format!("**Deprecated:** {}\n\n", hint));
+            }
+            if let Some(description) = &entry.description {
+                docs.push_str(&format!("{}\n\n", description));
+            }
+            for (port, type_name, doc) in [
+                ("Inputs", entry.input_type_name, &entry.input_doc),
+                ("Output", entry.output_type_name, &entry.output_doc),
+            ] {
+                docs.push_str(&format!("- {}: `{}`", port, type_name));
+                if let Some(doc) = doc {
+                    docs.push_str(&format!(" - {}", doc));
+                }
+                docs.push('\n');
+            }
+            docs.push('\n');
+        }
+        docs
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn entry_for(&self, inner: &dyn InnerCompute) -> Option<&RegistryEntry> {
+        self.entry_of(Any::type_id(inner.as_any()))
+    }
+
+    /// The current version of the node library, written into saved graphs.
+    #[cfg(feature = "serde")]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn set_version(&mut self, version: u32) -> &mut Self {
+        self.version = version;
+        self
+    }
+
+    /// Registers a migration used to upgrade graphs saved with an older version.
+    #[cfg(feature = "serde")]
+    pub fn add_migration<M: Migration + 'static>(&mut self, migration: M) -> &mut Self {
+        self.migrations.push(Box::new(migration));
+        self
+    }
+
+    /// Declares the version of the op library in `namespace`, which packs using its ops
+    /// require when loaded, see `GraphPack`.
+    #[cfg(feature = "serde")]
+    pub fn set_namespace_version(
+        &mut self,
+        namespace: impl Into<String>,
+        version: u32,
+    ) -> &mut Self {
+        self.namespace_versions.insert(namespace.into(), version);
+        self
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn namespace_version(&self, namespace: &str) -> Option<u32> {
+        self.namespace_versions.get(namespace).copied()
+    }
+
+    /// Library the op `name` belongs to: the longest namespace with a declared version
+    /// containing it, or the namespace of the op at version 0.
+    #[cfg(feature = "serde")]
+    pub(crate) fn library_of<'a>(&'a self, name: &'a str) -> Option<(&'a str, u32)> {
+        let mut namespace = name;
+        while let Some((parent, _)) = namespace.rsplit_once('.') {
+            namespace = parent;
+            if let Some(version) = self.namespace_version(namespace) {
+                return Some((namespace, version));
+            }
+        }
+        name.rsplit_once('.').map(|(namespace, _)| (namespace, 0))
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn migration_from(&self, version: u32) -> Option<&dyn Migration> {
+        self.migrations
+            .iter()
+            .find(|migration| migration.source_version() == version)
+            .map(|migration| migration.as_ref())
+    }
+}
+
+/// Checks that `name` is made of non-empty segments separated by dots, like `std.math.add`.
+fn check_identifier(name: &str) -> Result<(), ComputeGraphErrors> {
+    let valid = name.split('.').all(|segment| {
+        !segment.is_empty()
+            && segment
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(ComputeGraphErrors::InvalidParameter(
+            format!("'{}' is not a valid op identifier", name).into(),
+        ))
+    }
+}
+
+/// Operation registered at link time by `register_operation!`.
+#[cfg(feature = "inventory")]
+pub struct OperationRegistration {
+    pub register: fn(&mut NodeRegistry) -> Result<(), ComputeGraphErrors>,
+}
+
+#[cfg(feature = "inventory")]
+inventory::collect!(OperationRegistration);
+
+#[cfg(feature = "inventory")]
+impl NodeRegistry {
+    /// Registers every operation submitted with `register_operation!` by the crates
+    /// linked into the binary, in no particular order. Fails if two of them collide.
+    pub fn register_linked(&mut self) -> Result<&mut Self, ComputeGraphErrors> {
+        for registration in inventory::iter::<OperationRegistration> {
+            (registration.register)(self)?;
+        }
+        Ok(self)
+    }
+}
+
+/// Registers an operation into `NodeRegistry::register_linked` at link time, so crates
+/// of nodes don't need to be wired up by hand. An optional closure configures the entry.
+///
+/// ```ignore
+/// register_operation!("double" => Double);
+/// register_operation!("noise" => Noise, |entry| {
+///     entry.describe("Fractal noise.");
+/// });
+/// ```
+#[cfg(feature = "inventory")]
+#[macro_export]
+macro_rules! register_operation {
+    ($name:expr => $op:ty) => {
+        $crate::register_operation!($name => $op, |_entry| {});
+    };
+    ($name:expr => $op:ty, |$entry:ident| $configure:block) => {
+        $crate::inventory::submit! {
+            $crate::prelude::OperationRegistration {
+                register: |registry| {
+                    let $entry = registry.register::<$op, _, _>($name)?;
+                    $configure
+                    Ok(())
+                },
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod registry_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_op_docs() -> Result<(), ComputeGraphErrors> {
+        let mut registry = NodeRegistry::new();
+        registry
+            .register::<MulInputs<f64>, _, _>("mul")?
+            .describe("Multiplies all inputs.")
+            .document_input("Factors")
+            .document_output("Product of the factors");
+        registry.register::<AddInputs<f64>, _, _>("add")?;
+
+        let mut graph = Graph::new();
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        let meta = graph.get_node_meta(&mul_handle)?;
+        let entry = registry.entry_of(meta.op_type).unwrap();
+        assert_eq!(entry.description(), Some("Multiplies all inputs."));
+        assert_eq!(entry.input_doc(), Some("Factors"));
+        assert!(registry.get("add").unwrap().description().is_none());
+
+        assert_eq!(
+            registry.reference_docs(),
+            "## add\n\n- Inputs: `f64`\n- Output: `f64`\n\n\
+             ## mul\n\nMultiplies all inputs.\n\n\
+             - Inputs: `f64` - Factors\n- Output: `f64` - Product of the factors\n\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_namespaces() -> Result<(), ComputeGraphErrors> {
+        let mut registry = NodeRegistry::new();
+        registry.register::<AddInputs<f64>, _, _>("std.math.add")?;
+        registry.register::<MulInputs<f64>, _, _>("std.math.mul")?;
+        registry.register::<Constant<f64>, _, _>("mycorp.pricing.margin")?;
+        registry.alias("add", "std.math.add")?;
+
+        let entry = registry.get("add").unwrap();
+        assert_eq!(entry.name(), "std.math.add");
+        assert_eq!(
+            (entry.namespace(), entry.short_name()),
+            (Some("std.math"), "add")
+        );
+        assert_eq!(registry.resolve("add"), Some("std.math.add"));
+        let names = |namespace| {
+            registry
+                .namespace(namespace)
+                .iter()
+                .map(|entry| entry.name().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("std"), ["std.math.add", "std.math.mul"]);
+        assert!(names("std.ma").is_empty());
+
+        assert!(matches!(
+            registry.register::<SubInputs<f64>, _, _>("std.math.add"),
+            Err(ComputeGraphErrors::NameCollision(_))
+        ));
+        assert!(matches!(
+            registry.register::<MulInputs<f64>, _, _>("mycorp.mul"),
+            Err(ComputeGraphErrors::NameCollision(_))
+        ));
+        assert!(matches!(
+            registry.register::<SubInputs<f64>, _, _>("add"),
+            Err(ComputeGraphErrors::NameCollision(_))
+        ));
+        assert!(matches!(
+            registry.alias("add", "std.math.mul"),
+            Err(ComputeGraphErrors::NameCollision(_))
+        ));
+        assert!(matches!(
+            registry.register::<SubInputs<f64>, _, _>("std..sub"),
+            Err(ComputeGraphErrors::InvalidParameter(_))
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "inventory")]
+    crate::register_operation!("linked-mul" => MulInputs<f64>, |entry| {
+        entry.describe("Registered at link time.");
+    });
+
+    #[cfg(feature = "inventory")]
+    #[test]
+    fn test_register_linked() -> Result<(), ComputeGraphErrors> {
+        let mut registry = NodeRegistry::new();
+        registry.register_linked()?;
+        let entry = registry.get("linked-mul").unwrap();
+        assert_eq!(entry.description(), Some("Registered at link time."));
+        assert_eq!(
+            registry.name_of(std::any::TypeId::of::<MulInputs<f64>>()),
+            Some("linked-mul")
+        );
+        Ok(())
+    }
+}
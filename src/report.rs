@@ -0,0 +1,250 @@
+use crate::checkpoint::CheckpointStore;
+use crate::com_graph::{ComputeGraph, EvalContext};
+use crate::graph::{ComputeGraphErrors, NodeHandle};
+use std::any::{Any, TypeId};
+use std::cmp::Reverse;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeStatus {
+    Computed,
+    /// Restored from a `CheckpointStore`, or kept from the previous evaluation of a
+    /// `Compute::cacheable` node, instead of being evaluated.
+    Cached,
+    /// Skipped because the time budget ran out; the previous output was kept.
+    Stale,
+    /// Failed, and its `NodePolicy` supplied the output instead.
+    FellBack,
+}
+
+#[derive(Clone, Debug)]
+pub struct NodeReport {
+    pub node: NodeHandle,
+    pub name: String,
+    pub status: NodeStatus,
+    /// Number of inputs the node read, counting the graph input.
+    pub inputs: usize,
+    pub retries: u32,
+    pub duration: Duration,
+}
+
+/// Output of an evaluation together with information about how it was produced.
+#[derive(Clone, Debug)]
+pub struct ComputeReport<Out> {
+    pub output: Out,
+    /// One entry per evaluated node, in compute order.
+    pub nodes: Vec<NodeReport>,
+    /// Problems that did not stop the evaluation, such as retries and fallbacks.
+    pub warnings: Vec<String>,
+    pub duration: Duration,
+}
+
+impl<Out> ComputeReport<Out> {
+    pub fn node(&self, node_handle: &NodeHandle) -> Option<&NodeReport> {
+        self.nodes.iter().find(|node| node.node == *node_handle)
+    }
+
+    pub fn with_status(&self, status: NodeStatus) -> Vec<NodeHandle> {
+        self.nodes
+            .iter()
+            .filter(|node| node.status == status)
+            .map(|node| node.node)
+            .collect()
+    }
+
+    /// The `count` nodes that took longest, slowest first.
+    pub fn slowest(&self, count: usize) -> Vec<&NodeReport> {
+        let mut nodes = self.nodes.iter().collect::<Vec<_>>();
+        nodes.sort_by_key(|node| Reverse(node.duration));
+        nodes.truncate(count);
+        nodes
+    }
+
+    pub fn stale(&self) -> Vec<NodeHandle> {
+        self.with_status(NodeStatus::Stale)
+    }
+
+    /// True if any node output did not come from evaluating it with the current input.
+    pub fn is_stale(&self) -> bool {
+        self.nodes
+            .iter()
+            .any(|node| matches!(node.status, NodeStatus::Stale | NodeStatus::FellBack))
+    }
+}
+
+impl<In, Out> ComputeGraph<In, Out> {
+    /// Like `try_compute`, but also reports how long every node took and how it was handled.
+    pub fn compute_detailed(&self, input: &In) -> Result<ComputeReport<Out>, ComputeGraphErrors>
+    where
+        In: Any + Clone,
+        Out: Any + Clone,
+    {
+        self.evaluate(input, None, None)
+    }
+
+    /// Evaluates the graph within a soft time budget. Once `budget` is exceeded, the
+    /// remaining nodes that are not marked critical keep their previous outputs and are
+    /// reported as stale.
+    pub fn compute_within(
+        &self,
+        input: &In,
+        budget: Duration,
+    ) -> Result<ComputeReport<Out>, ComputeGraphErrors>
+    where
+        In: Any + Clone,
+        Out: Any + Clone,
+    {
+        self.evaluate(input, Some(budget), None)
+    }
+
+    pub(crate) fn evaluate(
+        &self,
+        input: &In,
+        budget: Option<Duration>,
+        checkpoints: Option<(&[u64], &mut dyn CheckpointStore)>,
+    ) -> Result<ComputeReport<Out>, ComputeGraphErrors>
+    where
+        In: Any + Clone,
+        Out: Any + Clone,
+    {
+        self.with_context(|context| self.evaluate_in(input, budget, checkpoints, context))
+    }
+
+    fn evaluate_in(
+        &self,
+        input: &In,
+        budget: Option<Duration>,
+        mut checkpoints: Option<(&[u64], &mut dyn CheckpointStore)>,
+        context: &mut EvalContext,
+    ) -> Result<ComputeReport<Out>, ComputeGraphErrors>
+    where
+        In: Any + Clone,
+        Out: Any + Clone,
+    {
+        let start = Instant::now();
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+        let mut warnings = Vec::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            let node_start = Instant::now();
+            let mut retries = 0;
+            let status = if budget.is_some_and(|budget| !node.critical && start.elapsed() > budget)
+            {
+                NodeStatus::Stale
+            } else if checkpoints
+                .as_ref()
+                .is_some_and(|(keys, store)| self.load_checkpoint(i, keys[i], *store, context))
+            {
+                NodeStatus::Cached
+            } else {
+                let outcome = self.compute_node(i, input, context)?;
+                if let Some((keys, store)) = checkpoints.as_mut() {
+                    self.save_checkpoint(i, keys[i], *store, context);
+                }
+                retries = outcome.retries;
+                if retries > 0 {
+                    warnings.push(format!("'{}' needed {} retries", node.name, retries));
+                }
+                match outcome.fallback {
+                    Some(error) => {
+                        warnings.push(format!("'{}' used its fallback: {}", node.name, error));
+                        NodeStatus::FellBack
+                    }
+                    None if outcome.cached => NodeStatus::Cached,
+                    None => NodeStatus::Computed,
+                }
+            };
+            nodes.push(NodeReport {
+                node: node.handle,
+                name: node.name.clone(),
+                status,
+                inputs: node.inputs.len()
+                    + usize::from(
+                        node.connected_to_input && node.func.input_type() != TypeId::of::<()>(),
+                    ),
+                retries,
+                duration: node_start.elapsed(),
+            });
+        }
+        Ok(ComputeReport {
+            output: context.output::<Out>().clone(),
+            nodes,
+            warnings,
+            duration: start.elapsed(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod report_tests {
+    use crate::prelude::*;
+    use std::thread;
+    use std::time::Duration;
+
+    #[derive(Clone)]
+    struct Slow(u64);
+
+    impl Compute for Slow {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            thread::sleep(Duration::from_millis(self.0));
+            inputs.iter().copied().sum()
+        }
+    }
+
+    #[test]
+    fn test_deadline_skips_non_critical() -> Result<(), ComputeGraphErrors> {
+        //  Input ── slow ── cheap ── critical
+        let mut graph = Graph::new();
+        let slow_handle = graph.insert_node("slow", Slow(20));
+        let cheap_handle = graph.insert_node("cheap", Slow(0));
+        let critical_handle = graph.insert_node("critical", Slow(0));
+        graph.add_input(&cheap_handle, &slow_handle)?;
+        graph.add_input(&critical_handle, &cheap_handle)?;
+        graph.connect_to_input(&critical_handle)?;
+        graph.set_critical(&critical_handle, true)?;
+        graph.set_output_node(&critical_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+
+        let report = compute_graph.compute_within(&1.0, Duration::from_secs(10))?;
+        assert_eq!(report.output, 2.0);
+        assert!(!report.is_stale());
+
+        let report = compute_graph.compute_within(&5.0, Duration::from_millis(1))?;
+        assert_eq!(report.stale(), vec![cheap_handle]);
+        assert_eq!(report.output, 6.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compute_detailed() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let slow_handle = graph.insert_node("slow", Slow(5));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&add_handle, &slow_handle)?;
+        graph.set_output_node(&add_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+
+        let report = compute_graph.compute_detailed(&3.0)?;
+        assert_eq!(report.output, 3.0);
+        assert_eq!(
+            report.with_status(NodeStatus::Computed),
+            vec![slow_handle, add_handle]
+        );
+        assert!(report.node(&slow_handle).unwrap().duration >= Duration::from_millis(5));
+        assert_eq!(report.slowest(1)[0].node, slow_handle);
+        assert_eq!(report.node(&slow_handle).unwrap().inputs, 1);
+        assert_eq!(report.node(&add_handle).unwrap().inputs, 1);
+        assert!(report.duration >= report.node(&slow_handle).unwrap().duration);
+        assert!(report.warnings.is_empty());
+
+        let mut store = MemoryCheckpoints::new();
+        compute_graph.compute_checkpointed(&3.0, 3, &mut store)?;
+        let report = compute_graph.compute_checkpointed(&3.0, 3, &mut store)?;
+        assert_eq!(
+            report.with_status(NodeStatus::Cached),
+            vec![slow_handle, add_handle]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+use crate::compute::{Compute, Value};
+use crate::ports::PortCompute;
+
+/// Value of a stream together with the time it was taken at, like a sensor reading.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sample<T> {
+    pub time: f64,
+    pub value: T,
+}
+
+impl<T> Sample<T> {
+    pub fn new(time: f64, value: T) -> Self {
+        Self { time, value }
+    }
+}
+
+/// Time of the evaluation: the sum of the time steps it read as graph input so far, as fed
+/// by `Simulator`. Evaluated through `ComputeGraph::try_compute_mut`.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Clock {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    time: f64,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self { time: 0.0 }
+    }
+}
+
+impl Compute for Clock {
+    type In = f64;
+    type Out = f64;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.time + inputs.iter().copied().sum::<f64>()
+    }
+
+    fn try_compute_mut(&mut self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        self.time = self.compute(inputs);
+        Ok(self.time)
+    }
+}
+
+/// The two latest distinct samples of a stream.
+#[derive(Clone, Default)]
+struct History<T> {
+    previous: Option<Sample<T>>,
+    latest: Option<Sample<T>>,
+}
+
+impl<T: Clone> History<T> {
+    fn push(&mut self, sample: &Sample<T>) {
+        if self
+            .latest
+            .as_ref()
+            .is_none_or(|latest| sample.time > latest.time)
+        {
+            self.previous = self.latest.replace(sample.clone());
+        }
+    }
+}
+
+/// Resamples a stream to the rate of the graph, holding the latest sample taken at or
+/// before the time on port 1, usually a `Clock`. Port 0 reads the stream.
+#[derive(Clone, Default)]
+pub struct SampleHold<T> {
+    history: History<T>,
+}
+
+impl<T> SampleHold<T> {
+    pub fn new() -> Self {
+        Self {
+            history: History {
+                previous: None,
+                latest: None,
+            },
+        }
+    }
+}
+
+impl<T: Value> PortCompute for SampleHold<T> {
+    type Ports = (Sample<T>, f64);
+    type Out = T;
+    fn compute(&self, ports: (&Sample<T>, &f64)) -> Self::Out {
+        self.clone().try_compute_mut(ports).unwrap()
+    }
+
+    fn try_compute_mut(&mut self, (sample, now): (&Sample<T>, &f64)) -> Result<T, String> {
+        self.history.push(sample);
+        let History { previous, latest } = &self.history;
+        // Samples from the future are only used while there is nothing older
+        let held = [latest, previous]
+            .into_iter()
+            .flatten()
+            .find(|sample| sample.time <= *now)
+            .or(latest.as_ref());
+        Ok(held.map(|sample| sample.value.clone()).unwrap_or_default())
+    }
+}
+
+/// Resamples a stream of `f64`s to the rate of the graph, interpolating linearly between
+/// its two latest samples at the time on port 1, usually a `Clock`. Times outside of them
+/// hold the nearest one. Port 0 reads the stream.
+#[derive(Clone, Default)]
+pub struct SampleLerp {
+    history: History<f64>,
+}
+
+impl SampleLerp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PortCompute for SampleLerp {
+    type Ports = (Sample<f64>, f64);
+    type Out = f64;
+    fn compute(&self, ports: (&Sample<f64>, &f64)) -> Self::Out {
+        self.clone().try_compute_mut(ports).unwrap()
+    }
+
+    fn try_compute_mut(&mut self, (sample, now): (&Sample<f64>, &f64)) -> Result<f64, String> {
+        self.history.push(sample);
+        Ok(match (&self.history.previous, &self.history.latest) {
+            (Some(previous), Some(latest)) => {
+                let t = (now - previous.time) / (latest.time - previous.time);
+                previous.value + (latest.value - previous.value) * t.clamp(0.0, 1.0)
+            }
+            (_, latest) => latest.map_or(0.0, |latest| latest.value),
+        })
+    }
+}
+
+#[cfg(test)]
+mod resample_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_resample_stream() -> Result<(), ComputeGraphErrors> {
+        //  Input (dt) ── clock ── hold, lerp (port 1) ── pack
+        //  sensor ────────────── hold, lerp (port 0) ──┘
+        let mut graph = Graph::new();
+        let clock_handle = graph.insert_node("clock", Clock::new());
+        let sensor_handle =
+            graph.insert_node("sensor", Parameter::new("sensor", Sample::new(0.0, 0.0)));
+        let hold_handle = graph.insert_port_node("hold", SampleHold::<f64>::new());
+        let lerp_handle = graph.insert_port_node("lerp", SampleLerp::new());
+        let pack_handle = graph.insert_node("pack", Pack2::<f64>::new());
+        for handle in [hold_handle.erase(), lerp_handle.erase()] {
+            graph.add_input_port(&handle, 0, &sensor_handle)?;
+            graph.add_input_port(&handle, 1, &clock_handle)?;
+            graph.add_input(&pack_handle, &handle)?;
+        }
+        graph.set_output_node(&pack_handle)?;
+        let mut compute_graph = graph.build::<f64, (f64, f64)>()?;
+
+        // The sensor updates at its own rate, and its last reading is ahead of the clock
+        let mut resampled = Vec::new();
+        for reading in [None, Some((0.5, 2.0)), Some((1.5, 6.0)), None] {
+            if let Some((time, value)) = reading {
+                compute_graph.set_parameter("sensor", Sample::new(time, value))?;
+            }
+            resampled.push(compute_graph.compute_mut(&0.25));
+        }
+        assert_eq!(resampled, [(0.0, 0.0), (2.0, 2.0), (2.0, 3.0), (2.0, 4.0)]);
+        Ok(())
+    }
+}
@@ -0,0 +1,389 @@
+use crate::com_graph::ComputeGraph;
+use crate::graph::{ComputeGraphErrors, Graph, NodeHandle};
+use crate::operations::{AddInputs, And, Compare, CompareOp, Constant, Not, Or, Select};
+use std::collections::HashMap;
+
+/// Condition over named numeric features, either built with the helper
+/// constructors or parsed from an expression like `age >= 18 && !(score < 0.5)`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    Compare {
+        feature: String,
+        op: CompareOp,
+        value: f64,
+    },
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+    Negate(Box<Condition>),
+}
+
+impl Condition {
+    pub fn compare(feature: impl Into<String>, op: CompareOp, value: f64) -> Self {
+        Condition::Compare {
+            feature: feature.into(),
+            op,
+            value,
+        }
+    }
+
+    pub fn lt(feature: impl Into<String>, value: f64) -> Self {
+        Self::compare(feature, CompareOp::Lt, value)
+    }
+
+    pub fn le(feature: impl Into<String>, value: f64) -> Self {
+        Self::compare(feature, CompareOp::Le, value)
+    }
+
+    pub fn gt(feature: impl Into<String>, value: f64) -> Self {
+        Self::compare(feature, CompareOp::Gt, value)
+    }
+
+    pub fn ge(feature: impl Into<String>, value: f64) -> Self {
+        Self::compare(feature, CompareOp::Ge, value)
+    }
+
+    pub fn and(self, other: Condition) -> Self {
+        match self {
+            Condition::All(mut conditions) => {
+                conditions.push(other);
+                Condition::All(conditions)
+            }
+            condition => Condition::All(vec![condition, other]),
+        }
+    }
+
+    pub fn or(self, other: Condition) -> Self {
+        match self {
+            Condition::Any(mut conditions) => {
+                conditions.push(other);
+                Condition::Any(conditions)
+            }
+            condition => Condition::Any(vec![condition, other]),
+        }
+    }
+
+    pub fn negate(self) -> Self {
+        Condition::Negate(Box::new(self))
+    }
+
+    pub fn parse(expression: &str) -> Result<Condition, String> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let condition = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(condition),
+            Some(token) => Err(format!("unexpected '{}'", token)),
+        }
+    }
+
+    fn add_to_graph(
+        &self,
+        graph: &mut Graph,
+        features: &mut HashMap<String, NodeHandle>,
+    ) -> Result<NodeHandle, ComputeGraphErrors> {
+        let (handle, children) = match self {
+            Condition::Compare { feature, op, value } => {
+                let feature_handle = *features
+                    .entry(feature.clone())
+                    .or_insert_with(|| graph.insert_node(feature.clone(), Constant(0.0)).erase());
+                let handle = graph.insert_node(
+                    format!("{} {} {}", feature, op_symbol(*op), value),
+                    Compare::new(*op, *value),
+                );
+                graph.add_input(&handle, &feature_handle)?;
+                return Ok(handle.erase());
+            }
+            Condition::All(conditions) => (graph.insert_node("all", And), conditions.as_slice()),
+            Condition::Any(conditions) => (graph.insert_node("any", Or), conditions.as_slice()),
+            Condition::Negate(condition) => (
+                graph.insert_node("not", Not),
+                std::slice::from_ref(condition.as_ref()),
+            ),
+        };
+        for child in children {
+            let child_handle = child.add_to_graph(graph, features)?;
+            graph.add_input(&handle, &child_handle)?;
+        }
+        graph.disconnect_from_input(&handle)?;
+        Ok(handle.erase())
+    }
+}
+
+fn op_symbol(op: CompareOp) -> &'static str {
+    match op {
+        CompareOp::Lt => "<",
+        CompareOp::Le => "<=",
+        CompareOp::Gt => ">",
+        CompareOp::Ge => ">=",
+        CompareOp::Eq => "==",
+        CompareOp::Ne => "!=",
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' {
+            let mut token = String::new();
+            while let Some(&c) = chars.peek() {
+                let exponent_sign = (c == '-' || c == '+') && token.ends_with(['e', 'E']);
+                if c.is_alphanumeric() || c == '_' || c == '.' || exponent_sign || token.is_empty()
+                {
+                    token.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(token);
+        } else {
+            chars.next();
+            let token = match (c, chars.peek()) {
+                ('&', Some('&')) | ('|', Some('|')) | ('<' | '>' | '=' | '!', Some('=')) => {
+                    let second = chars.next().unwrap();
+                    format!("{}{}", c, second)
+                }
+                ('<' | '>' | '!' | '(' | ')', _) => c.to_string(),
+                _ => return Err(format!("unexpected '{}'", c)),
+            };
+            tokens.push(token);
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<&str> {
+        let token = self.tokens.get(self.pos)?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.tokens.get(self.pos).is_some_and(|t| t == token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Condition, String> {
+        let mut conditions = vec![self.and()?];
+        while self.eat("||") {
+            conditions.push(self.and()?);
+        }
+        Ok(match conditions.len() {
+            1 => conditions.pop().unwrap(),
+            _ => Condition::Any(conditions),
+        })
+    }
+
+    fn and(&mut self) -> Result<Condition, String> {
+        let mut conditions = vec![self.unary()?];
+        while self.eat("&&") {
+            conditions.push(self.unary()?);
+        }
+        Ok(match conditions.len() {
+            1 => conditions.pop().unwrap(),
+            _ => Condition::All(conditions),
+        })
+    }
+
+    fn unary(&mut self) -> Result<Condition, String> {
+        if self.eat("!") {
+            return Ok(self.unary()?.negate());
+        }
+        if self.eat("(") {
+            let condition = self.or()?;
+            if !self.eat(")") {
+                return Err("expected ')'".to_string());
+            }
+            return Ok(condition);
+        }
+        let feature = self.next().ok_or("expected a feature name")?.to_string();
+        let op = match self.next() {
+            Some("<") => CompareOp::Lt,
+            Some("<=") => CompareOp::Le,
+            Some(">") => CompareOp::Gt,
+            Some(">=") => CompareOp::Ge,
+            Some("==") => CompareOp::Eq,
+            Some("!=") => CompareOp::Ne,
+            other => {
+                return Err(format!(
+                    "expected a comparison after '{}', found {:?}",
+                    feature, other
+                ))
+            }
+        };
+        let value = self.next().ok_or("expected a number")?;
+        let value = value
+            .parse::<f64>()
+            .map_err(|_| format!("'{}' is not a number", value))?;
+        Ok(Condition::compare(feature, op, value))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Rule {
+    pub name: String,
+    pub condition: Condition,
+    pub weight: f64,
+}
+
+/// Declarative list of weighted rules. The score is the base value plus the
+/// weights of all rules whose condition holds.
+#[derive(Clone, Debug, Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+    base: f64,
+}
+
+impl RuleSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn base(&mut self, base: f64) -> &mut Self {
+        self.base = base;
+        self
+    }
+
+    pub fn add_rule(
+        &mut self,
+        name: impl Into<String>,
+        condition: Condition,
+        weight: f64,
+    ) -> &mut Self {
+        self.rules.push(Rule {
+            name: name.into(),
+            condition,
+            weight,
+        });
+        self
+    }
+
+    /// Adds a rule with a condition parsed by `Condition::parse`.
+    pub fn parse_rule(
+        &mut self,
+        name: impl Into<String>,
+        expression: &str,
+        weight: f64,
+    ) -> Result<&mut Self, String> {
+        let condition = Condition::parse(expression)?;
+        Ok(self.add_rule(name, condition, weight))
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Generates the graph: one constant per feature, comparison and boolean nodes for the
+    /// conditions, a `Select` per rule turning it into its weight, and a sum as output.
+    pub fn build(&self) -> Result<RuleGraph, ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let mut features = HashMap::new();
+        let sum_handle = graph.insert_node("score", AddInputs::<f64>::new());
+        let base_handle = graph.insert_node("base", Constant(self.base));
+        graph.add_input(&sum_handle, &base_handle)?;
+        for rule in self.rules.iter() {
+            let condition_handle = rule.condition.add_to_graph(&mut graph, &mut features)?;
+            let weight_handle = graph.insert_node(rule.name.clone(), Select::new(rule.weight, 0.0));
+            graph.add_input(&weight_handle, &condition_handle)?;
+            graph.add_input(&sum_handle, &weight_handle)?;
+        }
+        graph.set_output_node(&sum_handle)?;
+        let compute_graph = graph.build()?;
+        Ok(RuleGraph {
+            graph,
+            compute_graph,
+            features,
+        })
+    }
+}
+
+/// A `RuleSet` compiled to a compute graph. Features are set by name before scoring.
+pub struct RuleGraph {
+    graph: Graph,
+    compute_graph: ComputeGraph<(), f64>,
+    features: HashMap<String, NodeHandle>,
+}
+
+impl RuleGraph {
+    /// The generated authoring graph, e.g. for inspection or export.
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn features(&self) -> impl Iterator<Item = &str> {
+        self.features.keys().map(|name| name.as_str())
+    }
+
+    pub fn set_feature(&mut self, name: &str, value: f64) -> Result<(), ComputeGraphErrors> {
+        let handle = self.features.get(name).ok_or_else(|| {
+            ComputeGraphErrors::InvalidParameter(format!("unknown feature '{}'", name).into())
+        })?;
+        self.compute_graph.set_param(handle, "value", value)
+    }
+
+    pub fn score(&mut self, features: &[(&str, f64)]) -> Result<f64, ComputeGraphErrors> {
+        for (name, value) in features {
+            self.set_feature(name, *value)?;
+        }
+        self.compute_graph.try_compute(&())
+    }
+}
+
+#[cfg(test)]
+mod rules_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_parse_condition() {
+        assert_eq!(
+            Condition::parse("age >= 18 && !(income < 1e4 || debt > 5)"),
+            Ok(Condition::ge("age", 18.0).and(
+                Condition::Any(vec![
+                    Condition::lt("income", 1e4),
+                    Condition::gt("debt", 5.0)
+                ])
+                .negate()
+            ))
+        );
+        assert_eq!(
+            Condition::parse("temp > -2.5"),
+            Ok(Condition::gt("temp", -2.5))
+        );
+        assert!(Condition::parse("age >= ").is_err());
+        assert!(Condition::parse("age 18").is_err());
+        assert!(Condition::parse("(age > 1").is_err());
+    }
+
+    #[test]
+    fn test_rule_graph() -> Result<(), ComputeGraphErrors> {
+        let mut rules = RuleSet::new();
+        rules
+            .base(1.0)
+            .add_rule("adult", Condition::ge("age", 18.0), 2.0)
+            .parse_rule("risky", "debt > 1000 || missed_payments >= 2", -5.0)
+            .unwrap();
+        let mut rule_graph = rules.build()?;
+        assert_eq!(rule_graph.features().count(), 3);
+
+        assert_eq!(rule_graph.score(&[("age", 30.0)])?, 3.0);
+        assert_eq!(rule_graph.score(&[("missed_payments", 3.0)])?, -2.0);
+        assert_eq!(
+            rule_graph.score(&[("age", 12.0), ("missed_payments", 0.0)])?,
+            1.0
+        );
+        assert!(rule_graph.score(&[("height", 1.8)]).is_err());
+        Ok(())
+    }
+}
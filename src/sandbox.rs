@@ -0,0 +1,373 @@
+use crate::com_graph::{ComputeGraph, EvalContext};
+use crate::compute::Value;
+use crate::graph::ComputeGraphErrors;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::any::{Any, TypeId};
+use std::cell::Cell;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+thread_local! {
+    static TRACKER: Cell<*const Budget> = const { Cell::new(ptr::null()) };
+}
+static TRACKING_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Global allocator counting the bytes allocated by sandboxed nodes, needed for
+/// `Sandbox::max_node_allocation`. Allocations past the limit return null, so a node
+/// should allocate large buffers fallibly (`Vec::try_reserve`) to fail instead of aborting.
+/// Install it in the host binary:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: TrackingAllocator = TrackingAllocator(std::alloc::System);
+/// ```
+pub struct TrackingAllocator<A = System>(pub A);
+
+impl<A> TrackingAllocator<A> {
+    /// Counts `size` requested bytes, returning false for requests past the scope's budget.
+    fn track(&self, size: usize) -> bool {
+        TRACKING_INSTALLED.store(true, Ordering::Relaxed);
+        // `try_with` fails once the thread is tearing down its locals
+        TRACKER
+            .try_with(|tracker| {
+                let budget = tracker.get();
+                if budget.is_null() {
+                    return true;
+                }
+                // The budget outlives the scope that registered it, see `TrackScope`
+                unsafe { &*budget }.take(size)
+            })
+            .unwrap_or(true)
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if !self.track(layout.size()) {
+            return ptr::null_mut();
+        }
+        self.0.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0.dealloc(ptr, layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if !self.track(layout.size()) {
+            return ptr::null_mut();
+        }
+        self.0.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if !self.track(new_size.saturating_sub(layout.size())) {
+            return ptr::null_mut();
+        }
+        self.0.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Bytes allocated by a sandboxed node, and the most it asked for past `limit`.
+struct Budget {
+    allocated: AtomicUsize,
+    refused: AtomicUsize,
+    limit: usize,
+}
+
+impl Budget {
+    fn new(limit: Option<usize>) -> Self {
+        Self {
+            allocated: AtomicUsize::new(0),
+            refused: AtomicUsize::new(0),
+            limit: limit.unwrap_or(usize::MAX),
+        }
+    }
+
+    /// Refused requests aren't counted, so the node can still allocate to report its failure.
+    fn take(&self, size: usize) -> bool {
+        let taken =
+            self.allocated
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |allocated| {
+                    allocated
+                        .checked_add(size)
+                        .filter(|total| *total <= self.limit)
+                });
+        if let Err(allocated) = taken {
+            self.refused
+                .fetch_max(allocated.saturating_add(size), Ordering::Relaxed);
+        }
+        taken.is_ok()
+    }
+
+    /// Bytes the node allocated or tried to.
+    fn requested(&self) -> usize {
+        self.allocated
+            .load(Ordering::Relaxed)
+            .max(self.refused.load(Ordering::Relaxed))
+    }
+}
+
+/// Counts the current thread's allocations against `budget` while alive.
+struct TrackScope {
+    _budget: Arc<Budget>,
+}
+
+impl TrackScope {
+    fn enter(budget: Arc<Budget>) -> Self {
+        TRACKER.with(|tracker| tracker.set(Arc::as_ptr(&budget)));
+        Self { _budget: budget }
+    }
+}
+
+impl Drop for TrackScope {
+    fn drop(&mut self) {
+        TRACKER.with(|tracker| tracker.set(ptr::null()));
+    }
+}
+
+/// Per-node resource limits for `ComputeGraph::compute_sandboxed`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Sandbox {
+    max_node_time: Option<Duration>,
+    max_node_allocation: Option<usize>,
+}
+
+impl Sandbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_node_time(mut self, max_node_time: Duration) -> Self {
+        self.max_node_time = Some(max_node_time);
+        self
+    }
+
+    /// Total bytes a node may allocate in one evaluation. Requires `TrackingAllocator`.
+    pub fn max_node_allocation(mut self, bytes: usize) -> Self {
+        self.max_node_allocation = Some(bytes);
+        self
+    }
+}
+
+/// How often a waiting evaluation checks the allocation counter of a running node.
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+impl<In, Out> ComputeGraph<In, Out>
+where
+    In: Value,
+    Out: Value,
+{
+    /// Evaluates every node on its own thread under its `NodePolicy`, and fails as soon as a
+    /// node runs longer or allocates more than `sandbox` allows, or panics. Threads can't be
+    /// killed, so an offending node is abandoned and keeps running until it returns.
+    pub fn compute_sandboxed(
+        &self,
+        input: &In,
+        sandbox: &Sandbox,
+    ) -> Result<Out, ComputeGraphErrors> {
+        if sandbox.max_node_allocation.is_some() && !TRACKING_INSTALLED.load(Ordering::Relaxed) {
+            return Err(ComputeGraphErrors::InvalidParameter(
+                "allocation limits need TrackingAllocator as the global allocator".into(),
+            ));
+        }
+        self.with_context(|context| self.sandboxed_in(input, sandbox, context))
+    }
+
+    fn sandboxed_in(
+        &self,
+        input: &In,
+        sandbox: &Sandbox,
+        context: &mut EvalContext,
+    ) -> Result<Out, ComputeGraphErrors> {
+        for (index, node) in self.nodes.iter().enumerate() {
+            let inputs = node
+                .inputs
+                .iter()
+                .map(|input| {
+                    self.nodes[*input]
+                        .func
+                        .clone_output(context.outputs[*input].as_ref())
+                })
+                .collect::<Vec<_>>();
+            let reads_input =
+                node.connected_to_input && node.func.input_type() != TypeId::of::<()>();
+            let graph_input = input.clone();
+            // Starting from the last output keeps stale fallbacks working
+            let mut output = node.func.clone_output(context.outputs[index].as_ref());
+            let func = node.func.clone();
+            let policy = node.policy.clone();
+            let name = node.name.clone();
+            let budget = Arc::new(Budget::new(sandbox.max_node_allocation));
+            let scope_budget = budget.clone();
+            let (sender, receiver) = channel();
+            // Held until the node's result arrives, so effects never overlap. An abandoned
+            // node no longer holds it.
+            let _effect = self.effect_guard(index);
+            let spawned = thread::Builder::new().name(name.clone()).spawn(move || {
+                let _scope = TrackScope::enter(scope_budget);
+                let mut refs = inputs
+                    .iter()
+                    .map(|input| input.as_ref() as &dyn Any)
+                    .collect::<Vec<_>>();
+                if reads_input {
+                    refs.push(&graph_input);
+                }
+                let result = policy
+                    .run(&name, func.as_ref(), &refs, output.as_mut())
+                    .map(|_| output);
+                let _ = sender.send(result);
+            });
+            let failed = |reason: String| {
+                ComputeGraphErrors::format_node_failed(&node.name, &reason).with_node(node.handle)
+            };
+            spawned.map_err(|err| failed(err.to_string()))?;
+
+            let start = Instant::now();
+            let over_allocation = || {
+                let bytes = budget.requested();
+                sandbox
+                    .max_node_allocation
+                    .filter(|limit| bytes > *limit)
+                    .map(|limit| failed(format!("allocated {} bytes, more than {}", bytes, limit)))
+            };
+            let output = loop {
+                let elapsed = start.elapsed();
+                if let Some(limit) = sandbox.max_node_time.filter(|limit| elapsed > *limit) {
+                    return Err(failed(format!("ran longer than {:?}", limit)));
+                }
+                if let Some(error) = over_allocation() {
+                    return Err(error);
+                }
+                match receiver.recv_timeout(POLL_INTERVAL) {
+                    // A refused allocation makes the node fail on its own, report why instead
+                    Ok(result) => match over_allocation() {
+                        Some(error) => return Err(error),
+                        None => break result.map_err(|err| err.with_node(node.handle))?,
+                    },
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        return Err(failed("panicked".to_string()));
+                    }
+                }
+            };
+            context.set_output(index, output);
+        }
+        Ok(context.output::<Out>().clone())
+    }
+}
+
+#[cfg(test)]
+mod sandbox_tests {
+    use crate::com_graph::ComputeGraph;
+    use crate::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[derive(Clone, Copy)]
+    struct Hog {
+        sleep: u64,
+        bytes: usize,
+    }
+
+    impl Compute for Hog {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            std::thread::sleep(Duration::from_millis(self.sleep));
+            let buffer = vec![1u8; self.bytes];
+            **inputs.first().unwrap() + buffer.iter().map(|b| *b as f64).sum::<f64>()
+        }
+    }
+
+    fn hog_graph(hog: Hog) -> Result<ComputeGraph<f64, f64>, ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let hog_handle = graph.insert_node("hog", hog);
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&add_handle, &hog_handle)?;
+        graph.set_output_node(&add_handle)?;
+        graph.build()
+    }
+
+    // Allocation limits are tested in `tests/tracking_allocator.rs`, which installs
+    // `TrackingAllocator` for its whole binary
+    #[test]
+    fn test_sandbox_limits() -> Result<(), ComputeGraphErrors> {
+        let sandbox = Sandbox::new().max_node_time(Duration::from_millis(200));
+
+        let compute_graph = hog_graph(Hog {
+            sleep: 0,
+            bytes: 10,
+        })?;
+        assert_eq!(compute_graph.compute_sandboxed(&1.0, &sandbox)?, 11.0);
+
+        let slow = hog_graph(Hog {
+            sleep: 5_000,
+            bytes: 0,
+        })?;
+        let Err(ComputeGraphErrors::NodeFailed(message)) = slow.compute_sandboxed(&1.0, &sandbox)
+        else {
+            panic!("time limit not enforced");
+        };
+        assert_eq!(message, "'hog' failed: ran longer than 200ms");
+
+        assert!(matches!(
+            compute_graph.compute_sandboxed(&1.0, &sandbox.max_node_allocation(1 << 20)),
+            Err(ComputeGraphErrors::InvalidParameter(_))
+        ));
+        Ok(())
+    }
+
+    /// Counts how many instances run at the same time.
+    #[derive(Clone)]
+    struct Effect {
+        running: Arc<AtomicUsize>,
+        overlaps: Arc<AtomicUsize>,
+    }
+
+    impl Compute for Effect {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            if self.running.fetch_add(1, Ordering::SeqCst) > 0 {
+                self.overlaps.fetch_add(1, Ordering::SeqCst);
+            }
+            thread::sleep(Duration::from_millis(2));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            inputs.iter().copied().sum()
+        }
+    }
+
+    #[test]
+    fn test_sandboxed_effects_do_not_overlap() -> Result<(), ComputeGraphErrors> {
+        let effect = Effect {
+            running: Arc::new(AtomicUsize::new(0)),
+            overlaps: Arc::new(AtomicUsize::new(0)),
+        };
+        let mut graph = Graph::new();
+        let effect_handle = graph.insert_node("effect", effect.clone());
+        graph.set_scheduling(&effect_handle, Scheduling::Ordered)?;
+        graph.connect_to_input(&effect_handle)?;
+        graph.set_output_node(&effect_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+
+        let sandbox = Sandbox::new().max_node_time(Duration::from_secs(5));
+        thread::scope(|scope| {
+            let threads = (0..4)
+                .map(|_| scope.spawn(|| compute_graph.compute_sandboxed(&1.0, &sandbox)))
+                .collect::<Vec<_>>();
+            for thread in threads {
+                assert_eq!(thread.join().unwrap()?, 1.0);
+            }
+            Ok::<_, ComputeGraphErrors>(())
+        })?;
+        assert_eq!(effect.overlaps.load(Ordering::SeqCst), 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use crate::com_graph::ComputeNode;
+use crate::graph::{ComputeGraphErrors, Graph, NodeHandle};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// When a node may run relative to the others. Pure nodes are `Free` and run whenever
+/// their inputs are ready. Nodes with side effects, like writing a file or calling a
+/// service, can ask for stronger guarantees.
+///
+/// Non-`Free` nodes never run concurrently with each other, also not across threads
+/// evaluating the same `ComputeGraph`, and always run in compute order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Scheduling {
+    #[default]
+    Free,
+    /// Runs after every earlier `Ordered` or `Exclusive` node, while pure nodes may
+    /// still run next to it.
+    Ordered,
+    /// A barrier: `compute_parallel` runs it alone, after every node earlier in compute
+    /// order and before every later one.
+    Exclusive,
+}
+
+impl Scheduling {
+    pub fn is_free(&self) -> bool {
+        *self == Scheduling::Free
+    }
+}
+
+/// For every node that isn't `Free`, the closest such node before it in compute order.
+/// Executors run the two in that order, as if connected by `Graph::add_order`.
+pub(crate) fn previous_effects(nodes: &[ComputeNode]) -> Vec<Option<usize>> {
+    let mut last = None;
+    nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| {
+            if node.scheduling.is_free() {
+                return None;
+            }
+            last.replace(index)
+        })
+        .collect()
+}
+
+impl Graph {
+    /// Sets the execution guarantees of `node_handle`, see `Scheduling`.
+    pub fn set_scheduling(
+        &mut self,
+        node_handle: &NodeHandle,
+        scheduling: Scheduling,
+    ) -> Result<(), ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        if let Some(node) = self.node_mut(node_handle.key) {
+            node.scheduling = scheduling;
+        }
+        self.touch();
+        let key = node_handle.key;
+        self.record(|graph| {
+            format!(
+                "set scheduling of '{}' to {:?}",
+                graph.name_for_log(key),
+                scheduling
+            )
+        });
+        Ok(())
+    }
+
+    pub fn get_scheduling(
+        &self,
+        node_handle: &NodeHandle,
+    ) -> Result<Scheduling, ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        Ok(self.nodes[node_handle.key].scheduling)
+    }
+}
+
+#[cfg(test)]
+mod scheduling_tests {
+    use crate::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    /// Counts how many instances run at the same time.
+    #[derive(Clone)]
+    struct Effect {
+        running: Arc<AtomicUsize>,
+        overlaps: Arc<AtomicUsize>,
+    }
+
+    impl Compute for Effect {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            if self.running.fetch_add(1, Ordering::SeqCst) > 0 {
+                self.overlaps.fetch_add(1, Ordering::SeqCst);
+            }
+            thread::sleep(Duration::from_millis(2));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            inputs.iter().copied().sum()
+        }
+    }
+
+    #[test]
+    fn test_effects_do_not_overlap() -> Result<(), ComputeGraphErrors> {
+        let effect = Effect {
+            running: Arc::new(AtomicUsize::new(0)),
+            overlaps: Arc::new(AtomicUsize::new(0)),
+        };
+        let mut graph = Graph::new();
+        let sum_handle = graph.insert_node("sum", AddInputs::<f64>::new());
+        let mut effects = Vec::new();
+        for i in 0..4 {
+            let effect_handle = graph.insert_node(format!("effect{}", i), effect.clone());
+            graph.add_input(&sum_handle, &effect_handle)?;
+            effects.push(effect_handle);
+        }
+        graph.set_scheduling(&effects[0], Scheduling::Ordered)?;
+        graph.set_scheduling(&effects[1], Scheduling::Ordered)?;
+        graph.set_scheduling(&effects[2], Scheduling::Exclusive)?;
+        assert_eq!(graph.get_scheduling(&effects[3])?, Scheduling::Free);
+        graph.set_scheduling(&effects[3], Scheduling::Ordered)?;
+        let offset_handle = graph.insert_node("offset", Constant(10.0));
+        graph.add_input(&sum_handle, &offset_handle)?;
+        graph.set_output_node(&sum_handle)?;
+        let compute_graph = Arc::new(graph.build::<f64, f64>()?);
+
+        let threads = (0..4)
+            .map(|_| {
+                let compute_graph = compute_graph.clone();
+                thread::spawn(move || compute_graph.try_compute(&1.0))
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap()?, 14.0);
+        }
+        assert_eq!(effect.overlaps.load(Ordering::SeqCst), 0);
+
+        #[cfg(feature = "rayon")]
+        {
+            let widths = compute_graph
+                .levels
+                .iter()
+                .map(|level| level.len())
+                .collect::<Vec<_>>();
+            // effect0 | effect1 | effect2 alone | effect3 and offset | sum
+            assert_eq!(widths, [1, 1, 1, 2, 1]);
+            assert_eq!(compute_graph.compute_parallel(&1.0)?, 14.0);
+            assert_eq!(effect.overlaps.load(Ordering::SeqCst), 0);
+        }
+        Ok(())
+    }
+}
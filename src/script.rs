@@ -0,0 +1,216 @@
+use crate::compute::{Compute, Value};
+use crate::graph::ComputeGraphErrors;
+use rhai::{Array, Dynamic, Engine, Scope, AST};
+use std::any::type_name;
+use std::hash::Hasher;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+const DEFAULT_MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Node running a user-defined rhai script, so end users can customize pipelines without
+/// recompiling. The script is compiled once, when the node is created. Each evaluation
+/// sees the inputs as the `inputs` array and under the names given to `with_names`, and
+/// outputs the value of the script's last expression. Scripts compute with `i64`, `f64`,
+/// `bool` and `String`; other input types are passed through opaquely.
+///
+/// With the serde feature, the node serializes as its source and is recompiled on load.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "", try_from = "ScriptSource", into = "ScriptSource")
+)]
+pub struct ScriptNode<In, Out> {
+    source: String,
+    names: Vec<String>,
+    max_operations: u64,
+    engine: Arc<Engine>,
+    ast: Arc<AST>,
+    _types: PhantomData<fn(&In) -> Out>,
+}
+
+impl<In, Out> Clone for ScriptNode<In, Out> {
+    fn clone(&self) -> Self {
+        Self {
+            source: self.source.clone(),
+            names: self.names.clone(),
+            max_operations: self.max_operations,
+            engine: self.engine.clone(),
+            ast: self.ast.clone(),
+            _types: PhantomData,
+        }
+    }
+}
+
+fn engine(max_operations: u64) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(max_operations);
+    engine
+}
+
+impl<In, Out> ScriptNode<In, Out> {
+    /// Compiles `source`, failing with `ComputeGraphErrors::InvalidParameter` on syntax errors.
+    pub fn new(source: impl Into<String>) -> Result<Self, ComputeGraphErrors> {
+        let source = source.into();
+        let engine = engine(DEFAULT_MAX_OPERATIONS);
+        let ast = engine.compile(&source).map_err(|err| {
+            ComputeGraphErrors::InvalidParameter(format!("script doesn't compile: {}", err).into())
+        })?;
+        Ok(Self {
+            source,
+            names: Vec::new(),
+            max_operations: DEFAULT_MAX_OPERATIONS,
+            engine: Arc::new(engine),
+            ast: Arc::new(ast),
+            _types: PhantomData,
+        })
+    }
+
+    /// Names the inputs in the scope of the script, in the order they are connected.
+    pub fn with_names<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.names = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Limits the operations one evaluation may run, so endless loops fail instead of
+    /// hanging the graph. Defaults to a million.
+    pub fn max_operations(mut self, max_operations: u64) -> Self {
+        self.max_operations = max_operations;
+        self.engine = Arc::new(engine(max_operations));
+        self
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+impl<In, Out> Compute for ScriptNode<In, Out>
+where
+    In: Value,
+    Out: Value,
+{
+    type In = In;
+    type Out = Out;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.try_compute(inputs).unwrap()
+    }
+
+    fn try_compute(&self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        let mut scope = Scope::new();
+        let values = inputs
+            .iter()
+            .map(|input| Dynamic::from((*input).clone()))
+            .collect::<Array>();
+        for (name, value) in self.names.iter().zip(values.iter()) {
+            scope.push_dynamic(name.as_str(), value.clone());
+        }
+        scope.push("inputs", values);
+        let result = self
+            .engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast)
+            .map_err(|err| format!("script failed: {}", err))?;
+        let result_type = result.type_name();
+        result.try_cast::<Out>().ok_or_else(|| {
+            format!(
+                "script returned '{}' instead of '{}'",
+                result_type,
+                type_name::<Out>()
+            )
+        })
+    }
+
+    fn fingerprint(&self, state: &mut dyn Hasher) {
+        state.write(self.source.as_bytes());
+        for name in self.names.iter() {
+            state.write(name.as_bytes());
+        }
+        state.write_u64(self.max_operations);
+    }
+}
+
+/// Serialized form of a `ScriptNode`.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ScriptSource {
+    source: String,
+    #[serde(default)]
+    names: Vec<String>,
+    max_operations: u64,
+}
+
+#[cfg(feature = "serde")]
+impl<In, Out> TryFrom<ScriptSource> for ScriptNode<In, Out> {
+    type Error = ComputeGraphErrors;
+    fn try_from(script: ScriptSource) -> Result<Self, Self::Error> {
+        Ok(ScriptNode::new(script.source)?
+            .with_names(script.names)
+            .max_operations(script.max_operations))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<In, Out> From<ScriptNode<In, Out>> for ScriptSource {
+    fn from(node: ScriptNode<In, Out>) -> Self {
+        Self {
+            source: node.source,
+            names: node.names,
+            max_operations: node.max_operations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod script_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_script_node() -> Result<(), ComputeGraphErrors> {
+        //  price, tax ── total
+        let mut graph = Graph::new();
+        let price_handle = graph.insert_node("price", Constant(20.0));
+        let tax_handle = graph.insert_node("tax", Constant(0.25));
+        let total_handle = graph.insert_node(
+            "total",
+            ScriptNode::<f64, f64>::new("let total = price * (1.0 + tax); total.round()")?
+                .with_names(["price", "tax"]),
+        );
+        graph.add_input(&total_handle, &price_handle)?;
+        graph.add_input(&total_handle, &tax_handle)?;
+        graph.set_output_node(&total_handle)?;
+        assert_eq!(graph.build::<(), f64>()?.try_compute(&())?, 25.0);
+
+        let count = ScriptNode::<f64, i64>::new("inputs.len()")?;
+        assert_eq!(count.compute(&[&1.0, &2.0, &3.0]), 3);
+
+        assert!(matches!(
+            ScriptNode::<f64, f64>::new("let = 1"),
+            Err(ComputeGraphErrors::InvalidParameter(_))
+        ));
+        let wrong_type = ScriptNode::<f64, f64>::new("\"text\"")?;
+        assert_eq!(
+            wrong_type.try_compute(&[]),
+            Err("script returned 'string' instead of 'f64'".to_string())
+        );
+        let endless = ScriptNode::<f64, f64>::new("loop {}")?.max_operations(1_000);
+        assert!(endless
+            .try_compute(&[])
+            .unwrap_err()
+            .starts_with("script failed"));
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&count).unwrap();
+            let loaded: ScriptNode<f64, i64> = serde_json::from_str(&json).unwrap();
+            assert_eq!(
+                (loaded.source(), loaded.compute(&[&1.0])),
+                ("inputs.len()", 1)
+            );
+            assert!(serde_json::from_str::<ScriptNode<f64, i64>>(
+                r#"{"source": "let = 1", "max_operations": 10}"#
+            )
+            .is_err());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,910 @@
+use crate::compute::{Compute, InnerCompute};
+use crate::graph::{ComputeGraphErrors, Graph, NodeHandle, NodeStyle};
+use crate::provenance::ProvenanceEntry;
+use crate::registry::NodeRegistry;
+use crate::scheduling::Scheduling;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hasher;
+
+/// Version of the envelope layout itself, bumped when `SerializedGraph` changes shape.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SerializedNode {
+    pub id: usize,
+    pub name: String,
+    pub op: String,
+    pub config: serde_json::Value,
+    pub inputs: Vec<usize>,
+    /// Port of each input of a `PortCompute` node, empty for other nodes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<usize>,
+    /// Nodes this one is ordered after, see `Graph::add_order`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<usize>,
+    pub connected_to_input: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "NodeStyle::is_empty")]
+    pub style: NodeStyle,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub critical: bool,
+    #[serde(default, skip_serializing_if = "Scheduling::is_free")]
+    pub scheduling: Scheduling,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SerializedGraph {
+    pub nodes: Vec<SerializedNode>,
+    pub output_node: Option<usize>,
+}
+
+/// A saved graph together with the versions it was written with.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GraphEnvelope {
+    pub format_version: u32,
+    /// The `NodeRegistry::version` of the node library that wrote the graph.
+    pub version: u32,
+    pub graph: SerializedGraph,
+    /// Mutation log of the graph, see `Graph::track_provenance`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provenance: Vec<ProvenanceEntry>,
+}
+
+/// Upgrades a serialized graph from `source_version()` to `source_version() + 1`.
+pub trait Migration {
+    fn source_version(&self) -> u32;
+    fn migrate(&self, graph: &mut SerializedGraph) -> Result<(), String>;
+}
+
+/// Migration renaming an operation identifier.
+pub struct RenameOp {
+    pub source_version: u32,
+    pub old_name: String,
+    pub new_name: String,
+}
+
+impl RenameOp {
+    pub fn new(
+        source_version: u32,
+        old_name: impl Into<String>,
+        new_name: impl Into<String>,
+    ) -> Self {
+        Self {
+            source_version,
+            old_name: old_name.into(),
+            new_name: new_name.into(),
+        }
+    }
+}
+
+impl Migration for RenameOp {
+    fn source_version(&self) -> u32 {
+        self.source_version
+    }
+    fn migrate(&self, graph: &mut SerializedGraph) -> Result<(), String> {
+        for node in graph
+            .nodes
+            .iter_mut()
+            .filter(|node| node.op == self.old_name)
+        {
+            node.op = self.new_name.clone();
+        }
+        Ok(())
+    }
+}
+
+impl<F> Migration for (u32, F)
+where
+    F: Fn(&mut SerializedGraph) -> Result<(), String>,
+{
+    fn source_version(&self) -> u32 {
+        self.0
+    }
+    fn migrate(&self, graph: &mut SerializedGraph) -> Result<(), String> {
+        (self.1)(graph)
+    }
+}
+
+/// Ops a saved graph uses that the registry lacks, e.g. because the crate or feature
+/// providing them isn't part of this build. See `Graph::deserialize_degraded`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MissingOps {
+    ops: BTreeMap<String, Vec<usize>>,
+}
+
+impl MissingOps {
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Identifiers of the missing ops, sorted.
+    pub fn ops(&self) -> impl Iterator<Item = &str> {
+        self.ops.keys().map(|op| op.as_str())
+    }
+
+    /// Ids of the nodes using `op`, see `SerializedNode::id`.
+    pub fn nodes_using(&self, op: &str) -> &[usize] {
+        self.ops.get(op).map_or(&[], |nodes| nodes.as_slice())
+    }
+}
+
+impl fmt::Display for MissingOps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (op, nodes)) in self.ops.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "'{}' used by {} node(s)", op, nodes.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// Placeholder for a node whose op is missing from the registry, see
+/// `Graph::deserialize_degraded`. Keeps the op and config, so the node is saved unchanged.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnavailableOp {
+    op: String,
+    config: serde_json::Value,
+}
+
+impl UnavailableOp {
+    pub fn op(&self) -> &str {
+        &self.op
+    }
+
+    pub fn config(&self) -> &serde_json::Value {
+        &self.config
+    }
+}
+
+impl Compute for UnavailableOp {
+    type In = ();
+    type Out = ();
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.try_compute(inputs).unwrap()
+    }
+
+    fn try_compute(&self, _inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        Err(format!("op '{}' is not available in this build", self.op))
+    }
+
+    fn fingerprint(&self, state: &mut dyn Hasher) {
+        state.write(self.op.as_bytes());
+        state.write(self.config.to_string().as_bytes());
+    }
+}
+
+/// The op `inner` stands in for, if it is an `UnavailableOp`.
+pub(crate) fn unavailable_op(inner: &dyn InnerCompute) -> Option<&str> {
+    inner
+        .as_any()
+        .downcast_ref::<UnavailableOp>()
+        .map(|unavailable| unavailable.op())
+}
+
+fn is_unavailable(graph: &Graph, handle: &NodeHandle) -> bool {
+    unavailable_op(graph.nodes[handle.key].inner.as_ref()).is_some()
+}
+
+/// A problem found while validating a serialized graph against a registry.
+/// Node ids refer to `SerializedNode::id`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoadIssue {
+    DuplicateId {
+        node: usize,
+    },
+    UnknownOp {
+        node: usize,
+        name: String,
+        op: String,
+    },
+    InvalidConfig {
+        node: usize,
+        name: String,
+        op: String,
+        message: String,
+    },
+    MissingNode {
+        node: usize,
+        name: String,
+        missing: usize,
+    },
+    MissingOutputNode {
+        missing: usize,
+    },
+    TypeMismatch {
+        node: usize,
+        name: String,
+        input: usize,
+        expected: &'static str,
+        found: &'static str,
+    },
+    Deprecated {
+        node: usize,
+        name: String,
+        op: String,
+        hint: String,
+    },
+    /// A `GraphPack` needs ops from a namespace the registry has none of.
+    MissingNamespace {
+        namespace: String,
+        version: u32,
+    },
+    /// A `GraphPack` needs a newer version of a namespace than the registry has.
+    OutdatedNamespace {
+        namespace: String,
+        required: u32,
+        found: u32,
+    },
+}
+
+impl LoadIssue {
+    /// Warnings are reported by `validate` but don't prevent loading the graph.
+    pub fn is_warning(&self) -> bool {
+        matches!(self, LoadIssue::Deprecated { .. })
+    }
+}
+
+impl fmt::Display for LoadIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadIssue::DuplicateId { node } => write!(f, "node id {} is used more than once", node),
+            LoadIssue::UnknownOp { node, name, op } => {
+                write!(f, "node {} '{}' uses unknown op '{}'", node, name, op)
+            }
+            LoadIssue::InvalidConfig {
+                node,
+                name,
+                op,
+                message,
+            } => write!(
+                f,
+                "node {} '{}' has an invalid config for '{}': {}",
+                node, name, op, message
+            ),
+            LoadIssue::MissingNode {
+                node,
+                name,
+                missing,
+            } => write!(
+                f,
+                "node {} '{}' takes input from missing node {}",
+                node, name, missing
+            ),
+            LoadIssue::MissingOutputNode { missing } => {
+                write!(f, "output node {} does not exist", missing)
+            }
+            LoadIssue::TypeMismatch {
+                node,
+                name,
+                input,
+                expected,
+                found,
+            } => write!(
+                f,
+                "node {} '{}' expects input type '{}' but node {} outputs '{}'",
+                node, name, expected, input, found
+            ),
+            LoadIssue::Deprecated {
+                node,
+                name,
+                op,
+                hint,
+            } => write!(
+                f,
+                "node {} '{}' uses deprecated op '{}': {}",
+                node, name, op, hint
+            ),
+            LoadIssue::MissingNamespace { namespace, version } => write!(
+                f,
+                "requires ops from '{}' version {}, which are not registered",
+                namespace, version
+            ),
+            LoadIssue::OutdatedNamespace {
+                namespace,
+                required,
+                found,
+            } => write!(
+                f,
+                "requires '{}' version {} but version {} is registered",
+                namespace, required, found
+            ),
+        }
+    }
+}
+
+impl GraphEnvelope {
+    /// Checks the graph against `registry` without building it, reporting every issue found.
+    pub fn validate(&self, registry: &NodeRegistry) -> Vec<LoadIssue> {
+        self.check(registry).1
+    }
+
+    /// Ops used by the graph that `registry` lacks.
+    pub fn missing_ops(&self, registry: &NodeRegistry) -> MissingOps {
+        let mut missing = MissingOps::default();
+        for node in self.graph.nodes.iter() {
+            if !registry.contains(&node.op) {
+                missing
+                    .ops
+                    .entry(node.op.clone())
+                    .or_default()
+                    .push(node.id);
+            }
+        }
+        missing
+    }
+
+    /// Compute objects of the nodes, `None` where they couldn't be created, and the issues found.
+    fn check(
+        &self,
+        registry: &NodeRegistry,
+    ) -> (Vec<Option<Box<dyn InnerCompute>>>, Vec<LoadIssue>) {
+        let mut issues = Vec::new();
+        let mut ids = HashMap::new();
+        for (index, node) in self.graph.nodes.iter().enumerate() {
+            if ids.insert(node.id, index).is_some() {
+                issues.push(LoadIssue::DuplicateId { node: node.id });
+            }
+        }
+
+        let mut objects = Vec::new();
+        for node in self.graph.nodes.iter() {
+            let entry = registry.get(&node.op);
+            match entry {
+                None => {
+                    issues.push(LoadIssue::UnknownOp {
+                        node: node.id,
+                        name: node.name.clone(),
+                        op: node.op.clone(),
+                    });
+                    objects.push(None);
+                }
+                Some(entry) => {
+                    if let Some(hint) = entry.deprecation() {
+                        issues.push(LoadIssue::Deprecated {
+                            node: node.id,
+                            name: node.name.clone(),
+                            op: node.op.clone(),
+                            hint: hint.to_string(),
+                        });
+                    }
+                    match entry.deserialize(node.config.clone()) {
+                        Ok(object) => objects.push(Some(object)),
+                        Err(err) => {
+                            issues.push(LoadIssue::InvalidConfig {
+                                node: node.id,
+                                name: node.name.clone(),
+                                op: node.op.clone(),
+                                message: err.to_string(),
+                            });
+                            objects.push(None);
+                        }
+                    }
+                }
+            }
+
+            for input in node.inputs.iter() {
+                let Some(input_node) = ids.get(input).map(|index| &self.graph.nodes[*index]) else {
+                    issues.push(LoadIssue::MissingNode {
+                        node: node.id,
+                        name: node.name.clone(),
+                        missing: *input,
+                    });
+                    continue;
+                };
+                if let (Some(entry), Some(input_entry)) = (entry, registry.get(&input_node.op)) {
+                    if input_entry.output_type() != entry.input_type() {
+                        issues.push(LoadIssue::TypeMismatch {
+                            node: node.id,
+                            name: node.name.clone(),
+                            input: *input,
+                            expected: entry.input_type_name(),
+                            found: input_entry.output_type_name(),
+                        });
+                    }
+                }
+            }
+            for before in node.after.iter() {
+                if !ids.contains_key(before) {
+                    issues.push(LoadIssue::MissingNode {
+                        node: node.id,
+                        name: node.name.clone(),
+                        missing: *before,
+                    });
+                }
+            }
+        }
+
+        if let Some(output) = self.graph.output_node {
+            if !ids.contains_key(&output) {
+                issues.push(LoadIssue::MissingOutputNode { missing: output });
+            }
+        }
+        (objects, issues)
+    }
+
+    /// Applies registered migrations until the envelope matches the registry version.
+    pub fn migrate(&mut self, registry: &NodeRegistry) -> Result<(), ComputeGraphErrors> {
+        if self.format_version > FORMAT_VERSION {
+            return Err(ComputeGraphErrors::Serialization(
+                format!(
+                    "unsupported format version {}, expected at most {}",
+                    self.format_version, FORMAT_VERSION
+                )
+                .into(),
+            ));
+        }
+        if self.version > registry.version() {
+            return Err(ComputeGraphErrors::Serialization(
+                format!(
+                    "graph was saved with version {} which is newer than {}",
+                    self.version,
+                    registry.version()
+                )
+                .into(),
+            ));
+        }
+        while self.version < registry.version() {
+            let migration = registry.migration_from(self.version).ok_or_else(|| {
+                ComputeGraphErrors::Serialization(
+                    format!("no migration registered from version {}", self.version).into(),
+                )
+            })?;
+            migration.migrate(&mut self.graph).map_err(|err| {
+                ComputeGraphErrors::Serialization(
+                    format!("migration from version {} failed: {}", self.version, err).into(),
+                )
+            })?;
+            self.version += 1;
+        }
+        Ok(())
+    }
+}
+
+impl Graph {
+    pub fn serialize(&self, registry: &NodeRegistry) -> Result<GraphEnvelope, ComputeGraphErrors> {
+        let order = self.canonical_order();
+        let ids = order
+            .iter()
+            .enumerate()
+            .map(|(id, key)| (*key, id))
+            .collect::<HashMap<_, _>>();
+
+        let mut nodes = Vec::new();
+        for key in order {
+            let node = &self.nodes[key];
+            let (op, config) = match node.inner.as_any().downcast_ref::<UnavailableOp>() {
+                Some(unavailable) => (unavailable.op.clone(), unavailable.config.clone()),
+                None => {
+                    let entry = registry.entry_for(node.inner.as_ref()).ok_or_else(|| {
+                        ComputeGraphErrors::Serialization(
+                            format!("'{}' has an unregistered node type", node.name).into(),
+                        )
+                    })?;
+                    let config = entry
+                        .serialize(node.inner.as_any())
+                        .map_err(|err| ComputeGraphErrors::Serialization(err.to_string().into()))?;
+                    (entry.name().to_string(), config)
+                }
+            };
+            nodes.push(SerializedNode {
+                id: ids[&key],
+                name: node.name.clone(),
+                op,
+                config,
+                inputs: node.inputs.iter().map(|input| ids[input]).collect(),
+                ports: node.ports.clone(),
+                after: node.after.iter().map(|before| ids[before]).collect(),
+                connected_to_input: node.connected_to_input,
+                tags: {
+                    let mut tags = node.tags.clone();
+                    tags.sort();
+                    tags
+                },
+                style: node.style.clone(),
+                critical: node.critical,
+                scheduling: node.scheduling,
+            });
+        }
+
+        Ok(GraphEnvelope {
+            format_version: FORMAT_VERSION,
+            version: registry.version(),
+            graph: SerializedGraph {
+                nodes,
+                output_node: self.output_node.map(|key| ids[&key]),
+            },
+            provenance: self.provenance().to_vec(),
+        })
+    }
+
+    pub fn deserialize(
+        envelope: GraphEnvelope,
+        registry: &NodeRegistry,
+    ) -> Result<Graph, ComputeGraphErrors> {
+        Graph::deserialize_with_warnings(envelope, registry).map(|(graph, _)| graph)
+    }
+
+    /// Like `deserialize`, but also returns the warnings found, such as deprecated ops.
+    pub fn deserialize_with_warnings(
+        envelope: GraphEnvelope,
+        registry: &NodeRegistry,
+    ) -> Result<(Graph, Vec<LoadIssue>), ComputeGraphErrors> {
+        Graph::load(envelope, registry, false).map(|(graph, warnings, _)| (graph, warnings))
+    }
+
+    /// Like `deserialize`, but nodes with ops `registry` lacks, e.g. because the feature
+    /// providing them is disabled in this build, are loaded as `UnavailableOp` placeholders
+    /// instead of failing. The graph can be inspected, edited and saved again, but building
+    /// a part of it that depends on a placeholder fails with `CapabilityDenied`.
+    pub fn deserialize_degraded(
+        envelope: GraphEnvelope,
+        registry: &NodeRegistry,
+    ) -> Result<(Graph, MissingOps), ComputeGraphErrors> {
+        Graph::load(envelope, registry, true).map(|(graph, _, missing)| (graph, missing))
+    }
+
+    fn load(
+        mut envelope: GraphEnvelope,
+        registry: &NodeRegistry,
+        degraded: bool,
+    ) -> Result<(Graph, Vec<LoadIssue>, MissingOps), ComputeGraphErrors> {
+        envelope.migrate(registry)?;
+        let missing = envelope.missing_ops(registry);
+        let (objects, issues) = envelope.check(registry);
+        let (warnings, errors): (Vec<_>, Vec<_>) = issues
+            .into_iter()
+            .filter(|issue| !(degraded && matches!(issue, LoadIssue::UnknownOp { .. })))
+            .partition(|issue| issue.is_warning());
+        if !errors.is_empty() {
+            return Err(ComputeGraphErrors::InvalidGraph(errors));
+        }
+
+        let mut graph = Graph::new();
+        let mut handles = HashMap::new();
+        for (node, inner) in envelope.graph.nodes.iter().zip(objects) {
+            // Only nodes with missing ops are left without an object by now
+            let inner = inner.unwrap_or_else(|| {
+                Box::new(UnavailableOp {
+                    op: node.op.clone(),
+                    config: node.config.clone(),
+                })
+            });
+            handles.insert(node.id, graph.insert_inner(node.name.clone(), inner));
+        }
+        for node in envelope.graph.nodes.iter() {
+            let handle = handles[&node.id];
+            if !node.ports.is_empty() && node.ports.len() != node.inputs.len() {
+                return Err(ComputeGraphErrors::Serialization(
+                    format!(
+                        "node {} '{}' has {} ports for {} inputs",
+                        node.id,
+                        node.name,
+                        node.ports.len(),
+                        node.inputs.len()
+                    )
+                    .into(),
+                ));
+            }
+            for (index, input) in node.inputs.iter().enumerate() {
+                let input_handle = handles[input];
+                let port = node.ports.get(index).copied();
+                if is_unavailable(&graph, &handle) || is_unavailable(&graph, &input_handle) {
+                    // Placeholders have no types to check the edge against
+                    let graph_node = graph.node_mut(handle.key).unwrap();
+                    graph_node.inputs.push(input_handle.key);
+                    graph_node.ports.extend(port);
+                } else if let Some(port) = port {
+                    graph.add_input_port(&handle, port, &input_handle)?;
+                } else {
+                    graph.add_input(&handle, &input_handle)?;
+                }
+            }
+            for before in node.after.iter() {
+                graph.add_order(&handles[before], &handle)?;
+            }
+            if node.connected_to_input {
+                graph.connect_to_input(&handle)?;
+            } else {
+                graph.disconnect_from_input(&handle)?;
+            }
+            for tag in node.tags.iter() {
+                graph.add_tag(&handle, tag.clone())?;
+            }
+            graph.set_style(&handle, node.style.clone())?;
+            graph.set_critical(&handle, node.critical)?;
+            graph.set_scheduling(&handle, node.scheduling)?;
+        }
+        if let Some(output) = envelope.graph.output_node {
+            graph.set_output_node(&handles[&output])?;
+        }
+        graph.restore_provenance(envelope.provenance);
+        Ok((graph, warnings, missing))
+    }
+
+    pub fn to_json(&self, registry: &NodeRegistry) -> Result<String, ComputeGraphErrors> {
+        serde_json::to_string_pretty(&self.serialize(registry)?)
+            .map_err(|err| ComputeGraphErrors::Serialization(err.to_string().into()))
+    }
+
+    pub fn from_json(json: &str, registry: &NodeRegistry) -> Result<Graph, ComputeGraphErrors> {
+        let envelope = serde_json::from_str(json)
+            .map_err(|err| ComputeGraphErrors::Serialization(err.to_string().into()))?;
+        Graph::deserialize(envelope, registry)
+    }
+}
+
+#[cfg(test)]
+mod serialize_tests {
+    use crate::prelude::*;
+    use crate::serialize::*;
+
+    fn registry() -> Result<NodeRegistry, ComputeGraphErrors> {
+        let mut registry = NodeRegistry::new();
+        registry.register::<Constant<f64>, _, _>("constant")?;
+        registry.register::<AddInputs<f64>, _, _>("add")?;
+        registry.register::<MulInputs<f64>, _, _>("mul")?;
+        Ok(registry)
+    }
+
+    #[test]
+    fn test_roundtrip_with_migration() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("the_answer", Constant(42.0));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&add_handle, &const_handle)?;
+        graph.connect_to_input(&add_handle)?;
+        graph.set_output_node(&add_handle)?;
+
+        // Version 0 of the library called the add op "sum"
+        let mut old_registry = NodeRegistry::new();
+        old_registry.register::<Constant<f64>, _, _>("constant")?;
+        old_registry.register::<AddInputs<f64>, _, _>("sum")?;
+        graph.set_style(&add_handle, NodeStyle::group("math").with_color("#ff8800"))?;
+        let json = graph.to_json(&old_registry)?;
+
+        let mut registry = registry()?;
+        assert!(Graph::from_json(&json, &registry).is_err());
+
+        registry
+            .set_version(2)
+            .add_migration(RenameOp::new(0, "sum", "add"))
+            .add_migration((1, |graph: &mut SerializedGraph| {
+                for node in graph.nodes.iter_mut().filter(|node| node.op == "constant") {
+                    node.config = serde_json::json!(node.config.as_f64().unwrap_or(0.0) / 2.0);
+                }
+                Ok(())
+            }));
+
+        let mut loaded = Graph::from_json(&json, &registry)?;
+        let compute_graph = loaded.build::<f64, f64>()?;
+        assert_eq!(compute_graph.compute(&1.0), 22.0);
+
+        assert_eq!(loaded.groups().len(), 1);
+        assert_eq!(loaded.groups()[0].0, "math");
+
+        let envelope = loaded.serialize(&registry)?;
+        assert_eq!(envelope.version, 2);
+        assert_eq!(
+            envelope.graph.nodes[1].style.color.as_deref(),
+            Some("#ff8800")
+        );
+        assert_eq!(envelope.graph.nodes.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_degraded_roundtrip_keeps_ports() -> Result<(), ComputeGraphErrors> {
+        let json = r#"{
+            "format_version": 1,
+            "version": 0,
+            "graph": {
+                "nodes": [
+                    {"id": 0, "name": "a", "op": "constant", "config": 1.0,
+                     "inputs": [], "connected_to_input": false},
+                    {"id": 1, "name": "b", "op": "constant", "config": 2.0,
+                     "inputs": [], "connected_to_input": false},
+                    {"id": 2, "name": "lerp", "op": "lerp", "config": null,
+                     "inputs": [1, 0], "ports": [1, 0], "connected_to_input": false}
+                ],
+                "output_node": 2
+            }
+        }"#;
+        let envelope: GraphEnvelope = serde_json::from_str(json)
+            .map_err(|err| ComputeGraphErrors::Serialization(err.to_string().into()))?;
+        let registry = registry()?;
+        let (graph, missing) = Graph::deserialize_degraded(envelope, &registry)?;
+        assert_eq!(missing.nodes_using("lerp"), [2]);
+        let saved = graph.serialize(&registry)?;
+        let lerp = saved
+            .graph
+            .nodes
+            .iter()
+            .find(|node| node.name == "lerp")
+            .unwrap();
+        assert_eq!(lerp.ports, [1, 0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_keeps_order() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let first_handle = graph.insert_node("first", Constant(1.0));
+        let second_handle = graph.insert_node("second", Constant(2.0));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&add_handle, &second_handle)?;
+        graph.add_order(&first_handle, &second_handle)?;
+        graph.set_output_node(&add_handle)?;
+
+        let registry = registry()?;
+        let envelope = graph.serialize(&registry)?;
+        let loaded = Graph::deserialize(envelope.clone(), &registry)?;
+        assert_eq!(loaded.serialize(&registry)?, envelope);
+        let second = envelope
+            .graph
+            .nodes
+            .iter()
+            .find(|node| node.name == "second")
+            .unwrap();
+        assert_eq!(second.after.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_validation_issues() -> Result<(), ComputeGraphErrors> {
+        let node = |id, op: &str, config, inputs| SerializedNode {
+            id,
+            name: format!("node{}", id),
+            op: op.to_string(),
+            config,
+            inputs,
+            ports: vec![],
+            after: vec![],
+            connected_to_input: false,
+            tags: vec![],
+            style: NodeStyle::default(),
+            critical: false,
+            scheduling: Scheduling::Free,
+        };
+        let envelope = GraphEnvelope {
+            format_version: FORMAT_VERSION,
+            version: 0,
+            graph: SerializedGraph {
+                nodes: vec![
+                    node(0, "constant", serde_json::json!(1.0), vec![]),
+                    node(1, "constant", serde_json::json!("text"), vec![]),
+                    node(2, "div", serde_json::json!(null), vec![0]),
+                    node(3, "add", serde_json::json!({}), vec![0, 7]),
+                ],
+                output_node: Some(9),
+            },
+            provenance: vec![],
+        };
+        let mut registry = registry()?;
+        registry.register::<Constant<bool>, _, _>("flag")?;
+
+        let issues = envelope.validate(&registry);
+        assert_eq!(issues.len(), 4, "{:?}", issues);
+        assert!(matches!(
+            issues[0],
+            LoadIssue::InvalidConfig { node: 1, .. }
+        ));
+        assert!(matches!(issues[1], LoadIssue::UnknownOp { node: 2, .. }));
+        assert!(matches!(
+            issues[2],
+            LoadIssue::MissingNode {
+                node: 3,
+                missing: 7,
+                ..
+            }
+        ));
+        assert_eq!(issues[3], LoadIssue::MissingOutputNode { missing: 9 });
+
+        let mut mismatched = envelope.clone();
+        mismatched.graph.nodes = vec![
+            node(0, "flag", serde_json::json!(true), vec![]),
+            node(1, "add", serde_json::json!({}), vec![0]),
+        ];
+        mismatched.graph.output_node = Some(1);
+        let issues = mismatched.validate(&registry);
+        assert!(matches!(
+            issues[..],
+            [LoadIssue::TypeMismatch {
+                node: 1,
+                input: 0,
+                ..
+            }]
+        ));
+        assert!(matches!(
+            Graph::deserialize(envelope, &registry),
+            Err(ComputeGraphErrors::InvalidGraph(issues)) if issues.len() == 4
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deprecated_ops_warn() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("the_answer", Constant(42.0));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&add_handle, &const_handle)?;
+        graph.set_output_node(&add_handle)?;
+
+        let mut registry = registry()?;
+        let envelope = graph.serialize(&registry)?;
+        registry
+            .register::<AddInputs<f64>, _, _>("add")?
+            .deprecate("use 'mul' with a constant 1.0");
+        let issues = envelope.validate(&registry);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].is_warning());
+        assert_eq!(
+            issues[0].to_string(),
+            "node 1 'add' uses deprecated op 'add': use 'mul' with a constant 1.0"
+        );
+
+        let (mut loaded, warnings) = Graph::deserialize_with_warnings(envelope, &registry)?;
+        assert_eq!(warnings, issues);
+        assert_eq!(loaded.build::<(), f64>()?.compute(&()), 42.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_degraded_loading() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let rate_handle = graph.insert_node("rate", Constant(2.0));
+        let scaled_handle = graph.insert_node("scaled", MulInputs::<f64>::new());
+        let total_handle = graph.insert_node("total", AddInputs::<f64>::new());
+        graph.add_input(&scaled_handle, &rate_handle)?;
+        graph.add_input(&total_handle, &rate_handle)?;
+        graph.add_tag(&scaled_handle, "pricing")?;
+        graph.set_output_node(&scaled_handle)?;
+        let envelope = graph.serialize(&registry()?)?;
+
+        // A build without the library providing "mul"
+        let mut lean = NodeRegistry::new();
+        lean.register::<Constant<f64>, _, _>("constant")?;
+        lean.register::<AddInputs<f64>, _, _>("add")?;
+        assert!(Graph::deserialize(envelope.clone(), &lean).is_err());
+
+        let (mut loaded, missing) = Graph::deserialize_degraded(envelope.clone(), &lean)?;
+        assert_eq!(missing, envelope.missing_ops(&lean));
+        assert_eq!(missing.ops().collect::<Vec<_>>(), ["mul"]);
+        let scaled_id = envelope
+            .graph
+            .nodes
+            .iter()
+            .find(|node| node.op == "mul")
+            .unwrap()
+            .id;
+        assert_eq!(missing.nodes_using("mul"), [scaled_id]);
+        assert_eq!(missing.to_string(), "'mul' used by 1 node(s)");
+
+        let metas = loaded.get_all_node_metas();
+        let scaled = metas.iter().find(|meta| meta.name == "scaled").unwrap();
+        let total = metas.iter().find(|meta| meta.name == "total").unwrap();
+        assert_eq!(scaled.inputs.len(), 1);
+        assert!(matches!(
+            loaded.build::<(), ()>(),
+            Err(ComputeGraphErrors::CapabilityDenied(message)) if message.contains("'mul'")
+        ));
+        assert_eq!(
+            loaded
+                .build_for_node::<(), f64>(&total.this_node)?
+                .compute(&()),
+            2.0
+        );
+
+        // Placeholders are saved as they were loaded
+        assert_eq!(loaded.serialize(&lean)?.graph, envelope.graph);
+        Ok(())
+    }
+}
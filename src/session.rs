@@ -0,0 +1,148 @@
+use crate::graph::{ComputeGraphErrors, Graph};
+
+/// Token for optimistic concurrent editing, obtained from `Graph::edit_session`.
+/// Edits made through `Graph::edit` fail with `ConflictingEdit` if the graph
+/// changed since the session last saw it, whether through another session or
+/// by calling the graph's methods directly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EditSession {
+    graph_id: usize,
+    revision: u64,
+}
+
+impl EditSession {
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Accepts the graph's current state, e.g. after the user reviewed the other changes.
+    pub fn rebase(&mut self, graph: &Graph) {
+        *self = graph.edit_session();
+    }
+}
+
+impl Graph {
+    pub fn edit_session(&self) -> EditSession {
+        EditSession {
+            graph_id: self.id,
+            revision: self.revision(),
+        }
+    }
+
+    /// Runs `edit` if the graph didn't change since `session` last saw it. An edit failing
+    /// with an error is rolled back, leaving the graph and `session` as they were.
+    pub fn edit<R, F>(
+        &mut self,
+        session: &mut EditSession,
+        edit: F,
+    ) -> Result<R, ComputeGraphErrors>
+    where
+        F: FnOnce(&mut Graph) -> Result<R, ComputeGraphErrors>,
+    {
+        if session.graph_id != self.id || session.revision != self.revision() {
+            return Err(ComputeGraphErrors::ConflictingEdit(
+                format!(
+                    "session is at revision {} but the graph is at revision {}",
+                    session.revision,
+                    self.revision()
+                )
+                .into(),
+            ));
+        }
+        // Nodes are shared with the copy, so only the ones the edit touches are cloned
+        let before = self.clone();
+        let result = edit(self);
+        if result.is_err() {
+            *self = before;
+        }
+        session.revision = self.revision();
+        result
+    }
+}
+
+#[cfg(test)]
+mod session_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_conflicting_sessions() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("the_answer", Constant(42.0));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+
+        let mut first = graph.edit_session();
+        let mut second = graph.edit_session();
+
+        graph.edit(&mut first, |g| g.add_input(&add_handle, &const_handle))?;
+        assert!(matches!(
+            graph.edit(&mut second, |g| {
+                g.remove_node(&const_handle)?;
+                Ok(())
+            }),
+            Err(ComputeGraphErrors::ConflictingEdit(_))
+        ));
+        assert_eq!(graph.get_node_meta(&add_handle)?.inputs.len(), 1);
+
+        // The first session keeps editing since it saw its own change
+        graph.edit(&mut first, |g| g.set_param(&const_handle, "value", 1.0))?;
+
+        second.rebase(&graph);
+        graph.edit(&mut second, |g| {
+            g.set_output_node(&add_handle)?;
+            Ok(())
+        })?;
+        assert_eq!(second.revision(), graph.revision());
+        Ok(())
+    }
+
+    #[test]
+    fn test_direct_edits_conflict() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("the_answer", Constant(42.0));
+        let mut session = graph.edit_session();
+        let stale = |graph: &mut Graph, session: &mut EditSession| {
+            let result = graph.edit(session, |g| g.set_output_node(&const_handle));
+            session.rebase(graph);
+            matches!(result, Err(ComputeGraphErrors::ConflictingEdit(_)))
+        };
+
+        graph.set_param(&const_handle, "value", 1.0)?;
+        assert!(stale(&mut graph, &mut session));
+        graph.add_tag(&const_handle, "tagged")?;
+        assert!(stale(&mut graph, &mut session));
+        graph.probe(&const_handle)?;
+        assert!(stale(&mut graph, &mut session));
+        graph.set_limits(GraphLimits::default());
+        assert!(stale(&mut graph, &mut session));
+        graph.reject_cycles(true);
+        assert!(stale(&mut graph, &mut session));
+        assert!(!stale(&mut graph, &mut session));
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_edits_roll_back() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("the_answer", Constant(42.0));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        let mut session = graph.edit_session();
+        let revision = graph.revision();
+
+        assert!(graph
+            .edit(&mut session, |g| {
+                g.add_input(&add_handle, &const_handle)?;
+                g.set_param(&const_handle, "missing", 1.0)
+            })
+            .is_err());
+        assert!(graph.get_node_meta(&add_handle)?.inputs.is_empty());
+        assert_eq!(graph.revision(), revision);
+        assert_eq!(session.revision(), revision);
+
+        // Rejected values leave the revision alone
+        assert!(graph.set_param(&const_handle, "value", true).is_err());
+        assert_eq!(graph.revision(), revision);
+        graph.set_param(&const_handle, "value", 1.0)?;
+        assert_eq!(graph.revision(), revision + 1);
+        Ok(())
+    }
+}
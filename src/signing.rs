@@ -0,0 +1,87 @@
+use crate::graph::{ComputeGraphErrors, Graph};
+use crate::registry::NodeRegistry;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+impl Graph {
+    /// Bytes covered by signatures: the canonical serialized nodes and output, with the versions
+    /// they were written with. The provenance log is left out.
+    pub fn signed_bytes(&self, registry: &NodeRegistry) -> Result<Vec<u8>, ComputeGraphErrors> {
+        let envelope = self.serialize(registry)?;
+        serde_json::to_vec(&(envelope.format_version, envelope.version, &envelope.graph))
+            .map_err(|err| ComputeGraphErrors::Serialization(err.to_string().into()))
+    }
+
+    /// Ed25519 signature over `signed_bytes`.
+    pub fn sign(
+        &self,
+        registry: &NodeRegistry,
+        key: &SigningKey,
+    ) -> Result<Signature, ComputeGraphErrors> {
+        Ok(key.sign(&self.signed_bytes(registry)?))
+    }
+
+    /// Fails with `InvalidSignature` unless `signature` was made over this graph with the
+    /// private half of `public_key`.
+    pub fn verify(
+        &self,
+        registry: &NodeRegistry,
+        signature: &Signature,
+        public_key: &VerifyingKey,
+    ) -> Result<(), ComputeGraphErrors> {
+        public_key
+            .verify(&self.signed_bytes(registry)?, signature)
+            .map_err(|_| ComputeGraphErrors::InvalidSignature)
+    }
+
+    /// Loads a graph from JSON, refusing it unless `signature` verifies with `public_key`.
+    pub fn from_signed_json(
+        json: &str,
+        registry: &NodeRegistry,
+        signature: &Signature,
+        public_key: &VerifyingKey,
+    ) -> Result<Graph, ComputeGraphErrors> {
+        let graph = Graph::from_json(json, registry)?;
+        graph.verify(registry, signature, public_key)?;
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod signing_tests {
+    use crate::prelude::*;
+    use ed25519_dalek::SigningKey;
+
+    #[test]
+    fn test_sign_and_verify() -> Result<(), ComputeGraphErrors> {
+        let mut registry = NodeRegistry::new();
+        registry.register::<Constant<f64>, _, _>("constant")?;
+        registry.register::<MulInputs<f64>, _, _>("mul")?;
+        let mut graph = Graph::new();
+        let rate_handle = graph.insert_node("rate", Constant(0.05));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &rate_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.set_output_node(&mul_handle)?;
+
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let other_key = SigningKey::from_bytes(&[8; 32]);
+        let signature = graph.sign(&registry, &key)?;
+        graph.verify(&registry, &signature, &key.verifying_key())?;
+        assert!(matches!(
+            graph.verify(&registry, &signature, &other_key.verifying_key()),
+            Err(ComputeGraphErrors::InvalidSignature)
+        ));
+
+        let json = graph.to_json(&registry)?;
+        let mut loaded =
+            Graph::from_signed_json(&json, &registry, &signature, &key.verifying_key())?;
+        assert_eq!(loaded.build::<f64, f64>()?.compute(&100.0), 5.0);
+
+        let tampered = json.replace("0.05", "0.5");
+        assert!(matches!(
+            Graph::from_signed_json(&tampered, &registry, &signature, &key.verifying_key()),
+            Err(ComputeGraphErrors::InvalidSignature)
+        ));
+        Ok(())
+    }
+}
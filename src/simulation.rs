@@ -0,0 +1,153 @@
+use crate::com_graph::ComputeGraph;
+use crate::graph::ComputeGraphErrors;
+use std::any::Any;
+use std::time::Duration;
+
+/// Advances a stateful graph in fixed time steps, however irregularly `advance` is called,
+/// so simulations stay deterministic. The graph reads the step size in seconds as its
+/// input and is evaluated through `ComputeGraph::try_compute_mut`.
+pub struct Simulator<Out> {
+    graph: ComputeGraph<f64, Out>,
+    dt: Duration,
+    accumulator: Duration,
+    max_steps: usize,
+    steps: u64,
+    output: Option<Out>,
+}
+
+impl<Out> Simulator<Out>
+where
+    Out: Any + Clone,
+{
+    /// Panics if `dt` is zero.
+    pub fn new(graph: ComputeGraph<f64, Out>, dt: Duration) -> Self {
+        assert!(!dt.is_zero(), "the time step must not be zero");
+        Self {
+            graph,
+            dt,
+            accumulator: Duration::ZERO,
+            max_steps: usize::MAX,
+            steps: 0,
+            output: None,
+        }
+    }
+
+    /// Limits the steps taken by one `advance`. Time for the steps beyond it is dropped,
+    /// so a frame that took too long slows the simulation down instead of making the next
+    /// frame even longer.
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn dt(&self) -> Duration {
+        self.dt
+    }
+
+    /// Steps taken since the simulator was created.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Simulated time, the steps taken times `dt`.
+    pub fn time(&self) -> Duration {
+        Duration::from_nanos((self.dt.as_nanos() * self.steps as u128) as u64)
+    }
+
+    /// Output of the last step, `None` before the first one.
+    pub fn output(&self) -> Option<&Out> {
+        self.output.as_ref()
+    }
+
+    /// How far the leftover time is into the next step, from 0 up to 1, for interpolating
+    /// between the last two outputs when rendering.
+    pub fn alpha(&self) -> f64 {
+        self.accumulator.as_secs_f64() / self.dt.as_secs_f64()
+    }
+
+    /// Evaluates one step, regardless of the accumulated time.
+    pub fn step(&mut self) -> Result<&Out, ComputeGraphErrors> {
+        let output = self.graph.try_compute_mut(&self.dt.as_secs_f64())?;
+        self.steps += 1;
+        Ok(self.output.insert(output))
+    }
+
+    /// Adds `elapsed` to the accumulated time and takes as many steps as fit in it,
+    /// returning their number. On failure, the time of the failed step and the ones after
+    /// it stays accumulated.
+    pub fn advance(&mut self, elapsed: Duration) -> Result<usize, ComputeGraphErrors> {
+        self.accumulator += elapsed;
+        let mut steps = 0;
+        while self.accumulator >= self.dt {
+            if steps == self.max_steps {
+                let dt = self.dt.as_nanos();
+                self.accumulator = Duration::from_nanos((self.accumulator.as_nanos() % dt) as u64);
+                break;
+            }
+            self.step()?;
+            self.accumulator -= self.dt;
+            steps += 1;
+        }
+        Ok(steps)
+    }
+
+    pub fn into_graph(self) -> ComputeGraph<f64, Out> {
+        self.graph
+    }
+}
+
+#[cfg(test)]
+mod simulation_tests {
+    use crate::prelude::*;
+    use std::time::Duration;
+
+    /// Running sum of its inputs over all calls.
+    #[derive(Clone, Copy, Default)]
+    struct Integrator {
+        total: f64,
+    }
+
+    impl Compute for Integrator {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            self.total + inputs.iter().copied().sum::<f64>()
+        }
+        fn try_compute_mut(&mut self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+            self.total = self.compute(inputs);
+            Ok(self.total)
+        }
+    }
+
+    #[test]
+    fn test_fixed_timestep() -> Result<(), ComputeGraphErrors> {
+        //  Input (dt) ── mul ── position
+        //  speed ────────┘
+        let mut graph = Graph::new();
+        let speed_handle = graph.insert_node("speed", Constant(2.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        let position_handle = graph.insert_node("position", Integrator::default());
+        graph.add_input(&mul_handle, &speed_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.add_input(&position_handle, &mul_handle)?;
+        graph.set_output_node(&position_handle)?;
+
+        let dt = Duration::from_millis(10);
+        let mut simulator = Simulator::new(graph.build::<f64, f64>()?, dt);
+        assert_eq!(simulator.output(), None);
+        assert_eq!(simulator.advance(Duration::from_millis(35))?, 3);
+        assert!((simulator.alpha() - 0.5).abs() < 1e-9);
+        assert_eq!(simulator.advance(Duration::from_millis(5))?, 1);
+        assert_eq!(simulator.advance(Duration::from_millis(4))?, 0);
+        assert_eq!(simulator.steps(), 4);
+        assert_eq!(simulator.time(), Duration::from_millis(40));
+        assert!((simulator.output().unwrap() - 0.08).abs() < 1e-9);
+
+        // A long frame only takes the allowed steps, keeping the fraction of a step
+        let mut simulator = simulator.max_steps(2);
+        assert_eq!(simulator.advance(Duration::from_millis(100))?, 2);
+        assert!((simulator.alpha() - 0.4).abs() < 1e-9);
+        assert!((simulator.output().unwrap() - 0.12).abs() < 1e-9);
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use crate::graph::{ComputeGraphErrors, Graph, GraphKey, Node};
+use slotmap::SlotMap;
+use std::sync::Arc;
+
+/// Nodes and output node of a graph at some point. Nodes are shared with the graph,
+/// so capturing one is cheap.
+#[derive(Clone)]
+pub(crate) struct GraphState {
+    pub(crate) nodes: SlotMap<GraphKey, Arc<Node>>,
+    pub(crate) output_node: Option<GraphKey>,
+}
+
+impl GraphState {
+    pub(crate) fn capture(graph: &Graph) -> Self {
+        Self {
+            nodes: graph.nodes.clone(),
+            output_node: graph.output_node,
+        }
+    }
+}
+
+impl Graph {
+    /// Stores the current nodes and output node under `name`, replacing an earlier snapshot
+    /// of that name. Unchanged nodes are shared, so snapshots are cheap to keep around.
+    pub fn snapshot(&mut self, name: impl Into<String>) {
+        let state = GraphState::capture(self);
+        self.snapshots.insert(name.into(), state);
+    }
+
+    /// Rolls the graph back to snapshot `name`. Handles from that time are valid again.
+    pub fn restore(&mut self, name: &str) -> Result<(), ComputeGraphErrors> {
+        let state = self.snapshots.get(name).cloned().ok_or_else(|| {
+            ComputeGraphErrors::InvalidParameter(format!("no snapshot named '{}'", name).into())
+        })?;
+        self.nodes = state.nodes;
+        self.output_node = state.output_node;
+        self.touch();
+        self.record(|_| format!("restored snapshot '{}'", name));
+        Ok(())
+    }
+
+    pub fn snapshot_names(&self) -> Vec<&str> {
+        let mut names = self
+            .snapshots
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    pub fn remove_snapshot(&mut self, name: &str) -> bool {
+        self.snapshots.remove(name).is_some()
+    }
+}
+
+#[cfg(test)]
+mod snapshot_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_snapshot_restore() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let rate_handle = graph.insert_node("rate", Constant(2.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &rate_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.set_output_node(&mul_handle)?;
+        graph.snapshot("good");
+
+        graph.set_param(&rate_handle, "value", 3.0)?;
+        graph.remove_node(&mul_handle)?;
+        assert!(graph.build::<f64, f64>().is_err());
+
+        graph.restore("good")?;
+        assert_eq!(graph.build::<f64, f64>()?.compute(&5.0), 10.0);
+        assert_eq!(graph.get_name(&mul_handle)?, "mul");
+        assert_eq!(graph.snapshot_names(), ["good"]);
+        assert!(graph.remove_snapshot("good"));
+        assert!(graph.restore("good").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+use crate::com_graph::ComputeGraph;
+use crate::compute::Value;
+use crate::graph::ComputeGraphErrors;
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::thread;
+
+/// Output of one predicted input, `None` until its evaluation finished.
+struct Prediction<Out> {
+    output: Mutex<Option<Option<Out>>>,
+    done: Condvar,
+}
+
+impl<Out> Prediction<Out> {
+    fn finish(&self, output: Option<Out>) {
+        *self.output.lock().unwrap() = Some(output);
+        self.done.notify_all();
+    }
+}
+
+/// Predictions waiting for a worker. Entries whose prediction was replaced in the meantime
+/// are skipped.
+struct Queue<In, Out> {
+    pending: VecDeque<(In, Weak<Prediction<Out>>)>,
+    workers: usize,
+}
+
+/// Evaluates likely next inputs ahead of time on background threads, hiding the latency
+/// of expensive graphs behind interactive controls like sliders. See `predict`.
+pub struct Speculative<In, Out> {
+    graph: Arc<ComputeGraph<In, Out>>,
+    predictions: Mutex<Vec<(In, Arc<Prediction<Out>>)>>,
+    queue: Arc<Mutex<Queue<In, Out>>>,
+    max_threads: usize,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl<In, Out> Speculative<In, Out>
+where
+    In: Value + PartialEq,
+    Out: Value,
+{
+    pub fn new(graph: ComputeGraph<In, Out>) -> Self {
+        Self {
+            graph: Arc::new(graph),
+            predictions: Mutex::new(Vec::new()),
+            queue: Arc::new(Mutex::new(Queue {
+                pending: VecDeque::new(),
+                workers: 0,
+            })),
+            max_threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Most predictions evaluated at once, one thread each. Defaults to the number of cores.
+    pub fn max_threads(mut self, threads: usize) -> Self {
+        self.max_threads = threads.max(1);
+        self
+    }
+
+    /// Starts evaluating `inputs` in the background, replacing earlier predictions.
+    /// Inputs that were already predicted keep their evaluation.
+    pub fn predict(&self, inputs: impl IntoIterator<Item = In>) {
+        let mut predictions = self.predictions.lock().unwrap();
+        let mut previous = std::mem::take(&mut *predictions);
+        let mut queue = self.queue.lock().unwrap();
+        for input in inputs {
+            if predictions.iter().any(|(predicted, _)| *predicted == input) {
+                continue;
+            }
+            if let Some(index) = previous
+                .iter()
+                .position(|(predicted, _)| *predicted == input)
+            {
+                predictions.push(previous.swap_remove(index));
+                continue;
+            }
+            let prediction = Arc::new(Prediction {
+                output: Mutex::new(None),
+                done: Condvar::new(),
+            });
+            queue
+                .pending
+                .push_back((input.clone(), Arc::downgrade(&prediction)));
+            predictions.push((input, prediction));
+        }
+        // Abandoned evaluations that already started run to completion
+        queue
+            .pending
+            .retain(|(_, prediction)| prediction.strong_count() > 0);
+        while queue.workers < self.max_threads.min(queue.pending.len()) {
+            queue.workers += 1;
+            let graph = self.graph.clone();
+            let queue = self.queue.clone();
+            thread::spawn(move || work(&graph, &queue));
+        }
+    }
+
+    /// Evaluates the graph for `input`, taking the output of a matching prediction if
+    /// there is one. A prediction still being evaluated is waited for.
+    pub fn try_compute(&self, input: &In) -> Result<Out, ComputeGraphErrors> {
+        let prediction = self
+            .predictions
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(predicted, _)| predicted == input)
+            .map(|(_, prediction)| prediction.clone());
+        if let Some(prediction) = prediction {
+            let mut output = prediction.output.lock().unwrap();
+            while output.is_none() {
+                output = prediction.done.wait(output).unwrap();
+            }
+            // A failed prediction is evaluated again to report its error
+            if let Some(Some(output)) = output.as_ref() {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(output.clone());
+            }
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.graph.try_compute(input)
+    }
+
+    pub fn compute(&self, input: &In) -> Out {
+        self.try_compute(input).unwrap()
+    }
+
+    /// Evaluations answered by a prediction.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+/// Evaluates queued predictions until there are none left. A panicking evaluation counts
+/// as failed, so `Speculative::try_compute` doesn't wait for it forever.
+fn work<In: Value, Out: Value>(graph: &ComputeGraph<In, Out>, queue: &Mutex<Queue<In, Out>>) {
+    loop {
+        let (input, prediction) = {
+            let mut queue = queue.lock().unwrap();
+            let Some(next) = queue.pending.pop_front() else {
+                queue.workers -= 1;
+                return;
+            };
+            next
+        };
+        let Some(prediction) = prediction.upgrade() else {
+            continue;
+        };
+        let output = panic::catch_unwind(AssertUnwindSafe(|| graph.try_compute(&input)));
+        prediction.finish(output.ok().and_then(Result::ok));
+    }
+}
+
+#[cfg(test)]
+mod speculate_tests {
+    use crate::prelude::*;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[derive(Clone)]
+    struct Expensive(Arc<AtomicUsize>);
+
+    impl Compute for Expensive {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(10));
+            *inputs[0] * 2.0
+        }
+    }
+
+    #[test]
+    fn test_speculative_inputs() -> Result<(), ComputeGraphErrors> {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut graph = Graph::new();
+        let expensive_handle = graph.insert_node("expensive", Expensive(calls.clone()));
+        graph.set_output_node(&expensive_handle)?;
+        let speculative = Speculative::new(graph.build::<f64, f64>()?);
+
+        speculative.predict([1.0, 2.0]);
+        assert_eq!(speculative.try_compute(&2.0)?, 4.0);
+        assert_eq!(speculative.try_compute(&1.0)?, 2.0);
+        assert_eq!(speculative.try_compute(&3.0)?, 6.0);
+        assert_eq!((speculative.hits(), speculative.misses()), (2, 1));
+
+        // Predicting 2.0 again keeps its evaluation
+        speculative.predict([2.0]);
+        assert_eq!(speculative.try_compute(&2.0)?, 4.0);
+        assert_eq!(speculative.hits(), 3);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    /// Tracks how many evaluations run at once, panicking on negative inputs.
+    #[derive(Clone, Default)]
+    struct Concurrent {
+        running: Arc<AtomicUsize>,
+        most: Arc<AtomicUsize>,
+    }
+
+    impl Compute for Concurrent {
+        type In = f64;
+        type Out = f64;
+        fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.most.fetch_max(running, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(5));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            assert!(*inputs[0] >= 0.0, "negative input");
+            *inputs[0] + 1.0
+        }
+    }
+
+    #[test]
+    fn test_bounded_and_panicking_predictions() -> Result<(), ComputeGraphErrors> {
+        let node = Concurrent::default();
+        let mut graph = Graph::new();
+        let node_handle = graph.insert_node("node", node.clone());
+        graph.set_output_node(&node_handle)?;
+        let speculative = Speculative::new(graph.build::<f64, f64>()?).max_threads(2);
+
+        speculative.predict((0..8).map(|x| x as f64));
+        for x in 0..8 {
+            assert_eq!(speculative.try_compute(&(x as f64))?, x as f64 + 1.0);
+        }
+        assert_eq!(speculative.hits(), 8);
+        assert!(node.most.load(Ordering::SeqCst) <= 2);
+
+        // The failed prediction is evaluated again, panicking on the caller's thread
+        speculative.predict([-1.0]);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| speculative.try_compute(&-1.0)));
+        assert!(result.is_err());
+        assert_eq!(speculative.misses(), 1);
+        speculative.predict([9.0]);
+        assert_eq!(speculative.try_compute(&9.0)?, 10.0);
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use crate::com_graph::ComputeGraph;
+use crate::compute::{Compute, Value};
+use crate::graph::{ComputeGraphErrors, Graph};
+use crate::typed::TypedNodeHandle;
+use std::hash::Hasher;
+
+/// A built graph evaluated as a single node, see `Graph::insert_subgraph`.
+#[derive(Clone)]
+pub struct Subgraph<In, Out> {
+    graph: ComputeGraph<In, Out>,
+}
+
+impl<In: Value, Out: Value> Subgraph<In, Out> {
+    pub fn new(graph: ComputeGraph<In, Out>) -> Self {
+        Self { graph }
+    }
+}
+
+impl<In: Value, Out: Value> Compute for Subgraph<In, Out> {
+    type In = In;
+    type Out = Out;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.try_compute(inputs).unwrap()
+    }
+
+    fn try_compute(&self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        // Graphs without an input get none, see `InnerCompute::inner_try_compute`
+        let input = match inputs {
+            [] => &In::default(),
+            [input] => *input,
+            _ => return Err(format!("subgraph takes 1 input, got {}", inputs.len())),
+        };
+        self.graph.try_compute(input).map_err(|err| err.to_string())
+    }
+
+    fn fingerprint(&self, state: &mut dyn Hasher) {
+        if let Some(output) = self.graph.node_fingerprints().last() {
+            state.write_u64(*output);
+        }
+    }
+}
+
+impl Graph {
+    /// Inserts `subgraph` as a single node, reading the node's only input as its graph
+    /// input and yielding the output of its output node. Building blocks made this way can
+    /// be reused across graphs.
+    pub fn insert_subgraph<N, In, Out>(
+        &mut self,
+        name: N,
+        mut subgraph: Graph,
+    ) -> Result<TypedNodeHandle<In, Out>, ComputeGraphErrors>
+    where
+        N: Into<String>,
+        In: Value,
+        Out: Value,
+    {
+        let compute_graph = subgraph.build::<In, Out>()?;
+        Ok(self.insert_node(name, Subgraph::new(compute_graph)))
+    }
+}
+
+#[cfg(test)]
+mod subgraph_tests {
+    use crate::prelude::*;
+
+    fn affine(scale: f64, offset: f64) -> Result<Graph, ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let scale_handle = graph.insert_node("scale", Constant(scale));
+        let offset_handle = graph.insert_node("offset", Constant(offset));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&mul_handle, &scale_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.add_input(&add_handle, &mul_handle)?;
+        graph.add_input(&add_handle, &offset_handle)?;
+        graph.set_output_node(&add_handle)?;
+        Ok(graph)
+    }
+
+    #[test]
+    fn test_subgraph_node() -> Result<(), ComputeGraphErrors> {
+        //  Input ── first ── second
+        let mut graph = Graph::new();
+        let first_handle = graph.insert_subgraph::<_, f64, f64>("first", affine(2.0, 1.0)?)?;
+        let second_handle = graph.insert_subgraph::<_, f64, f64>("second", affine(10.0, 0.0)?)?;
+        graph.add_typed_input(&second_handle, &first_handle);
+        graph.disconnect_from_input(&second_handle)?;
+        graph.set_output_node(&second_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+        assert_eq!(compute_graph.compute(&3.0), 70.0);
+
+        // Plans and checkpoints tell subgraphs apart by their contents
+        let fingerprint = |scale| -> Result<u64, ComputeGraphErrors> {
+            let mut graph = Graph::new();
+            let handle = graph.insert_subgraph::<_, f64, f64>("affine", affine(scale, 0.0)?)?;
+            graph.set_output_node(&handle)?;
+            Ok(graph.fingerprint())
+        };
+        assert_ne!(fingerprint(1.0)?, fingerprint(2.0)?);
+
+        assert!(matches!(
+            graph.insert_subgraph::<_, f64, bool>("wrong", affine(1.0, 0.0)?),
+            Err(ComputeGraphErrors::WrongTypes(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use crate::com_graph::{ComputeGraph, EvalContext};
+use crate::graph::{ComputeGraphErrors, NodeHandle};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+type SameFn = fn(&dyn Any, &dyn Any) -> bool;
+
+fn same<T: PartialEq + 'static>(a: &dyn Any, b: &dyn Any) -> bool {
+    a.downcast_ref::<T>() == b.downcast_ref::<T>()
+}
+
+/// Values of one node over a run. A value is only stored at the steps where it changed,
+/// when its type can be compared.
+struct NodeHistory {
+    node: NodeHandle,
+    name: String,
+    changes: Vec<(usize, Box<dyn Any + Send + Sync>)>,
+}
+
+/// Every node's output at every step of a run, one step per `ComputeGraph::compute_traced`,
+/// so a debugger can scrub back through the history of an evaluation. Values of the
+/// primitive numeric types and `bool` are compressed; register other types with `compare`.
+pub struct Trace {
+    nodes: Vec<NodeHistory>,
+    inputs: Vec<Box<dyn Any + Send + Sync>>,
+    comparable: HashMap<TypeId, SameFn>,
+}
+
+impl Trace {
+    fn new(nodes: Vec<NodeHistory>) -> Self {
+        let mut trace = Self {
+            nodes,
+            inputs: Vec::new(),
+            comparable: HashMap::new(),
+        };
+        macro_rules! compare {
+            ($($t:ty),*) => {
+                $(trace.compare::<$t>();)*
+            };
+        }
+        compare!(f64, f32, i64, i32, i16, i8, u64, u32, u16, u8, usize, isize, bool);
+        trace
+    }
+
+    /// Only stores values of type `T` at the steps where they differ from the step before.
+    pub fn compare<T: PartialEq + 'static>(&mut self) -> &mut Self {
+        self.comparable.insert(TypeId::of::<T>(), same::<T>);
+        self
+    }
+
+    /// Number of recorded steps.
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Traced nodes with their names, in compute order.
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeHandle, &str)> {
+        self.nodes
+            .iter()
+            .map(|history| (history.node, history.name.as_str()))
+    }
+
+    /// Number of values actually stored, at most `len() * nodes().count()`.
+    pub fn stored_values(&self) -> usize {
+        self.nodes.iter().map(|history| history.changes.len()).sum()
+    }
+
+    pub fn input_at<In: 'static>(&self, step: usize) -> Option<&In> {
+        self.inputs.get(step)?.downcast_ref()
+    }
+
+    /// Output of `node_handle` at `step`, or `None` for unknown nodes and steps.
+    pub fn value_at(&self, node_handle: &NodeHandle, step: usize) -> Option<&dyn Any> {
+        if step >= self.len() {
+            return None;
+        }
+        let history = self.history(node_handle)?;
+        let index = history
+            .changes
+            .partition_point(|(changed, _)| *changed <= step);
+        let (_, value) = &history.changes[index.checked_sub(1)?];
+        Some(value.as_ref())
+    }
+
+    pub fn get<T: 'static>(&self, node_handle: &NodeHandle, step: usize) -> Option<&T> {
+        self.value_at(node_handle, step)?.downcast_ref()
+    }
+
+    /// Steps at which the output of `node_handle` changed, to jump between them in a UI.
+    /// For types that can't be compared, that is every step.
+    pub fn changes(&self, node_handle: &NodeHandle) -> Vec<usize> {
+        self.history(node_handle)
+            .map(|history| history.changes.iter().map(|(step, _)| *step).collect())
+            .unwrap_or_default()
+    }
+
+    fn history(&self, node_handle: &NodeHandle) -> Option<&NodeHistory> {
+        self.nodes
+            .iter()
+            .find(|history| history.node == *node_handle)
+    }
+}
+
+impl<In, Out> ComputeGraph<In, Out> {
+    /// Empty trace for `compute_traced`.
+    pub fn new_trace(&self) -> Trace {
+        Trace::new(
+            self.nodes
+                .iter()
+                .map(|node| NodeHistory {
+                    node: node.handle,
+                    name: node.name.clone(),
+                    changes: Vec::new(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Like `try_compute`, and appends the input and every node output to `trace` as a new step.
+    pub fn compute_traced(&self, input: &In, trace: &mut Trace) -> Result<Out, ComputeGraphErrors>
+    where
+        In: Any + Clone + Send + Sync,
+        Out: Any + Clone,
+    {
+        let matches = trace.nodes.len() == self.nodes.len()
+            && trace
+                .nodes
+                .iter()
+                .zip(self.nodes.iter())
+                .all(|(history, node)| history.node == node.handle);
+        if !matches {
+            return Err(ComputeGraphErrors::InvalidParameter(
+                "trace was created for another graph".into(),
+            ));
+        }
+        self.with_context(|context| {
+            let output = self.try_compute_with(input, context)?;
+            trace.record(self, input, context);
+            Ok(output)
+        })
+    }
+}
+
+impl Trace {
+    /// Appends `input` and the node outputs in `context` as a new step.
+    fn record<In, Out>(&mut self, graph: &ComputeGraph<In, Out>, input: &In, context: &EvalContext)
+    where
+        In: Any + Clone + Send + Sync,
+    {
+        let step = self.inputs.len();
+        self.inputs.push(Box::new(input.clone()));
+        for (index, history) in self.nodes.iter_mut().enumerate() {
+            let value = &context.outputs[index];
+            let unchanged = history.changes.last().is_some_and(|(_, last)| {
+                self.comparable
+                    .get(&Any::type_id(last.as_ref()))
+                    .is_some_and(|same| same(last.as_ref(), value.as_ref()))
+            });
+            if !unchanged {
+                let value = graph.nodes[index].func.clone_output(value.as_ref());
+                history.changes.push((step, value));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod trace_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_trace_history() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let rate_handle = graph.insert_node("rate", Constant(2.0));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_input(&mul_handle, &rate_handle)?;
+        graph.connect_to_input(&mul_handle)?;
+        graph.set_output_node(&mul_handle)?;
+        let compute_graph = graph.build::<f64, f64>()?;
+
+        let mut trace = compute_graph.new_trace();
+        for x in [1.0, 1.0, 3.0, 3.0, 3.0, 5.0] {
+            compute_graph.compute_traced(&x, &mut trace)?;
+        }
+        assert_eq!(trace.len(), 6);
+        assert_eq!(trace.get::<f64>(&mul_handle, 3), Some(&6.0));
+        assert_eq!(trace.get::<f64>(&mul_handle, 5), Some(&10.0));
+        assert_eq!(trace.get::<f64>(&rate_handle, 4), Some(&2.0));
+        assert_eq!(trace.input_at::<f64>(2), Some(&3.0));
+        assert!(trace.value_at(&mul_handle, 6).is_none());
+
+        // The constant is stored once and `mul` only when its value changed
+        assert_eq!(trace.changes(&mul_handle), [0, 2, 5]);
+        assert_eq!(trace.stored_values(), 4);
+
+        let mut graph = Graph::new();
+        let handle = graph.insert_node("one", Constant(1.0));
+        graph.set_output_node(&handle)?;
+        assert!(graph
+            .build::<(), f64>()?
+            .compute_traced(&(), &mut trace)
+            .is_err());
+        Ok(())
+    }
+}
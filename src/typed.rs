@@ -0,0 +1,130 @@
+use crate::graph::{ComputeGraphErrors, Graph, NodeHandle};
+use std::any::TypeId;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+/// `NodeHandle` that also knows the input and output types of its node, so
+/// `Graph::add_typed_input` can check edges at compile time. Derefs to the erased handle
+/// for every other graph method.
+pub struct TypedNodeHandle<In, Out> {
+    handle: NodeHandle,
+    _types: PhantomData<fn(In) -> Out>,
+}
+
+impl<In, Out> TypedNodeHandle<In, Out> {
+    pub(crate) fn new(handle: NodeHandle) -> Self {
+        Self {
+            handle,
+            _types: PhantomData,
+        }
+    }
+
+    pub fn erase(&self) -> NodeHandle {
+        self.handle
+    }
+}
+
+impl<In, Out> Deref for TypedNodeHandle<In, Out> {
+    type Target = NodeHandle;
+    fn deref(&self) -> &NodeHandle {
+        &self.handle
+    }
+}
+
+impl<In, Out> From<TypedNodeHandle<In, Out>> for NodeHandle {
+    fn from(typed: TypedNodeHandle<In, Out>) -> Self {
+        typed.handle
+    }
+}
+
+impl<In, Out> Clone for TypedNodeHandle<In, Out> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<In, Out> Copy for TypedNodeHandle<In, Out> {}
+
+impl<In, Out> fmt::Debug for TypedNodeHandle<In, Out> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.handle.fmt(f)
+    }
+}
+
+impl<In, Out> PartialEq for TypedNodeHandle<In, Out> {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl<In, Out> Eq for TypedNodeHandle<In, Out> {}
+
+impl<In, Out> PartialEq<NodeHandle> for TypedNodeHandle<In, Out> {
+    fn eq(&self, other: &NodeHandle) -> bool {
+        self.handle == *other
+    }
+}
+
+impl<In, Out> PartialEq<TypedNodeHandle<In, Out>> for NodeHandle {
+    fn eq(&self, other: &TypedNodeHandle<In, Out>) -> bool {
+        *self == other.handle
+    }
+}
+
+impl<In, Out> Hash for TypedNodeHandle<In, Out> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.handle.hash(state)
+    }
+}
+
+impl Graph {
+    /// Like `add_input`, but only compiles when the output of `input_node_handle` is the
+    /// input type of `node_handle`.
+    pub fn add_typed_input<T, In, Out>(
+        &mut self,
+        node_handle: &TypedNodeHandle<T, Out>,
+        input_node_handle: &TypedNodeHandle<In, T>,
+    ) {
+        self.add_input(node_handle, input_node_handle)
+            .expect("typed handles have matching types");
+    }
+
+    /// Typed handle for `node_handle`, or `None` if its node has other input or output types.
+    pub fn typed<In: 'static, Out: 'static>(
+        &self,
+        node_handle: &NodeHandle,
+    ) -> Result<Option<TypedNodeHandle<In, Out>>, ComputeGraphErrors> {
+        self.check_handle(node_handle)?;
+        let node = &self.nodes[node_handle.key];
+        let matches = node.inner.input_type() == TypeId::of::<In>()
+            && node.inner.output_type() == TypeId::of::<Out>();
+        Ok(matches.then(|| TypedNodeHandle::new(*node_handle)))
+    }
+}
+
+#[cfg(test)]
+mod typed_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_typed_handles() -> Result<(), ComputeGraphErrors> {
+        let mut graph = Graph::new();
+        let rate_handle = graph.insert_node("rate", Constant(0.05));
+        let flag_handle = graph.insert_node("flag", Constant(true));
+        let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+        graph.add_typed_input(&mul_handle, &rate_handle);
+        // graph.add_typed_input(&mul_handle, &flag_handle); does not compile
+        assert!(graph.add_input(&mul_handle, &flag_handle).is_err());
+        graph.connect_to_input(&mul_handle)?;
+        graph.set_output_node(&mul_handle)?;
+        assert_eq!(graph.build::<f64, f64>()?.compute(&10.0), 0.5);
+
+        let erased: NodeHandle = mul_handle.into();
+        assert_eq!(erased, mul_handle);
+        assert_eq!(graph.typed::<f64, f64>(&erased)?, Some(mul_handle));
+        assert!(graph.typed::<(), f64>(&erased)?.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use crate::graph::{Graph, NodeHandle, NodeMeta};
+use std::sync::Arc;
+
+#[derive(Debug)]
+struct ViewData {
+    nodes: Vec<NodeMeta>,
+    output_node: Option<NodeHandle>,
+}
+
+/// Immutable snapshot of a graph's topology and metadata.
+/// It holds no compute objects, so it is `Send + Sync` and cloning it is just a refcount bump.
+#[derive(Clone, Debug)]
+pub struct GraphView {
+    data: Arc<ViewData>,
+}
+
+impl GraphView {
+    pub fn nodes(&self) -> &[NodeMeta] {
+        &self.data.nodes
+    }
+
+    pub fn node(&self, node_handle: &NodeHandle) -> Option<&NodeMeta> {
+        self.data
+            .nodes
+            .iter()
+            .find(|node| node.this_node == *node_handle)
+    }
+
+    pub fn output_node(&self) -> Option<NodeHandle> {
+        self.data.output_node
+    }
+
+    /// Nodes that take `node_handle` as one of their inputs.
+    pub fn consumers(&self, node_handle: &NodeHandle) -> Vec<NodeHandle> {
+        self.data
+            .nodes
+            .iter()
+            .filter(|node| node.inputs.contains(node_handle))
+            .map(|node| node.this_node)
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.nodes.is_empty()
+    }
+}
+
+impl Graph {
+    pub fn view(&self) -> GraphView {
+        let nodes = self.get_all_node_metas();
+        let output_node = self.output_node.map(|key| NodeHandle {
+            key,
+            graph_id: self.id,
+        });
+        GraphView {
+            data: Arc::new(ViewData { nodes, output_node }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod view_tests {
+    use crate::prelude::*;
+    use std::thread;
+
+    #[test]
+    fn test_view_is_detached_snapshot() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<GraphView>();
+
+        let mut graph = Graph::new();
+        let const_handle = graph.insert_node("the_answer", Constant(42.0));
+        let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+        graph.add_input(&add_handle, &const_handle).unwrap();
+        graph.set_output_node(&add_handle).unwrap();
+
+        let view = graph.view();
+        let reader = {
+            let view = view.clone();
+            thread::spawn(move || {
+                let add = view.node(&add_handle).unwrap();
+                (
+                    add.name.clone(),
+                    add.input_type_name,
+                    view.consumers(&const_handle),
+                )
+            })
+        };
+        graph.remove_node(&const_handle).unwrap();
+
+        let (name, type_name, consumers) = reader.join().unwrap();
+        assert_eq!(name, "add");
+        assert_eq!(type_name, "f64");
+        assert_eq!(consumers, vec![add_handle]);
+        assert_eq!(view.len(), 2);
+        assert_eq!(view.output_node(), Some(add_handle.erase()));
+        assert_eq!(view.node(&const_handle).unwrap().params[0].name, "value");
+        assert_eq!(graph.view().len(), 1);
+    }
+}
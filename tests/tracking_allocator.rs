@@ -0,0 +1,104 @@
+//! Tests needing `TrackingAllocator` as the global allocator, which applies to the whole
+//! test binary and so lives apart from the unit tests.
+
+use compute_graph::prelude::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::time::Duration;
+
+thread_local! {
+    static COUNTED: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Counts the allocations of the current thread inside `count_allocations`.
+struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = COUNTED.try_with(|counted| counted.set(counted.get().map(|count| count + 1)));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator<Counting> = TrackingAllocator(Counting);
+
+fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    COUNTED.with(|counted| counted.set(Some(0)));
+    let result = f();
+    let count = COUNTED.with(|counted| counted.take()).unwrap();
+    (result, count)
+}
+
+#[derive(Clone, Copy)]
+struct Hog {
+    bytes: usize,
+}
+
+impl Compute for Hog {
+    type In = f64;
+    type Out = f64;
+    fn compute(&self, inputs: &[&Self::In]) -> Self::Out {
+        self.try_compute(inputs).unwrap()
+    }
+
+    fn try_compute(&self, inputs: &[&Self::In]) -> Result<Self::Out, String> {
+        // Allocations past the sandbox limit fail, which `vec!` would turn into an abort
+        let mut buffer = Vec::new();
+        buffer
+            .try_reserve_exact(self.bytes)
+            .map_err(|err| err.to_string())?;
+        buffer.resize(self.bytes, 1u8);
+        Ok(**inputs.first().unwrap() + buffer.iter().map(|b| *b as f64).sum::<f64>())
+    }
+}
+
+fn hog_graph(hog: Hog) -> Result<Graph, ComputeGraphErrors> {
+    let mut graph = Graph::new();
+    let hog_handle = graph.insert_node("hog", hog);
+    let add_handle = graph.insert_node("add", AddInputs::<f64>::new());
+    graph.add_input(&add_handle, &hog_handle)?;
+    graph.set_output_node(&add_handle)?;
+    Ok(graph)
+}
+
+#[test]
+fn test_sandbox_allocation_limit() -> Result<(), ComputeGraphErrors> {
+    let sandbox = Sandbox::new()
+        .max_node_time(Duration::from_secs(5))
+        .max_node_allocation(1 << 20);
+
+    let modest = hog_graph(Hog { bytes: 10 })?.build::<f64, f64>()?;
+    assert_eq!(modest.compute_sandboxed(&1.0, &sandbox)?, 11.0);
+
+    let greedy = hog_graph(Hog { bytes: 4 << 20 })?.build::<f64, f64>()?;
+    assert!(matches!(
+        greedy.compute_sandboxed(&1.0, &sandbox),
+        Err(ComputeGraphErrors::NodeFailed(message)) if message.contains("allocated")
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_batch_into_does_not_allocate() -> Result<(), ComputeGraphErrors> {
+    let mut graph = Graph::new();
+    let gain_handle = graph.insert_node("gain", Constant(3.0));
+    let mul_handle = graph.insert_node("mul", MulInputs::<f64>::new());
+    graph.add_input(&mul_handle, &gain_handle)?;
+    graph.connect_to_input(&mul_handle)?;
+    graph.set_output_node(&mul_handle)?;
+    let compute_graph = graph.build::<f64, f64>()?;
+    let inputs = (0..1000).map(|x| x as f64).collect::<Vec<_>>();
+
+    let mut outputs = vec![0.0; inputs.len()];
+    let (result, allocations) =
+        count_allocations(|| compute_graph.compute_batch_into(&inputs, &mut outputs));
+    result?;
+    assert_eq!(allocations, 0);
+    assert_eq!(outputs[999], 2997.0);
+    Ok(())
+}